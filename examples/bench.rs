@@ -0,0 +1,147 @@
+#![no_std]
+#![feature(start)]
+#![forbid(unsafe_code)]
+
+// times the per pixel drawing paths in mode 3 and mode 4 and shows the
+// scanlines each took, build it with and without the iwram feature to compare
+//     cargo make bench
+//     cargo make bench-iwram
+
+use embedded_graphics_gba::{
+    gba_palette, load_palette_bg, scanlines, wait_for_vblank, Mode3Display, Mode4Display, Profiler,
+    VideoMode,
+};
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X8, MonoTextStyle},
+    pixelcolor::Bgr555,
+    prelude::*,
+    primitives::{Circle, Line, PrimitiveStyle, Rectangle, Triangle},
+    text::{Baseline, Text},
+};
+
+use gba::{fatal, vram::bitmap::Page};
+
+gba_palette!(mod colors {
+    BLACK = 0x000000,
+    WHITE = 0xFFFFFF,
+    RED = 0xFF0000,
+    GREEN = 0x00FF00,
+});
+
+const FRAMES: u32 = 60; // frames averaged per result
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    fatal!("{}", info);
+    loop {}
+}
+
+#[start]
+fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    load_palette_bg(&colors::PALETTE, 0);
+    let mut profiler = Profiler::new();
+
+    let mut mode4 = Mode4Display { page: Page::Zero }.enable(false);
+    let mode4_ticks = average(&mut profiler, || {
+        mode4.clear(colors::BLACK).ok();
+        draw_scene(&mut mode4, colors::RED, colors::GREEN, colors::WHITE).ok();
+    });
+
+    let mut mode3 = Mode3Display.enable(false);
+    let mode3_ticks = average(&mut profiler, || {
+        mode3.clear(Bgr555::BLACK).ok();
+        draw_scene(&mut mode3, Bgr555::RED, Bgr555::GREEN, Bgr555::WHITE).ok();
+    });
+
+    let style = MonoTextStyle::new(&FONT_6X8, Bgr555::WHITE);
+    let area = Rectangle::new(Point::new(0, 140), Size::new(240, 20));
+    mode3.fill_solid(&area, Bgr555::BLACK).ok();
+    for (i, (label, ticks)) in [("mode 3", mode3_ticks), ("mode 4", mode4_ticks)]
+        .iter()
+        .enumerate()
+    {
+        let mut buffer = [0; 10];
+        let position = Point::new(4, 140 + i as i32 * 10);
+        Text::with_baseline(label, position, style, Baseline::Top)
+            .draw(&mut mode3)
+            .ok();
+        Text::with_baseline(
+            digits(scanlines(*ticks), &mut buffer),
+            position + Point::new(48, 0),
+            style,
+            Baseline::Top,
+        )
+        .draw(&mut mode3)
+        .ok();
+    }
+
+    loop {
+        wait_for_vblank();
+    }
+}
+
+// timer ticks of draw, averaged over FRAMES frames
+fn average(profiler: &mut Profiler, mut draw: impl FnMut()) -> u32 {
+    profiler.reset();
+    let mut total = 0;
+    for _ in 0..FRAMES {
+        wait_for_vblank();
+        profiler.measure(&mut draw);
+        total += profiler.end_frame().last;
+    }
+    total / FRAMES
+}
+
+// primitives that go through draw_iter pixel by pixel, not fill_solid
+fn draw_scene<D>(
+    display: &mut D,
+    fill: D::Color,
+    stroke: D::Color,
+    text: D::Color,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget,
+{
+    for i in 0..4 {
+        Circle::new(Point::new(10 + i * 55, 10), 50)
+            .into_styled(PrimitiveStyle::with_fill(fill))
+            .draw(display)?;
+    }
+    Triangle::new(
+        Point::new(10, 130),
+        Point::new(120, 65),
+        Point::new(230, 130),
+    )
+    .into_styled(PrimitiveStyle::with_fill(stroke))
+    .draw(display)?;
+    for i in 0..12 {
+        Line::new(Point::new(0, i * 10), Point::new(239, 130 - i * 10))
+            .into_styled(PrimitiveStyle::with_stroke(text, 1))
+            .draw(display)?;
+    }
+    for row in 0..4 {
+        Text::with_baseline(
+            "The quick brown fox jumps over the lazy dog",
+            Point::new(0, 70 + row * 10),
+            MonoTextStyle::new(&FONT_6X8, text),
+            Baseline::Top,
+        )
+        .draw(display)?;
+    }
+    Ok(())
+}
+
+// n in decimal
+fn digits(mut n: u32, buffer: &mut [u8; 10]) -> &str {
+    let mut start = buffer.len();
+    loop {
+        start -= 1;
+        buffer[start] = b'0' + (n % 10) as u8;
+        n /= 10;
+        if n == 0 {
+            break;
+        }
+    }
+    core::str::from_utf8(&buffer[start..]).unwrap_or("?")
+}
@@ -1,30 +1,29 @@
 #![no_std]
 #![feature(start)]
 #![forbid(unsafe_code)]
-#![feature(exclusive_range_pattern)]
-#![feature(bindings_after_at)]
 
-use embedded_graphics_gba::{Mode3Display, PaletteColor, Tile8bppDisplay};
+use embedded_graphics_gba::{
+    enable_vblank_irq, wait_for_vblank, Cursor, CursorShape, Mode3Display, OamManager,
+    PaletteColor, Sprite, SpriteSize, Tile8bppDisplay,
+};
 
-use core::convert::{Infallible, TryInto};
+use core::convert::Infallible;
 
 use embedded_graphics::{
-    fonts::{Font6x8, Text},
     image::Image,
+    mono_font::{ascii::FONT_6X8, MonoTextStyle},
     pixelcolor::Bgr555,
     prelude::*,
-    primitives::{Rectangle, Triangle},
-    style::{PrimitiveStyle, TextStyle},
+    primitives::{PrimitiveStyle, Rectangle, Triangle},
+    text::{Baseline, Text},
 };
 
 use gba::{
     fatal,
     io::{
-        display::{DisplayControlSetting, DisplayMode, DisplayStatusSetting, DISPCNT, DISPSTAT},
-        irq::{set_irq_handler, IrqEnableSetting, IrqFlags, BIOS_IF, IE, IF, IME},
+        display::{DisplayControlSetting, DisplayMode, DISPCNT},
         keypad::read_key_input,
     },
-    oam::{write_obj_attributes, OBJAttr0, OBJAttr1, OBJAttr2, ObjectAttributes},
     palram::index_palram_obj_8bpp,
     vram::get_8bpp_character_block,
     Color,
@@ -69,20 +68,29 @@ fn main(_argc: isize, _argv: *const *const u8) -> isize {
     register_palette();
     draw_cursor().ok();
 
+    // one cursor shape per color
+    let mut shapes = [CursorShape::new(Sprite::new(512, SpriteSize::S8x8)); COLORS.len()];
+    for (i, shape) in shapes.iter_mut().enumerate() {
+        // Mode3 tiles start at 512 and 8bpp tiles are even with 2x width
+        shape.sprite = shape
+            .sprite
+            .with_tile_id(512 + i as u16 * 2)
+            .with_8bpp(true);
+    }
+    let mut oam = OamManager::new();
+    let mut cursor = Cursor::new(&mut oam, &shapes).unwrap();
+    oam.commit();
+
     // setup interrupts
-    set_irq_handler(irq_handler);
-    DISPSTAT.write(DisplayStatusSetting::new().with_vblank_irq_enable(true));
-    IE.write(IrqFlags::new().with_vblank(true));
-    IME.write(IrqEnableSetting::IRQ_YES);
+    enable_vblank_irq();
     DISPCNT.write(DISPCNT.read().with_force_vblank(false)); // enable display
 
     // state variables
-    let mut point = Point::new(120, 80); // start in middle of display
     let mut index = 0; // index into color palette
 
     loop {
         // sleep until vblank interrupt
-        gba::bios::vblank_interrupt_wait();
+        wait_for_vblank();
 
         // read buttons input
         let input = read_key_input();
@@ -100,44 +108,33 @@ fn main(_argc: isize, _argv: *const *const u8) -> isize {
             if index >= COLORS.len() {
                 index = 0;
             }
+            cursor.set_shape(&mut oam, index);
             while read_key_input().b() {
                 // wait for button to be released
-                gba::bios::vblank_interrupt_wait();
+                wait_for_vblank();
             }
         }
 
-        // update point
+        // move cursor, it stays on screen
         let offset = Point::new(input.x_tribool() as i32, input.y_tribool() as i32);
-        point += offset;
-
-        // draw cursor and pixel
-        if let Ok((x @ 0..240, y @ 0..160)) = point.try_into() {
-            move_cursor(index as u16, x as u16, y as u16);
-            if input.a() {
-                Pixel(Point::new(x as i32, y as i32), COLORS[index])
-                    .draw(&mut display)
-                    .ok();
-            }
-        } else {
-            point -= offset; // undo
+        cursor.move_by(&mut oam, offset);
+        oam.commit();
+
+        // draw pixel
+        if input.a() {
+            Pixel(cursor.position(), COLORS[index])
+                .draw(&mut display)
+                .ok();
         }
     }
 }
 
-extern "C" fn irq_handler(flags: IrqFlags) {
-    if flags.vblank() {
-        // need to clear vblank flag in bios and hardware
-        BIOS_IF.write(BIOS_IF.read().with_vblank(true));
-        IF.write(IF.read().with_vblank(true));
-    }
-}
-
 fn draw_canvas<D>(display: &mut D) -> Result<(), D::Error>
 where
     D: DrawTarget<Color = Bgr555>,
 {
-    let tga = Tga::from_slice(include_bytes!("../assets/background.tga")).unwrap();
-    let image: Image<_, Bgr555> = Image::new(&tga, Point::zero());
+    let tga: Tga<Bgr555> = Tga::from_slice(include_bytes!("../assets/background.tga")).unwrap();
+    let image = Image::new(&tga, Point::zero());
     image.draw(display)?;
     Ok(())
 }
@@ -149,15 +146,27 @@ where
     Rectangle::new(Point::new(0, 0), Size::new(49, 25))
         .into_styled(PrimitiveStyle::with_fill(Bgr555::WHITE))
         .draw(display)?;
-    Text::new("A: Draw", Point::new(1, 1))
-        .into_styled(TextStyle::new(Font6x8, Bgr555::RED))
-        .draw(display)?;
-    Text::new("B: Color", Point::new(1, 9))
-        .into_styled(TextStyle::new(Font6x8, Bgr555::GREEN))
-        .draw(display)?;
-    Text::new("S: Clear", Point::new(1, 17))
-        .into_styled(TextStyle::new(Font6x8, Bgr555::BLUE))
-        .draw(display)?;
+    Text::with_baseline(
+        "A: Draw",
+        Point::new(1, 1),
+        MonoTextStyle::new(&FONT_6X8, Bgr555::RED),
+        Baseline::Top,
+    )
+    .draw(display)?;
+    Text::with_baseline(
+        "B: Color",
+        Point::new(1, 9),
+        MonoTextStyle::new(&FONT_6X8, Bgr555::GREEN),
+        Baseline::Top,
+    )
+    .draw(display)?;
+    Text::with_baseline(
+        "S: Clear",
+        Point::new(1, 17),
+        MonoTextStyle::new(&FONT_6X8, Bgr555::BLUE),
+        Baseline::Top,
+    )
+    .draw(display)?;
     Ok(())
 }
 
@@ -180,15 +189,3 @@ fn draw_cursor() -> Result<(), Infallible> {
 
     Ok(())
 }
-
-fn move_cursor(index: u16, x: u16, y: u16) {
-    write_obj_attributes(
-        0, // overwritting object 0
-        ObjectAttributes {
-            attr0: OBJAttr0::new().with_row_coordinate(y).with_is_8bpp(true),
-            attr1: OBJAttr1::new().with_col_coordinate(x),
-            // Mode3 tiles start at 512 and 8bpp tiles are even with 2x width
-            attr2: OBJAttr2::new().with_tile_id(512 + (index * 2)),
-        },
-    );
-}
@@ -0,0 +1,45 @@
+// host side of the test harness, runs the harness_rom example in an emulator
+// and compares its frames with harness.golden, build the rom first with
+//     cargo make harness
+// a run with BLESS set writes the golden file instead, without one the run
+// fails, so a golden file that went missing isn't quietly written again
+//     cargo make harness-bless
+// the emulator command defaults to mgba-rom-test, set EMULATOR to change it
+
+use embedded_graphics_gba::{compare_golden, write_golden, Harness};
+use std::{env, path::Path, process};
+
+fn main() {
+    let rom = Path::new("target/harness_rom.gba");
+    let golden = Path::new("examples/harness.golden");
+    let emulator = env::var("EMULATOR").unwrap_or_else(|_| String::from("mgba-rom-test"));
+
+    let checksums = match Harness::new(emulator).run(rom) {
+        Ok(checksums) => checksums,
+        Err(error) => {
+            eprintln!("running {}: {}", rom.display(), error);
+            process::exit(2);
+        }
+    };
+
+    if env::var_os("BLESS").is_some() {
+        write_golden(golden, &checksums).expect("writing the golden file");
+        println!("wrote {} scenes to {}", checksums.len(), golden.display());
+        return;
+    }
+
+    let mismatches = match compare_golden(golden, &checksums) {
+        Ok(mismatches) => mismatches,
+        Err(error) => {
+            eprintln!("{}, set BLESS to write it", error);
+            process::exit(2);
+        }
+    };
+    for mismatch in &mismatches {
+        println!("{}", mismatch.describe());
+    }
+    if !mismatches.is_empty() {
+        process::exit(1);
+    }
+    println!("{} scenes match", checksums.len());
+}
@@ -0,0 +1,166 @@
+#![no_std]
+#![feature(start)]
+#![forbid(unsafe_code)]
+
+// test rom for the harness feature, draws the same scene with each display
+// and reports a checksum of every frame to sram, run it with
+//     cargo make harness
+
+use embedded_graphics_gba::{
+    gba_palette, load_palette_bg, report_done, report_scene, wait_for_vblank, Background,
+    Mode0Display, Mode3Display, Mode4Display, Mode5Display, PaletteColor, ScreenblockDisplay,
+    Tile4bppDisplay, TileEntry, VideoMode,
+};
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X8, MonoTextStyle},
+    pixelcolor::Bgr555,
+    prelude::*,
+    primitives::{Circle, Line, PrimitiveStyle, Rectangle, Triangle},
+    text::{Baseline, Text},
+};
+
+use gba::{
+    fatal,
+    io::{
+        background::{BGSize, BackgroundControlSetting},
+        display::{DisplayControlSetting, DisplayMode, DISPCNT},
+    },
+    vram::{bitmap::Page, get_4bpp_character_block},
+};
+
+gba_palette!(mod colors {
+    BLACK = 0x000000,
+    WHITE = 0xFFFFFF,
+    RED = 0xFF0000,
+    GREEN = 0x00FF00,
+    BLUE = 0x0000FF,
+});
+
+#[panic_handler]
+fn panic(info: &core::panic::PanicInfo) -> ! {
+    fatal!("{}", info);
+    loop {}
+}
+
+#[start]
+fn main(_argc: isize, _argv: *const *const u8) -> isize {
+    load_palette_bg(&colors::PALETTE, 0);
+
+    // scene 0, mode 3
+    let mut mode3 = Mode3Display.enable(false);
+    mode3.clear_vram();
+    draw_scene(
+        &mut mode3,
+        [Bgr555::WHITE, Bgr555::RED, Bgr555::GREEN, Bgr555::BLUE],
+    )
+    .ok();
+    show(0);
+
+    // scene 1, mode 4
+    let mut mode4 = Mode4Display { page: Page::Zero }.enable(false);
+    mode4.clear_vram();
+    draw_scene(&mut mode4, indices()).ok();
+    show(1);
+
+    // scene 2, mode 5
+    let mut mode5 = Mode5Display { page: Page::Zero }.enable(false);
+    mode5.clear_vram();
+    draw_scene(
+        &mut mode5,
+        [Bgr555::WHITE, Bgr555::RED, Bgr555::GREEN, Bgr555::BLUE],
+    )
+    .ok();
+    show(2);
+
+    // scene 3, an 8bpp tiled background
+    let mut mode0 = Mode0Display::new(Background::Bg0, 0, 31).enable(false);
+    mode0.clear_vram();
+    draw_scene(&mut mode0, indices()).ok();
+    show(3);
+
+    // scene 4, 4bpp tiles, the nibble packing of Tile4bppDisplay
+    draw_tiles_4bpp().ok();
+    show(4);
+
+    report_done(5);
+    loop {
+        wait_for_vblank();
+    }
+}
+
+fn indices() -> [PaletteColor; 4] {
+    [colors::WHITE, colors::RED, colors::GREEN, colors::BLUE]
+}
+
+// wait for the frame to be shown, then report it
+fn show(scene: usize) {
+    wait_for_vblank();
+    report_scene(scene).ok();
+}
+
+fn draw_scene<D>(display: &mut D, palette: [D::Color; 4]) -> Result<(), D::Error>
+where
+    D: DrawTarget,
+{
+    let [white, red, green, blue] = palette;
+    Rectangle::new(Point::new(4, 4), Size::new(60, 40))
+        .into_styled(PrimitiveStyle::with_fill(red))
+        .draw(display)?;
+    Circle::new(Point::new(70, 10), 45)
+        .into_styled(PrimitiveStyle::with_stroke(green, 3))
+        .draw(display)?;
+    Triangle::new(
+        Point::new(10, 120),
+        Point::new(60, 60),
+        Point::new(110, 120),
+    )
+    .into_styled(PrimitiveStyle::with_fill(blue))
+    .draw(display)?;
+    Line::new(Point::new(0, 159), Point::new(239, 0))
+        .into_styled(PrimitiveStyle::with_stroke(white, 1))
+        .draw(display)?;
+    Text::with_baseline(
+        "Harness",
+        Point::new(120, 140),
+        MonoTextStyle::new(&FONT_6X8, white),
+        Baseline::Top,
+    )
+    .draw(display)?;
+    Ok(())
+}
+
+// a 2x2 block of 4bpp tiles on bg0 with a circle across all four
+fn draw_tiles_4bpp() -> Result<(), core::convert::Infallible> {
+    DISPCNT.write(
+        DisplayControlSetting::new()
+            .with_mode(DisplayMode::Mode0)
+            .with_bg0(true),
+    );
+    Background::Bg0.set_control(
+        BackgroundControlSetting::new()
+            .with_char_base_block(0)
+            .with_screen_base_block(31)
+            .with_size(BGSize::Zero),
+    );
+    let mut map = ScreenblockDisplay::new(31);
+    map.clear(TileEntry::new(0))?;
+    let blank = Tile4bppDisplay::new(PaletteColor::TANSPARENT);
+    get_4bpp_character_block(0).index(0).write(blank.tile);
+    for i in 0..4 {
+        let mut tile = Tile4bppDisplay::new(PaletteColor::TANSPARENT);
+        let offset = Point::new((i % 2) * 8, (i / 2) * 8);
+        Circle::new(Point::new(1, 1) - offset, 14)
+            .into_styled(PrimitiveStyle::with_fill(colors::RED))
+            .draw(&mut tile)?;
+        get_4bpp_character_block(0)
+            .index(i as usize + 1)
+            .write(tile.tile);
+        Pixel(
+            Point::new(4 + i % 2, 4 + i / 2),
+            TileEntry::new(i as u16 + 1),
+        )
+        .draw(&mut map)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use crate::{blend::blend, read::ReadPixel};
+use embedded_graphics::{pixelcolor::Bgr555, prelude::*, primitives::Line};
+
+// anti-aliased one pixel wide lines with xiaolin wu's algorithm, for vector
+// style ui where jagged lines stand out on the small screen
+// the two pixels across the line at each step are blended with what's already
+// drawn, read back from vram, so this is slower than FastLine
+pub trait AntialiasedLine: ReadPixel<Color = Bgr555> + Sized {
+    fn draw_antialiased_line(&mut self, line: &Line, color: Bgr555) -> Result<(), Self::Error> {
+        let (mut start, mut end) = (line.start, line.end);
+        let steep = (end.y - start.y).abs() > (end.x - start.x).abs();
+        if steep {
+            start = Point::new(start.y, start.x);
+            end = Point::new(end.y, end.x);
+        }
+        if start.x > end.x {
+            core::mem::swap(&mut start, &mut end);
+        }
+
+        // y along the line in 16.16 fixed point
+        let dx = end.x - start.x;
+        let gradient = if dx == 0 {
+            0
+        } else {
+            ((end.y - start.y) << 16) / dx
+        };
+        let mut y = start.y << 16;
+        for x in start.x..=end.x {
+            let fraction = ((y & 0xFFFF) >> 8) as u8; // coverage of the lower pixel
+            let point = Point::new(x, y >> 16);
+            let (upper, lower) = if steep {
+                (
+                    Point::new(point.y, point.x),
+                    Point::new(point.y + 1, point.x),
+                )
+            } else {
+                (point, point + Point::new(0, 1))
+            };
+            plot(self, upper, color, 255 - fraction)?;
+            plot(self, lower, color, fraction)?;
+            y += gradient;
+        }
+        Ok(())
+    }
+}
+
+impl<D: ReadPixel<Color = Bgr555>> AntialiasedLine for D {}
+
+fn plot<D: ReadPixel<Color = Bgr555>>(
+    display: &mut D,
+    point: Point,
+    color: Bgr555,
+    alpha: u8,
+) -> Result<(), D::Error> {
+    match display.read_pixel(point) {
+        Some(under) if alpha > 0 => Pixel(point, blend(color, under, alpha)).draw(display),
+        _ => Ok(()),
+    }
+}
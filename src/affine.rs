@@ -0,0 +1,192 @@
+use crate::{
+    arena::Vram,
+    background::Background,
+    bg_vram::{BgScreenblocks, BgTiles, BgVramError},
+    dma,
+    fixed::Fixed8,
+    mmio,
+    mode::VideoMode,
+    obj_affine::AffineMatrix,
+    obj_tiles::Bpp,
+    vram::{self, SCREENBLOCK_SIZE},
+    Mode5Display, PaletteColor,
+};
+use core::convert::{Infallible, TryInto};
+use embedded_graphics::{geometry::Size, prelude::*};
+use gba::{
+    io::{
+        background::{BGSize, BackgroundControlSetting},
+        display::DisplayMode,
+    },
+    vram::bitmap::Mode5,
+};
+
+// bg2 affine parameter registers
+const BG2PA: usize = 0x400_0020;
+const BG2PB: usize = 0x400_0022;
+const BG2PC: usize = 0x400_0024;
+const BG2PD: usize = 0x400_0026;
+const BG2X: usize = 0x400_0028;
+const BG2Y: usize = 0x400_002C;
+
+const TILES_WIDE: usize = 16; // smallest affine map is 16x16 tiles
+const TILE_BYTES: usize = 64; // affine tiles are always 8bpp
+
+// bg2 of mode 1 or mode 2 used as a 128x128 8bpp bitmap
+// affine map entries are 8 bit, so only 256 unique tiles (one charblock) can
+// be on the map at once, which limits the canvas to the 128x128 map size
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AffineBgDisplay {
+    charblock: usize,
+}
+
+impl AffineBgDisplay {
+    pub const WIDTH: usize = 128;
+    pub const HEIGHT: usize = 128;
+
+    // picking the charblock and screenblock by hand bypasses the Vram arena,
+    // see from_vram
+    pub fn new(charblock: usize, screenblock: usize) -> Self {
+        assert!(charblock < 4, "there are only 4 background charblocks");
+        assert!(screenblock < 32, "there are only 32 screenblocks");
+        assert!(
+            screenblock * SCREENBLOCK_SIZE / vram::CHARBLOCK_SIZE != charblock,
+            "screenblock overlaps the tiles"
+        );
+
+        // give each map entry a unique tile, two 8 bit entries per halfword
+        let map = vram::screenblock_address(screenblock);
+        for tile in (0..TILES_WIDE * TILES_WIDE).step_by(2) {
+            mmio::write16(map + tile, (tile as u16) | ((tile as u16 + 1) << 8));
+        }
+
+        Background::Bg2.set_control(
+            BackgroundControlSetting::new()
+                .with_char_base_block(charblock as u16)
+                .with_screen_base_block(screenblock as u16)
+                .with_size(BGSize::Zero), // 128x128 for affine
+        );
+
+        let display = AffineBgDisplay { charblock };
+        display.set_matrix(AffineMatrix::IDENTITY);
+        display.set_reference(Fixed8::ZERO, Fixed8::ZERO);
+        display
+    }
+
+    // a display on a charblock of tiles and a screenblock claimed from vram,
+    // hand the blocks back with vram.bg.free_tiles and free_screenblocks
+    pub fn from_vram(vram: &mut Vram) -> Result<(Self, BgTiles, BgScreenblocks), BgVramError> {
+        let count = TILES_WIDE * TILES_WIDE;
+        let (tiles, screenblocks) = vram.tiles_and_screenblock(0..4, count, Bpp::Bpp8)?;
+        let display = Self::new(tiles.charblock(), screenblocks.screenblock());
+        Ok((display, tiles, screenblocks))
+    }
+
+    // matrix mapping screen pixels to background pixels
+    pub fn set_matrix(&self, matrix: AffineMatrix) {
+        set_bg2_matrix(matrix);
+    }
+
+    // background position of the screen's top left pixel, 20.8 in hardware
+    pub fn set_reference(&self, x: Fixed8, y: Fixed8) {
+        set_bg2_reference(x, y);
+    }
+
+    // reference point at a whole pixel offset, scrolls like a text background
+    // when the matrix is the identity
+    pub fn set_scroll(&self, offset: Point) {
+        self.set_reference(Fixed8::from_int(offset.x), Fixed8::from_int(offset.y));
+    }
+
+    // wrap around instead of showing transparent pixels outside of the map
+    pub fn set_wrapping(&self, wrap: bool) {
+        let control = Background::Bg2.control();
+        Background::Bg2.set_control(control.with_affine_display_overflow_wrapping(wrap));
+    }
+
+    fn address(&self, x: usize, y: usize) -> usize {
+        let tile = (y / 8) * TILES_WIDE + (x / 8);
+        vram::charblock_address(self.charblock) + tile * TILE_BYTES + (y % 8) * 8 + (x % 8)
+    }
+}
+
+impl DrawTarget for AffineBgDisplay {
+    type Color = PaletteColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x @ 0..=127, y @ 0..=127)) = coord.try_into() {
+                vram::write8(self.address(x as usize, y as usize), color.into_storage());
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let word = u32::from_ne_bytes([color.into_storage(); 4]);
+        let dst = vram::charblock_address(self.charblock) as *mut u32;
+        unsafe { dma::fill32(&word, dst, (vram::CHARBLOCK_SIZE / 4) as u16) };
+        Ok(())
+    }
+}
+
+impl OriginDimensions for AffineBgDisplay {
+    fn size(&self) -> Size {
+        Size::new(Self::WIDTH as u32, Self::HEIGHT as u32)
+    }
+}
+
+impl VideoMode for AffineBgDisplay {
+    const MODE: DisplayMode = DisplayMode::Mode1;
+
+    fn clear_vram(&mut self) {
+        self.clear(PaletteColor::TANSPARENT).ok();
+    }
+}
+
+// the bitmap modes draw bg2 with the affine parameters too
+impl Mode5Display {
+    // stretch the 160x128 page to fill the 240x160 screen in hardware, each
+    // parameter is rounded up so the last screen pixel still lands on the page
+    pub fn stretch_to_screen(&self) {
+        let step = |page: usize, screen: usize| ((page << 8) + screen - 1) / screen;
+        set_bg2_matrix(AffineMatrix {
+            pa: step(Mode5::WIDTH, 240) as i16,
+            pb: 0,
+            pc: 0,
+            pd: step(Mode5::HEIGHT, 160) as i16,
+        });
+        set_bg2_reference(Fixed8::ZERO, Fixed8::ZERO);
+    }
+
+    // back to one screen pixel per page pixel
+    pub fn unstretch(&self) {
+        set_bg2_matrix(AffineMatrix::IDENTITY);
+        set_bg2_reference(Fixed8::ZERO, Fixed8::ZERO);
+    }
+
+    // one screen pixel per page pixel with the page in the middle of the
+    // screen, the border shows the backdrop
+    pub fn center_on_screen(&self) {
+        let x = (Mode5::WIDTH as i32 - 240) / 2;
+        let y = (Mode5::HEIGHT as i32 - 160) / 2;
+        set_bg2_matrix(AffineMatrix::IDENTITY);
+        set_bg2_reference(Fixed8::from_int(x), Fixed8::from_int(y));
+    }
+}
+
+fn set_bg2_matrix(matrix: AffineMatrix) {
+    mmio::write16(BG2PA, matrix.pa as u16);
+    mmio::write16(BG2PB, matrix.pb as u16);
+    mmio::write16(BG2PC, matrix.pc as u16);
+    mmio::write16(BG2PD, matrix.pd as u16);
+}
+
+fn set_bg2_reference(x: Fixed8, y: Fixed8) {
+    mmio::write32(BG2X, x.0 as u32);
+    mmio::write32(BG2Y, y.0 as u32);
+}
@@ -0,0 +1,154 @@
+use crate::oam::OamManager;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Frame {
+    pub tile_id: u16,
+    pub ticks: u16, // vblanks to show the frame for
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AnimationMode {
+    Loop,     // 0 1 2 0 1 2 ...
+    PingPong, // 0 1 2 1 0 1 ...
+    OneShot,  // 0 1 2, then stays on the last frame
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Animation<'a> {
+    pub frames: &'a [Frame],
+    pub mode: AnimationMode,
+}
+
+impl<'a> Animation<'a> {
+    pub const fn new(frames: &'a [Frame], mode: AnimationMode) -> Self {
+        Animation { frames, mode }
+    }
+}
+
+// an animation playing on the object in an oam slot
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AnimationHandle(usize);
+
+#[derive(Copy, Clone)]
+struct Playback<'a> {
+    animation: &'a Animation<'a>,
+    slot: usize,
+    frame: usize,
+    ticks: u16, // ticks left on the current frame
+    forward: bool,
+    finished: bool,
+}
+
+impl<'a> Playback<'a> {
+    // move to the next frame, returns true when the shown tile changes
+    fn advance(&mut self) -> bool {
+        let last = self.animation.frames.len() - 1;
+        let previous = self.frame;
+        match self.animation.mode {
+            AnimationMode::Loop => {
+                self.frame = if self.frame >= last {
+                    0
+                } else {
+                    self.frame + 1
+                }
+            }
+            AnimationMode::OneShot => {
+                if self.frame >= last {
+                    self.finished = true;
+                } else {
+                    self.frame += 1;
+                }
+            }
+            AnimationMode::PingPong if last == 0 => {}
+            AnimationMode::PingPong => {
+                if self.forward && self.frame >= last {
+                    self.forward = false;
+                } else if !self.forward && self.frame == 0 {
+                    self.forward = true;
+                }
+                self.frame = if self.forward {
+                    self.frame + 1
+                } else {
+                    self.frame - 1
+                };
+            }
+        }
+        self.ticks = self.animation.frames[self.frame].ticks;
+        self.frame != previous
+    }
+}
+
+// up to N animations advanced together once per vblank
+pub struct Animations<'a, const N: usize> {
+    playing: [Option<Playback<'a>>; N],
+}
+
+impl<'a, const N: usize> Animations<'a, N> {
+    pub fn new() -> Self {
+        Animations { playing: [None; N] }
+    }
+
+    // start animation on the object in oam slot, showing its first frame
+    // returns None when all N animations are in use or there are no frames
+    pub fn play(
+        &mut self,
+        oam: &mut OamManager,
+        slot: usize,
+        animation: &'a Animation<'a>,
+    ) -> Option<AnimationHandle> {
+        let first = animation.frames.first()?;
+        let index = self.playing.iter().position(Option::is_none)?;
+        self.playing[index] = Some(Playback {
+            animation,
+            slot,
+            frame: 0,
+            ticks: first.ticks,
+            forward: true,
+            finished: false,
+        });
+        set_tile_id(oam, slot, first.tile_id);
+        Some(AnimationHandle(index))
+    }
+
+    // stop animating, the object keeps its current frame
+    pub fn stop(&mut self, handle: AnimationHandle) {
+        if let Some(playback) = self.playing.get_mut(handle.0) {
+            *playback = None;
+        }
+    }
+
+    // a one shot animation that reached its last frame, or a stopped one
+    pub fn finished(&self, handle: AnimationHandle) -> bool {
+        match self.playing.get(handle.0) {
+            Some(Some(playback)) => playback.finished,
+            _ => true,
+        }
+    }
+
+    // advance every animation by one vblank and update the shadow oam
+    pub fn tick(&mut self, oam: &mut OamManager) {
+        for playback in self.playing.iter_mut().flatten() {
+            if playback.finished {
+                continue;
+            }
+            playback.ticks = playback.ticks.saturating_sub(1);
+            if playback.ticks == 0 && playback.advance() {
+                let tile_id = playback.animation.frames[playback.frame].tile_id;
+                set_tile_id(oam, playback.slot, tile_id);
+            }
+        }
+    }
+}
+
+impl<'a, const N: usize> Default for Animations<'a, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn set_tile_id(oam: &mut OamManager, slot: usize, tile_id: u16) {
+    if let Some(mut attributes) = oam.get(slot) {
+        attributes.attr2 = attributes.attr2.with_tile_id(tile_id);
+        oam.set(slot, attributes);
+    }
+}
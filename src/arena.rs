@@ -0,0 +1,86 @@
+use crate::{
+    bg_vram::{BgScreenblocks, BgTiles, BgVramAllocator, BgVramError},
+    obj_tiles::{Bpp, ObjTileAllocator},
+    shared::interrupt_free,
+    sprite::ObjLayout,
+};
+use core::{
+    ops::Range,
+    ptr::{addr_of, addr_of_mut},
+};
+
+static mut TAKEN: bool = false;
+
+// all of vram, background and obj, as one value taken once at startup
+// tiles and maps are claimed through it and come back as handles that aren't
+// Copy, so two subsystems can't end up owning the same bytes, pass &mut Vram
+// to constructors like Console::from_vram or Mode0Display::from_vram instead
+// of picking blocks by hand, the constructors taking charblock, screenblock or
+// tile numbers bypass the arena
+pub struct Vram {
+    pub bg: BgVramAllocator,
+    pub obj: ObjTileAllocator,
+}
+
+impl Vram {
+    // the arena, None after the first call
+    // in the bitmap modes the framebuffer fills background vram and the lower
+    // half of obj vram, so only obj tiles 512 and up are handed out
+    pub fn take(layout: ObjLayout) -> Option<Self> {
+        let taken = interrupt_free(|| unsafe {
+            let taken = core::ptr::read_volatile(addr_of!(TAKEN));
+            core::ptr::write_volatile(addr_of_mut!(TAKEN), true);
+            taken
+        });
+        if taken {
+            return None;
+        }
+        let mut bg = BgVramAllocator::new();
+        if layout.bitmap_mode {
+            for charblock in [0, 2].iter() {
+                bg.reserve_tiles(*charblock, 0, 1024, Bpp::Bpp4).ok();
+            }
+        }
+        Some(Vram {
+            bg,
+            obj: ObjTileAllocator::new(layout),
+        })
+    }
+
+    // count tiles from tile id 0 of the first of charblocks where they are
+    // free, for displays that address their tiles from the charblock start
+    pub(crate) fn tiles_at_start(
+        &mut self,
+        charblocks: Range<usize>,
+        count: usize,
+        bpp: Bpp,
+    ) -> Result<BgTiles, BgVramError> {
+        let mut error = BgVramError::OutOfRange; // until a charblock could fit them
+        for charblock in charblocks {
+            match self.bg.reserve_tiles(charblock, 0, count, bpp) {
+                Ok(tiles) => return Ok(tiles),
+                Err(BgVramError::OutOfRange) => {}
+                Err(_) => error = BgVramError::Full,
+            }
+        }
+        Err(error)
+    }
+
+    // tiles_at_start and a screenblock for the map, the tiles are given back
+    // when no screenblock is free
+    pub(crate) fn tiles_and_screenblock(
+        &mut self,
+        charblocks: Range<usize>,
+        count: usize,
+        bpp: Bpp,
+    ) -> Result<(BgTiles, BgScreenblocks), BgVramError> {
+        let tiles = self.tiles_at_start(charblocks, count, bpp)?;
+        match self.bg.alloc_screenblocks(1) {
+            Ok(screenblocks) => Ok((tiles, screenblocks)),
+            Err(error) => {
+                self.bg.free_tiles(tiles);
+                Err(error)
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{
+    animation::{Animation, AnimationMode, Frame},
+    vram,
+};
+
+// sprite sheet exported from aseprite and packed for the gba as two files:
+//
+// frames: the tiles of every frame back to back, each frame laid out for 1d
+// obj mapping (aseprite --sheet-type rows, converted with grit -ftb -mRtf)
+//
+// table: little endian u16 values, converted from aseprite's json
+//   frame_count, tiles_per_frame (32 byte tile ids), tag_count, 0
+//   frame_count durations in milliseconds
+//   tag_count tags of from frame, to frame, direction (0 forward, 1 reverse, 2 ping-pong)
+
+const OBJ_VRAM_BASE: usize = 0x601_0000;
+const HEADER_VALUES: usize = 4;
+const TAG_VALUES: usize = 3;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SheetError {
+    TruncatedTable,
+    TruncatedFrames,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AsepriteSheet<'a> {
+    frames: &'a [u8],
+    table: &'a [u8],
+}
+
+impl<'a> AsepriteSheet<'a> {
+    pub fn new(frames: &'a [u8], table: &'a [u8]) -> Result<Self, SheetError> {
+        let sheet = AsepriteSheet { frames, table };
+        if table.len() < HEADER_VALUES * 2 {
+            return Err(SheetError::TruncatedTable);
+        }
+        let values = HEADER_VALUES + sheet.frame_count() + sheet.tag_count() * TAG_VALUES;
+        if table.len() < values * 2 {
+            return Err(SheetError::TruncatedTable);
+        }
+        if frames.len() < sheet.frame_count() * sheet.frame_bytes() {
+            return Err(SheetError::TruncatedFrames);
+        }
+        Ok(sheet)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.value(0) as usize
+    }
+
+    pub fn tiles_per_frame(&self) -> usize {
+        self.value(1) as usize
+    }
+
+    pub fn tag_count(&self) -> usize {
+        self.value(2) as usize
+    }
+
+    pub fn frame_bytes(&self) -> usize {
+        self.tiles_per_frame() * 32
+    }
+
+    // copy every frame to obj vram starting at first_tile_id
+    pub fn upload(&self, first_tile_id: u16) {
+        let frames = &self.frames[..self.frame_count() * self.frame_bytes()];
+        vram::copy_bytes(frames, OBJ_VRAM_BASE + first_tile_id as usize * 32);
+    }
+
+    pub fn frame_tile_id(&self, first_tile_id: u16, frame: usize) -> u16 {
+        first_tile_id + (frame * self.tiles_per_frame()) as u16
+    }
+
+    // frame duration in vblanks, at least 1
+    pub fn frame_ticks(&self, frame: usize) -> u16 {
+        let ms = self.value(HEADER_VALUES + frame) as u32;
+        ((ms * 60 + 999) / 1000).max(1) as u16
+    }
+
+    // fill frames with the tag's frames and return an animation playing them
+    // returns None if the tag doesn't exist or frames is too short
+    pub fn animation<'f>(
+        &self,
+        first_tile_id: u16,
+        tag: usize,
+        frames: &'f mut [Frame],
+    ) -> Option<Animation<'f>> {
+        if tag >= self.tag_count() {
+            return None;
+        }
+        let base = HEADER_VALUES + self.frame_count() + tag * TAG_VALUES;
+        let (from, to) = (self.value(base) as usize, self.value(base + 1) as usize);
+        let direction = self.value(base + 2);
+        if to < from || to >= self.frame_count() {
+            return None;
+        }
+        let frames = frames.get_mut(..to - from + 1)?;
+        for (i, frame) in frames.iter_mut().enumerate() {
+            let index = if direction == 1 { to - i } else { from + i };
+            *frame = Frame {
+                tile_id: self.frame_tile_id(first_tile_id, index),
+                ticks: self.frame_ticks(index),
+            };
+        }
+        let mode = if direction == 2 {
+            AnimationMode::PingPong
+        } else {
+            AnimationMode::Loop
+        };
+        Some(Animation::new(frames, mode))
+    }
+
+    fn value(&self, index: usize) -> u16 {
+        u16::from_le_bytes([self.table[index * 2], self.table[index * 2 + 1]])
+    }
+}
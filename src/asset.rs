@@ -0,0 +1,229 @@
+use crate::{color::FromGbaColor, palette::PaletteKind, PaletteColor};
+use embedded_graphics::{
+    geometry::Size, image::ImageDrawable, pixelcolor::Bgr555, prelude::*, primitives::Rectangle,
+};
+use gba::{
+    vram::{Tile4bpp, Tile8bpp},
+    Color,
+};
+
+// compile time conversion of uncompressed color mapped (indexed) tga images
+// into tiles and a palette, so they can be embedded in rom as ready to copy
+// vram and palram data:
+//
+// const SPRITE: TileImage<Tile4bpp, 4, 16> = include_gba_image!(Tile4bpp, "sprite.tga");
+//
+// the image is cut into 8x8 tiles in row major order, and its width and
+// height must be multiples of 8
+
+pub struct TileImage<T, const TILES: usize, const COLORS: usize> {
+    pub tiles: [T; TILES],
+    pub palette: [Color; COLORS],
+}
+
+pub(crate) const HEADER_LEN: usize = 18;
+const COLOR_MAPPED: u8 = 1;
+
+pub(crate) const fn u16_at(data: &[u8], offset: usize) -> usize {
+    data[offset] as usize | (data[offset + 1] as usize) << 8
+}
+
+pub(crate) const fn width(tga: &[u8]) -> usize {
+    u16_at(tga, 12)
+}
+
+pub(crate) const fn height(tga: &[u8]) -> usize {
+    u16_at(tga, 14)
+}
+
+const fn color_map_entry_bytes(tga: &[u8]) -> usize {
+    (tga[7] as usize + 7) / 8
+}
+
+const fn color_map_offset(tga: &[u8]) -> usize {
+    HEADER_LEN + tga[0] as usize // skip the image id
+}
+
+pub(crate) const fn pixel_offset(tga: &[u8]) -> usize {
+    color_map_offset(tga) + u16_at(tga, 5) * color_map_entry_bytes(tga)
+}
+
+const fn check(tga: &[u8]) {
+    if tga[1] != 1 || tga[2] != COLOR_MAPPED || tga[16] != 8 {
+        panic!("only uncompressed 8 bit color mapped tga images are supported");
+    }
+    if width(tga) % 8 != 0 || height(tga) % 8 != 0 {
+        panic!("image width and height must be multiples of 8");
+    }
+}
+
+// palette index of the pixel at x, y with y counted from the top
+pub(crate) const fn index(tga: &[u8], x: usize, y: usize) -> u8 {
+    let top_down = tga[17] & (1 << 5) != 0;
+    let row = if top_down { y } else { height(tga) - 1 - y };
+    tga[pixel_offset(tga) + row * width(tga) + x]
+}
+
+// position of pixel i of tile t in the image
+const fn tile_pixel(tga: &[u8], tile: usize, i: usize) -> (usize, usize) {
+    let tiles_wide = width(tga) / 8;
+    (
+        (tile % tiles_wide) * 8 + i % 8,
+        (tile / tiles_wide) * 8 + i / 8,
+    )
+}
+
+pub const fn tga_tiles_8bpp<const N: usize>(tga: &[u8]) -> [Tile8bpp; N] {
+    check(tga);
+    let mut tiles = [Tile8bpp([0; 16]); N];
+    let mut tile = 0;
+    while tile < N {
+        let mut i = 0;
+        while i < 64 {
+            let (x, y) = tile_pixel(tga, tile, i);
+            tiles[tile].0[i / 4] |= (index(tga, x, y) as u32) << ((i % 4) * 8);
+            i += 1;
+        }
+        tile += 1;
+    }
+    tiles
+}
+
+// pixels keep the low nibble of their index, use a palette bank for the rest
+pub const fn tga_tiles_4bpp<const N: usize>(tga: &[u8]) -> [Tile4bpp; N] {
+    check(tga);
+    let mut tiles = [Tile4bpp([0; 8]); N];
+    let mut tile = 0;
+    while tile < N {
+        let mut i = 0;
+        while i < 64 {
+            let (x, y) = tile_pixel(tga, tile, i);
+            tiles[tile].0[i / 8] |= ((index(tga, x, y) & 0xF) as u32) << ((i % 8) * 4);
+            i += 1;
+        }
+        tile += 1;
+    }
+    tiles
+}
+
+// the first N color map entries as Bgr555 colors, missing entries are black
+pub const fn tga_palette<const N: usize>(tga: &[u8]) -> [Color; N] {
+    check(tga);
+    let mut palette = [Color(0); N];
+    let entries = u16_at(tga, 5);
+    let mut i = 0;
+    while i < N && i < entries {
+        palette[i] = palette_entry(tga, i);
+        i += 1;
+    }
+    palette
+}
+
+pub(crate) const fn palette_entry(tga: &[u8], i: usize) -> Color {
+    let bytes = color_map_entry_bytes(tga);
+    let offset = color_map_offset(tga) + i * bytes;
+    if bytes == 2 {
+        // 15/16 bit entries are already 5 bits per channel, but red is high
+        let value = u16_at(tga, offset);
+        let (r, g, b) = ((value >> 10) & 0x1F, (value >> 5) & 0x1F, value & 0x1F);
+        Color((r | (g << 5) | (b << 10)) as u16)
+    } else {
+        // 24/32 bit entries are stored b, g, r
+        let (b, g, r) = (tga[offset] >> 3, tga[offset + 1] >> 3, tga[offset + 2] >> 3);
+        Color(r as u16 | (g as u16) << 5 | (b as u16) << 10)
+    }
+}
+
+// the image isn't an uncompressed 8 bit color mapped tga
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UnsupportedTga;
+
+// an indexed tga drawn as palette indices, straight onto Mode4Display or a
+// tile display without converting through Bgr555, any size is fine
+//     let tga = IndexedTga::new(include_bytes!("title.tga"))?;
+//     tga.load_palette(PaletteKind::Background, 0);
+//     Image::new(&tga, Point::zero()).draw(&mut mode4)?;
+#[derive(Debug, Copy, Clone)]
+pub struct IndexedTga<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> IndexedTga<'a> {
+    pub fn new(data: &'a [u8]) -> Result<Self, UnsupportedTga> {
+        let supported = data.len() >= HEADER_LEN
+            && data[1] == 1
+            && data[2] == COLOR_MAPPED
+            && data[16] == 8
+            && data.len() >= pixel_offset(data) + width(data) * height(data);
+        if supported {
+            Ok(IndexedTga { data })
+        } else {
+            Err(UnsupportedTga)
+        }
+    }
+
+    pub fn colors(&self) -> usize {
+        u16_at(self.data, 5)
+    }
+
+    // palette index of the pixel at x, y
+    pub(crate) fn index(&self, x: usize, y: usize) -> u8 {
+        index(self.data, x, y)
+    }
+
+    // copy the color map to palram starting at entry first
+    pub fn load_palette(&self, kind: PaletteKind, first: u8) {
+        let count = self.colors().min(256 - first as usize);
+        for i in 0..count {
+            let color = palette_entry(self.data, i);
+            kind.write(first + i as u8, Bgr555::from_gba_color(color));
+        }
+    }
+}
+
+impl<'a> ImageDrawable for IndexedTga<'a> {
+    type Color = PaletteColor;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = PaletteColor>,
+    {
+        self.draw_sub_image(target, &self.bounding_box())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = PaletteColor>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        let indices = clipped
+            .points()
+            .map(|point| PaletteColor::new(index(self.data, point.x as usize, point.y as usize)));
+        let top_left = clipped.top_left - area.top_left;
+        target.fill_contiguous(&Rectangle::new(top_left, clipped.size), indices)
+    }
+}
+
+impl<'a> OriginDimensions for IndexedTga<'a> {
+    fn size(&self) -> Size {
+        Size::new(width(self.data) as u32, height(self.data) as u32)
+    }
+}
+
+// embed a tga image as tiles and a palette, the tile and color counts come
+// from the TileImage type the result is assigned to
+#[macro_export]
+macro_rules! include_gba_image {
+    (Tile8bpp, $path:expr) => {
+        $crate::TileImage {
+            tiles: $crate::tga_tiles_8bpp(include_bytes!($path)),
+            palette: $crate::tga_palette(include_bytes!($path)),
+        }
+    };
+    (Tile4bpp, $path:expr) => {
+        $crate::TileImage {
+            tiles: $crate::tga_tiles_4bpp(include_bytes!($path)),
+            palette: $crate::tga_palette(include_bytes!($path)),
+        }
+    };
+}
@@ -0,0 +1,367 @@
+use crate::{
+    asset::{height, index, palette_entry, pixel_offset, u16_at, width, HEADER_LEN},
+    canvas::CanvasTile,
+    dedup::deduplicate,
+    obj_tiles::Bpp,
+    png,
+    tiled_layer::tiled_entry,
+    IndexedTga, PaletteColor, TileEntry,
+};
+use gba::vram::{Tile4bpp, Tile8bpp};
+use std::{
+    borrow::Cow,
+    format,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    string::String,
+    vec::Vec,
+};
+
+// asset conversion for a build script, with the assets-build feature, so the
+// files a game includes are made by the same code that reads them at runtime
+//     // build.rs
+//     let out = std::env::var("OUT_DIR").unwrap();
+//     let mut assets = AssetFile::create(format!("{}/assets.rs", out))?;
+//     assets.tiled_image("LEVEL", &std::fs::read("level.png")?, Bpp::Bpp4, true)?;
+//     assets.raw_image("TITLE", &std::fs::read("title.tga")?)?;
+//     assets.tiled_tmx("LEVEL_LAYOUT", &std::fs::read_to_string("level.tmx")?, None)?;
+//     // main.rs
+//     include!(concat!(env!("OUT_DIR"), "/assets.rs"));
+//     LEVEL_TILES.upload(0, 0);
+//     LEVEL_MAP.upload(28);
+// images are png or uncompressed tga files, tiled ones indexed pngs or 8 bit
+// color mapped tgas like include_gba_image! takes, pngs are read as the tga
+// they'd convert to, interlaced ones aren't supported
+// the written consts are the grit views, so the game needs no other loader
+
+const TRUE_COLOR: u8 = 2;
+const SCREENBLOCK_TILES: usize = 32;
+
+// writes Rust source holding converted assets
+pub struct AssetFile<W: Write> {
+    out: W,
+}
+
+impl AssetFile<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> AssetFile<W> {
+    pub fn new(out: W) -> Self {
+        AssetFile { out }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    // an indexed image as NAME_TILES, NAME_MAP and NAME_PALETTE
+    // with dedup the tiles are deduplicated with flips like deduplicate(),
+    // the map is padded to whole screenblocks in GritMap's order
+    pub fn tiled_image(
+        &mut self,
+        name: &str,
+        image: &[u8],
+        bpp: Bpp,
+        dedup: bool,
+    ) -> io::Result<()> {
+        let tga = &*as_tga(image)?;
+        check_tiled(tga)?;
+        let (tiles, map) = match bpp {
+            Bpp::Bpp4 => {
+                let (tiles, map) = tileset::<Tile4bpp>(tga, dedup)?;
+                (words(tiles.iter().map(|tile| tile.words())), map)
+            }
+            Bpp::Bpp8 => {
+                let (tiles, map) = tileset::<Tile8bpp>(tga, dedup)?;
+                (words(tiles.iter().map(|tile| tile.words())), map)
+            }
+        };
+        let tiles_wide = width(tga) / 8;
+        let tiles_high = height(tga) / 8;
+        let mut entries = Vec::new();
+        for block_y in 0..(tiles_high + SCREENBLOCK_TILES - 1) / SCREENBLOCK_TILES {
+            for block_x in 0..(tiles_wide + SCREENBLOCK_TILES - 1) / SCREENBLOCK_TILES {
+                for row in 0..SCREENBLOCK_TILES {
+                    for col in 0..SCREENBLOCK_TILES {
+                        let x = block_x * SCREENBLOCK_TILES + col;
+                        let y = block_y * SCREENBLOCK_TILES + row;
+                        let entry = if x < tiles_wide && y < tiles_high {
+                            map[y * tiles_wide + x]
+                        } else {
+                            TileEntry::new(0)
+                        };
+                        entries.extend_from_slice(&entry.0.into_inner().to_le_bytes());
+                    }
+                }
+            }
+        }
+        self.grit(&suffixed(name, "_TILES"), "GritTiles", &tiles)?;
+        self.grit(&suffixed(name, "_MAP"), "GritMap", &entries)?;
+        self.palette(&suffixed(name, "_PALETTE"), tga)
+    }
+
+    // an indexed image's color map as a GritPalette
+    pub fn palette(&mut self, name: &str, image: &[u8]) -> io::Result<()> {
+        let tga = &*as_tga(image)?;
+        IndexedTga::new(tga).map_err(|_| invalid("palettes need an indexed image"))?;
+        let colors = u16_at(tga, 5);
+        let bytes: Vec<u8> = (0..colors)
+            .flat_map(|i| palette_entry(tga, i).0.to_le_bytes())
+            .collect();
+        self.grit(name, "GritPalette", &bytes)
+    }
+
+    // an indexed or true color image as NAME, little endian Bgr555 for
+    // RawGbaImage, and NAME_WIDTH
+    //     let title = RawGbaImage::new(TITLE, TITLE_WIDTH);
+    pub fn raw_image(&mut self, name: &str, image: &[u8]) -> io::Result<()> {
+        let tga = &*as_tga(image)?;
+        if tga.len() < HEADER_LEN {
+            return Err(invalid("tga header is truncated"));
+        }
+        let (w, h) = (width(tga), height(tga));
+        let mut bytes = Vec::with_capacity(w * h * 2);
+        for y in 0..h {
+            for x in 0..w {
+                bytes.extend_from_slice(&true_color(tga, x, y)?.to_le_bytes());
+            }
+        }
+        writeln!(self.out, "pub const {}: &[u8] = &[", name)?;
+        self.bytes(&bytes)?;
+        writeln!(self.out, "];")?;
+        writeln!(self.out, "pub const {}_WIDTH: u32 = {};", name, w)
+    }
+
+    // a tile layer of a Tiled .tmx map with csv layer data as NAME, map entries
+    // for ScrollingMap or TileMap, and NAME_WIDTH, the layer with that name or
+    // the first one, gids are counted from the map's first tileset, see
+    // TiledLayer for how they become tile ids
+    //     let map = ScrollingMap::new(Background::Bg0, 28, LEVEL_LAYOUT, LEVEL_LAYOUT_WIDTH);
+    pub fn tiled_tmx(&mut self, name: &str, tmx: &str, layer: Option<&str>) -> io::Result<()> {
+        let first_gid = tmx
+            .find("<tileset")
+            .and_then(|start| attribute(&tmx[start..], "firstgid"))
+            .and_then(|gid| gid.parse().ok())
+            .unwrap_or(1);
+        let mut rest = tmx;
+        let element = loop {
+            let start = rest
+                .find("<layer")
+                .ok_or_else(|| invalid("the tmx has no such tile layer"))?;
+            rest = &rest[start..];
+            if layer.map_or(true, |layer| attribute(rest, "name") == Some(layer)) {
+                break rest;
+            }
+            rest = &rest[1..];
+        };
+        let data = element
+            .find("<data")
+            .map(|start| &element[start..])
+            .ok_or_else(|| invalid("the layer has no data"))?;
+        if attribute(data, "encoding") != Some("csv") || attribute(data, "compression").is_some() {
+            return Err(invalid("save the tmx with csv layer data"));
+        }
+        let end = data
+            .find("</data>")
+            .ok_or_else(|| invalid("the layer data is truncated"))?;
+        let csv = &data[data.find('>').unwrap_or(0) + 1..end];
+        let width = attribute(element, "width")
+            .and_then(|width| width.parse().ok())
+            .ok_or_else(|| invalid("the layer has no width"))?;
+        self.tiled_layer(name, csv, width, first_gid)
+    }
+
+    // a layer exported from Tiled as csv, one row per line, like tiled_tmx
+    pub fn tiled_csv(&mut self, name: &str, csv: &str, first_gid: u32) -> io::Result<()> {
+        let width = csv
+            .lines()
+            .map(|line| line.split(',').filter(|gid| !gid.trim().is_empty()).count())
+            .find(|&count| count > 0)
+            .ok_or_else(|| invalid("the csv has no tiles"))?;
+        self.tiled_layer(name, csv, width, first_gid)
+    }
+
+    fn tiled_layer(
+        &mut self,
+        name: &str,
+        csv: &str,
+        width: usize,
+        first_gid: u32,
+    ) -> io::Result<()> {
+        let entries = csv
+            .split(|c| c == ',' || c == '\n')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| {
+                let gid = gid.parse().map_err(|_| invalid("bad gid in the layer"))?;
+                tiled_entry(gid, first_gid)
+                    .map(|entry| entry.0.into_inner())
+                    .map_err(|_| invalid("the layer has rotated tiles or too many tiles"))
+            })
+            .collect::<io::Result<Vec<u16>>>()?;
+        if entries.len() % width != 0 {
+            return Err(invalid("the layer is not a whole number of rows"));
+        }
+        writeln!(self.out, "pub const {}: &[u16] = &[", name)?;
+        for line in entries.chunks(16) {
+            write!(self.out, "   ")?;
+            for entry in line {
+                write!(self.out, " 0x{:04X},", entry)?;
+            }
+            writeln!(self.out)?;
+        }
+        writeln!(self.out, "];")?;
+        writeln!(self.out, "pub const {}_WIDTH: usize = {};", name, width)
+    }
+
+    fn grit(&mut self, name: &str, view: &str, bytes: &[u8]) -> io::Result<()> {
+        writeln!(
+            self.out,
+            "pub const {}: embedded_graphics_gba::{}<'static> = embedded_graphics_gba::{}(&[",
+            name, view, view
+        )?;
+        self.bytes(bytes)?;
+        writeln!(self.out, "]);")
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        for line in bytes.chunks(16) {
+            write!(self.out, "   ")?;
+            for byte in line {
+                write!(self.out, " 0x{:02X},", byte)?;
+            }
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// the value of an attribute in the xml start tag element begins with
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let tag = &element[..element.find('>')?];
+    let mut pattern = String::from(" ");
+    pattern.push_str(name);
+    pattern.push_str("=\"");
+    let start = tag.find(pattern.as_str())? + pattern.len();
+    let len = tag[start..].find('"')?;
+    Some(&tag[start..start + len])
+}
+
+fn suffixed(name: &str, suffix: &str) -> String {
+    let mut name = String::from(name);
+    name.push_str(suffix);
+    name
+}
+
+// a png decoded to a tga, a tga as it is
+fn as_tga(image: &[u8]) -> io::Result<Cow<[u8]>> {
+    if png::is_png(image) {
+        png::to_tga(image).map(Cow::Owned)
+    } else {
+        Ok(Cow::Borrowed(image))
+    }
+}
+
+// what include_gba_image! panics on, as an error
+fn check_tiled(tga: &[u8]) -> io::Result<()> {
+    IndexedTga::new(tga).map_err(|_| invalid("tiled images need an indexed image"))?;
+    if width(tga) % 8 != 0 || height(tga) % 8 != 0 {
+        return Err(invalid("image width and height must be multiples of 8"));
+    }
+    Ok(())
+}
+
+// the image cut into tiles in row major order, deduplicated or not, and the
+// map entry of each
+fn tileset<T: CanvasTile>(tga: &[u8], dedup: bool) -> io::Result<(Vec<T>, Vec<TileEntry>)> {
+    let tiles_wide = width(tga) / 8;
+    let count = tiles_wide * (height(tga) / 8);
+    let tiles = (0..count)
+        .map(|tile| {
+            let mut t = T::filled(PaletteColor::TANSPARENT);
+            for i in 0..64 {
+                let (x, y) = (
+                    (tile % tiles_wide) * 8 + i % 8,
+                    (tile / tiles_wide) * 8 + i / 8,
+                );
+                let color = index(tga, x, y);
+                if color as u32 >= 1 << T::BITS {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "tile {} pixel ({}, {}), at ({}, {}) in the image, uses color {}, \
+                             past the {} colors of a {}bpp tile",
+                            tile,
+                            i % 8,
+                            i / 8,
+                            x,
+                            y,
+                            color,
+                            1 << T::BITS,
+                            T::BITS
+                        ),
+                    ));
+                }
+                t.set_pixel(i % 8, i / 8, PaletteColor::new(color));
+            }
+            Ok(t)
+        })
+        .collect::<io::Result<Vec<T>>>()?;
+    if !dedup {
+        let map = (0..count).map(|i| TileEntry::new(i as u16)).collect();
+        return Ok((tiles, map));
+    }
+    let mut unique = std::vec![T::filled(PaletteColor::TANSPARENT); count];
+    let mut map = std::vec![TileEntry::new(0); count];
+    let len = deduplicate(tiles.iter(), true, 0, &mut unique, &mut map)
+        .map_err(|_| invalid("too many unique tiles"))?;
+    unique.truncate(len);
+    Ok((unique, map))
+}
+
+fn words<'a>(tiles: impl Iterator<Item = &'a [u32]>) -> Vec<u8> {
+    tiles
+        .flatten()
+        .flat_map(|word| word.to_le_bytes())
+        .collect()
+}
+
+// Bgr555 color of the pixel at x, y with y counted from the top
+fn true_color(tga: &[u8], x: usize, y: usize) -> io::Result<u16> {
+    if IndexedTga::new(tga).is_ok() {
+        return Ok(palette_entry(tga, index(tga, x, y) as usize).0);
+    }
+    if tga.len() < HEADER_LEN || tga[1] != 0 || tga[2] != TRUE_COLOR {
+        return Err(invalid("raw images need an uncompressed tga"));
+    }
+    let bytes = (tga[16] as usize + 7) / 8;
+    let top_down = tga[17] & (1 << 5) != 0;
+    let row = if top_down { y } else { height(tga) - 1 - y };
+    let offset = pixel_offset(tga) + (row * width(tga) + x) * bytes;
+    let pixel = tga
+        .get(offset..offset + bytes)
+        .ok_or_else(|| invalid("tga pixel data is truncated"))?;
+    let (r, g, b) = match bytes {
+        // 15/16 bit pixels are already 5 bits per channel, but red is high
+        2 => {
+            let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+            ((value >> 10) & 0x1F, (value >> 5) & 0x1F, value & 0x1F)
+        }
+        // 24/32 bit pixels are stored b, g, r
+        3 | 4 => (
+            (pixel[2] >> 3) as u16,
+            (pixel[1] >> 3) as u16,
+            (pixel[0] >> 3) as u16,
+        ),
+        _ => return Err(invalid("unsupported tga pixel depth")),
+    };
+    Ok(r | g << 5 | b << 10)
+}
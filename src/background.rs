@@ -0,0 +1,60 @@
+use crate::{mmio::DISPCNT, video_state::write_shadowed};
+use embedded_graphics::prelude::*;
+use gba::io::{
+    background::{BackgroundControlSetting, BG0CNT, BG1CNT, BG2CNT, BG3CNT},
+    display::DisplayControlSetting,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Background {
+    Bg0,
+    Bg1,
+    Bg2, // affine in mode 1 and mode 2
+    Bg3, // affine in mode 2
+}
+
+impl Background {
+    pub fn control(self) -> BackgroundControlSetting {
+        match self {
+            Background::Bg0 => BG0CNT.read(),
+            Background::Bg1 => BG1CNT.read(),
+            Background::Bg2 => BG2CNT.read(),
+            Background::Bg3 => BG3CNT.read(),
+        }
+    }
+
+    pub fn set_control(self, setting: BackgroundControlSetting) {
+        match self {
+            Background::Bg0 => BG0CNT.write(setting),
+            Background::Bg1 => BG1CNT.write(setting),
+            Background::Bg2 => BG2CNT.write(setting),
+            Background::Bg3 => BG3CNT.write(setting),
+        }
+    }
+
+    // set this background's enable bit in a DISPCNT setting
+    pub fn enable(self, setting: DisplayControlSetting, enable: bool) -> DisplayControlSetting {
+        match self {
+            Background::Bg0 => setting.with_bg0(enable),
+            Background::Bg1 => setting.with_bg1(enable),
+            Background::Bg2 => setting.with_bg2(enable),
+            Background::Bg3 => setting.with_bg3(enable),
+        }
+    }
+
+    // background pixel shown at the screen's top left, for text backgrounds
+    // the offset wraps around the map, and the registers are write only
+    pub fn set_scroll(self, offset: Point) {
+        let hofs = 0x400_0010 + self as usize * 4; // BGnHOFS, BGnVOFS follows
+        write_shadowed(hofs, offset.x as u16 & 0x1FF);
+        write_shadowed(hofs + 2, offset.y as u16 & 0x1FF);
+    }
+
+    pub fn show(self) {
+        DISPCNT.write(self.enable(DISPCNT.read(), true));
+    }
+
+    pub fn hide(self) {
+        DISPCNT.write(self.enable(DISPCNT.read(), false));
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{obj_tiles::Bpp, vram};
+use gba::vram::VRAM_BASE_USIZE;
+
+const UNIT: usize = 32; // bytes, one 4bpp tile
+const UNITS: usize = 0x10000 / UNIT; // background vram, charblocks 0 to 3
+const CHARBLOCK_UNITS: usize = vram::CHARBLOCK_SIZE / UNIT;
+const SCREENBLOCK_UNITS: usize = vram::SCREENBLOCK_SIZE / UNIT;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BgVramError {
+    Full,         // no free run is long enough
+    Overlap(u16), // the run overlaps data placed before, at this byte offset
+    OutOfRange,   // past the end of background vram or the tile ids
+}
+
+// tiles handed out by BgVramAllocator, ids count from the charblock's start
+#[derive(Debug, PartialEq)]
+pub struct BgTiles {
+    charblock: usize,
+    first_tile: u16,
+    count: u16,
+    bpp: Bpp,
+}
+
+impl BgTiles {
+    pub fn charblock(&self) -> usize {
+        self.charblock
+    }
+
+    // first tile id, for the map entries
+    pub fn tile_id(&self) -> u16 {
+        self.first_tile
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.count as usize
+    }
+
+    // copy tile data to the tiles, anything past the last tile is dropped
+    pub fn upload(&self, data: &[u8]) {
+        let (start, len) = self.units();
+        let len = data.len().min(len * UNIT);
+        vram::copy_bytes(&data[..len], VRAM_BASE_USIZE + start * UNIT);
+    }
+
+    fn units(&self) -> (usize, usize) {
+        let per_tile = units_per_tile(self.bpp);
+        let start = self.charblock * CHARBLOCK_UNITS + self.first_tile as usize * per_tile;
+        (start, self.count as usize * per_tile)
+    }
+}
+
+// consecutive screenblocks handed out by BgVramAllocator, 1 for a 256x256
+// background, 2 for 512x256 or 256x512 and 4 for 512x512
+#[derive(Debug, PartialEq)]
+pub struct BgScreenblocks {
+    first: usize,
+    count: usize,
+}
+
+impl BgScreenblocks {
+    // screenblock for the background's control register
+    pub fn screenblock(&self) -> usize {
+        self.first
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    fn units(&self) -> (usize, usize) {
+        (
+            self.first * SCREENBLOCK_UNITS,
+            self.count * SCREENBLOCK_UNITS,
+        )
+    }
+}
+
+// hands out tile data and map space in background vram for the tiled modes
+// charblocks and screenblocks are two views of the same 64k, charblock 1
+// starts at screenblock 8, so tile data running into a map corrupts both
+// every allocation is checked against the others and fails instead
+// tiles are found from the start of their charblock, maps from the end of vram
+pub struct BgVramAllocator {
+    used: [u32; UNITS / 32], // bit n is the 32 bytes at offset n * 32
+}
+
+impl BgVramAllocator {
+    pub fn new() -> Self {
+        BgVramAllocator {
+            used: [0; UNITS / 32],
+        }
+    }
+
+    // first fit run of count tiles addressable from charblock
+    pub fn alloc_tiles(
+        &mut self,
+        charblock: usize,
+        count: usize,
+        bpp: Bpp,
+    ) -> Result<BgTiles, BgVramError> {
+        let (start, end) = tile_range(charblock, bpp)?;
+        let per_tile = units_per_tile(bpp);
+        let len = count * per_tile;
+        if count == 0 || start + len > end {
+            return Err(BgVramError::OutOfRange);
+        }
+        let first = (start..=end - len)
+            .step_by(per_tile)
+            .find(|first| self.first_used(*first, len).is_none())
+            .ok_or(BgVramError::Full)?;
+        self.mark(first, len, true);
+        Ok(BgTiles {
+            charblock,
+            first_tile: ((first - start) / per_tile) as u16,
+            count: count as u16,
+            bpp,
+        })
+    }
+
+    // count tiles at a fixed tile id, like data converted with known ids
+    pub fn reserve_tiles(
+        &mut self,
+        charblock: usize,
+        first_tile: u16,
+        count: usize,
+        bpp: Bpp,
+    ) -> Result<BgTiles, BgVramError> {
+        let (start, end) = tile_range(charblock, bpp)?;
+        let per_tile = units_per_tile(bpp);
+        let first = start + first_tile as usize * per_tile;
+        let len = count * per_tile;
+        if count == 0 || first + len > end {
+            return Err(BgVramError::OutOfRange);
+        }
+        self.reserve(first, len)?;
+        Ok(BgTiles {
+            charblock,
+            first_tile,
+            count: count as u16,
+            bpp,
+        })
+    }
+
+    // count consecutive free screenblocks, the highest free run
+    pub fn alloc_screenblocks(&mut self, count: usize) -> Result<BgScreenblocks, BgVramError> {
+        if count == 0 || count > 32 {
+            return Err(BgVramError::OutOfRange);
+        }
+        let len = count * SCREENBLOCK_UNITS;
+        let first = (0..=32 - count)
+            .rev()
+            .find(|first| self.first_used(first * SCREENBLOCK_UNITS, len).is_none())
+            .ok_or(BgVramError::Full)?;
+        self.mark(first * SCREENBLOCK_UNITS, len, true);
+        Ok(BgScreenblocks { first, count })
+    }
+
+    // count screenblocks starting at a fixed screenblock
+    pub fn reserve_screenblocks(
+        &mut self,
+        screenblock: usize,
+        count: usize,
+    ) -> Result<BgScreenblocks, BgVramError> {
+        if count == 0 || screenblock + count > 32 {
+            return Err(BgVramError::OutOfRange);
+        }
+        self.reserve(screenblock * SCREENBLOCK_UNITS, count * SCREENBLOCK_UNITS)?;
+        Ok(BgScreenblocks {
+            first: screenblock,
+            count,
+        })
+    }
+
+    pub fn free_tiles(&mut self, tiles: BgTiles) {
+        let (start, len) = tiles.units();
+        self.mark(start, len, false);
+    }
+
+    pub fn free_screenblocks(&mut self, screenblocks: BgScreenblocks) {
+        let (start, len) = screenblocks.units();
+        self.mark(start, len, false);
+    }
+
+    // free bytes of background vram
+    pub fn free_bytes(&self) -> usize {
+        (0..UNITS).filter(|unit| !self.is_used(*unit)).count() * UNIT
+    }
+
+    fn reserve(&mut self, start: usize, len: usize) -> Result<(), BgVramError> {
+        if let Some(unit) = self.first_used(start, len) {
+            return Err(BgVramError::Overlap((unit * UNIT) as u16));
+        }
+        self.mark(start, len, true);
+        Ok(())
+    }
+
+    fn first_used(&self, start: usize, len: usize) -> Option<usize> {
+        (start..start + len).find(|unit| self.is_used(*unit))
+    }
+
+    fn mark(&mut self, start: usize, len: usize, used: bool) {
+        for unit in start..start + len {
+            if used {
+                self.used[unit / 32] |= 1 << (unit % 32);
+            } else {
+                self.used[unit / 32] &= !(1 << (unit % 32));
+            }
+        }
+    }
+
+    fn is_used(&self, unit: usize) -> bool {
+        self.used[unit / 32] & (1 << (unit % 32)) != 0
+    }
+}
+
+impl Default for BgVramAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn units_per_tile(bpp: Bpp) -> usize {
+    match bpp {
+        Bpp::Bpp4 => 1,
+        Bpp::Bpp8 => 2,
+    }
+}
+
+// units reachable with the 1024 tile ids of a map entry from charblock
+fn tile_range(charblock: usize, bpp: Bpp) -> Result<(usize, usize), BgVramError> {
+    if charblock >= 4 {
+        return Err(BgVramError::OutOfRange);
+    }
+    let start = charblock * CHARBLOCK_UNITS;
+    let end = (start + 1024 * units_per_tile(bpp)).min(UNITS);
+    Ok((start, end))
+}
@@ -0,0 +1,63 @@
+use crate::read::ReadPixel;
+use embedded_graphics::{geometry::Size, pixelcolor::Bgr555, prelude::*};
+
+// mix a over b, alpha 0 gives b and 255 gives a
+pub fn blend(a: Bgr555, b: Bgr555, alpha: u8) -> Bgr555 {
+    let alpha = alpha as u16;
+    let channel = |a: u8, b: u8| ((a as u16 * alpha + b as u16 * (255 - alpha)) / 255) as u8;
+    Bgr555::new(
+        channel(a.r(), b.r()),
+        channel(a.g(), b.g()),
+        channel(a.b(), b.b()),
+    )
+}
+
+// draw with partial transparency onto a display that can be read back
+// each pixel is read from vram, blended in software and written again, so this
+// is much slower than drawing directly
+pub struct Translucent<D> {
+    pub display: D,
+    pub alpha: u8,
+}
+
+impl<D> Translucent<D>
+where
+    D: ReadPixel<Color = Bgr555>,
+{
+    pub fn new(display: D, alpha: u8) -> Self {
+        Translucent { display, alpha }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+}
+
+impl<D> DrawTarget for Translucent<D>
+where
+    D: ReadPixel<Color = Bgr555>,
+{
+    type Color = Bgr555;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Some(below) = self.display.read_pixel(coord) {
+                Pixel(coord, blend(color, below, self.alpha)).draw(&mut self.display)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<D> OriginDimensions for Translucent<D>
+where
+    D: ReadPixel<Color = Bgr555>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    dma, vram, Flip, Mode3Display, Mode4Display, Mode4RowWriter, Mode5Display, PaletteColor,
+};
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+use gba::vram::VRAM_BASE_USIZE;
+
+// bitmap displays that can be blitted between, a source and destination of the
+// same type can be the same framebuffer or different pages
+pub trait Framebuffer: DrawTarget {
+    const PIXEL_BYTES: usize;
+
+    // address of the top left pixel
+    fn address(&self) -> usize;
+
+    // move the pixels of area by offset with dma row copies, ordered so the
+    // rows of area are read before they are overwritten, what the area leaves
+    // behind keeps its pixels, for software scrolling and marquees
+    fn shift(&mut self, area: &Rectangle, offset: Point)
+    where
+        Self: Sized,
+    {
+        let surface = Surface::of(self);
+        copy(surface, area, surface, area.top_left + offset, Flip::None);
+    }
+}
+
+impl Framebuffer for Mode3Display {
+    const PIXEL_BYTES: usize = 2;
+
+    fn address(&self) -> usize {
+        VRAM_BASE_USIZE
+    }
+}
+
+impl Framebuffer for Mode4Display {
+    const PIXEL_BYTES: usize = 1;
+
+    fn address(&self) -> usize {
+        vram::page_address(self.page)
+    }
+}
+
+impl Framebuffer for Mode5Display {
+    const PIXEL_BYTES: usize = 2;
+
+    fn address(&self) -> usize {
+        vram::page_address(self.page)
+    }
+}
+
+// copy src_area of src to dst_top_left of dst, mirrored according to flip
+// each row goes through a buffer, so areas of one framebuffer can overlap as
+// long as they don't with a vertical flip, like Mode3Display::blit_flipped
+pub fn blit<D: Framebuffer>(
+    src: &D,
+    src_area: &Rectangle,
+    dst: &mut D,
+    dst_top_left: Point,
+    flip: Flip,
+) {
+    copy(
+        Surface::of(src),
+        src_area,
+        Surface::of(dst),
+        dst_top_left,
+        flip,
+    );
+}
+
+#[derive(Copy, Clone)]
+struct Surface {
+    address: usize,
+    size: Size,
+    pixel_bytes: usize,
+}
+
+impl Surface {
+    fn of<D: Framebuffer>(display: &D) -> Self {
+        Surface {
+            address: display.address(),
+            size: display.bounding_box().size,
+            pixel_bytes: D::PIXEL_BYTES,
+        }
+    }
+}
+
+fn copy(src: Surface, src_area: &Rectangle, dst: Surface, dst_top_left: Point, flip: Flip) {
+    let (src_size, dst_size) = (src.size, dst.size);
+    let clip = src_area.intersection(&Rectangle::new(Point::zero(), src_size));
+    let (width, height) = (src_area.size.width as i32, src_area.size.height as i32);
+
+    // where the on screen part of the source ends up, clipped to dst
+    let lo = clip.top_left - src_area.top_left;
+    let hi = lo + clip.size;
+    let (x1, x2) = if flip.horizontal() {
+        (width - hi.x, width - lo.x)
+    } else {
+        (lo.x, hi.x)
+    };
+    let (y1, y2) = if flip.vertical() {
+        (height - hi.y, height - lo.y)
+    } else {
+        (lo.y, hi.y)
+    };
+    let area = Rectangle::new(
+        dst_top_left + Point::new(x1, y1),
+        Size::new((x2 - x1) as u32, (y2 - y1) as u32),
+    )
+    .intersection(&Rectangle::new(Point::zero(), dst_size));
+
+    let len = area.size.width as usize;
+    let col = area.top_left.x - dst_top_left.x; // first column within the area
+    let src_x = (if flip.horizontal() {
+        src_area.top_left.x + width - col - len as i32
+    } else {
+        src_area.top_left.x + col
+    }) as usize;
+
+    let bottom_up = dst_top_left.y > src_area.top_left.y;
+    let mut buffer = [0u16; 240]; // a row of 16bpp pixels, or of 8bpp pixels
+    for i in 0..area.size.height as i32 {
+        let y = if bottom_up {
+            area.top_left.y + area.size.height as i32 - 1 - i
+        } else {
+            area.top_left.y + i
+        };
+        let row = y - dst_top_left.y; // row within the copied area
+        let src_y = src_area.top_left.y
+            + if flip.vertical() {
+                height - 1 - row
+            } else {
+                row
+            };
+
+        let src_row = src.address + (src_y as usize * src_size.width as usize) * src.pixel_bytes;
+        let dst_row = dst.address + (y as usize * dst_size.width as usize) * dst.pixel_bytes;
+        let dst_x = area.top_left.x as usize;
+
+        if src.pixel_bytes == 2 {
+            let pixels = &mut buffer[..len];
+            unsafe {
+                dma::copy16(
+                    (src_row + src_x * 2) as *const u16,
+                    pixels.as_mut_ptr(),
+                    len as u16,
+                )
+            };
+            if flip.horizontal() {
+                pixels.reverse();
+            }
+            unsafe {
+                dma::copy16(
+                    pixels.as_ptr(),
+                    (dst_row + dst_x * 2) as *mut u16,
+                    len as u16,
+                )
+            };
+        } else {
+            // copy the halfwords holding the row, then work on bytes
+            let first = src_x & !1;
+            let halfwords = (src_x + len + 1) / 2 - first / 2;
+            unsafe {
+                dma::copy16(
+                    (src_row + first) as *const u16,
+                    buffer.as_mut_ptr(),
+                    halfwords as u16,
+                )
+            };
+            let bytes = unsafe {
+                core::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, halfwords * 2)
+            };
+            let pixels = &mut bytes[src_x - first..src_x - first + len];
+            if flip.horizontal() {
+                pixels.reverse();
+            }
+            let mut writer = Mode4RowWriter::at(dst_row + dst_x);
+            for &index in pixels.iter() {
+                writer.push(PaletteColor::new(index));
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    area_pixels, for_each_run, read::ReadPixel, Mode4Display, Mode4RowWriter, PaletteColor,
+};
+use core::convert::Infallible;
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+use gba::vram::bitmap::{Mode4, Page};
+
+// Mode4Display writing every pixel to both pages, for chrome like a border or
+// status bar drawn once when the rest of the screen is page flipped, so it
+// doesn't have to be redrawn to the back page after every flip
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Mode4BothPagesDisplay;
+
+impl Mode4BothPagesDisplay {
+    // runs that follow each other along a row share a writer per page, like
+    // Mode4Display
+    fn write_runs<I>(&mut self, pixels: I)
+    where
+        I: IntoIterator<Item = Pixel<PaletteColor>>,
+    {
+        let mut writers: Option<(usize, Mode4RowWriter, Mode4RowWriter)> = None; // next x, y
+        for_each_run(pixels, self.size(), |x, y, len, color| {
+            let next = y * Mode4::WIDTH + x;
+            if writers.as_ref().map_or(true, |(at, _, _)| *at != next) {
+                writers = Some((
+                    next,
+                    Mode4RowWriter::new(Page::Zero, x, y),
+                    Mode4RowWriter::new(Page::One, x, y),
+                ));
+            }
+            if let Some((at, zero, one)) = writers.as_mut() {
+                zero.push_run(color, len);
+                one.push_run(color, len);
+                *at += len;
+            }
+        });
+    }
+}
+
+impl DrawTarget for Mode4BothPagesDisplay {
+    type Color = PaletteColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.write_runs(pixels);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        for page in [Page::Zero, Page::One].iter() {
+            Mode4Display { page: *page }.clear(color)?;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        for page in [Page::Zero, Page::One].iter() {
+            Mode4Display { page: *page }.fill_solid(area, color)?;
+        }
+        Ok(())
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.write_runs(area_pixels(area, colors));
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Mode4BothPagesDisplay {
+    fn size(&self) -> Size {
+        Size::new(Mode4::WIDTH as u32, Mode4::HEIGHT as u32)
+    }
+}
+
+// both pages hold the same pixels, so page 0 is read
+impl ReadPixel for Mode4BothPagesDisplay {
+    fn read_pixel(&self, point: Point) -> Option<PaletteColor> {
+        Mode4Display::page0().read_pixel(point)
+    }
+}
@@ -0,0 +1,130 @@
+use core::convert::Infallible;
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+// what to do with pixels drawn outside of a display, the displays clip them
+// silently, wrap a display in BoundsChecked to catch layout bugs instead
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BoundsPolicy {
+    Clip,        // skip them, like the displays do
+    DebugAssert, // panic in debug builds, clip in release builds
+    Error,       // stop drawing and return OutOfBounds
+}
+
+impl Default for BoundsPolicy {
+    fn default() -> Self {
+        BoundsPolicy::Clip
+    }
+}
+
+// the first point drawn outside of the display
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OutOfBounds(pub Point);
+
+pub struct BoundsChecked<D> {
+    pub display: D,
+    pub policy: BoundsPolicy,
+}
+
+impl<D> BoundsChecked<D>
+where
+    D: DrawTarget<Error = Infallible>,
+{
+    pub fn new(display: D) -> Self {
+        BoundsChecked {
+            display,
+            policy: BoundsPolicy::default(),
+        }
+    }
+
+    pub fn with_policy(self, policy: BoundsPolicy) -> Self {
+        BoundsChecked { policy, ..self }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    // apply the policy to a point outside the display, Ok means skip it
+    fn outside(policy: BoundsPolicy, point: Point) -> Result<(), OutOfBounds> {
+        match policy {
+            BoundsPolicy::Clip => Ok(()),
+            BoundsPolicy::DebugAssert => {
+                debug_assert!(false, "drawing outside of the display at {:?}", point);
+                Ok(())
+            }
+            BoundsPolicy::Error => Err(OutOfBounds(point)),
+        }
+    }
+
+    // an area must have both corners on the display
+    fn check_area(&self, area: &Rectangle) -> Result<(), OutOfBounds> {
+        let size = self.display.bounding_box().size;
+        match area.bottom_right() {
+            None => Ok(()), // empty
+            Some(_) if !inside(size, area.top_left) => Self::outside(self.policy, area.top_left),
+            Some(corner) if !inside(size, corner) => Self::outside(self.policy, corner),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+impl<D> DrawTarget for BoundsChecked<D>
+where
+    D: DrawTarget<Error = Infallible>,
+{
+    type Color = D::Color;
+    type Error = OutOfBounds;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        // pixels go to the display as one iterator so its fast paths still
+        // apply, the first error ends the iterator and skipped pixels are
+        // clipped by the display
+        let size = self.display.bounding_box().size;
+        let policy = self.policy;
+        let mut result = Ok(());
+        let pixels = pixels.into_iter().take_while(|Pixel(point, _)| {
+            inside(size, *point) || {
+                result = Self::outside(policy, *point);
+                result.is_ok()
+            }
+        });
+        self.display.draw_iter(pixels).ok();
+        result
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.check_area(area)?;
+        self.display.fill_contiguous(area, colors).ok();
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.check_area(area)?;
+        self.display.fill_solid(area, color).ok();
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.clear(color).ok();
+        Ok(())
+    }
+}
+
+impl<D> OriginDimensions for BoundsChecked<D>
+where
+    D: DrawTarget<Error = Infallible>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+fn inside(size: Size, point: Point) -> bool {
+    point.x >= 0 && point.y >= 0 && point.x < size.width as i32 && point.y < size.height as i32
+}
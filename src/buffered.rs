@@ -0,0 +1,252 @@
+use crate::{
+    dma,
+    dma_queue::{DmaQueue, Transfer},
+    flush::Flush,
+    frame::{OutOfVBlank, VBlankBudget},
+    queued::QueueFull,
+    read::ReadPixel,
+};
+use core::convert::{Infallible, TryInto};
+use embedded_graphics::{
+    geometry::Size,
+    pixelcolor::{raw::RawU16, Bgr555},
+    prelude::*,
+};
+use gba::vram::{bitmap::Mode3, VRAM_BASE_USIZE};
+
+const WIDTH: usize = Mode3::WIDTH;
+const HEIGHT: usize = Mode3::HEIGHT;
+const CLEAN: (u8, u8) = (u8::MAX, 0);
+const FULL: (u8, u8) = (0, WIDTH as u8 - 1);
+pub const MODE3_BUFFER_WORDS: usize = WIDTH * HEIGHT / 2; // two pixels per word
+
+// Mode3Display drawing to a buffer that flush() copies to vram with dma
+// the 75KiB buffer only fits in ewram, so declare it in an ewram section:
+//     #[link_section = ".ewram"]
+//     static mut BUFFER: [u32; MODE3_BUFFER_WORDS] = [0; MODE3_BUFFER_WORDS];
+// words keep it aligned for 32 bit dma, the left pixel is the low halfword
+// only the span of each row drawn to since the last flush is copied, a full
+// copy takes longer than vblank, with_split(n) also limits each flush to a 1/n
+// band of rows, so a full frame is shown over n vblanks
+pub struct BufferedMode3Display<'a> {
+    buffer: &'a mut [u32; MODE3_BUFFER_WORDS],
+    dirty: [(u8, u8); HEIGHT], // inclusive first and last dirty column, first > last is clean
+    parts: usize,
+    next: usize, // part copied by the next flush
+}
+
+impl<'a> BufferedMode3Display<'a> {
+    pub fn new(buffer: &'a mut [u32; MODE3_BUFFER_WORDS]) -> Self {
+        BufferedMode3Display {
+            buffer,
+            dirty: [CLEAN; HEIGHT],
+            parts: 1,
+            next: 0,
+        }
+    }
+
+    pub fn with_split(self, parts: usize) -> Self {
+        BufferedMode3Display {
+            parts: parts.max(1).min(HEIGHT),
+            next: 0,
+            ..self
+        }
+    }
+
+    // copy everything on the next flush, like after vram was drawn to directly
+    pub fn invalidate(&mut self) {
+        self.dirty = [FULL; HEIGHT];
+    }
+
+    // copy the dirty spans of rows first..last (exclusive) to vram
+    // runs of fully dirty rows are copied in one transfer
+    pub fn flush_rows(&mut self, first: usize, last: usize) {
+        self.copy_rows(
+            first,
+            last,
+            || HEIGHT,
+            |copy| {
+                unsafe { dma::run(copy) };
+                true
+            },
+        );
+    }
+
+    // like flush() but each copy is only started while budget has lines left,
+    // so none of them run into the drawn screen, rows that weren't copied stay
+    // dirty for the next vblank, runs of full rows are split into copies of
+    // no more rows than there are lines left, a row copies in under a line
+    //     draw_budgeted(2, |budget| buffer.flush_budgeted(budget))
+    pub fn flush_budgeted(&mut self, budget: &VBlankBudget) -> Result<(), OutOfVBlank> {
+        let rows = (HEIGHT + self.parts - 1) / self.parts;
+        let first = self.next * rows;
+        let lines_left = || budget.lines_left() as usize;
+        let copied = self.copy_rows(first, first + rows, lines_left, |copy| {
+            if budget.check().is_err() {
+                return false;
+            }
+            unsafe { dma::run(copy) };
+            true
+        });
+        if !copied {
+            return Err(OutOfVBlank);
+        }
+        self.next = (self.next + 1) % self.parts;
+        Ok(())
+    }
+
+    // like flush() but the copies go on a dma queue, so this returns without
+    // waiting for them, the band advances once all of its copies are queued
+    // rows that didn't fit stay dirty for the next call
+    // drawing before the copies complete is fine, the rows are dirty again
+    pub fn flush_queued<const N: usize>(
+        &mut self,
+        queue: &mut DmaQueue<N>,
+    ) -> Result<(), QueueFull> {
+        let rows = (HEIGHT + self.parts - 1) / self.parts;
+        let first = self.next * rows;
+        let queued = self.copy_rows(
+            first,
+            first + rows,
+            || HEIGHT,
+            |transfer| queue.push(transfer).is_ok(),
+        );
+        if !queued {
+            return Err(QueueFull);
+        }
+        self.next = (self.next + 1) % self.parts;
+        Ok(())
+    }
+
+    // hand each dirty span of rows first..last to copy, marking rows clean as
+    // copy accepts them, false when copy refused one
+    fn copy_rows(
+        &mut self,
+        first: usize,
+        last: usize,
+        max_rows: impl Fn() -> usize,
+        mut copy: impl FnMut(Transfer) -> bool,
+    ) -> bool {
+        let last = last.min(HEIGHT);
+        let mut run: Option<usize> = None; // first row of a run of full rows
+        for row in first..last {
+            let (x1, x2) = self.dirty[row];
+            if (x1, x2) == FULL {
+                run.get_or_insert(row);
+                continue;
+            }
+            if let Some(start) = run.take() {
+                if !self.copy_run(start, row, &max_rows, &mut copy) {
+                    return false;
+                }
+            }
+            if x1 <= x2 && !copy(self.transfer(row, x1 as usize, (x2 - x1) as usize + 1)) {
+                return false;
+            }
+            self.dirty[row] = CLEAN;
+        }
+        match run {
+            Some(start) => self.copy_run(start, last, &max_rows, &mut copy),
+            None => true,
+        }
+    }
+
+    // full rows start to end, in copies of at most max_rows() rows each
+    fn copy_run(
+        &mut self,
+        mut start: usize,
+        end: usize,
+        max_rows: &impl Fn() -> usize,
+        copy: &mut impl FnMut(Transfer) -> bool,
+    ) -> bool {
+        while start < end {
+            let stop = end.min(start + max_rows().max(1));
+            if !copy(self.transfer(start, 0, (stop - start) * WIDTH)) {
+                return false;
+            }
+            self.dirty[start..stop].fill(CLEAN);
+            start = stop;
+        }
+        true
+    }
+
+    // the copy of count pixels starting at x, y to vram
+    fn transfer(&self, y: usize, x: usize, count: usize) -> Transfer {
+        let index = y * WIDTH + x;
+        let dst = VRAM_BASE_USIZE + index * 2;
+        if index % 2 == 0 && count % 2 == 0 {
+            Transfer {
+                src: self.buffer[index / 2..].as_ptr() as usize,
+                dst,
+                count: (count / 2) as u16,
+                wide: true,
+            }
+        } else {
+            Transfer {
+                src: self.buffer.as_ptr() as usize + index * 2,
+                dst,
+                count: count as u16,
+                wide: false,
+            }
+        }
+    }
+}
+
+impl<'a> Flush for BufferedMode3Display<'a> {
+    fn flush(&mut self) {
+        let rows = (HEIGHT + self.parts - 1) / self.parts;
+        self.flush_rows(self.next * rows, (self.next + 1) * rows);
+        self.next = (self.next + 1) % self.parts;
+    }
+}
+
+impl<'a> DrawTarget for BufferedMode3Display<'a> {
+    type Color = Bgr555;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x @ 0..=239, y @ 0..=159)) = coord.try_into() {
+                let (x, y): (u32, u32) = (x, y);
+                let index = y as usize * WIDTH + x as usize;
+                let word = &mut self.buffer[index / 2];
+                let shift = (index % 2) * 16;
+                *word &= !(0xFFFF << shift); // clear halfword
+                *word |= (color.into_storage() as u32) << shift; // set halfword
+                let span = &mut self.dirty[y as usize];
+                *span = (span.0.min(x as u8), span.1.max(x as u8));
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let color = color.into_storage() as u32;
+        let word = color | color << 16;
+        unsafe { dma::fill32(&word, self.buffer.as_mut_ptr(), MODE3_BUFFER_WORDS as u16) };
+        self.invalidate();
+        Ok(())
+    }
+}
+
+impl<'a> OriginDimensions for BufferedMode3Display<'a> {
+    fn size(&self) -> Size {
+        Size::new(WIDTH as u32, HEIGHT as u32)
+    }
+}
+
+impl<'a> ReadPixel for BufferedMode3Display<'a> {
+    fn read_pixel(&self, point: Point) -> Option<Bgr555> {
+        if let Ok((x @ 0..=239, y @ 0..=159)) = point.try_into() {
+            let (x, y): (u32, u32) = (x, y);
+            let index = y as usize * WIDTH + x as usize;
+            let color = self.buffer[index / 2] >> ((index % 2) * 16);
+            Some(Bgr555::from(RawU16::new(color as u16)))
+        } else {
+            None
+        }
+    }
+}
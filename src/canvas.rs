@@ -0,0 +1,324 @@
+use crate::{dma, sprite::ObjMapping, vram, PaletteColor, PaletteColor4};
+use core::convert::{Infallible, TryInto};
+use embedded_graphics::{geometry::Size, prelude::*};
+use gba::vram::{Tile4bpp, Tile8bpp};
+
+const OBJ_VRAM_BASE: usize = 0x601_0000;
+const TILE_ID_BYTES: usize = 32; // obj tile ids count 32 byte units
+
+// 8bpp drawing surface for a multi tile object, W by H tiles
+// a SpriteCanvas<4, 4> backs a 32x32 object
+pub struct SpriteCanvas<const W: usize, const H: usize> {
+    pub tiles: [[Tile8bpp; W]; H],
+}
+
+impl<const W: usize, const H: usize> SpriteCanvas<W, H> {
+    pub fn new(color: PaletteColor) -> Self {
+        let word = u32::from_ne_bytes([color.into_storage(); 4]);
+        SpriteCanvas {
+            tiles: [[Tile8bpp([word; 16]); W]; H],
+        }
+    }
+
+    // solidity of every tile, see TileCanvas::masks
+    pub fn masks(&self) -> [[TileMask; W]; H] {
+        masks(&self.tiles)
+    }
+
+    // copy the tiles to obj vram for an object using tile_id
+    // 8bpp tiles take two tile ids each, so tile_id should be even
+    pub fn upload(&self, tile_id: u16, mapping: ObjMapping) {
+        for (row, tiles) in self.tiles.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                let id = mapping.tile_id(tile_id, col as u16 * 2, row as u16, W as u16 * 2);
+                let dst = (OBJ_VRAM_BASE + id as usize * TILE_ID_BYTES) as *mut u32;
+                unsafe { dma::copy32(tile.0.as_ptr(), dst, 16) };
+            }
+        }
+    }
+}
+
+impl<const W: usize, const H: usize> DrawTarget for SpriteCanvas<W, H> {
+    type Color = PaletteColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x, y)) = coord.try_into() {
+                let (x, y): (u32, u32) = (x, y);
+                if x as usize >= W * 8 || y as usize >= H * 8 {
+                    continue;
+                }
+                let tile = &mut self.tiles[y as usize / 8][x as usize / 8];
+                let index: u32 = (x % 8) + ((y % 8) * 8); // index into [u8; 64] array
+                let word: &mut u32 = &mut tile.0[index as usize / 4];
+                *word &= !(0xFF << ((index % 4) * 8)); // clear byte
+                *word |= (color.into_storage() as u32) << ((index % 4) * 8); // set byte
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<const W: usize, const H: usize> OriginDimensions for SpriteCanvas<W, H> {
+    fn size(&self) -> Size {
+        Size::new(W as u32 * 8, H as u32 * 8)
+    }
+}
+
+// the solid pixels of a tile, bit x of row y is set where the pixel isn't
+// transparent, for per pixel collision against the art that is drawn
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct TileMask(pub [u8; 8]);
+
+impl TileMask {
+    pub const EMPTY: Self = TileMask([0; 8]);
+    pub const FULL: Self = TileMask([0xFF; 8]);
+
+    pub fn is_solid(&self, x: usize, y: usize) -> bool {
+        x < 8 && y < 8 && self.0[y] & (1 << x) != 0
+    }
+
+    // no solid pixels, nothing to collide with
+    pub fn is_empty(&self) -> bool {
+        *self == Self::EMPTY
+    }
+
+    // every pixel solid, per tile collision is enough
+    pub fn is_full(&self) -> bool {
+        *self == Self::FULL
+    }
+
+    // whether any solid pixel of other, moved by offset, lands on one of this
+    pub fn overlaps(&self, other: &TileMask, offset: Point) -> bool {
+        if offset.x.abs() >= 8 || offset.y.abs() >= 8 {
+            return false;
+        }
+        (0..8).any(|y| {
+            let other_y = y - offset.y;
+            if !(0..8).contains(&other_y) {
+                return false;
+            }
+            let row = other.0[other_y as usize];
+            let moved = if offset.x >= 0 {
+                row << offset.x
+            } else {
+                row >> -offset.x
+            };
+            self.0[y as usize] & moved != 0
+        })
+    }
+}
+
+// a 4bpp or 8bpp tile as packed palette indices
+pub trait CanvasTile: Copy {
+    const BITS: usize;
+
+    fn filled(color: PaletteColor) -> Self;
+    fn words(&self) -> &[u32];
+
+    // the tile is in ram, but the vram pixel helpers work on any halfword
+    fn pixel(&self, x: usize, y: usize) -> PaletteColor;
+    fn set_pixel(&mut self, x: usize, y: usize, color: PaletteColor);
+
+    // the pixels that aren't index 0
+    fn mask(&self) -> TileMask {
+        let mut mask = TileMask::EMPTY;
+        for (y, row) in mask.0.iter_mut().enumerate() {
+            for x in 0..8 {
+                if self.pixel(x, y) != PaletteColor::TANSPARENT {
+                    *row |= 1 << x;
+                }
+            }
+        }
+        mask
+    }
+}
+
+impl CanvasTile for Tile4bpp {
+    const BITS: usize = 4;
+
+    fn filled(color: PaletteColor) -> Self {
+        Tile4bpp([u32::from_ne_bytes([color.into_storage() & 0xF; 4]) * 0x11; 8])
+    }
+
+    fn words(&self) -> &[u32] {
+        &self.0
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> PaletteColor {
+        let index = y * 8 + x;
+        let address = self.0.as_ptr() as usize + index / 4 * 2; // halfword of 4 pixels
+        PaletteColor::new(vram::read4(address, index))
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: PaletteColor) {
+        let index = y * 8 + x;
+        let address = self.0.as_mut_ptr() as usize + index / 4 * 2; // halfword of 4 pixels
+        vram::write4(address, index, color.into_storage());
+    }
+}
+
+impl CanvasTile for Tile8bpp {
+    const BITS: usize = 8;
+
+    fn filled(color: PaletteColor) -> Self {
+        Tile8bpp([u32::from_ne_bytes([color.into_storage(); 4]); 16])
+    }
+
+    fn words(&self) -> &[u32] {
+        &self.0
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> PaletteColor {
+        let index = y * 8 + x;
+        PaletteColor::new(self.0[index / 4].to_le_bytes()[index % 4])
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, color: PaletteColor) {
+        let index = y * 8 + x;
+        vram::write8(self.0.as_mut_ptr() as usize + index, color.into_storage());
+    }
+}
+
+// background tiles drawn as one W by H tile canvas, then copied to a charblock
+// in one transfer, like a 32x32 icon as TileCanvas<Tile4bpp, 4, 4>
+// tiles are stored and uploaded in row major order
+pub struct TileCanvas<T, const W: usize, const H: usize> {
+    pub tiles: [[T; W]; H],
+}
+
+impl<T: CanvasTile, const W: usize, const H: usize> TileCanvas<T, W, H> {
+    pub fn new(color: PaletteColor) -> Self {
+        TileCanvas {
+            tiles: [[T::filled(color); W]; H],
+        }
+    }
+
+    // solidity of every tile, worked out from the drawn tiles when asked for
+    // so drawing doesn't pay for it, keep the result next to the uploaded
+    // tiles for collision checks
+    //     let solid = canvas.masks();
+    //     let blocked = solid[ty][tx].is_solid(px % 8, py % 8);
+    pub fn masks(&self) -> [[TileMask; W]; H] {
+        masks(&self.tiles)
+    }
+
+    // copy every tile to charblock, the top left tile at index start_index
+    pub fn upload(&self, charblock: usize, start_index: usize) {
+        copy_tiles::<T>(
+            self.tiles.as_ptr() as *const u32,
+            W * H,
+            charblock,
+            start_index,
+        );
+    }
+}
+
+// copy tiles, like a deduplicated tileset, to charblock starting at start_index
+pub fn upload_tiles<T: CanvasTile>(tiles: &[T], charblock: usize, start_index: usize) {
+    copy_tiles::<T>(
+        tiles.as_ptr() as *const u32,
+        tiles.len(),
+        charblock,
+        start_index,
+    );
+}
+
+fn masks<T: CanvasTile, const W: usize, const H: usize>(tiles: &[[T; W]; H]) -> [[TileMask; W]; H] {
+    let mut masks = [[TileMask::EMPTY; W]; H];
+    for (masks, tiles) in masks.iter_mut().zip(tiles.iter()) {
+        for (mask, tile) in masks.iter_mut().zip(tiles.iter()) {
+            *mask = tile.mask();
+        }
+    }
+    masks
+}
+
+fn copy_tiles<T: CanvasTile>(src: *const u32, count: usize, charblock: usize, start_index: usize) {
+    let tile_bytes = T::BITS * 8;
+    let dst = vram::charblock_address(charblock) + start_index * tile_bytes;
+    let words = count * tile_bytes / 4;
+    assert!(
+        dst + words * 4 <= vram::charblock_address(6),
+        "tiles would run past the end of vram"
+    );
+    unsafe { dma::copy32(src, dst as *mut u32, words as u16) };
+}
+
+impl<T: CanvasTile, const W: usize, const H: usize> DrawTarget for TileCanvas<T, W, H> {
+    type Color = PaletteColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x, y)) = coord.try_into() {
+                let (x, y): (u32, u32) = (x, y);
+                let (x, y) = (x as usize, y as usize);
+                if x < W * 8 && y < H * 8 {
+                    self.tiles[y / 8][x / 8].set_pixel(x % 8, y % 8, color);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: CanvasTile, const W: usize, const H: usize> OriginDimensions for TileCanvas<T, W, H> {
+    fn size(&self) -> Size {
+        Size::new(W as u32 * 8, H as u32 * 8)
+    }
+}
+
+// TileCanvas of 4bpp tiles drawn with PaletteColor4, the bank of each color is
+// dropped and only its index packed, pick the bank on the map entries
+//     let mut icon = TileCanvas4bpp::<4, 4>::new(PaletteColor4::new(0, 0));
+//     icon.upload(0, 16);
+pub struct TileCanvas4bpp<const W: usize, const H: usize> {
+    pub canvas: TileCanvas<Tile4bpp, W, H>,
+}
+
+impl<const W: usize, const H: usize> TileCanvas4bpp<W, H> {
+    pub fn new(color: PaletteColor4) -> Self {
+        TileCanvas4bpp {
+            canvas: TileCanvas::new(PaletteColor::new(color.index())),
+        }
+    }
+
+    pub fn masks(&self) -> [[TileMask; W]; H] {
+        self.canvas.masks()
+    }
+
+    // copy every tile to charblock, the top left tile at index start_index
+    pub fn upload(&self, charblock: usize, start_index: usize) {
+        self.canvas.upload(charblock, start_index);
+    }
+}
+
+impl<const W: usize, const H: usize> DrawTarget for TileCanvas4bpp<W, H> {
+    type Color = PaletteColor4;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.canvas.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(coord, color)| Pixel(coord, PaletteColor::new(color.index()))),
+        )
+    }
+}
+
+impl<const W: usize, const H: usize> OriginDimensions for TileCanvas4bpp<W, H> {
+    fn size(&self) -> Size {
+        self.canvas.size()
+    }
+}
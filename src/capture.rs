@@ -0,0 +1,281 @@
+use crate::{
+    dma,
+    mmio::{self, DISPCNT},
+    vram,
+};
+use embedded_graphics::{
+    geometry::Size,
+    pixelcolor::{raw::RawU16, Bgr555, Rgb888},
+    prelude::*,
+};
+use gba::{
+    io::display::DisplayMode,
+    vram::{bitmap::Page, VRAM_BASE_USIZE},
+};
+
+// frame capture for tests running in an emulator, hash or copy what is shown
+// so rendering can be compared against known frames
+
+const BG_PALRAM: usize = 0x500_0000;
+const PALRAM_BYTES: usize = 0x200;
+const SRAM_BASE: usize = 0xE00_0000;
+const SRAM_BYTES: usize = 0x1_0000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CaptureTooLarge;
+
+// the vram holding the shown frame, as an address and length in bytes
+// the bitmap modes are the shown page, the tiled modes are all of background
+// vram since any of it can be on screen
+pub fn shown_frame() -> (usize, usize) {
+    let dispcnt = DISPCNT.read();
+    let page = if dispcnt.frame1() {
+        Page::One
+    } else {
+        Page::Zero
+    };
+    match dispcnt.mode() {
+        DisplayMode::Mode3 => (VRAM_BASE_USIZE, 240 * 160 * 2),
+        DisplayMode::Mode4 => (vram::page_address(page), 240 * 160),
+        DisplayMode::Mode5 => (vram::page_address(page), 160 * 128 * 2),
+        _ => (VRAM_BASE_USIZE, 0x1_0000),
+    }
+}
+
+// fnv-1a hash of the shown frame, plus the background palette for every mode
+// except mode 3 and mode 5, where pixels are colors
+pub fn checksum() -> u32 {
+    let (address, bytes) = shown_frame();
+    let mut hash = hash_halfwords(FNV_OFFSET, address, bytes);
+    match DISPCNT.read().mode() {
+        DisplayMode::Mode3 | DisplayMode::Mode5 => {}
+        _ => hash = hash_halfwords(hash, BG_PALRAM, PALRAM_BYTES),
+    }
+    hash
+}
+
+// copy the shown frame into buffer, like one in ewram, returns the bytes copied
+pub fn capture(buffer: &mut [u32]) -> Result<usize, CaptureTooLarge> {
+    let (address, bytes) = shown_frame();
+    if buffer.len() * 4 < bytes {
+        return Err(CaptureTooLarge);
+    }
+    for (i, chunk) in buffer[..bytes / 4].chunks_mut(0x4000).enumerate() {
+        let src = (address + i * 0x1_0000) as *const u32;
+        unsafe { dma::copy32(src, chunk.as_mut_ptr(), chunk.len() as u16) };
+    }
+    Ok(bytes)
+}
+
+// copy the shown frame to sram at offset, sram only takes byte writes, and a
+// mode 3 frame doesn't fit in 64KiB
+pub fn capture_sram(offset: usize) -> Result<usize, CaptureTooLarge> {
+    let (address, bytes) = shown_frame();
+    if offset + bytes > SRAM_BYTES {
+        return Err(CaptureTooLarge);
+    }
+    for i in (0..bytes).step_by(2) {
+        let [low, high] = mmio::read16(address + i).to_le_bytes();
+        let dst = (SRAM_BASE + offset + i) as *mut u8;
+        unsafe {
+            dst.write_volatile(low);
+            dst.add(1).write_volatile(high);
+        }
+    }
+    Ok(bytes)
+}
+
+// pixel formats export() writes, row major with no padding
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExportFormat {
+    Bgr555, // the gba's own little endian halfwords, red in the low bits
+    Rgb888, // 3 bytes a pixel, red first, like a png's rgb rows
+}
+
+impl ExportFormat {
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            ExportFormat::Bgr555 => 2,
+            ExportFormat::Rgb888 => 3,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExportError {
+    NotBitmapMode, // the tiled modes have no framebuffer to walk
+    BufferTooSmall,
+}
+
+// the shown frame of a bitmap mode as colors, mode 4's palette indices looked
+// up in palram, for documentation screenshots and test goldens that tools
+// outside the rom turn back into images
+//     let mut screen = [0; 240 * 160 * 3];
+//     let size = export(&mut screen, ExportFormat::Rgb888)?;
+// returns the size of the image, 240x160 or 160x128 for mode 5
+pub fn export(buffer: &mut [u8], format: ExportFormat) -> Result<Size, ExportError> {
+    let (address, _) = shown_frame();
+    let mode = DISPCNT.read().mode();
+    let size = match mode {
+        DisplayMode::Mode3 | DisplayMode::Mode4 => Size::new(240, 160),
+        DisplayMode::Mode5 => Size::new(160, 128),
+        _ => return Err(ExportError::NotBitmapMode),
+    };
+    let pixels = (size.width * size.height) as usize;
+    let bytes = format.bytes_per_pixel();
+    if buffer.len() < pixels * bytes {
+        return Err(ExportError::BufferTooSmall);
+    }
+    for (i, out) in buffer[..pixels * bytes].chunks_exact_mut(bytes).enumerate() {
+        let raw = match mode {
+            DisplayMode::Mode4 => {
+                let halfword = mmio::read16(address + (i & !1));
+                let index = (halfword >> ((i & 1) * 8)) & 0xFF;
+                mmio::read16(BG_PALRAM + index as usize * 2)
+            }
+            _ => mmio::read16(address + i * 2),
+        };
+        match format {
+            ExportFormat::Bgr555 => out.copy_from_slice(&raw.to_le_bytes()),
+            ExportFormat::Rgb888 => {
+                let color = Rgb888::from(Bgr555::from(RawU16::new(raw)));
+                out.copy_from_slice(&[color.r(), color.g(), color.b()]);
+            }
+        }
+    }
+    Ok(size)
+}
+
+// a test report in sram, read back by the harness feature's runner: the magic,
+// the scene count as a little endian u32, then each scene's checksum, the
+// magic is written last so a half written report isn't read
+pub const REPORT_MAGIC: [u8; 4] = *b"EGBA";
+pub const REPORT_HEADER: usize = 8;
+
+// record the checksum of the shown frame as scene's result
+pub fn report_scene(scene: usize) -> Result<(), CaptureTooLarge> {
+    let offset = REPORT_HEADER + scene * 4;
+    if offset + 4 > SRAM_BYTES {
+        return Err(CaptureTooLarge);
+    }
+    write_sram(offset, &checksum().to_le_bytes());
+    Ok(())
+}
+
+// mark the report complete after scenes scenes
+pub fn report_done(scenes: usize) {
+    write_sram(4, &(scenes as u32).to_le_bytes());
+    write_sram(0, &REPORT_MAGIC);
+}
+
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"EGBS";
+// DISPCNT and BG0CNT to BG3CNT
+const SNAPSHOT_REGISTERS: [usize; 5] = [0x400_0000, 0x400_0008, 0x400_000A, 0x400_000C, 0x400_000E];
+
+// a compressed snapshot of the display in sram, for finding out what was on
+// screen when a game crashed on real hardware, call it from the panic handler
+// and dump the save file later
+//     #[panic_handler]
+//     fn panic(_: &PanicInfo) -> ! {
+//         let _ = snapshot_sram(0);
+//         loop {}
+//     }
+// the layout, all little endian: SNAPSHOT_MAGIC, DISPCNT, BG0CNT to BG3CNT, 2
+// bytes of padding, the 512 byte background palette, then the shown frame as
+// shown_frame() sees it in the bios run length format, header included, which
+// compressed_header() and grit's tools read, the magic is cleared first and
+// written last so a snapshot that didn't fit or was cut short isn't read
+// only volatile reads and byte writes are used, no dma that the crash may have
+// left running, returns the bytes written, a busy mode 3 frame can compress
+// to more than sram holds
+pub fn snapshot_sram(offset: usize) -> Result<usize, CaptureTooLarge> {
+    if offset + SNAPSHOT_MAGIC.len() > SRAM_BYTES {
+        return Err(CaptureTooLarge);
+    }
+    // so an older snapshot isn't left marked complete while this one overwrites it
+    write_sram(offset, &[0; 4]);
+    let mut sram = SramWriter {
+        offset: offset + REPORT_MAGIC.len(),
+    };
+    for register in SNAPSHOT_REGISTERS.iter() {
+        sram.write_all(&mmio::read16(*register).to_le_bytes())?;
+    }
+    sram.write_all(&[0, 0])?;
+    for i in (0..PALRAM_BYTES).step_by(2) {
+        sram.write_all(&mmio::read16(BG_PALRAM + i).to_le_bytes())?;
+    }
+    let (address, bytes) = shown_frame();
+    sram.write_all(&(0x30 | ((bytes as u32) << 8)).to_le_bytes())?;
+    let byte = |i: usize| mmio::read16(address + (i & !1)).to_le_bytes()[i & 1];
+    let mut literal = [0; 128];
+    let mut literals = 0;
+    let mut i = 0;
+    while i < bytes {
+        let value = byte(i);
+        let mut run = 1;
+        while run < 130 && i + run < bytes && byte(i + run) == value {
+            run += 1;
+        }
+        if run >= 3 {
+            sram.write_literals(&literal[..literals])?;
+            literals = 0;
+            sram.write_all(&[0x80 | (run - 3) as u8, value])?;
+            i += run;
+        } else {
+            literal[literals] = value;
+            literals += 1;
+            if literals == literal.len() {
+                sram.write_literals(&literal)?;
+                literals = 0;
+            }
+            i += 1;
+        }
+    }
+    sram.write_literals(&literal[..literals])?;
+    write_sram(offset, &SNAPSHOT_MAGIC);
+    Ok(sram.offset - offset)
+}
+
+// bytes written to sram one at a time, failing at the end of sram
+struct SramWriter {
+    offset: usize,
+}
+
+impl SramWriter {
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), CaptureTooLarge> {
+        if self.offset + bytes.len() > SRAM_BYTES {
+            return Err(CaptureTooLarge);
+        }
+        write_sram(self.offset, bytes);
+        self.offset += bytes.len();
+        Ok(())
+    }
+
+    // an uncompressed block of 1 to 128 bytes, nothing for none
+    fn write_literals(&mut self, bytes: &[u8]) -> Result<(), CaptureTooLarge> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        self.write_all(&[(bytes.len() - 1) as u8])?;
+        self.write_all(bytes)
+    }
+}
+
+fn write_sram(offset: usize, bytes: &[u8]) {
+    for (i, byte) in bytes.iter().enumerate() {
+        let dst = (SRAM_BASE + offset + i) as *mut u8;
+        unsafe { dst.write_volatile(*byte) };
+    }
+}
+
+const FNV_OFFSET: u32 = 0x811C_9DC5;
+const FNV_PRIME: u32 = 0x0100_0193;
+
+fn hash_halfwords(mut hash: u32, address: usize, bytes: usize) -> u32 {
+    for i in (0..bytes).step_by(2) {
+        for byte in mmio::read16(address + i).to_le_bytes().iter() {
+            hash = (hash ^ *byte as u32).wrapping_mul(FNV_PRIME);
+        }
+    }
+    hash
+}
@@ -0,0 +1,228 @@
+use crate::{
+    arena::Vram,
+    bg_vram::{BgTiles, BgVramError},
+    dma,
+    obj_tiles::Bpp,
+    vram, PaletteColor, PaletteColor4,
+};
+use core::convert::{Infallible, TryInto};
+use embedded_graphics::{geometry::Size, prelude::*};
+
+const TILE_BYTES: usize = 64; // 8bpp
+const TILE_BYTES_4BPP: usize = 32;
+const VRAM_CHARBLOCKS: usize = 6; // 4 for backgrounds, 2 for objects
+
+// 8bpp tiles starting at a charblock, drawn as one canvas of tiles_wide by
+// tiles_high tiles in row major order
+// a single charblock holds 256 tiles (128x128 pixels), larger canvases run
+// into the following charblocks, so 256x256 pixels takes all 4 bg charblocks
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CharblockDisplay {
+    charblock: usize,
+    tiles_wide: usize,
+    tiles_high: usize,
+}
+
+impl CharblockDisplay {
+    // one charblock as 16x16 tiles
+    pub fn new(charblock: usize) -> Self {
+        Self::with_tiles(charblock, 16, 16)
+    }
+
+    // a charblock picked by hand bypasses the Vram arena, see from_vram
+    pub fn with_tiles(charblock: usize, tiles_wide: usize, tiles_high: usize) -> Self {
+        let end = charblock * vram::CHARBLOCK_SIZE + tiles_wide * tiles_high * TILE_BYTES;
+        assert!(
+            end <= VRAM_CHARBLOCKS * vram::CHARBLOCK_SIZE,
+            "tiles would run past the end of vram"
+        );
+        CharblockDisplay {
+            charblock,
+            tiles_wide,
+            tiles_high,
+        }
+    }
+
+    // tiles_wide by tiles_high background tiles claimed from vram, from tile
+    // id 0 of a charblock, hand them back with vram.bg.free_tiles
+    pub fn from_vram(
+        vram: &mut Vram,
+        tiles_wide: usize,
+        tiles_high: usize,
+    ) -> Result<(Self, BgTiles), BgVramError> {
+        let tiles = vram.tiles_at_start(0..4, tiles_wide * tiles_high, Bpp::Bpp8)?;
+        let display = Self::with_tiles(tiles.charblock(), tiles_wide, tiles_high);
+        Ok((display, tiles))
+    }
+
+    // tile id, relative to the charblock, of the tile holding a pixel
+    pub fn tile_id(&self, point: Point) -> Option<usize> {
+        match point.try_into() {
+            Ok((x, y)) if self.contains(x, y) => {
+                Some((y as usize / 8) * self.tiles_wide + (x as usize / 8))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles_wide * self.tiles_high
+    }
+
+    fn contains(&self, x: u32, y: u32) -> bool {
+        (x as usize) < self.tiles_wide * 8 && (y as usize) < self.tiles_high * 8
+    }
+
+    pub(crate) fn address(&self, x: usize, y: usize) -> usize {
+        let tile = (y / 8) * self.tiles_wide + (x / 8);
+        vram::charblock_address(self.charblock) + tile * TILE_BYTES + (y % 8) * 8 + (x % 8)
+    }
+}
+
+impl DrawTarget for CharblockDisplay {
+    type Color = PaletteColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x, y)) = coord.try_into() {
+                if self.contains(x, y) {
+                    vram::write8(self.address(x as usize, y as usize), color.into_storage());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let word = u32::from_ne_bytes([color.into_storage(); 4]);
+        let dst = vram::charblock_address(self.charblock) as *mut u32;
+        let words = self.tile_count() * TILE_BYTES / 4;
+        // a dma count is 16 bits, so clear in charblock sized pieces
+        for offset in (0..words).step_by(vram::CHARBLOCK_SIZE / 4) {
+            let count = (words - offset).min(vram::CHARBLOCK_SIZE / 4);
+            unsafe { dma::fill32(&word, dst.add(offset), count as u16) };
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for CharblockDisplay {
+    fn size(&self) -> Size {
+        Size::new(self.tiles_wide as u32 * 8, self.tiles_high as u32 * 8)
+    }
+}
+
+// 4bpp tiles starting at a charblock, like CharblockDisplay, two pixels to a
+// byte, so a charblock holds 512 tiles (256x128 pixels)
+// only the index within the bank is stored, the map entry or object showing
+// the tiles picks the palette bank
+//     let mut canvas = Charblock4bppDisplay::new(0);
+//     Circle::new(Point::new(4, 4), 40)
+//         .into_styled(PrimitiveStyle::with_fill(PaletteColor4::new(2, 5)))
+//         .draw(&mut canvas)?;
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Charblock4bppDisplay {
+    charblock: usize,
+    tiles_wide: usize,
+    tiles_high: usize,
+}
+
+impl Charblock4bppDisplay {
+    // one charblock as 32x16 tiles
+    pub fn new(charblock: usize) -> Self {
+        Self::with_tiles(charblock, 32, 16)
+    }
+
+    // a charblock picked by hand bypasses the Vram arena, see from_vram
+    pub fn with_tiles(charblock: usize, tiles_wide: usize, tiles_high: usize) -> Self {
+        let end = charblock * vram::CHARBLOCK_SIZE + tiles_wide * tiles_high * TILE_BYTES_4BPP;
+        assert!(
+            end <= VRAM_CHARBLOCKS * vram::CHARBLOCK_SIZE,
+            "tiles would run past the end of vram"
+        );
+        Charblock4bppDisplay {
+            charblock,
+            tiles_wide,
+            tiles_high,
+        }
+    }
+
+    // like CharblockDisplay::from_vram, with 4bpp tiles
+    pub fn from_vram(
+        vram: &mut Vram,
+        tiles_wide: usize,
+        tiles_high: usize,
+    ) -> Result<(Self, BgTiles), BgVramError> {
+        let tiles = vram.tiles_at_start(0..4, tiles_wide * tiles_high, Bpp::Bpp4)?;
+        let display = Self::with_tiles(tiles.charblock(), tiles_wide, tiles_high);
+        Ok((display, tiles))
+    }
+
+    // tile id, relative to the charblock, of the tile holding a pixel
+    pub fn tile_id(&self, point: Point) -> Option<usize> {
+        match point.try_into() {
+            Ok((x, y)) if self.contains(x, y) => {
+                Some((y as usize / 8) * self.tiles_wide + (x as usize / 8))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles_wide * self.tiles_high
+    }
+
+    fn contains(&self, x: u32, y: u32) -> bool {
+        (x as usize) < self.tiles_wide * 8 && (y as usize) < self.tiles_high * 8
+    }
+
+    // the halfword holding the pixel, four pixels to a halfword
+    fn address(&self, x: usize, y: usize) -> usize {
+        let tile = (y / 8) * self.tiles_wide + (x / 8);
+        vram::charblock_address(self.charblock)
+            + tile * TILE_BYTES_4BPP
+            + (y % 8) * 4
+            + (x % 8) / 4 * 2
+    }
+}
+
+impl DrawTarget for Charblock4bppDisplay {
+    type Color = PaletteColor4;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x, y)) = coord.try_into() {
+                if self.contains(x, y) {
+                    let (x, y) = (x as usize, y as usize);
+                    vram::write4(self.address(x, y), x % 4, color.index());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let word = color.index() as u32 * 0x1111_1111;
+        let dst = vram::charblock_address(self.charblock) as *mut u32;
+        let words = self.tile_count() * TILE_BYTES_4BPP / 4;
+        for offset in (0..words).step_by(vram::CHARBLOCK_SIZE / 4) {
+            let count = (words - offset).min(vram::CHARBLOCK_SIZE / 4);
+            unsafe { dma::fill32(&word, dst.add(offset), count as u16) };
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Charblock4bppDisplay {
+    fn size(&self) -> Size {
+        Size::new(self.tiles_wide as u32 * 8, self.tiles_high as u32 * 8)
+    }
+}
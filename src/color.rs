@@ -0,0 +1,29 @@
+use embedded_graphics::pixelcolor::{raw::RawU16, Bgr555};
+use gba::Color;
+
+// gba::Color and the embedded-graphics colors both live outside this crate,
+// so From can't be implemented between them here, these traits take its place
+// for Bgr555 and every color that converts to and from it, like Rgb555 and
+// Rgb888, which are scaled and reordered by embedded-graphics
+//     let color = Rgb888::new(255, 128, 0).into_gba_color();
+//     let back: Rgb888 = Rgb888::from_gba_color(color);
+// gba::Color holds red in the low bits like Bgr555, so that step is a copy
+pub trait IntoGbaColor {
+    fn into_gba_color(self) -> Color;
+}
+
+pub trait FromGbaColor {
+    fn from_gba_color(color: Color) -> Self;
+}
+
+impl<C: Into<Bgr555>> IntoGbaColor for C {
+    fn into_gba_color(self) -> Color {
+        Color(self.into().into_storage())
+    }
+}
+
+impl<C: From<Bgr555>> FromGbaColor for C {
+    fn from_gba_color(color: Color) -> Self {
+        C::from(Bgr555::from(RawU16::new(color.0)))
+    }
+}
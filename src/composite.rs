@@ -0,0 +1,42 @@
+use crate::{palette::PaletteKind, Tile8bppDisplay};
+use embedded_graphics::{pixelcolor::Bgr555, prelude::*};
+
+// where palette indices get their colors
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TilePalette<'a> {
+    Colors(&'a [Bgr555]), // index i is colors[i], indices past the end are skipped
+    Palram(PaletteKind),
+}
+
+impl<'a> TilePalette<'a> {
+    fn color(self, index: u8) -> Option<Bgr555> {
+        match self {
+            TilePalette::Colors(colors) => colors.get(index as usize).copied(),
+            TilePalette::Palram(kind) => Some(kind.read(index)),
+        }
+    }
+}
+
+impl Tile8bppDisplay {
+    // draw the tile onto a Bgr555 display like Mode3Display with its top left
+    // at point, index 0 is transparent, to preview sprite art on a bitmap
+    // mode or draw a software cursor
+    pub fn draw_at<D>(
+        &self,
+        target: &mut D,
+        point: Point,
+        palette: TilePalette,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Bgr555>,
+    {
+        target.draw_iter(self.iter_pixels().filter_map(|(offset, index)| {
+            match index.into_storage() {
+                0 => None,
+                index => palette
+                    .color(index)
+                    .map(|color| Pixel(point + offset, color)),
+            }
+        }))
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{background::Background, mmio::DISPCNT, oam::OamManager};
+use core::ops::Range;
+
+// one layer of the screen, objects are split into up to 4 groups so sprites
+// can go between backgrounds, like a player between the playfield and the hud
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Layer {
+    Bg(Background),
+    Objects(u8), // group 0 to 3
+}
+
+// z order of the screen in one place, layers are listed front to back and the
+// bg and obj priority bits follow from the order
+//     const HUD: Layer = Layer::Bg(Background::Bg0);
+//     const PLAYFIELD: Layer = Layer::Bg(Background::Bg1);
+//     const SKY: Layer = Layer::Bg(Background::Bg2);
+//     let compositor = Compositor::new(&[HUD, Layer::Objects(0), PLAYFIELD, SKY]);
+// objects are drawn in front of backgrounds with the same priority, so a group
+// gets the priority of the next background behind it
+pub struct Compositor {
+    bg: [Option<u8>; 4],      // priority of each listed background
+    objects: [Option<u8>; 4], // priority of each listed object group
+}
+
+impl Compositor {
+    pub fn new(order: &[Layer]) -> Self {
+        let mut compositor = Compositor {
+            bg: [None; 4],
+            objects: [None; 4],
+        };
+        let mut priority = 0;
+        for layer in order {
+            match *layer {
+                Layer::Bg(bg) => {
+                    assert!(
+                        compositor.bg[bg as usize].is_none(),
+                        "{:?} listed twice",
+                        bg
+                    );
+                    compositor.bg[bg as usize] = Some(priority);
+                    priority += 1;
+                }
+                Layer::Objects(group) => {
+                    assert!(group < 4, "there are only 4 object groups");
+                    compositor.objects[group as usize] = Some(priority.min(3));
+                }
+            }
+        }
+        compositor
+    }
+
+    // priority for a Sprite in group, 3 when the group wasn't listed
+    pub fn object_priority(&self, group: u8) -> u8 {
+        self.objects
+            .get(group as usize)
+            .copied()
+            .flatten()
+            .unwrap_or(3)
+    }
+
+    pub fn bg_priority(&self, bg: Background) -> Option<u8> {
+        self.bg[bg as usize]
+    }
+
+    // write the background priorities and show exactly the listed layers
+    pub fn apply(&self) {
+        let mut dispcnt = DISPCNT.read();
+        for bg in [
+            Background::Bg0,
+            Background::Bg1,
+            Background::Bg2,
+            Background::Bg3,
+        ]
+        .iter()
+        {
+            if let Some(priority) = self.bg[*bg as usize] {
+                bg.set_control(bg.control().with_bg_priority(priority as u16));
+            }
+            dispcnt = bg.enable(dispcnt, self.bg[*bg as usize].is_some());
+        }
+        let objects = self.objects.iter().any(Option::is_some);
+        DISPCNT.write(dispcnt.with_obj(objects));
+    }
+
+    // move the objects in oam slots to group's priority, the rest of their
+    // attributes are kept
+    pub fn apply_objects(&self, oam: &mut OamManager, slots: Range<usize>, group: u8) {
+        let priority = self.object_priority(group) as u16;
+        for slot in slots {
+            if let Some(mut attributes) = oam.get(slot) {
+                attributes.attr2 = attributes.attr2.with_priority(priority);
+                oam.set(slot, attributes);
+            }
+        }
+    }
+}
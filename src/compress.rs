@@ -0,0 +1,282 @@
+use crate::{
+    charblock::CharblockDisplay, mode::with_forced_blank, vram::page_address, Mode3Display,
+    Mode4Display, Mode5Display, Tile4bppDisplay, Tile8bppDisplay,
+};
+#[cfg(target_arch = "arm")]
+use core::arch::asm;
+use gba::vram::VRAM_BASE_USIZE;
+
+// bios decompression of data compressed with the gba lz77 or run length
+// formats (gbalzss, grit -gzl / -gzr)
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Compression {
+    Lz77,
+    Rle,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DecompressError {
+    UnknownFormat,
+    Misaligned, // the bios needs the data to start on a word boundary
+    TooLarge,   // decompressed data would not fit in the destination
+}
+
+// format and decompressed length from the 4 byte header
+pub fn compressed_header(data: &[u8]) -> Option<(Compression, usize)> {
+    let header = u32::from_le_bytes([*data.get(0)?, *data.get(1)?, *data.get(2)?, *data.get(3)?]);
+    let compression = match (header >> 4) & 0xF {
+        1 => Compression::Lz77,
+        3 => Compression::Rle,
+        _ => return None,
+    };
+    Some((compression, (header >> 8) as usize))
+}
+
+// vram needs the 16 bit write variants, work ram can use the 8 bit ones
+#[cfg(target_arch = "arm")]
+unsafe fn decompress(compression: Compression, src: *const u8, dst: *mut u8, vram: bool) {
+    match (compression, vram) {
+        (Compression::Lz77, false) => asm!(
+            "swi 0x11",
+            inout("r0") src => _,
+            inout("r1") dst => _,
+            out("r2") _,
+            out("r3") _,
+        ),
+        (Compression::Lz77, true) => asm!(
+            "swi 0x12",
+            inout("r0") src => _,
+            inout("r1") dst => _,
+            out("r2") _,
+            out("r3") _,
+        ),
+        (Compression::Rle, false) => asm!(
+            "swi 0x14",
+            inout("r0") src => _,
+            inout("r1") dst => _,
+            out("r2") _,
+            out("r3") _,
+        ),
+        (Compression::Rle, true) => asm!(
+            "swi 0x15",
+            inout("r0") src => _,
+            inout("r1") dst => _,
+            out("r2") _,
+            out("r3") _,
+        ),
+    }
+}
+
+// the same formats decoded in software off the gba, for host builds and tests,
+// vram is written through mmio so it lands in the host-vram memory
+#[cfg(not(target_arch = "arm"))]
+unsafe fn decompress(compression: Compression, src: *const u8, dst: *mut u8, vram: bool) {
+    use crate::{mmio, vram};
+
+    let byte = |i: usize| src.add(i).read();
+    let put = |i: usize, value: u8| {
+        if vram {
+            vram::write8(dst as usize + i, value);
+        } else {
+            dst.add(i).write(value);
+        }
+    };
+    let get = |i: usize| {
+        if vram {
+            (mmio::read16((dst as usize + i) & !1) >> ((i & 1) * 8)) as u8
+        } else {
+            dst.add(i).read()
+        }
+    };
+
+    let len = u32::from_le_bytes([byte(0), byte(1), byte(2), byte(3)]) as usize >> 8;
+    let (mut i, mut out) = (4, 0); // read and write positions
+    match compression {
+        Compression::Lz77 => {
+            while out < len {
+                let flags = byte(i);
+                i += 1;
+                for bit in 0..8 {
+                    if out >= len {
+                        break;
+                    }
+                    if flags & (0x80 >> bit) == 0 {
+                        put(out, byte(i)); // literal
+                        i += 1;
+                        out += 1;
+                        continue;
+                    }
+                    // copy count bytes from disp bytes back in the output
+                    let (high, low) = (byte(i), byte(i + 1));
+                    i += 2;
+                    let count = (high >> 4) as usize + 3;
+                    let disp = (((high as usize & 0xF) << 8) | low as usize) + 1;
+                    if disp > out {
+                        return; // points before the start, corrupt data
+                    }
+                    for _ in 0..count.min(len - out) {
+                        put(out, get(out - disp));
+                        out += 1;
+                    }
+                }
+            }
+        }
+        Compression::Rle => {
+            while out < len {
+                let flag = byte(i);
+                i += 1;
+                if flag & 0x80 != 0 {
+                    // a run of one byte
+                    let count = (flag & 0x7F) as usize + 3;
+                    let value = byte(i);
+                    i += 1;
+                    for _ in 0..count.min(len - out) {
+                        put(out, value);
+                        out += 1;
+                    }
+                } else {
+                    // uncompressed bytes
+                    let count = (flag & 0x7F) as usize + 1;
+                    for _ in 0..count.min(len - out) {
+                        put(out, byte(i));
+                        i += 1;
+                        out += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn checked(data: &[u8], capacity: usize) -> Result<Compression, DecompressError> {
+    let (compression, len) = compressed_header(data).ok_or(DecompressError::UnknownFormat)?;
+    if data.as_ptr() as usize % 4 != 0 {
+        return Err(DecompressError::Misaligned);
+    }
+    if len > capacity {
+        return Err(DecompressError::TooLarge);
+    }
+    Ok(compression)
+}
+
+// decompress into vram at address, holding at most capacity bytes
+pub(crate) fn decompress_to_vram(
+    data: &[u8],
+    address: usize,
+    capacity: usize,
+) -> Result<(), DecompressError> {
+    let compression = checked(data, capacity)?;
+    unsafe { decompress(compression, data.as_ptr(), address as *mut u8, true) };
+    Ok(())
+}
+
+fn decompress_to_ram(data: &[u8], dst: &mut [u32]) -> Result<(), DecompressError> {
+    let compression = checked(data, dst.len() * 4)?;
+    unsafe {
+        decompress(
+            compression,
+            data.as_ptr(),
+            dst.as_mut_ptr() as *mut u8,
+            false,
+        )
+    };
+    Ok(())
+}
+
+impl Mode3Display {
+    // decompress a 240x160 Bgr555 bitmap straight into the framebuffer, with
+    // the display force blanked since mode 3 has no hidden page to load into
+    pub fn load_compressed(&mut self, data: &[u8]) -> Result<(), DecompressError> {
+        with_forced_blank(|| decompress_to_vram(data, VRAM_BASE_USIZE, 240 * 160 * 2))
+    }
+}
+
+impl Mode4Display {
+    // decompress a 240x160 8bpp bitmap into the page
+    pub fn load_compressed(&mut self, data: &[u8]) -> Result<(), DecompressError> {
+        decompress_to_vram(data, page_address(self.page), 240 * 160)
+    }
+}
+
+impl Mode5Display {
+    // decompress a 160x128 Bgr555 bitmap into the page
+    pub fn load_compressed(&mut self, data: &[u8]) -> Result<(), DecompressError> {
+        decompress_to_vram(data, page_address(self.page), 160 * 128 * 2)
+    }
+}
+
+impl CharblockDisplay {
+    // decompress 8bpp tiles starting at the first tile
+    pub fn load_compressed(&mut self, data: &[u8]) -> Result<(), DecompressError> {
+        decompress_to_vram(data, self.address(0, 0), self.tile_count() * 64)
+    }
+}
+
+impl Tile4bppDisplay {
+    pub fn load_compressed(&mut self, data: &[u8]) -> Result<(), DecompressError> {
+        decompress_to_ram(data, &mut self.tile.0)
+    }
+}
+
+impl Tile8bppDisplay {
+    pub fn load_compressed(&mut self, data: &[u8]) -> Result<(), DecompressError> {
+        decompress_to_ram(data, &mut self.tile.0)
+    }
+}
+
+#[cfg(all(test, feature = "host-vram"))]
+mod tests {
+    use super::*;
+    use crate::mmio::{self, lock_host_memory};
+
+    // the bios needs word aligned data, and so does checked()
+    #[repr(align(4))]
+    struct Aligned([u8; 12]);
+
+    // "ABC", then 6 bytes copied from 3 back, then "D"
+    const LZ77: Aligned = Aligned([0x10, 10, 0, 0, 0x10, b'A', b'B', b'C', 0x30, 0x02, b'D', 0]);
+    // a run of four 7s, then 1 and 2 uncompressed
+    const RLE: Aligned = Aligned([0x30, 6, 0, 0, 0x81, 7, 0x01, 1, 2, 0, 0, 0]);
+
+    fn vram_bytes(address: usize, len: usize) -> std::vec::Vec<u8> {
+        (0..len)
+            .map(|i| (mmio::read16((address + i) & !1) >> (((address + i) & 1) * 8)) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn header_reports_format_and_length() {
+        assert_eq!(compressed_header(&LZ77.0), Some((Compression::Lz77, 10)));
+        assert_eq!(compressed_header(&RLE.0), Some((Compression::Rle, 6)));
+        assert_eq!(compressed_header(&[0x20, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn lz77_to_vram() {
+        let _memory = lock_host_memory();
+        let address = VRAM_BASE_USIZE + 0x100;
+        decompress_to_vram(&LZ77.0, address, 16).unwrap();
+        assert_eq!(vram_bytes(address, 11), b"ABCABCABCD\0");
+    }
+
+    #[test]
+    fn rle_to_ram() {
+        let mut dst = [0u32; 2];
+        decompress_to_ram(&RLE.0, &mut dst).unwrap();
+        assert_eq!(dst, [u32::from_le_bytes([7, 7, 7, 7]), 0x0201]);
+    }
+
+    #[test]
+    fn rejects_data_that_does_not_fit() {
+        let _memory = lock_host_memory();
+        assert_eq!(
+            decompress_to_vram(&LZ77.0, VRAM_BASE_USIZE, 8),
+            Err(DecompressError::TooLarge)
+        );
+        assert_eq!(
+            decompress_to_vram(&LZ77.0[1..], VRAM_BASE_USIZE, 16),
+            Err(DecompressError::UnknownFormat)
+        );
+    }
+}
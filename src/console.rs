@@ -0,0 +1,228 @@
+use crate::{
+    arena::Vram,
+    background::Background,
+    bg_vram::{BgScreenblocks, BgTiles, BgVramError},
+    font::FontBaker,
+    obj_tiles::Bpp,
+    screenblock::ScreenblockDisplay,
+    PaletteColor, PaletteKind, TileEntry,
+};
+use core::fmt;
+use embedded_graphics::{mono_font::ascii::FONT_6X8, pixelcolor::Bgr555, prelude::*};
+use gba::io::background::{BGSize, BackgroundControlSetting};
+
+const FIRST_CHAR: u8 = b' ';
+const LAST_CHAR: u8 = b'~';
+const UNKNOWN_CHAR: u8 = b'?';
+
+// text console on a 4bpp text background
+// the font is baked into tiles 0 to 94 of charblock, one per printable ascii
+// character, drawn with entry 1 of the palette bank chosen per character
+// the 32 row screenblock is used as a ring of rows, when the console fills
+// the oldest row is reused and the background scrolls down to show the new one
+// the console owns its tiles and screenblock until free() hands them back
+//     let mut console = Console::from_vram(Background::Bg0, &mut vram)?;
+pub struct Console {
+    bg: Background,
+    tiles: BgTiles,
+    screenblocks: BgScreenblocks,
+    map: ScreenblockDisplay,
+    col: usize,
+    row: usize,      // cursor row on screen
+    top: usize,      // map row at the top of the screen
+    scroll_y: usize, // current vertical scroll offset in pixels
+    palbank: u8,
+    smooth: bool, // scroll a few pixels per tick() instead of jumping
+}
+
+impl Console {
+    pub const COLUMNS: usize = 30;
+    pub const ROWS: usize = 20;
+    const MAP_ROWS: usize = 32;
+    const SCROLL_SPEED: usize = 2; // pixels per tick
+
+    const FONT_TILES: usize = (LAST_CHAR - FIRST_CHAR + 1) as usize;
+
+    // tiles has to start at tile id 0 of its charblock, with room for the font
+    pub fn new(bg: Background, tiles: BgTiles, screenblocks: BgScreenblocks) -> Self {
+        assert!(
+            tiles.tile_id() == 0 && tiles.tile_count() >= Self::FONT_TILES,
+            "the font needs tile ids 0 to 94"
+        );
+        let (charblock, screenblock) = (tiles.charblock(), screenblocks.screenblock());
+        bake_font(charblock);
+        Self::define_color(0, Bgr555::WHITE);
+
+        bg.set_control(
+            BackgroundControlSetting::new()
+                .with_char_base_block(charblock as u16)
+                .with_screen_base_block(screenblock as u16)
+                .with_size(BGSize::Zero), // 256x256
+        );
+
+        let mut console = Console {
+            bg,
+            tiles,
+            screenblocks,
+            map: ScreenblockDisplay::new(screenblock),
+            col: 0,
+            row: 0,
+            top: 0,
+            scroll_y: 0,
+            palbank: 0,
+            smooth: false,
+        };
+        console.clear();
+        console
+    }
+
+    // a console on tiles and a screenblock claimed from vram, the font needs
+    // tile ids 0 to 94 so it goes in the first charblock where they are free
+    pub fn from_vram(bg: Background, vram: &mut Vram) -> Result<Self, BgVramError> {
+        let (tiles, screenblocks) =
+            vram.tiles_and_screenblock(0..4, Self::FONT_TILES, Bpp::Bpp4)?;
+        Ok(Self::new(bg, tiles, screenblocks))
+    }
+
+    // scroll a few pixels per tick() instead of jumping to a new row
+    pub fn with_smooth(self, smooth: bool) -> Self {
+        Console { smooth, ..self }
+    }
+
+    pub fn bg(&self) -> Background {
+        self.bg
+    }
+
+    // give the tiles and screenblock back to vram, the background is left on
+    pub fn free(self, vram: &mut Vram) {
+        vram.bg.free_tiles(self.tiles);
+        vram.bg.free_screenblocks(self.screenblocks);
+    }
+
+    // set the text color of palette bank, entry 1 of the bank
+    pub fn define_color(palbank: u8, color: Bgr555) {
+        assert!(palbank < 16, "there are only 16 palette banks");
+        PaletteKind::Background.write(palbank * 16 + 1, color);
+    }
+
+    // print following characters with the palette bank holding color
+    // color is an absolute bg palette index, so bank n is indices 16n to 16n+15
+    pub fn set_color(&mut self, color: PaletteColor) {
+        self.palbank = color.into_storage() / 16;
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear(blank(0)).ok();
+        self.col = 0;
+        self.row = 0;
+        self.top = 0;
+        self.scroll_y = 0;
+        self.bg.set_scroll(Point::zero());
+    }
+
+    pub fn put_char(&mut self, c: u8) {
+        match c {
+            b'\n' => self.newline(),
+            b'\r' => self.col = 0,
+            _ => {
+                if self.col >= Self::COLUMNS {
+                    self.newline(); // wrap
+                }
+                let c = if (FIRST_CHAR..=LAST_CHAR).contains(&c) {
+                    c
+                } else {
+                    UNKNOWN_CHAR
+                };
+                let entry = TileEntry::new((c - FIRST_CHAR) as u16).with_palbank(self.palbank);
+                Pixel(self.cell(self.col, self.row), entry)
+                    .draw(&mut self.map)
+                    .ok();
+                self.col += 1;
+            }
+        }
+    }
+
+    // advance smooth scrolling, call once per vblank
+    pub fn tick(&mut self) {
+        let target = self.top * 8;
+        if self.scroll_y != target {
+            let distance = (target + Self::MAP_ROWS * 8 - self.scroll_y) % (Self::MAP_ROWS * 8);
+            let step = distance.min(Self::SCROLL_SPEED);
+            self.scroll_y = (self.scroll_y + step) % (Self::MAP_ROWS * 8);
+            self.bg.set_scroll(Point::new(0, self.scroll_y as i32));
+        }
+    }
+
+    // map cell of a screen position
+    fn cell(&self, col: usize, row: usize) -> Point {
+        Point::new(col as i32, ((self.top + row) % Self::MAP_ROWS) as i32)
+    }
+
+    fn newline(&mut self) {
+        self.col = 0;
+        if self.row + 1 < Self::ROWS {
+            self.row += 1;
+        } else {
+            self.scroll();
+        }
+    }
+
+    // reuse the oldest map row as the new bottom row
+    fn scroll(&mut self) {
+        self.top = (self.top + 1) % Self::MAP_ROWS;
+        for col in 0..32 {
+            Pixel(self.cell(col, Self::ROWS - 1), blank(self.palbank))
+                .draw(&mut self.map)
+                .ok();
+        }
+
+        // jump when not smooth, or when lagging enough to show rows being reused
+        let lag = (self.top * 8 + Self::MAP_ROWS * 8 - self.scroll_y) % (Self::MAP_ROWS * 8);
+        if !self.smooth || lag > (Self::MAP_ROWS - Self::ROWS) * 8 {
+            self.scroll_y = self.top * 8;
+            self.bg.set_scroll(Point::new(0, self.scroll_y as i32));
+        }
+    }
+}
+
+impl fmt::Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.bytes() {
+            self.put_char(c);
+        }
+        Ok(())
+    }
+}
+
+fn blank(palbank: u8) -> TileEntry {
+    TileEntry::new((b' ' - FIRST_CHAR) as u16).with_palbank(palbank)
+}
+
+// render each printable character into its own tile with color index 1
+fn bake_font(charblock: usize) {
+    FontBaker::new(&FONT_6X8)
+        .with_chars(FIRST_CHAR, LAST_CHAR)
+        .with_offset(Point::new(1, 0)) // center 6 pixel wide glyph
+        .bake(charblock, 0);
+}
+
+// print to a console
+#[macro_export]
+macro_rules! gba_print {
+    ($console:expr, $($arg:tt)*) => {{
+        use core::fmt::Write;
+        write!($console, $($arg)*).ok();
+    }};
+}
+
+// print to a console followed by a newline
+#[macro_export]
+macro_rules! gba_println {
+    ($console:expr) => {
+        $console.put_char(b'\n')
+    };
+    ($console:expr, $($arg:tt)*) => {{
+        use core::fmt::Write;
+        writeln!($console, $($arg)*).ok();
+    }};
+}
@@ -0,0 +1,124 @@
+use crate::{
+    oam::OamManager,
+    obj_tiles::TileHandle,
+    sprite::{ObjLayout, Sprite},
+};
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+// a cursor look, the sprite's position is ignored, and the hotspot is the
+// pixel of the sprite that points, like the tip of an arrow
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct CursorShape {
+    pub sprite: Sprite,
+    pub hotspot: Point,
+}
+
+impl CursorShape {
+    pub fn new(sprite: Sprite) -> Self {
+        CursorShape {
+            sprite,
+            hotspot: Point::zero(),
+        }
+    }
+
+    pub fn with_hotspot(self, hotspot: Point) -> Self {
+        CursorShape { hotspot, ..self }
+    }
+}
+
+// a pointer drawn with one object, so moving it never touches the picture
+// under it, the hotspot is kept inside of bounds, the screen by default
+// call commit() on the oam manager after changes to show them
+pub struct Cursor<'a> {
+    shapes: &'a [CursorShape],
+    shape: usize,
+    position: Point, // of the hotspot
+    bounds: Rectangle,
+    hidden: bool,
+    slot: usize,
+    tiles: Option<TileHandle>,
+}
+
+impl<'a> Cursor<'a> {
+    // None when no oam slot is free or there are no shapes
+    // the shapes' tile ids bypass the Vram arena, claim them from vram.obj and
+    // pass the handle to with_tiles so they're accounted for
+    pub fn new(oam: &mut OamManager, shapes: &'a [CursorShape]) -> Option<Self> {
+        if shapes.is_empty() {
+            return None;
+        }
+        let slot = oam.alloc(1)?;
+        let mut cursor = Cursor {
+            shapes,
+            shape: 0,
+            position: Point::new(120, 80),
+            bounds: Rectangle::new(Point::zero(), Size::new(240, 160)),
+            hidden: false,
+            slot,
+            tiles: None,
+        };
+        cursor.update(oam);
+        Some(cursor)
+    }
+
+    // keep the tiles the shapes are drawn with, free() hands them back
+    pub fn with_tiles(self, tiles: TileHandle) -> Self {
+        Cursor {
+            tiles: Some(tiles),
+            ..self
+        }
+    }
+
+    pub fn with_bounds(self, bounds: Rectangle) -> Self {
+        Cursor { bounds, ..self }
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn shape(&self) -> usize {
+        self.shape
+    }
+
+    // move the hotspot to point, clamped to the bounds
+    pub fn move_to(&mut self, oam: &mut OamManager, point: Point) {
+        let bottom_right = self.bounds.bottom_right().unwrap_or(self.bounds.top_left);
+        self.position = point
+            .component_max(self.bounds.top_left)
+            .component_min(bottom_right);
+        self.update(oam);
+    }
+
+    pub fn move_by(&mut self, oam: &mut OamManager, offset: Point) {
+        self.move_to(oam, self.position + offset);
+    }
+
+    // switch to shapes[index], out of range indices are ignored
+    pub fn set_shape(&mut self, oam: &mut OamManager, index: usize) {
+        if index < self.shapes.len() {
+            self.shape = index;
+            self.update(oam);
+        }
+    }
+
+    pub fn set_visible(&mut self, oam: &mut OamManager, visible: bool) {
+        self.hidden = !visible;
+        self.update(oam);
+    }
+
+    // give the oam slot back, and the tiles if the cursor kept them
+    pub fn free(self, oam: &mut OamManager) -> Option<TileHandle> {
+        oam.free(self.slot, 1);
+        self.tiles
+    }
+
+    fn update(&mut self, oam: &mut OamManager) {
+        let shape = self.shapes[self.shape];
+        let sprite = shape.sprite.with_position(self.position - shape.hotspot);
+        match sprite.attributes(ObjLayout::current()) {
+            Ok(attributes) if !self.hidden => oam.set(self.slot, attributes),
+            _ => oam.hide(self.slot),
+        }
+    }
+}
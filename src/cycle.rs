@@ -0,0 +1,106 @@
+use crate::palette::PaletteKind;
+
+// a run of palette entries rotated by one every few frames, the classic way to
+// animate water, lava and conveyor belts without touching the tiles
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PaletteCycle {
+    pub kind: PaletteKind,
+    pub first: u8,
+    pub len: u8,
+    pub period: u16,   // frames between steps
+    pub reverse: bool, // colors move to lower entries
+    counter: u16,
+}
+
+impl PaletteCycle {
+    pub fn new(kind: PaletteKind, first: u8, len: u8, period: u16) -> Self {
+        PaletteCycle {
+            kind,
+            first,
+            len: (len as usize).min(256 - first as usize) as u8,
+            period: period.max(1),
+            reverse: false,
+            counter: 0,
+        }
+    }
+
+    pub fn with_reverse(self, reverse: bool) -> Self {
+        PaletteCycle { reverse, ..self }
+    }
+
+    // count a frame, returns true when the colors moved
+    pub fn tick(&mut self) -> bool {
+        self.counter += 1;
+        if self.counter < self.period {
+            return false;
+        }
+        self.counter = 0;
+        self.step();
+        true
+    }
+
+    // rotate the entries by one now
+    pub fn step(&self) {
+        if self.len < 2 {
+            return;
+        }
+        let last = self.first + (self.len - 1);
+        if self.reverse {
+            let wrapped = self.kind.read(self.first);
+            for index in self.first..last {
+                self.kind.write(index, self.kind.read(index + 1));
+            }
+            self.kind.write(last, wrapped);
+        } else {
+            let wrapped = self.kind.read(last);
+            for index in (self.first..last).rev() {
+                self.kind.write(index + 1, self.kind.read(index));
+            }
+            self.kind.write(self.first, wrapped);
+        }
+    }
+}
+
+// up to N palette cycles running side by side, call tick() once per vblank,
+// from the vblank handler or after wait_for_vblank()
+pub struct PaletteCycler<const N: usize> {
+    cycles: [Option<PaletteCycle>; N],
+}
+
+impl<const N: usize> PaletteCycler<N> {
+    pub const fn new() -> Self {
+        PaletteCycler { cycles: [None; N] }
+    }
+
+    // None when all N are running
+    pub fn add(&mut self, cycle: PaletteCycle) -> Option<usize> {
+        let (id, free) = self
+            .cycles
+            .iter_mut()
+            .enumerate()
+            .find(|(_, entry)| entry.is_none())?;
+        *free = Some(cycle);
+        Some(id)
+    }
+
+    // stop a cycle, its colors stay where they are
+    pub fn remove(&mut self, id: usize) -> Option<PaletteCycle> {
+        self.cycles.get_mut(id)?.take()
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut PaletteCycle> {
+        self.cycles.get_mut(id)?.as_mut()
+    }
+
+    pub fn tick(&mut self) {
+        for cycle in self.cycles.iter_mut().flatten() {
+            cycle.tick();
+        }
+    }
+}
+
+impl<const N: usize> Default for PaletteCycler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
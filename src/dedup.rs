@@ -0,0 +1,76 @@
+use crate::{
+    canvas::{CanvasTile, TileCanvas},
+    screenblock::TileEntry,
+};
+
+// the tileset has no room for another unique tile
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TilesetFull;
+
+// reduce tiles to a tileset with each distinct tile once, and a map entry per
+// source tile pointing into it, a background of repeating tiles then takes
+// far fewer charblock slots
+// with flips, a tile that is a mirror image of an earlier one reuses it with
+// the entry's flip flags set
+// entries count tile ids from first_id, where the tileset will be uploaded,
+// returns the number of unique tiles written to tileset
+pub fn deduplicate<'a, T: CanvasTile + 'a>(
+    tiles: impl IntoIterator<Item = &'a T>,
+    flips: bool,
+    first_id: u16,
+    tileset: &mut [T],
+    map: &mut [TileEntry],
+) -> Result<usize, TilesetFull> {
+    let mut len = 0;
+    for (tile, entry) in tiles.into_iter().zip(map.iter_mut()) {
+        let found = tileset[..len].iter().enumerate().find_map(|(i, unique)| {
+            let variants: &[(bool, bool)] = if flips {
+                &[(false, false), (true, false), (false, true), (true, true)]
+            } else {
+                &[(false, false)]
+            };
+            variants
+                .iter()
+                .find(|(h, v)| flipped(unique, *h, *v).words() == tile.words())
+                .map(|(h, v)| (i, *h, *v))
+        });
+        let (index, hflip, vflip) = match found {
+            Some(found) => found,
+            None => {
+                *tileset.get_mut(len).ok_or(TilesetFull)? = *tile;
+                len += 1;
+                (len - 1, false, false)
+            }
+        };
+        *entry = TileEntry::new(first_id + index as u16)
+            .with_hflip(hflip)
+            .with_vflip(vflip);
+    }
+    Ok(len)
+}
+
+impl<T: CanvasTile, const W: usize, const H: usize> TileCanvas<T, W, H> {
+    // deduplicate the canvas tiles in row major order, map needs W * H entries
+    pub fn deduplicate(
+        &self,
+        flips: bool,
+        first_id: u16,
+        tileset: &mut [T],
+        map: &mut [TileEntry],
+    ) -> Result<usize, TilesetFull> {
+        deduplicate(self.tiles.iter().flatten(), flips, first_id, tileset, map)
+    }
+}
+
+// tile mirrored horizontally and/or vertically
+fn flipped<T: CanvasTile>(tile: &T, hflip: bool, vflip: bool) -> T {
+    let mut out = *tile;
+    for y in 0..8 {
+        for x in 0..8 {
+            let src_x = if hflip { 7 - x } else { x };
+            let src_y = if vflip { 7 - y } else { y };
+            out.set_pixel(x, y, tile.pixel(src_x, src_y));
+        }
+    }
+    out
+}
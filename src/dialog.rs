@@ -0,0 +1,207 @@
+use crate::{prop_font::PropFont, screenblock::TileEntry, sub_display::SubDisplay};
+use embedded_graphics::{
+    geometry::Size,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+// the 9 tiles of a box frame in row major order, top left corner, top edge,
+// top right corner, left edge, middle, right edge and the bottom row, edges
+// and the middle are repeated to fill the box
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct NineSlice {
+    pub tiles: [TileEntry; 9],
+}
+
+impl NineSlice {
+    // 9 tiles in a row starting at first
+    pub fn new(first: TileEntry) -> Self {
+        let mut tiles = [first; 9];
+        for (i, tile) in tiles.iter_mut().enumerate() {
+            *tile = first.with_tile_id(first.tile_id() + i as u16);
+        }
+        NineSlice { tiles }
+    }
+}
+
+// a bordered box with an inner area for content, like a text box or menu
+// on tiled backgrounds the frame is drawn from a NineSlice into the map, on
+// the bitmap modes it is a styled rectangle
+pub struct DialogBox {
+    pub area: Rectangle,
+    pub border: u32,  // frame width in pixels, the content starts inside it
+    pub padding: u32, // space between the frame and the content
+}
+
+impl DialogBox {
+    // with an 8 pixel border to match a NineSlice frame
+    pub fn new(area: Rectangle) -> Self {
+        DialogBox {
+            area,
+            border: 8,
+            padding: 0,
+        }
+    }
+
+    pub fn with_border(self, border: u32) -> Self {
+        DialogBox { border, ..self }
+    }
+
+    pub fn with_padding(self, padding: u32) -> Self {
+        DialogBox { padding, ..self }
+    }
+
+    // the area inside the border and padding
+    pub fn content_area(&self) -> Rectangle {
+        let inset = self.border + self.padding;
+        Rectangle::new(
+            self.area.top_left + Point::new(inset as i32, inset as i32),
+            Size::new(
+                self.area.size.width.saturating_sub(inset * 2),
+                self.area.size.height.saturating_sub(inset * 2),
+            ),
+        )
+    }
+
+    // a display for the content, drawing at 0, 0 draws at the content's top
+    // left corner and nothing is drawn over the frame
+    pub fn content<'a, D: DrawTarget>(&self, display: &'a mut D) -> SubDisplay<'a, D> {
+        SubDisplay::new(display, self.content_area())
+    }
+
+    // draw the frame and fill the box on a bitmap display, the stroke is drawn
+    // inside the area when style uses StrokeAlignment::Inside
+    pub fn draw_frame<D: DrawTarget>(
+        &self,
+        display: &mut D,
+        style: PrimitiveStyle<D::Color>,
+    ) -> Result<(), D::Error> {
+        self.area.into_styled(style).draw(display)
+    }
+
+    // draw the frame into a background map, the area is rounded to tiles
+    pub fn draw_tiles<D>(&self, map: &mut D, slice: &NineSlice) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = TileEntry>,
+    {
+        let top_left = self.area.top_left / 8;
+        let size = self.area.size / 8;
+        if size.width < 2 || size.height < 2 {
+            return Ok(()); // no room for the corners
+        }
+        let (x0, y0) = (top_left.x, top_left.y);
+        let (x1, y1) = (x0 + size.width as i32 - 1, y0 + size.height as i32 - 1);
+        let (inner_w, inner_h) = (size.width - 2, size.height - 2);
+        let t = &slice.tiles;
+        let parts = [
+            (Point::new(x0, y0), Size::new(1, 1), t[0]),
+            (Point::new(x0 + 1, y0), Size::new(inner_w, 1), t[1]),
+            (Point::new(x1, y0), Size::new(1, 1), t[2]),
+            (Point::new(x0, y0 + 1), Size::new(1, inner_h), t[3]),
+            (
+                Point::new(x0 + 1, y0 + 1),
+                Size::new(inner_w, inner_h),
+                t[4],
+            ),
+            (Point::new(x1, y0 + 1), Size::new(1, inner_h), t[5]),
+            (Point::new(x0, y1), Size::new(1, 1), t[6]),
+            (Point::new(x0 + 1, y1), Size::new(inner_w, 1), t[7]),
+            (Point::new(x1, y1), Size::new(1, 1), t[8]),
+        ];
+        for (top_left, size, tile) in parts.iter() {
+            map.fill_solid(&Rectangle::new(*top_left, *size), *tile)?;
+        }
+        Ok(())
+    }
+}
+
+// reveal text a few characters per frame, like an rpg text box
+// the text is word wrapped with a PropFont to the target's bounding box, so
+// draw to DialogBox::content, and only newly revealed characters are drawn
+// text that doesn't fit is split into pages, clear the content between pages
+pub struct Typewriter<'a> {
+    text: &'a str,           // the current page and everything after it
+    page_end: Option<usize>, // known after the first draw
+    shown: usize,            // bytes revealed
+    drawn: usize,            // bytes drawn
+    pub chars_per_tick: usize,
+}
+
+impl<'a> Typewriter<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Typewriter {
+            text,
+            page_end: None,
+            shown: 0,
+            drawn: 0,
+            chars_per_tick: 1,
+        }
+    }
+
+    pub fn with_speed(self, chars_per_tick: usize) -> Self {
+        Typewriter {
+            chars_per_tick,
+            ..self
+        }
+    }
+
+    // reveal the next characters, call once per frame, true when the page is
+    // fully shown
+    pub fn tick(&mut self) -> bool {
+        let end = self.end();
+        self.shown = self.text[self.shown..end]
+            .char_indices()
+            .nth(self.chars_per_tick)
+            .map_or(end, |(i, _)| self.shown + i);
+        self.done()
+    }
+
+    // show the rest of the page at once, like when a button is pressed
+    pub fn skip(&mut self) {
+        self.shown = self.end();
+    }
+
+    pub fn done(&self) -> bool {
+        self.shown >= self.end()
+    }
+
+    // the revealed text of the page
+    pub fn visible(&self) -> &'a str {
+        &self.text[..self.shown]
+    }
+
+    // true when there is text after this page
+    pub fn has_next_page(&self) -> bool {
+        self.end() < self.text.len()
+    }
+
+    // move to the next page, false when this was the last one
+    pub fn next_page(&mut self) -> bool {
+        if !self.has_next_page() {
+            return false;
+        }
+        let rest = self.text[self.end()..].trim_start_matches(|c: char| c == ' ' || c == '\n');
+        *self = Typewriter::new(rest).with_speed(self.chars_per_tick);
+        true
+    }
+
+    // draw the characters revealed since the last draw
+    pub fn draw<D: DrawTarget>(
+        &mut self,
+        font: &PropFont,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<(), D::Error> {
+        let area = target.bounding_box();
+        let fit =
+            font.draw_wrapped_part(self.text, self.drawn..self.shown, &area, color, target)?;
+        self.page_end = Some(fit);
+        self.shown = self.shown.min(fit);
+        self.drawn = self.shown;
+        Ok(())
+    }
+
+    fn end(&self) -> usize {
+        self.page_end.unwrap_or_else(|| self.text.len())
+    }
+}
@@ -0,0 +1,185 @@
+use embedded_graphics::{
+    geometry::Size,
+    pixelcolor::{Bgr555, Rgb888},
+    prelude::*,
+};
+
+// 4x4 bayer threshold matrix
+const BAYER: [[u8; 4]; 4] = [
+    [0, 8, 2, 10], //
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+
+// convert an 8 bit per channel color to 5 bits per channel, adding a threshold
+// from the position in the bayer matrix so gradients become patterns instead
+// of bands
+pub fn dither(color: Rgb888, point: Point) -> Bgr555 {
+    // a 5 bit step is 8 of the 8 bit values, so spread the threshold over 0..8
+    let threshold = BAYER[(point.y & 3) as usize][(point.x & 3) as usize] / 2;
+    let channel = |value: u8| ((value as u16 + threshold as u16) >> 3).min(31) as u8;
+    Bgr555::new(channel(color.r()), channel(color.g()), channel(color.b()))
+}
+
+// draw Rgb888 content onto a Bgr555 display with ordered dithering
+pub struct DitheredDisplay<D> {
+    pub display: D,
+}
+
+impl<D> DitheredDisplay<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    pub fn new(display: D) -> Self {
+        DitheredDisplay { display }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+}
+
+impl<D> DrawTarget for DitheredDisplay<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    type Color = Rgb888;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(coord, color)| Pixel(coord, dither(color, coord))),
+        )
+    }
+}
+
+impl<D> OriginDimensions for DitheredDisplay<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+const WIDTH: usize = 240;
+
+// draw Rgb888 content onto a Bgr555 display with floyd-steinberg error
+// diffusion, smoother than ordered dithering for photos and splash screens
+// the error is carried in two rows of 240 pixels, about 3KiB, and only
+// spreads correctly when pixels come left to right and top to bottom like
+// ImageRaw draws them, a jump to another row starts from no error
+pub struct DiffusedDisplay<D> {
+    pub display: D,
+    diffusion: Diffusion,
+}
+
+// the error carried to the rest of this row and the next
+struct Diffusion {
+    errors: [[[i16; 3]; WIDTH + 2]; 2], // in 16ths, padded for x - 1 and x + 1
+    current: usize,                     // errors[current] is row y
+    row: Option<i32>,
+}
+
+impl<D> DiffusedDisplay<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    pub fn new(display: D) -> Self {
+        DiffusedDisplay {
+            display,
+            diffusion: Diffusion {
+                errors: [[[0; 3]; WIDTH + 2]; 2],
+                current: 0,
+                row: None,
+            },
+        }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    // forget the carried error, before drawing another image
+    pub fn reset(&mut self) {
+        self.diffusion.reset();
+    }
+}
+
+impl Diffusion {
+    fn reset(&mut self) {
+        self.errors = [[[0; 3]; WIDTH + 2]; 2];
+        self.row = None;
+    }
+
+    fn seek(&mut self, y: i32) {
+        match self.row {
+            Some(row) if row == y => {}
+            Some(row) if row + 1 == y => {
+                self.errors[self.current] = [[0; 3]; WIDTH + 2];
+                self.current ^= 1;
+                self.row = Some(y);
+            }
+            _ => {
+                self.reset();
+                self.row = Some(y);
+            }
+        }
+    }
+
+    fn convert(&mut self, color: Rgb888, point: Point) -> Bgr555 {
+        if point.x < 0 || point.x >= WIDTH as i32 {
+            return Bgr555::from(color); // off screen, nothing to carry
+        }
+        self.seek(point.y);
+        let i = point.x as usize + 1;
+        let mut channels = [color.r(), color.g(), color.b()];
+        for (c, channel) in channels.iter_mut().enumerate() {
+            let value = (*channel as i16 + self.errors[self.current][i][c] / 16).clamp(0, 255);
+            let quantized = ((value + 4) >> 3).min(31);
+            let error = value - (quantized << 3 | quantized >> 2); // what 5 bits can't show
+            let next = &mut self.errors[self.current ^ 1];
+            next[i - 1][c] += error * 3;
+            next[i][c] += error * 5;
+            next[i + 1][c] += error;
+            self.errors[self.current][i + 1][c] += error * 7;
+            *channel = quantized as u8;
+        }
+        Bgr555::new(channels[0], channels[1], channels[2])
+    }
+}
+
+impl<D> DrawTarget for DiffusedDisplay<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    type Color = Rgb888;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let diffusion = &mut self.diffusion;
+        self.display.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(coord, color)| Pixel(coord, diffusion.convert(color, coord))),
+        )
+    }
+}
+
+impl<D> OriginDimensions for DiffusedDisplay<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
@@ -0,0 +1,179 @@
+use crate::dma_queue::Transfer;
+#[cfg(feature = "host-vram")]
+use crate::mmio;
+#[cfg(not(feature = "host-vram"))]
+use crate::shared::interrupt_free;
+#[cfg(not(feature = "host-vram"))]
+use gba::io::dma::DMA3;
+use gba::io::dma::{
+    DMAControlSetting, DMADestAddressControl, DMASrcAddressControl, DMAStartTiming,
+};
+
+// DMA3 runs immediately and halts the cpu until the transfer is done
+// a DmaQueue can leave a transfer waiting on DMA3 for vblank, an immediate
+// copy waits for that one to finish first so it isn't overwritten
+
+const COPY16: DMAControlSetting = DMAControlSetting::new()
+    .with_source_address_control(DMASrcAddressControl::Increment)
+    .with_dest_address_control(DMADestAddressControl::Increment)
+    .with_use_32bit(false)
+    .with_enabled(true);
+
+const COPY32: DMAControlSetting = COPY16.with_use_32bit(true);
+
+const FILL32: DMAControlSetting = COPY16
+    .with_source_address_control(DMASrcAddressControl::Fixed)
+    .with_use_32bit(true);
+
+#[cfg(not(feature = "host-vram"))]
+unsafe fn transfer(src: usize, dst: usize, count: u16, control: DMAControlSetting) {
+    if count == 0 {
+        return; // a count of 0 means max length to the hardware
+    }
+    while busy() {
+        // a queued transfer is waiting for vblank
+    }
+    start(src, dst, count, control);
+    while busy() {
+        // wait for the transfer to start and finish
+    }
+}
+
+// program DMA3 without waiting, for DmaQueue
+// the registers are written with interrupts off, an irq handler copying with
+// dma in between, like commit_palettes, would leave a mix of both transfers
+#[cfg(not(feature = "host-vram"))]
+pub(crate) unsafe fn start(src: usize, dst: usize, count: u16, control: DMAControlSetting) {
+    interrupt_free(|| {
+        DMA3::DMA3SAD.write(src as *const u32);
+        DMA3::DMA3DAD.write(dst as *mut u32);
+        DMA3::DMA3CNT_L.write(count);
+        DMA3::DMA3CNT_H.write(control);
+    });
+}
+
+// the host backend has no dma to wait on, the copy happens right away
+#[cfg(feature = "host-vram")]
+pub(crate) unsafe fn start(src: usize, dst: usize, count: u16, control: DMAControlSetting) {
+    transfer(src, dst, count, control);
+}
+
+// a transfer is running or waiting for its start timing
+#[cfg(not(feature = "host-vram"))]
+pub(crate) fn busy() -> bool {
+    DMA3::DMA3CNT_H.read().enabled()
+}
+
+#[cfg(feature = "host-vram")]
+pub(crate) fn busy() -> bool {
+    false
+}
+
+// run a queued copy now, waiting for it like the copies above
+pub(crate) unsafe fn run(copy: Transfer) {
+    let control = copy_control(copy.wide, false);
+    transfer(copy.src, copy.dst, copy.count, control);
+}
+
+// the control setting for a copy, 32 or 16 bit, started at vblank or right away
+pub(crate) fn copy_control(wide: bool, vblank: bool) -> DMAControlSetting {
+    let timing = if vblank {
+        DMAStartTiming::VBlank
+    } else {
+        DMAStartTiming::Immediate
+    };
+    COPY16.with_use_32bit(wide).with_start_time(timing)
+}
+
+// the same transfer through the host memory backend
+#[cfg(feature = "host-vram")]
+unsafe fn transfer(src: usize, dst: usize, count: u16, control: DMAControlSetting) {
+    let width = if control.use_32bit() { 4 } else { 2 };
+    let fixed = control.source_address_control() == DMASrcAddressControl::Fixed;
+    for i in 0..count as usize {
+        let src = if fixed { src } else { src + i * width };
+        let dst = dst + i * width;
+        if width == 4 {
+            mmio::write32(dst, mmio::read32(src));
+        } else {
+            mmio::write16(dst, mmio::read16(src));
+        }
+    }
+}
+
+// copy count halfwords from src to dst
+pub(crate) unsafe fn copy16(src: *const u16, dst: *mut u16, count: u16) {
+    transfer(src as usize, dst as usize, count, COPY16);
+}
+
+// copy count words from src to dst
+pub(crate) unsafe fn copy32(src: *const u32, dst: *mut u32, count: u16) {
+    transfer(src as usize, dst as usize, count, COPY32);
+}
+
+// write the word at src count times to dst
+pub(crate) unsafe fn fill32(src: *const u32, dst: *mut u32, count: u16) {
+    transfer(src as usize, dst as usize, count, FILL32);
+}
+
+#[cfg(all(test, feature = "host-vram"))]
+mod tests {
+    use super::*;
+    use crate::mmio::lock_host_memory;
+    use gba::vram::VRAM_BASE_USIZE;
+
+    #[test]
+    fn copy16_to_vram_and_back() {
+        let _memory = lock_host_memory();
+        let src: [u16; 5] = [1, 0x1234, 0xFFFF, 0x8000, 7];
+        let dst = VRAM_BASE_USIZE + 0x102;
+        unsafe { copy16(src.as_ptr(), dst as *mut u16, 5) };
+        for (i, value) in src.iter().enumerate() {
+            assert_eq!(mmio::read16(dst + i * 2), *value);
+        }
+        assert_eq!(mmio::read16(dst - 2), 0);
+        assert_eq!(mmio::read16(dst + 10), 0);
+
+        let mut back = [0u16; 5];
+        unsafe { copy16(dst as *const u16, back.as_mut_ptr(), 5) };
+        assert_eq!(back, src);
+    }
+
+    #[test]
+    fn copy32_to_vram_and_back() {
+        let _memory = lock_host_memory();
+        let src: [u32; 3] = [0x1111_2222, 0xDEAD_BEEF, 0x0000_FFFF];
+        let dst = VRAM_BASE_USIZE + 0x400;
+        unsafe { copy32(src.as_ptr(), dst as *mut u32, 3) };
+        // little endian, the low halfword first
+        assert_eq!(mmio::read16(dst), 0x2222);
+        assert_eq!(mmio::read16(dst + 2), 0x1111);
+        assert_eq!(mmio::read32(dst + 4), 0xDEAD_BEEF);
+        assert_eq!(mmio::read32(dst + 8), 0x0000_FFFF);
+        assert_eq!(mmio::read32(dst + 12), 0);
+
+        let mut back = [0u32; 3];
+        unsafe { copy32(dst as *const u32, back.as_mut_ptr(), 3) };
+        assert_eq!(back, src);
+    }
+
+    #[test]
+    fn fill32_repeats_the_source_word() {
+        let _memory = lock_host_memory();
+        let word = 0xABCD_0123u32;
+        let dst = VRAM_BASE_USIZE + 0x800;
+        unsafe { fill32(&word, dst as *mut u32, 4) };
+        for i in 0..4 {
+            assert_eq!(mmio::read32(dst + i * 4), word);
+        }
+        assert_eq!(mmio::read32(dst + 16), 0);
+    }
+
+    #[test]
+    fn zero_count_copies_nothing() {
+        let _memory = lock_host_memory();
+        let src = [0x5555u16; 2];
+        unsafe { copy16(src.as_ptr(), VRAM_BASE_USIZE as *mut u16, 0) };
+        assert_eq!(mmio::read16(VRAM_BASE_USIZE), 0);
+    }
+}
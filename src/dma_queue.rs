@@ -0,0 +1,156 @@
+use crate::{dma, queued::QueueFull};
+use gba::io::display::VCOUNT;
+
+// one copy waiting in a DmaQueue
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Transfer {
+    pub src: usize,
+    pub dst: usize,
+    pub count: u16, // halfwords or words
+    pub wide: bool, // 32 bit units
+}
+
+// identifies a queued transfer, for is_complete()
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TransferId(u32);
+
+// vram copies scheduled on DMA3 without waiting for them
+// poll() hands the next transfer to DMA3 set to start at vblank, so the game
+// keeps running until then, the cpu is still halted while a transfer runs
+// call on_vblank() from the vblank handler to run the rest of the queue back
+// to back while vblank lasts, whatever doesn't fit waits for the next vblank
+//     static QUEUE: SharedDisplay<DmaQueue<32>> = SharedDisplay::new();
+//     QUEUE.with(|queue| queue.on_vblank());
+// the source of a queued copy has to stay put until is_complete() says so
+pub struct DmaQueue<const N: usize> {
+    queue: [Option<Transfer>; N],
+    head: usize,
+    len: usize,
+    active: bool,   // DMA3 holds the transfer at head
+    queued: u32,    // ids handed out
+    completed: u32, // transfers done, every id below this is complete
+}
+
+impl<const N: usize> DmaQueue<N> {
+    pub const fn new() -> Self {
+        DmaQueue {
+            queue: [None; N],
+            head: 0,
+            len: 0,
+            active: false,
+            queued: 0,
+            completed: 0,
+        }
+    }
+
+    // copy count halfwords from src to dst
+    // src has to stay valid and unchanged until the copy is complete
+    pub unsafe fn copy16(
+        &mut self,
+        src: *const u16,
+        dst: *mut u16,
+        count: u16,
+    ) -> Result<TransferId, QueueFull> {
+        self.push(Transfer {
+            src: src as usize,
+            dst: dst as usize,
+            count,
+            wide: false,
+        })
+    }
+
+    // copy count words from src to dst, the same rules as copy16
+    pub unsafe fn copy32(
+        &mut self,
+        src: *const u32,
+        dst: *mut u32,
+        count: u16,
+    ) -> Result<TransferId, QueueFull> {
+        self.push(Transfer {
+            src: src as usize,
+            dst: dst as usize,
+            count,
+            wide: true,
+        })
+    }
+
+    pub(crate) fn push(&mut self, transfer: Transfer) -> Result<TransferId, QueueFull> {
+        if self.len == N {
+            return Err(QueueFull);
+        }
+        self.queue[(self.head + self.len) % N] = Some(transfer);
+        self.len += 1;
+        let id = TransferId(self.queued);
+        self.queued = self.queued.wrapping_add(1);
+        Ok(id)
+    }
+
+    // transfers queued or in flight
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_complete(&self, id: TransferId) -> bool {
+        self.completed.wrapping_sub(id.0) as i32 > 0
+    }
+
+    // retire a finished transfer and schedule the next one for vblank, returns
+    // true once the queue is empty
+    pub fn poll(&mut self) -> bool {
+        self.advance(true);
+        self.is_empty()
+    }
+
+    // run queued transfers right away until the queue is empty or vblank is
+    // over, from the vblank handler
+    pub fn on_vblank(&mut self) {
+        self.advance(false);
+        while self.len > 0 && VCOUNT.read() >= 160 {
+            self.advance(false);
+        }
+    }
+
+    fn advance(&mut self, vblank: bool) {
+        if self.active {
+            if dma::busy() {
+                return;
+            }
+            self.retire();
+        }
+        while self.len > 0 {
+            let transfer = match self.queue[self.head] {
+                Some(transfer) => transfer,
+                None => break,
+            };
+            if transfer.count == 0 {
+                self.retire(); // a count of 0 means max length to the hardware
+                continue;
+            }
+            let control = dma::copy_control(transfer.wide, vblank);
+            unsafe { dma::start(transfer.src, transfer.dst, transfer.count, control) };
+            self.active = true;
+            if !vblank && !dma::busy() {
+                self.retire(); // an immediate copy halts the cpu until it is done
+            }
+            break;
+        }
+    }
+
+    fn retire(&mut self) {
+        self.queue[self.head] = None;
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        self.active = false;
+        self.completed = self.completed.wrapping_add(1);
+    }
+}
+
+impl<const N: usize> Default for DmaQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,133 @@
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DrawListFull;
+
+// one recorded drawing operation
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Op<C> {
+    Span { start: Point, width: u32, color: C }, // a horizontal run from draw_iter
+    Fill { area: Rectangle, color: C },
+    Clear(C),
+}
+
+// drawing recorded into up to N operations and replayed onto any display
+// later, as many times as needed, like a menu drawn once and replayed onto
+// both mode 4 pages, or drawing done during the frame and replayed in vblank
+//     let mut menu: DrawList<PaletteColor, 64> = DrawList::new(Size::new(240, 160));
+//     draw_menu(&mut menu)?;
+//     menu.replay(&mut Mode4Display::page0())?;
+//     menu.replay(&mut Mode4Display::page1())?;
+// pixels in a row with one color are kept as one span, fill_solid and clear
+// as themselves, clear drops everything recorded before it
+pub struct DrawList<C, const N: usize> {
+    ops: [Option<Op<C>>; N],
+    len: usize,
+    size: Size, // what the list says its bounding box is while recording
+}
+
+impl<C: PixelColor, const N: usize> DrawList<C, N> {
+    pub fn new(size: Size) -> Self {
+        DrawList {
+            ops: [None; N],
+            len: 0,
+            size,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // forget everything recorded
+    pub fn reset(&mut self) {
+        self.len = 0;
+    }
+
+    // draw every operation onto display in the order recorded
+    pub fn replay<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        for op in self.ops[..self.len].iter().flatten() {
+            match *op {
+                Op::Span {
+                    start,
+                    width,
+                    color,
+                } => display.fill_solid(&Rectangle::new(start, Size::new(width, 1)), color)?,
+                Op::Fill { area, color } => display.fill_solid(&area, color)?,
+                Op::Clear(color) => display.clear(color)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, op: Op<C>) -> Result<(), DrawListFull> {
+        let slot = self.ops.get_mut(self.len).ok_or(DrawListFull)?;
+        *slot = Some(op);
+        self.len += 1;
+        Ok(())
+    }
+
+    fn push_pixel(&mut self, Pixel(coord, color): Pixel<C>) -> Result<(), DrawListFull> {
+        if let Some(Some(Op::Span {
+            start,
+            width,
+            color: last,
+        })) = self.len.checked_sub(1).map(|i| self.ops[i])
+        {
+            if last == color && coord == start + Point::new(width as i32, 0) {
+                self.ops[self.len - 1] = Some(Op::Span {
+                    start,
+                    width: width + 1,
+                    color,
+                });
+                return Ok(());
+            }
+        }
+        self.push(Op::Span {
+            start: coord,
+            width: 1,
+            color,
+        })
+    }
+}
+
+impl<C: PixelColor, const N: usize> DrawTarget for DrawList<C, N> {
+    type Color = C;
+    type Error = DrawListFull;
+
+    // pixels up to the one that didn't fit are recorded
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels.into_iter() {
+            self.push_pixel(pixel)?;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        self.push(Op::Fill { area: *area, color })
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.len = 0;
+        self.push(Op::Clear(color))
+    }
+}
+
+impl<C, const N: usize> OriginDimensions for DrawList<C, N> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
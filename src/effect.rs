@@ -0,0 +1,352 @@
+use crate::{background::Background, mmio, video_state::write_shadowed};
+use embedded_graphics::prelude::*;
+
+const BLDCNT: usize = 0x400_0050;
+const BLDALPHA: usize = 0x400_0052;
+const BLDY: usize = 0x400_0054;
+
+// layers selected as a blend target
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct BlendLayers {
+    pub bg0: bool,
+    pub bg1: bool,
+    pub bg2: bool,
+    pub bg3: bool,
+    pub obj: bool,
+    pub backdrop: bool,
+}
+
+impl BlendLayers {
+    pub const NONE: Self = Self {
+        bg0: false,
+        bg1: false,
+        bg2: false,
+        bg3: false,
+        obj: false,
+        backdrop: false,
+    };
+
+    pub const ALL: Self = Self {
+        bg0: true,
+        bg1: true,
+        bg2: true,
+        bg3: true,
+        obj: true,
+        backdrop: true,
+    };
+
+    fn bits(self) -> u16 {
+        self.bg0 as u16
+            | (self.bg1 as u16) << 1
+            | (self.bg2 as u16) << 2
+            | (self.bg3 as u16) << 3
+            | (self.obj as u16) << 4
+            | (self.backdrop as u16) << 5
+    }
+}
+
+// coefficients are in 16ths, and are capped at 16
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BlendMode {
+    None,
+    Alpha { first: u8, second: u8 }, // first * first target + second * second target
+    Brighten(u8),                    // first target towards white
+    Darken(u8),                      // first target towards black
+}
+
+// the color special effects registers, BLDCNT, BLDALPHA and BLDY
+// a sprite in semi transparent mode is alpha blended with the second target
+// no matter what first target and mode are set
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ColorEffect {
+    pub first: BlendLayers,
+    pub second: BlendLayers, // only used by alpha blending
+    pub mode: BlendMode,
+}
+
+impl ColorEffect {
+    pub fn new(mode: BlendMode) -> Self {
+        ColorEffect {
+            first: BlendLayers::NONE,
+            second: BlendLayers::NONE,
+            mode,
+        }
+    }
+
+    pub fn with_first(self, first: BlendLayers) -> Self {
+        ColorEffect { first, ..self }
+    }
+
+    pub fn with_second(self, second: BlendLayers) -> Self {
+        ColorEffect { second, ..self }
+    }
+
+    pub fn with_mode(self, mode: BlendMode) -> Self {
+        ColorEffect { mode, ..self }
+    }
+
+    pub fn apply(&self) {
+        let coefficient = |c: u8| c.min(16) as u16;
+        let mode = match self.mode {
+            BlendMode::None => 0,
+            BlendMode::Alpha { first, second } => {
+                mmio::write16(BLDALPHA, coefficient(first) | coefficient(second) << 8);
+                1
+            }
+            BlendMode::Brighten(amount) => {
+                write_shadowed(BLDY, coefficient(amount));
+                2
+            }
+            BlendMode::Darken(amount) => {
+                write_shadowed(BLDY, coefficient(amount));
+                3
+            }
+        };
+        mmio::write16(
+            BLDCNT,
+            self.first.bits() | mode << 6 | self.second.bits() << 8,
+        );
+    }
+
+    pub fn disable() {
+        mmio::write16(BLDCNT, 0);
+    }
+}
+
+// fade layers to or from black (or white) with the brightness effect
+// call tick() once per vblank
+pub struct ScreenFade {
+    pub layers: BlendLayers,
+    white: bool,
+    fade_in: bool, // from black to the picture
+    frames: u16,
+    frame: u16,
+}
+
+impl ScreenFade {
+    pub fn fade_in(frames: u16) -> Self {
+        Self::new(true, frames)
+    }
+
+    pub fn fade_out(frames: u16) -> Self {
+        Self::new(false, frames)
+    }
+
+    fn new(fade_in: bool, frames: u16) -> Self {
+        let fade = ScreenFade {
+            layers: BlendLayers::ALL,
+            white: false,
+            fade_in,
+            frames: frames.max(1),
+            frame: 0,
+        };
+        fade.write();
+        fade
+    }
+
+    pub fn with_layers(self, layers: BlendLayers) -> Self {
+        let fade = ScreenFade { layers, ..self };
+        fade.write();
+        fade
+    }
+
+    // fade to or from white instead of black
+    pub fn with_white(self, white: bool) -> Self {
+        let fade = ScreenFade { white, ..self };
+        fade.write();
+        fade
+    }
+
+    // step the fade, returns true once it is done
+    // a finished fade out stays black until the effect is disabled
+    pub fn tick(&mut self) -> bool {
+        if !self.done() {
+            self.frame += 1;
+            self.write();
+        }
+        self.done()
+    }
+
+    pub fn done(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    fn write(&self) {
+        let step = if self.fade_in {
+            self.frames - self.frame
+        } else {
+            self.frame
+        };
+        let amount = (step as u32 * 16 / self.frames as u32) as u8;
+        let mode = if self.white {
+            BlendMode::Brighten(amount)
+        } else {
+            BlendMode::Darken(amount)
+        };
+        ColorEffect::new(mode).with_first(self.layers).apply();
+    }
+}
+
+// master brightness, for dimming the screen behind a pause menu
+// levels go from -16, black, through 0, unchanged, to 16, white, and every
+// layer is a target, which replaces any other color effect while it isn't 0
+pub struct Brightness {
+    level: i8,
+    from: i8,
+    target: i8,
+    frames: u16,
+    frame: u16,
+}
+
+impl Brightness {
+    // starts unchanged
+    pub fn new() -> Self {
+        Brightness {
+            level: 0,
+            from: 0,
+            target: 0,
+            frames: 0,
+            frame: 0,
+        }
+    }
+
+    pub fn level(&self) -> i8 {
+        self.level
+    }
+
+    // change the brightness now, stops a fade
+    pub fn set(&mut self, level: i8) {
+        let level = level.max(-16).min(16);
+        self.from = level;
+        self.target = level;
+        self.frames = 0;
+        self.frame = 0;
+        self.write(level);
+    }
+
+    // start a fade from the current level, call tick() once per vblank
+    pub fn fade_to(&mut self, level: i8, frames: u16) {
+        self.from = self.level;
+        self.target = level.max(-16).min(16);
+        self.frames = frames.max(1);
+        self.frame = 0;
+    }
+
+    // step a fade, returns true once the target level is reached
+    pub fn tick(&mut self) -> bool {
+        if !self.done() {
+            self.frame += 1;
+            let (from, target) = (self.from as i32, self.target as i32);
+            let level = from + (target - from) * self.frame as i32 / self.frames as i32;
+            self.write(level as i8);
+        }
+        self.done()
+    }
+
+    pub fn done(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    fn write(&mut self, level: i8) {
+        self.level = level;
+        let mode = match level {
+            0 => return ColorEffect::disable(),
+            level if level > 0 => BlendMode::Brighten(level as u8),
+            level => BlendMode::Darken(-level as u8),
+        };
+        ColorEffect::new(mode).with_first(BlendLayers::ALL).apply();
+    }
+}
+
+impl Default for Brightness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// a random offset that dies down over a number of frames, for hits and
+// explosions, added to background scroll or to a TranslatedDisplay's camera
+//     shake.shake(6, 20); // on impact
+//     let offset = shake.tick(); // once per vblank
+//     shake.apply(Background::Bg0, scroll);
+//     world.camera = camera + offset; // bitmap modes
+// the pattern comes from a fixed seed, so a replay shakes the same way
+pub struct ScreenShake {
+    strength: u8, // pixels at the start
+    frames: u16,
+    frame: u16,
+    seed: u32, // xorshift state, never 0
+    offset: Point,
+}
+
+impl ScreenShake {
+    pub fn new() -> Self {
+        ScreenShake {
+            strength: 0,
+            frames: 0,
+            frame: 0,
+            seed: 0x2545_F491,
+            offset: Point::zero(),
+        }
+    }
+
+    // start shaking up to strength pixels each way, a weaker shake doesn't
+    // cut a stronger one short
+    pub fn shake(&mut self, strength: u8, frames: u16) {
+        if self.done() || strength >= self.amplitude() {
+            self.strength = strength;
+            self.frames = frames.max(1);
+            self.frame = 0;
+        }
+    }
+
+    // step the shake and return the new offset, zero once it is done
+    pub fn tick(&mut self) -> Point {
+        if self.done() {
+            self.offset = Point::zero();
+            return self.offset;
+        }
+        let amplitude = self.amplitude() as i32;
+        let x = self.random(amplitude);
+        let y = self.random(amplitude);
+        self.offset = Point::new(x, y);
+        self.frame += 1;
+        self.offset
+    }
+
+    pub fn offset(&self) -> Point {
+        self.offset
+    }
+
+    pub fn done(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    // scroll bg to scroll moved by the offset
+    pub fn apply(&self, bg: Background, scroll: Point) {
+        bg.set_scroll(scroll + self.offset);
+    }
+
+    // the strength left, going down to 0 on the last frame
+    fn amplitude(&self) -> u8 {
+        if self.done() {
+            return 0;
+        }
+        let left = (self.frames - self.frame) as u32;
+        ((self.strength as u32 * left + self.frames as u32 - 1) / self.frames as u32) as u8
+    }
+
+    // -amplitude to amplitude
+    fn random(&mut self, amplitude: i32) -> i32 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+        (self.seed % (2 * amplitude as u32 + 1)) as i32 - amplitude
+    }
+}
+
+impl Default for ScreenShake {
+    fn default() -> Self {
+        Self::new()
+    }
+}
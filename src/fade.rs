@@ -0,0 +1,95 @@
+use crate::palette::PaletteKind;
+use embedded_graphics::{pixelcolor::Bgr555, prelude::*};
+
+// fades both palettes from their colors when created to a target color
+// call tick() once per vblank, the whole palram is rewritten each tick
+pub struct PaletteFade {
+    bg: [Bgr555; 256],
+    obj: [Bgr555; 256],
+    target: Bgr555,
+    frames: u16,
+    frame: u16,
+    reverse: bool, // fade from the target back to the snapshot
+}
+
+impl PaletteFade {
+    // snapshot the current palettes
+    pub fn new(target: Bgr555, frames: u16) -> Self {
+        let mut fade = PaletteFade {
+            bg: [Bgr555::BLACK; 256],
+            obj: [Bgr555::BLACK; 256],
+            target,
+            frames: frames.max(1),
+            frame: 0,
+            reverse: false,
+        };
+        for i in 0..256 {
+            fade.bg[i] = PaletteKind::Background.read(i as u8);
+            fade.obj[i] = PaletteKind::Object.read(i as u8);
+        }
+        fade
+    }
+
+    pub fn to_black(frames: u16) -> Self {
+        Self::new(Bgr555::BLACK, frames)
+    }
+
+    pub fn to_white(frames: u16) -> Self {
+        Self::new(Bgr555::WHITE, frames)
+    }
+
+    // step towards the end of the fade, returns true once it is done
+    pub fn tick(&mut self) -> bool {
+        if self.done() {
+            return true;
+        }
+        self.frame += 1;
+        self.write();
+        self.done()
+    }
+
+    pub fn done(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    // start fading back from the target to the snapshot
+    pub fn fade_back(&mut self) {
+        self.reverse = !self.reverse;
+        self.frame = 0;
+    }
+
+    // put the snapshot back in palram
+    pub fn restore(&self) {
+        for i in 0..256 {
+            PaletteKind::Background.write(i as u8, self.bg[i]);
+            PaletteKind::Object.write(i as u8, self.obj[i]);
+        }
+    }
+
+    fn write(&self) {
+        let amount = if self.reverse {
+            self.frames - self.frame
+        } else {
+            self.frame
+        };
+        for i in 0..256 {
+            let bg = lerp(self.bg[i], self.target, amount, self.frames);
+            let obj = lerp(self.obj[i], self.target, amount, self.frames);
+            PaletteKind::Background.write(i as u8, bg);
+            PaletteKind::Object.write(i as u8, obj);
+        }
+    }
+}
+
+// step of steps from a to b
+fn lerp(a: Bgr555, b: Bgr555, step: u16, steps: u16) -> Bgr555 {
+    let channel = |a: u8, b: u8| {
+        let (a, b) = (a as i32, b as i32);
+        (a + (b - a) * step as i32 / steps as i32) as u8
+    };
+    Bgr555::new(
+        channel(a.r(), b.r()),
+        channel(a.g(), b.g()),
+        channel(a.b(), b.b()),
+    )
+}
@@ -0,0 +1,275 @@
+use crate::{fixed::isqrt, line::FastLine};
+use embedded_graphics::{
+    prelude::*,
+    primitives::{Circle, Ellipse, PrimitiveStyle, Rectangle, StrokeAlignment, StyledDrawable},
+};
+
+const MAX_CROSSINGS: usize = 32;
+
+// an 8x8 1bpp mask repeated over the display, bit x of row y set draws the
+// foreground, it is anchored to the display so neighbouring fills line up
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Stipple(pub [u8; 8]);
+
+impl Stipple {
+    pub const SOLID: Self = Stipple([0xFF; 8]);
+    pub const CHECKERBOARD: Self = Stipple([0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA, 0x55, 0xAA]);
+    pub const QUARTER: Self = Stipple([0x11, 0x00, 0x44, 0x00, 0x11, 0x00, 0x44, 0x00]);
+    pub const THREE_QUARTERS: Self = Stipple([0xEE, 0xFF, 0xBB, 0xFF, 0xEE, 0xFF, 0xBB, 0xFF]);
+    pub const HORIZONTAL_LINES: Self = Stipple([0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00, 0xFF, 0x00]);
+    pub const VERTICAL_LINES: Self = Stipple([0x55; 8]);
+
+    pub fn is_set(&self, point: Point) -> bool {
+        let row = self.0[point.y.rem_euclid(8) as usize];
+        row & (1 << point.x.rem_euclid(8)) != 0
+    }
+}
+
+// filled shapes on the bitmap displays as one span fill per row, instead of
+// testing every pixel of the bounding box against the shape
+pub trait FastFill: FastLine {
+    // the same pixels as a filled embedded-graphics Circle
+    fn fill_circle(&mut self, circle: &Circle, color: Self::Color) {
+        // twice the distance from the center, so odd diameters stay whole
+        let diameter = circle.diameter as i64;
+        let threshold = if diameter <= 4 {
+            diameter * diameter - diameter / 2
+        } else {
+            diameter * diameter
+        };
+        let top_left = circle.top_left;
+        let center = Point::new(top_left.x * 2, top_left.y * 2)
+            + Point::new(diameter as i32 - 1, diameter as i32 - 1);
+        for y in top_left.y..top_left.y + diameter as i32 {
+            let dy = (y * 2 - center.y) as i64;
+            let rest = threshold - dy * dy; // dx * dx has to be below this
+            if rest > 0 {
+                fill_row(self, center.x, y, isqrt(rest as u64 - 1) as i32, color);
+            }
+        }
+    }
+
+    // a filled ellipse in the bounding box of ellipse
+    fn fill_ellipse(&mut self, ellipse: &Ellipse, color: Self::Color) {
+        let (width, height) = (ellipse.size.width as i64, ellipse.size.height as i64);
+        if width == 0 || height == 0 {
+            return;
+        }
+        let top_left = ellipse.top_left;
+        let center = Point::new(top_left.x * 2, top_left.y * 2)
+            + Point::new(width as i32 - 1, height as i32 - 1);
+        // inside when (dx / width)^2 + (dy / height)^2 < 1, at twice the scale
+        let (a, b) = (width * width, height * height);
+        for y in top_left.y..top_left.y + height as i32 {
+            let dy = (y * 2 - center.y) as i64;
+            let rest = a * b - dy * dy * a; // dx * dx * b has to be below this
+            if rest > 0 {
+                fill_row(
+                    self,
+                    center.x,
+                    y,
+                    isqrt(((rest - 1) / b) as u64) as i32,
+                    color,
+                );
+            }
+        }
+    }
+
+    // fill a polygon with the even-odd rule, so convex, concave and self
+    // intersecting outlines all work, the last point joins the first
+    // pixels on the top and left edges are filled and on the bottom and right
+    // edges they aren't, so polygons sharing an edge don't overlap
+    // each row crosses at most MAX_CROSSINGS edges, the rest are ignored
+    fn fill_polygon(&mut self, points: &[Point], color: Self::Color) {
+        let height = self.bounding_box().size.height as i32;
+        let top = points.iter().map(|p| p.y).min().unwrap_or(0).max(0);
+        let bottom = points.iter().map(|p| p.y).max().unwrap_or(0).min(height);
+        let mut crossings = [0i32; MAX_CROSSINGS]; // x in 16.16 fixed point
+        for y in top..bottom {
+            let mut count = 0;
+            let edges = points.iter().zip(points.iter().cycle().skip(1));
+            for (p0, p1) in edges {
+                if (p0.y <= y) == (p1.y <= y) || count == MAX_CROSSINGS {
+                    continue; // doesn't cross this row
+                }
+                let dx = (p1.x - p0.x) as i64;
+                let dy = (p1.y - p0.y) as i64;
+                let x = ((p0.x as i64) << 16) + (((y - p0.y) as i64 * dx) << 16) / dy;
+                // insertion sort as they come in
+                let mut i = count;
+                while i > 0 && crossings[i - 1] > x as i32 {
+                    crossings[i] = crossings[i - 1];
+                    i -= 1;
+                }
+                crossings[i] = x as i32;
+                count += 1;
+            }
+            for pair in crossings[..count].chunks_exact(2) {
+                // pixels from the first whole pixel at or after the entering
+                // edge to the last one before the leaving edge
+                let left = (pair[0] + 0xFFFF) >> 16;
+                let right = ((pair[1] + 0xFFFF) >> 16) - 1;
+                if left <= right {
+                    self.draw_line(Point::new(left, y), Point::new(right, y), color);
+                }
+            }
+        }
+    }
+
+    // fill area with a stipple pattern, set bits in foreground and clear bits
+    // in background, or left alone when background is None, for 50% shading
+    // or greyed out widgets without alpha
+    //     display.fill_pattern(&button, Stipple::CHECKERBOARD, Bgr555::BLACK, None);
+    // each run of set or clear bits in a row is one span
+    fn fill_pattern(
+        &mut self,
+        area: &Rectangle,
+        stipple: Stipple,
+        foreground: Self::Color,
+        background: Option<Self::Color>,
+    ) {
+        let area = area.intersection(&self.bounding_box());
+        let right = match area.bottom_right() {
+            Some(bottom_right) => bottom_right.x,
+            None => return,
+        };
+        for y in area.rows() {
+            let mut x = area.top_left.x;
+            while x <= right {
+                let set = stipple.is_set(Point::new(x, y));
+                let mut end = x;
+                while end < right && stipple.is_set(Point::new(end + 1, y)) == set {
+                    end += 1;
+                }
+                let color = if set { Some(foreground) } else { background };
+                if let Some(color) = color {
+                    self.draw_line(Point::new(x, y), Point::new(end, y), color);
+                }
+                x = end + 1;
+            }
+        }
+    }
+
+    // the outline of rect, width pixels thick inside it, as width spans along
+    // the top and the bottom and width columns down each side between them
+    fn stroke_rectangle(&mut self, rect: &Rectangle, width: u32, color: Self::Color) {
+        let bottom_right = match rect.bottom_right() {
+            Some(bottom_right) => bottom_right,
+            None => return,
+        };
+        let (left, top) = (rect.top_left.x, rect.top_left.y);
+        let (right, bottom) = (bottom_right.x, bottom_right.y);
+        // a stroke wider than half the rectangle covers all of it
+        let rows = width.min((rect.size.height + 1) / 2) as i32;
+        let columns = width.min((rect.size.width + 1) / 2) as i32;
+        for i in 0..rows {
+            self.draw_line(Point::new(left, top + i), Point::new(right, top + i), color);
+            if bottom - i > top + i {
+                let y = bottom - i;
+                self.draw_line(Point::new(left, y), Point::new(right, y), color);
+            }
+        }
+        if top + rows > bottom - rows {
+            return;
+        }
+        let (top, bottom) = (top + rows, bottom - rows);
+        for i in 0..columns {
+            let x = left + i;
+            self.draw_line(Point::new(x, top), Point::new(x, bottom), color);
+            if right - i > x {
+                let x = right - i;
+                self.draw_line(Point::new(x, top), Point::new(x, bottom), color);
+            }
+        }
+    }
+
+    // draw a styled rectangle with the fill as one fill_solid and the stroke
+    // as stroke_rectangle, the same pixels as embedded-graphics draws for
+    // every stroke alignment, for bordered menu panels
+    fn draw_styled_rectangle(
+        &mut self,
+        rect: &Rectangle,
+        style: &PrimitiveStyle<Self::Color>,
+    ) -> Result<(), Self::Error> {
+        let width = match style.stroke_color {
+            Some(_) => style.stroke_width,
+            None => 0,
+        };
+        let outside = match style.stroke_alignment {
+            StrokeAlignment::Inside => 0,
+            StrokeAlignment::Center => width / 2,
+            StrokeAlignment::Outside => width,
+        };
+        let outer = rect.offset(outside as i32);
+        if let Some(color) = style.fill_color {
+            self.fill_solid(&outer.offset(-(width as i32)), color)?;
+        }
+        if let Some(color) = style.stroke_color {
+            self.stroke_rectangle(&outer, width, color);
+        }
+        Ok(())
+    }
+
+    // draw a styled circle, the fill takes the fast path and the stroke is
+    // drawn over it by embedded-graphics
+    fn draw_styled_circle(
+        &mut self,
+        circle: &Circle,
+        style: &PrimitiveStyle<Self::Color>,
+    ) -> Result<(), Self::Error> {
+        if let Some(color) = style.fill_color {
+            self.fill_circle(circle, color);
+        }
+        stroke(self, circle, style)
+    }
+
+    // draw a styled ellipse, the fill takes the fast path and the stroke is
+    // drawn over it by embedded-graphics
+    fn draw_styled_ellipse(
+        &mut self,
+        ellipse: &Ellipse,
+        style: &PrimitiveStyle<Self::Color>,
+    ) -> Result<(), Self::Error> {
+        if let Some(color) = style.fill_color {
+            self.fill_ellipse(ellipse, color);
+        }
+        stroke(self, ellipse, style)
+    }
+}
+
+impl<D: FastLine> FastFill for D {}
+
+// fill the pixels of row y that are at most reach half pixels from center_x,
+// which is in half pixels
+fn fill_row<D: FastLine>(display: &mut D, center_x: i32, y: i32, reach: i32, color: D::Color) {
+    // the distance in half pixels has the parity of the center
+    let reach = if (reach - center_x) % 2 != 0 {
+        reach - 1
+    } else {
+        reach
+    };
+    if reach >= 0 {
+        let (left, right) = ((center_x - reach) / 2, (center_x + reach) / 2);
+        display.draw_line(Point::new(left, y), Point::new(right, y), color);
+    }
+}
+
+// draw only the stroke of a styled shape
+fn stroke<D, P>(
+    display: &mut D,
+    shape: &P,
+    style: &PrimitiveStyle<D::Color>,
+) -> Result<(), D::Error>
+where
+    D: FastLine,
+    P: StyledDrawable<PrimitiveStyle<D::Color>, Color = D::Color, Output = ()>,
+{
+    match style.stroke_color {
+        Some(_) if style.stroke_width > 0 => {
+            let mut style = *style;
+            style.fill_color = None;
+            shape.draw_styled(&style, display)
+        }
+        _ => Ok(()),
+    }
+}
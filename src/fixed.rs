@@ -0,0 +1,243 @@
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+use embedded_graphics::prelude::*;
+
+// signed fixed point numbers stored in an i32
+// Fixed8 is the 8.8 (and 20.8) format of the affine registers, Fixed16 is
+// 16.16 for sub-pixel positions and velocities
+macro_rules! fixed {
+    ($name:ident, $frac:expr) => {
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+        pub struct $name(pub i32);
+
+        impl $name {
+            pub const FRACTION_BITS: u32 = $frac;
+            pub const ZERO: Self = Self(0);
+            pub const ONE: Self = Self(1 << $frac);
+
+            pub const fn from_int(value: i32) -> Self {
+                Self(value << $frac)
+            }
+
+            pub fn from_ratio(numerator: i32, denominator: i32) -> Self {
+                Self((((numerator as i64) << $frac) / denominator as i64) as i32)
+            }
+
+            // rounds towards negative infinity
+            pub const fn to_int(self) -> i32 {
+                self.0 >> $frac
+            }
+
+            pub const fn fract(self) -> Self {
+                Self(self.0 & ((1 << $frac) - 1))
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+            fn add(self, other: Self) -> Self {
+                Self(self.0.wrapping_add(other.0))
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, other: Self) -> Self {
+                Self(self.0.wrapping_sub(other.0))
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self(self.0.wrapping_neg())
+            }
+        }
+
+        impl Mul for $name {
+            type Output = Self;
+            fn mul(self, other: Self) -> Self {
+                Self(((self.0 as i64 * other.0 as i64) >> $frac) as i32)
+            }
+        }
+
+        impl Mul<i32> for $name {
+            type Output = Self;
+            fn mul(self, other: i32) -> Self {
+                Self(self.0.wrapping_mul(other))
+            }
+        }
+
+        impl Div for $name {
+            type Output = Self;
+            fn div(self, other: Self) -> Self {
+                Self((((self.0 as i64) << $frac) / other.0 as i64) as i32)
+            }
+        }
+    };
+}
+
+fixed!(Fixed8, 8);
+fixed!(Fixed16, 16);
+
+impl From<Fixed8> for Fixed16 {
+    fn from(value: Fixed8) -> Self {
+        Fixed16(value.0 << 8)
+    }
+}
+
+impl From<Fixed16> for Fixed8 {
+    fn from(value: Fixed16) -> Self {
+        Fixed8(value.0 >> 8)
+    }
+}
+
+// sin of a quarter turn in 64 steps, 4.12 fixed point
+const QUARTER_SIN: [i16; 65] = [
+    0, 101, 201, 301, 401, 501, 601, 700, //
+    799, 897, 995, 1092, 1189, 1285, 1380, 1474, //
+    1567, 1660, 1751, 1842, 1931, 2019, 2106, 2191, //
+    2276, 2359, 2440, 2520, 2598, 2675, 2751, 2824, //
+    2896, 2967, 3035, 3102, 3166, 3229, 3290, 3349, //
+    3406, 3461, 3513, 3564, 3612, 3659, 3703, 3745, //
+    3784, 3822, 3857, 3889, 3920, 3948, 3973, 3996, //
+    4017, 4036, 4052, 4065, 4076, 4085, 4091, 4095, //
+    4096,
+];
+
+// 0x10000 steps per turn like the bios affine calls, counter-clockwise
+// the lookup tables use the high byte, so there are 256 distinct angles
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Angle(pub u16);
+
+impl Angle {
+    pub const fn from_turns(numerator: u32, denominator: u32) -> Self {
+        Angle((((numerator as u64) << 16) / denominator as u64) as u16)
+    }
+
+    pub fn from_degrees(degrees: i32) -> Self {
+        Angle((degrees.rem_euclid(360) as u32 * 0x10000 / 360) as u16)
+    }
+
+    pub fn sin(self) -> Fixed16 {
+        let step = (self.0 >> 8) as u8;
+        let quarter = (step % 64) as usize;
+        let value = match step / 64 {
+            0 => QUARTER_SIN[quarter],
+            1 => QUARTER_SIN[64 - quarter],
+            2 => -QUARTER_SIN[quarter],
+            _ => -QUARTER_SIN[64 - quarter],
+        };
+        Fixed16((value as i32) << 4) // 4.12 to 16.16
+    }
+
+    pub fn cos(self) -> Fixed16 {
+        (self + Angle(0x4000)).sin()
+    }
+}
+
+impl Add for Angle {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Angle(self.0.wrapping_add(other.0))
+    }
+}
+
+impl Sub for Angle {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Angle(self.0.wrapping_sub(other.0))
+    }
+}
+
+// a sub-pixel position
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct FixedPoint {
+    pub x: Fixed16,
+    pub y: Fixed16,
+}
+
+impl FixedPoint {
+    pub const fn new(x: Fixed16, y: Fixed16) -> Self {
+        FixedPoint { x, y }
+    }
+
+    pub const fn from_point(point: Point) -> Self {
+        FixedPoint {
+            x: Fixed16::from_int(point.x),
+            y: Fixed16::from_int(point.y),
+        }
+    }
+
+    // the pixel containing this position
+    pub const fn to_point(self) -> Point {
+        Point::new(self.x.to_int(), self.y.to_int())
+    }
+}
+
+impl Add for FixedPoint {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        FixedPoint::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for FixedPoint {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for FixedPoint {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        FixedPoint::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+// integer square root, rounded down
+pub(crate) fn isqrt(n: u64) -> u64 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+#[cfg(all(test, feature = "host-vram"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_turns_small_fractions() {
+        assert_eq!(Angle::from_turns(0, 3), Angle(0));
+        assert_eq!(Angle::from_turns(1, 4), Angle(0x4000));
+        assert_eq!(Angle::from_turns(3, 8), Angle(0x6000));
+    }
+
+    #[test]
+    fn from_turns_large_numerator() {
+        assert_eq!(Angle::from_turns(100_000, 400_000), Angle(0x4000));
+        assert_eq!(
+            Angle::from_turns(3_000_000_000, 4_000_000_000),
+            Angle(0xC000)
+        );
+        assert_eq!(Angle::from_turns(u32::MAX, u32::MAX), Angle(0)); // a whole turn
+    }
+}
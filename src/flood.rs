@@ -0,0 +1,80 @@
+use crate::{line::FastLine, queued::QueueFull, read::ReadPixel};
+use embedded_graphics::prelude::*;
+
+// fill the area of one color around seed with color, like a paint bucket
+// spans of a row are filled at once and only the starts of spans on the rows
+// above and below are remembered, in stack instead of by recursion, a full
+// screen takes a few hundred entries at most for simple shapes, a large one
+// can go in ewram:
+//     #[link_section = ".ewram"]
+//     static mut STACK: [u32; 4096] = [0; 4096];
+// when stack runs out the spans already remembered are still filled, but the
+// parts of the area only reachable through a dropped one keep the old color
+// and QueueFull is returned, the area is left partly filled then, and since
+// the seed has the new color, calling again from it does nothing, continue
+// from a point that still has the old color or use a bigger stack
+pub fn flood_fill<D>(
+    display: &mut D,
+    seed: Point,
+    color: D::Color,
+    stack: &mut [u32],
+) -> Result<(), QueueFull>
+where
+    D: ReadPixel + FastLine,
+{
+    let target = match display.read_pixel(seed) {
+        Some(target) if target != color => target,
+        _ => return Ok(()), // off the display or already filled
+    };
+    let height = display.bounding_box().size.height as i32;
+    let matches =
+        |display: &D, x: i32, y: i32| display.read_pixel(Point::new(x, y)) == Some(target);
+
+    let mut len = 0;
+    push(stack, &mut len, seed.x, seed.y)?; // nothing filled yet
+    let mut full = false; // a span start was dropped
+
+    while len > 0 {
+        len -= 1;
+        let (x, y) = ((stack[len] & 0xFFFF) as i32, (stack[len] >> 16) as i32);
+        if !matches(display, x, y) {
+            continue; // filled since it was pushed
+        }
+        let mut left = x;
+        while matches(display, left - 1, y) {
+            left -= 1;
+        }
+        let mut right = x;
+        while matches(display, right + 1, y) {
+            right += 1;
+        }
+        display.draw_line(Point::new(left, y), Point::new(right, y), color);
+
+        for row in [y - 1, y + 1]
+            .iter()
+            .copied()
+            .filter(|row| (0..height).contains(row))
+        {
+            let mut in_span = false;
+            for col in left..=right {
+                let inside = matches(display, col, row);
+                if inside && !in_span && push(stack, &mut len, col, row).is_err() {
+                    full = true;
+                }
+                in_span = inside;
+            }
+        }
+    }
+    if full {
+        Err(QueueFull)
+    } else {
+        Ok(())
+    }
+}
+
+fn push(stack: &mut [u32], len: &mut usize, x: i32, y: i32) -> Result<(), QueueFull> {
+    let slot = stack.get_mut(*len).ok_or(QueueFull)?;
+    *slot = x as u32 | (y as u32) << 16;
+    *len += 1;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+use crate::{
+    both_pages::Mode4BothPagesDisplay,
+    bounds::BoundsChecked,
+    interlaced::Interlaced,
+    page::{PageFlipper, PagedDisplay},
+    remapped::RemappedDisplay,
+    scaled::ScaledDisplay,
+    sub_display::SubDisplay,
+    translated::TranslatedDisplay,
+    AffineBgDisplay, Charblock4bppDisplay, CharblockDisplay, DiffusedDisplay, DitheredDisplay,
+    GammaCorrected, Mode0Display, Mode3Display, Mode4Display, Mode5Display, Mode5DoubleBuffered,
+    QuantizedDisplay, Rotated, ScreenblockDisplay, Translucent, WindowClipped,
+};
+use embedded_graphics::draw_target::DrawTarget;
+
+// finish a frame of drawing, for a draw then flush loop like other display
+// drivers, call during vblank
+// displays that draw straight to vram have nothing to do, page flipped and
+// buffered displays show what was drawn
+pub trait Flush {
+    fn flush(&mut self);
+}
+
+impl Flush for Mode3Display {
+    fn flush(&mut self) {}
+}
+
+impl Flush for Mode4Display {
+    fn flush(&mut self) {}
+}
+
+impl Flush for Mode5Display {
+    fn flush(&mut self) {}
+}
+
+impl Flush for Mode5DoubleBuffered {
+    fn flush(&mut self) {
+        self.present();
+    }
+}
+
+impl Flush for Mode4BothPagesDisplay {
+    fn flush(&mut self) {}
+}
+
+impl Flush for Mode0Display {
+    fn flush(&mut self) {}
+}
+
+impl Flush for AffineBgDisplay {
+    fn flush(&mut self) {}
+}
+
+impl Flush for CharblockDisplay {
+    fn flush(&mut self) {}
+}
+
+impl Flush for Charblock4bppDisplay {
+    fn flush(&mut self) {}
+}
+
+impl Flush for ScreenblockDisplay {
+    fn flush(&mut self) {}
+}
+
+impl<D: PagedDisplay> Flush for PageFlipper<D> {
+    fn flush(&mut self) {
+        self.flip();
+    }
+}
+
+// adapters flush the display they draw to
+
+impl<D: Flush> Flush for Rotated<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<D: Flush> Flush for DiffusedDisplay<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<D: Flush> Flush for DitheredDisplay<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<D: Flush> Flush for GammaCorrected<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<D: Flush> Flush for Translucent<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<'a, D: Flush> Flush for QuantizedDisplay<'a, D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<'a, D: Flush> Flush for WindowClipped<'a, D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<D: Flush> Flush for ScaledDisplay<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<'a, D: Flush> Flush for SubDisplay<'a, D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<D: Flush> Flush for TranslatedDisplay<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<D: Flush> Flush for BoundsChecked<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+impl<'a, D: Flush> Flush for RemappedDisplay<'a, D> {
+    fn flush(&mut self) {
+        self.display.flush();
+    }
+}
+
+// the next pass draws the next rows
+impl<D: DrawTarget + Flush> Flush for Interlaced<D> {
+    fn flush(&mut self) {
+        self.display.flush();
+        self.advance();
+    }
+}
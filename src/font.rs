@@ -0,0 +1,141 @@
+use crate::{
+    arena::Vram,
+    mmio,
+    mode::with_forced_blank,
+    obj_tiles::{Bpp, ObjVramFull, TileHandle},
+    vram, PaletteColor, Tile4bppDisplay,
+};
+use embedded_graphics::{
+    geometry::Size,
+    mono_font::{MonoFont, MonoTextStyle},
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+const TILE_BYTES: usize = 32; // 4bpp
+
+// render a mono font into 4bpp background tiles, so tile text uses the same
+// fonts as the bitmap modes
+// each glyph takes a block of tiles covering its size, stored row major and
+// glyph after glyph, a 6x8 font is one tile per glyph, 10x20 is 2x3 tiles
+pub struct FontBaker<'a> {
+    font: &'a MonoFont<'a>,
+    first_char: u8,
+    last_char: u8,
+    offset: Point,
+    color: PaletteColor,
+}
+
+// where a baked font's glyphs are
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BakedFont {
+    pub first_tile: u16,
+    pub first_char: u8,
+    pub last_char: u8,
+    pub tiles_wide: u16, // tiles per glyph across
+    pub tiles_high: u16, // tiles per glyph down
+}
+
+impl<'a> FontBaker<'a> {
+    // printable ascii drawn with palette index 1
+    pub fn new(font: &'a MonoFont<'a>) -> Self {
+        FontBaker {
+            font,
+            first_char: b' ',
+            last_char: b'~',
+            offset: Point::zero(),
+            color: PaletteColor::new(1),
+        }
+    }
+
+    pub fn with_chars(self, first_char: u8, last_char: u8) -> Self {
+        FontBaker {
+            first_char,
+            last_char,
+            ..self
+        }
+    }
+
+    // move glyphs inside their tiles, like centering a 6 pixel glyph
+    pub fn with_offset(self, offset: Point) -> Self {
+        FontBaker { offset, ..self }
+    }
+
+    pub fn with_color(self, color: PaletteColor) -> Self {
+        FontBaker { color, ..self }
+    }
+
+    // a negative offset crops the glyph's top or left rather than adding tiles
+    pub fn glyph_tiles(&self) -> Size {
+        let offset = Size::new(self.offset.x.max(0) as u32, self.offset.y.max(0) as u32);
+        let size = self.font.character_size + offset;
+        Size::new((size.width + 7) / 8, (size.height + 7) / 8)
+    }
+
+    // draw every glyph to charblock starting at tile first_tile, with the
+    // display force blanked
+    pub fn bake(&self, charblock: usize, first_tile: u16) -> BakedFont {
+        with_forced_blank(|| self.bake_glyphs(charblock, first_tile))
+    }
+
+    // bake into obj tiles claimed from vram, for ObjText, rather than picking
+    // the charblock and tile by hand which bypasses the Vram arena
+    pub fn bake_obj(&self, vram: &mut Vram) -> Result<(BakedFont, TileHandle), ObjVramFull> {
+        let glyphs = self.last_char.saturating_sub(self.first_char) as usize + 1;
+        let tiles = self.glyph_tiles();
+        let count = glyphs * (tiles.width * tiles.height) as usize;
+        let handle = vram.obj.alloc_obj_tiles(count, Bpp::Bpp4)?;
+        Ok((self.bake(4, handle.tile_id()), handle))
+    }
+
+    fn bake_glyphs(&self, charblock: usize, first_tile: u16) -> BakedFont {
+        let tiles = self.glyph_tiles();
+        let per_glyph = (tiles.width * tiles.height) as usize;
+        let style = MonoTextStyle::new(self.font, self.color);
+        for (n, c) in (self.first_char..=self.last_char).enumerate() {
+            let bytes = [c];
+            let text = match core::str::from_utf8(&bytes) {
+                Ok(text) => text,
+                Err(_) => continue, // not ascii
+            };
+            for i in 0..per_glyph {
+                let (col, row) = (i as u32 % tiles.width, i as u32 / tiles.width);
+                let position = self.offset - Point::new(col as i32 * 8, row as i32 * 8);
+                let mut tile = Tile4bppDisplay::new(PaletteColor::TANSPARENT);
+                Text::with_baseline(text, position, style, Baseline::Top)
+                    .draw(&mut tile)
+                    .ok();
+                let index = first_tile as usize + n * per_glyph + i;
+                let address = vram::charblock_address(charblock) + index * TILE_BYTES;
+                for (j, word) in tile.tile.0.iter().enumerate() {
+                    mmio::write32(address + j * 4, *word);
+                }
+            }
+        }
+        BakedFont {
+            first_tile,
+            first_char: self.first_char,
+            last_char: self.last_char,
+            tiles_wide: tiles.width as u16,
+            tiles_high: tiles.height as u16,
+        }
+    }
+}
+
+impl BakedFont {
+    // tile id of the top left tile of c's glyph, the rest of the glyph follows
+    // in row major order
+    pub fn tile_id(&self, c: char) -> Option<u16> {
+        let c = c as u32;
+        if c < self.first_char as u32 || c > self.last_char as u32 {
+            return None;
+        }
+        let per_glyph = self.tiles_wide * self.tiles_high;
+        Some(self.first_tile + (c - self.first_char as u32) as u16 * per_glyph)
+    }
+
+    pub fn tile_count(&self) -> usize {
+        let glyphs = self.last_char.saturating_sub(self.first_char) as usize + 1;
+        glyphs * (self.tiles_wide * self.tiles_high) as usize
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{palette, scanline};
+use core::ptr::{addr_of, addr_of_mut};
+use gba::io::{
+    display::{DISPSTAT, VCOUNT},
+    irq::{set_irq_handler, IrqEnableSetting, IrqFlags, BIOS_IF, IE, IF, IME},
+};
+
+// vblank interrupt handling, a frame counter, and frame rate limiting
+//     enable_vblank_irq();
+//     let mut limiter = FrameLimiter::new(2); // 30 fps
+//     loop {
+//         update();
+//         limiter.vsync();
+//         draw();
+//     }
+
+// vblanks since enable_vblank_irq, written by the irq handler
+static mut FRAMES: u32 = 0;
+
+// install a handler for the vblank interrupt and turn it on
+// for a custom irq handler, call on_vblank_irq from it instead
+pub fn enable_vblank_irq() {
+    set_irq_handler(irq_handler);
+    DISPSTAT.write(DISPSTAT.read().with_vblank_irq_enable(true));
+    IE.write(IE.read().with_vblank(true));
+    IME.write(IrqEnableSetting::IRQ_YES);
+}
+
+// also installed by enable_hblank_irq
+pub(crate) extern "C" fn irq_handler(flags: IrqFlags) {
+    if flags.hblank() {
+        scanline::on_hblank_irq();
+    }
+    if flags.vblank() {
+        on_vblank_irq();
+    }
+}
+
+// count the frame and acknowledge the interrupt in the bios and hardware, so
+// vblank_interrupt_wait wakes up, give a scanline renderer its vblank and
+// commit buffered palettes
+pub fn on_vblank_irq() {
+    scanline::on_vblank();
+    palette::commit_palettes();
+    unsafe { addr_of_mut!(FRAMES).write_volatile(frame_count().wrapping_add(1)) };
+    BIOS_IF.write(BIOS_IF.read().with_vblank(true));
+    IF.write(IF.read().with_vblank(true));
+}
+
+// vblanks seen so far, wraps after about 2 years
+pub fn frame_count() -> u32 {
+    unsafe { addr_of!(FRAMES).read_volatile() }
+}
+
+// sleep until the next vblank
+pub fn wait_for_vblank() {
+    gba::bios::vblank_interrupt_wait();
+}
+
+const VDRAW_LINES: u16 = 160;
+const TOTAL_LINES: u16 = 228;
+
+// scanlines left before the screen is drawn again, 0 while it is being drawn
+// a line is about 1232 cycles, the whole vblank is 68 lines
+pub fn vblank_lines_left() -> u16 {
+    let line = VCOUNT.read();
+    if line >= VDRAW_LINES {
+        TOTAL_LINES - line
+    } else {
+        0
+    }
+}
+
+// the vblank window ran out, or wasn't open, before the work was done
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OutOfVBlank;
+
+// what is left of the vblank window handed to draw_budgeted, less a margin of
+// lines kept free for the work between two checks
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VBlankBudget {
+    margin: u16,
+}
+
+impl VBlankBudget {
+    pub fn lines_left(&self) -> u16 {
+        vblank_lines_left().saturating_sub(self.margin)
+    }
+
+    // Err once the margin is reached, call between pieces of work and
+    // return with ? so the rest is deferred to the next vblank
+    pub fn check(&self) -> Result<(), OutOfVBlank> {
+        if self.lines_left() > 0 {
+            Ok(())
+        } else {
+            Err(OutOfVBlank)
+        }
+    }
+}
+
+// run vram work only while the screen isn't drawn, f checks the budget between
+// pieces of work and stops when vblank is about to end, so nothing is written
+// to vram mid frame, returns Err right away when called outside of vblank
+//     let done = draw_budgeted(4, |budget| {
+//         while let Some(tile) = pending.next() {
+//             budget.check()?;
+//             tile.upload();
+//         }
+//         Ok(())
+//     });
+// margin is in lines, large enough for the longest piece of work
+pub fn draw_budgeted<T>(
+    margin: u16,
+    f: impl FnOnce(&VBlankBudget) -> Result<T, OutOfVBlank>,
+) -> Result<T, OutOfVBlank> {
+    let budget = VBlankBudget { margin };
+    budget.check()?;
+    f(&budget)
+}
+
+// wait for the moment to show a new frame
+pub trait VSync {
+    fn vsync(&mut self);
+}
+
+// every vblank, 60 fps
+pub struct VBlank;
+
+impl VSync for VBlank {
+    fn vsync(&mut self) {
+        wait_for_vblank();
+    }
+}
+
+// every nth vblank, 2 for 30 fps and 3 for 20 fps, for scenes too heavy to
+// draw every frame, a late frame waits for the next vblank instead of
+// catching up
+pub struct FrameLimiter {
+    divider: u32,
+    last: u32, // frame count at the last vsync
+}
+
+impl FrameLimiter {
+    pub fn new(divider: u32) -> Self {
+        FrameLimiter {
+            divider: divider.max(1),
+            last: frame_count(),
+        }
+    }
+}
+
+impl VSync for FrameLimiter {
+    fn vsync(&mut self) {
+        loop {
+            wait_for_vblank();
+            let now = frame_count();
+            if now.wrapping_sub(self.last) >= self.divider {
+                self.last = now;
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use embedded_graphics::{geometry::Size, pixelcolor::Bgr555, prelude::*};
+
+// the gba lcd has a gamma of about 4 while art is made for about 2.2, so
+// colors look dark and washed out on hardware
+// GAMMA[c] = 31 * (c / 31) ^ (2.2 / 4)
+const GAMMA: [u8; 32] = [
+    0, 5, 7, 9, 10, 11, 13, 14, 15, 16, 17, 18, 18, 19, 20, 21, //
+    22, 22, 23, 24, 24, 25, 26, 26, 27, 28, 28, 29, 29, 30, 30, 31,
+];
+
+// brighten a color so it shows on the lcd like it does on a monitor
+pub fn gamma_correct(color: Bgr555) -> Bgr555 {
+    Bgr555::new(
+        GAMMA[color.r() as usize],
+        GAMMA[color.g() as usize],
+        GAMMA[color.b() as usize],
+    )
+}
+
+// gamma correct every color drawn to a Bgr555 display
+pub struct GammaCorrected<D> {
+    pub display: D,
+}
+
+impl<D> GammaCorrected<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    pub fn new(display: D) -> Self {
+        GammaCorrected { display }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+}
+
+impl<D> DrawTarget for GammaCorrected<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    type Color = Bgr555;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(coord, color)| Pixel(coord, gamma_correct(color))),
+        )
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.clear(gamma_correct(color))
+    }
+}
+
+impl<D> OriginDimensions for GammaCorrected<D>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
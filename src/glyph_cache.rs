@@ -0,0 +1,140 @@
+use crate::RamFramebuffer;
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    pixelcolor::PixelColor,
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+#[derive(Copy, Clone, PartialEq)]
+struct GlyphKey<C> {
+    font: usize, // address of the MonoFont
+    color: C,
+    c: char,
+}
+
+// mono font glyphs rendered once into ram and then copied to the display with
+// fill_contiguous, instead of going through the font's pixel iterator on every
+// draw, for a score or a timer redrawn each frame
+//     let mut glyphs = GlyphCache::<Bgr555, 6, 10, 16>::new(Bgr555::BLACK);
+//     glyphs.draw_text(&mut display, &FONT_6X10, Bgr555::WHITE, "SCORE", Point::new(4, 4))?;
+// each of the N slots holds one glyph of one font in one color of up to W by
+// H pixels, bigger glyphs are cut off, glyphs are drawn opaque over background
+// when all slots are taken the least recently drawn glyph is replaced
+pub struct GlyphCache<C, const W: usize, const H: usize, const N: usize> {
+    background: C,
+    glyphs: [[[C; W]; H]; N],
+    keys: [Option<GlyphKey<C>>; N],
+    used: [u32; N], // tick each slot was last drawn on
+    tick: u32,
+}
+
+impl<C: PixelColor, const W: usize, const H: usize, const N: usize> GlyphCache<C, W, H, N> {
+    pub fn new(background: C) -> Self {
+        GlyphCache {
+            background,
+            glyphs: [[[background; W]; H]; N],
+            keys: [None; N],
+            used: [0; N],
+            tick: 0,
+        }
+    }
+
+    pub fn background(&self) -> C {
+        self.background
+    }
+
+    // forget every glyph, like after changing the background
+    pub fn clear(&mut self, background: C) {
+        self.background = background;
+        self.keys = [None; N];
+    }
+
+    // the area a glyph of font covers, cut to the slot size
+    pub fn glyph_size(font: &MonoFont<'_>) -> Size {
+        let size = font.character_size;
+        Size::new(size.width.min(W as u32), size.height.min(H as u32))
+    }
+
+    // draw c with its top left at top_left, rendering it first if it isn't
+    // cached
+    pub fn draw_char<D>(
+        &mut self,
+        display: &mut D,
+        font: &MonoFont<'_>,
+        color: C,
+        c: char,
+        top_left: Point,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let slot = match self.slot(font, color, c) {
+            Some(slot) => slot,
+            None => return Ok(()),
+        };
+        let size = Self::glyph_size(font);
+        let glyph = &self.glyphs[slot];
+        let colors = glyph[..size.height as usize]
+            .iter()
+            .flat_map(|row| row[..size.width as usize].iter().copied());
+        display.fill_contiguous(&Rectangle::new(top_left, size), colors)
+    }
+
+    // draw a line of text and return where the next character would go
+    pub fn draw_text<D>(
+        &mut self,
+        display: &mut D,
+        font: &MonoFont<'_>,
+        color: C,
+        text: &str,
+        top_left: Point,
+    ) -> Result<Point, D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let advance = (font.character_size.width + font.character_spacing) as i32;
+        let mut position = top_left;
+        for c in text.chars() {
+            self.draw_char(display, font, color, c, position)?;
+            position.x += advance;
+        }
+        Ok(position)
+    }
+
+    // index of the slot holding the glyph, rendering it into the least
+    // recently used slot when it isn't cached, None when there are no slots
+    fn slot(&mut self, font: &MonoFont<'_>, color: C, c: char) -> Option<usize> {
+        let key = GlyphKey {
+            font: font as *const MonoFont<'_> as usize,
+            color,
+            c,
+        };
+        self.tick = self.tick.wrapping_add(1);
+        let slot = match self.keys.iter().position(|k| *k == Some(key)) {
+            Some(slot) => slot,
+            None => {
+                let slot = (0..N).max_by_key(|&i| match self.keys[i] {
+                    Some(_) => self.tick.wrapping_sub(self.used[i]),
+                    None => u32::MAX,
+                })?;
+                self.render(slot, font, key);
+                slot
+            }
+        };
+        self.used[slot] = self.tick;
+        Some(slot)
+    }
+
+    fn render(&mut self, slot: usize, font: &MonoFont<'_>, key: GlyphKey<C>) {
+        let mut glyph = RamFramebuffer::<C, W, H>::new(self.background);
+        let mut buffer = [0u8; 4];
+        let text = key.c.encode_utf8(&mut buffer);
+        let style = MonoTextStyle::new(font, key.color);
+        // drawing into ram can't fail
+        let _ = Text::with_baseline(text, Point::zero(), style, Baseline::Top).draw(&mut glyph);
+        self.glyphs[slot] = glyph.pixels;
+        self.keys[slot] = Some(key);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::{blend::blend, dither::dither};
+use embedded_graphics::{
+    geometry::Size,
+    pixelcolor::{Bgr555, Rgb888},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GradientDirection {
+    Vertical,   // from the top row to the bottom row
+    Horizontal, // from the left column to the right column
+}
+
+// fill area going from one color to another, like a sky behind a title screen
+//     fill_gradient(&mut display, &sky, Bgr555::BLUE, Bgr555::WHITE, Vertical, false)?;
+// without dithering each run of rows or columns that rounds to the same color
+// is one fill_solid, with dithering every pixel gets the bayer pattern of
+// dither() so the 32 levels per channel don't show as bands, which costs a
+// pixel at a time through fill_contiguous
+pub fn fill_gradient<D>(
+    display: &mut D,
+    area: &Rectangle,
+    from: Bgr555,
+    to: Bgr555,
+    direction: GradientDirection,
+    dithered: bool,
+) -> Result<(), D::Error>
+where
+    D: DrawTarget<Color = Bgr555>,
+{
+    let area = area.intersection(&display.bounding_box());
+    if area.is_zero_sized() {
+        return Ok(());
+    }
+    let steps = match direction {
+        GradientDirection::Vertical => area.size.height,
+        GradientDirection::Horizontal => area.size.width,
+    };
+    if dithered {
+        let (from, to) = (Rgb888::from(from), Rgb888::from(to));
+        let colors = area.points().map(|point| {
+            let offset = point - area.top_left;
+            let step = match direction {
+                GradientDirection::Vertical => offset.y,
+                GradientDirection::Horizontal => offset.x,
+            };
+            dither(mix(from, to, step as u32, steps), point)
+        });
+        return display.fill_contiguous(&area, colors);
+    }
+    let color_at = |step: u32| blend(to, from, (step * 255 / (steps - 1).max(1)) as u8);
+    let mut start = 0;
+    while start < steps {
+        let color = color_at(start);
+        let mut end = start + 1;
+        while end < steps && color_at(end) == color {
+            end += 1;
+        }
+        let band = match direction {
+            GradientDirection::Vertical => Rectangle::new(
+                area.top_left + Point::new(0, start as i32),
+                Size::new(area.size.width, end - start),
+            ),
+            GradientDirection::Horizontal => Rectangle::new(
+                area.top_left + Point::new(start as i32, 0),
+                Size::new(end - start, area.size.height),
+            ),
+        };
+        display.fill_solid(&band, color)?;
+        start = end;
+    }
+    Ok(())
+}
+
+// the color step of steps along from from to to
+fn mix(from: Rgb888, to: Rgb888, step: u32, steps: u32) -> Rgb888 {
+    let last = (steps - 1).max(1) as i32;
+    let channel = |a: u8, b: u8| (a as i32 + (b as i32 - a as i32) * step as i32 / last) as u8;
+    Rgb888::new(
+        channel(from.r(), to.r()),
+        channel(from.g(), to.g()),
+        channel(from.b(), to.b()),
+    )
+}
@@ -0,0 +1,97 @@
+use crate::{vram, PaletteKind, TileEntry};
+use embedded_graphics::pixelcolor::{raw::RawU16, Bgr555};
+
+// zero copy views of grit's raw binary output (-ftb), as loaded with
+// include_bytes!, each uploaded with a single call
+
+const OBJ_CHARBLOCK: usize = 4;
+
+// .img.bin, tile graphics
+#[derive(Debug, Copy, Clone)]
+pub struct GritTiles<'a>(pub &'a [u8]);
+
+impl<'a> GritTiles<'a> {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // copy to a background charblock, starting offset bytes in
+    pub fn upload(&self, charblock: usize, offset: usize) {
+        vram::copy_bytes(self.0, vram::charblock_address(charblock) + offset);
+    }
+
+    // copy to obj vram starting at an obj tile id (32 byte units)
+    pub fn upload_obj(&self, tile_id: u16) {
+        let dst = vram::charblock_address(OBJ_CHARBLOCK) + tile_id as usize * 32;
+        vram::copy_bytes(self.0, dst);
+    }
+}
+
+// .map.bin, text background map entries
+#[derive(Debug, Copy, Clone)]
+pub struct GritMap<'a>(pub &'a [u8]);
+
+impl<'a> GritMap<'a> {
+    pub fn len(&self) -> usize {
+        self.0.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn entry(&self, index: usize) -> Option<TileEntry> {
+        let bytes = self.0.get(index * 2..index * 2 + 2)?;
+        Some(TileEntry(RawU16::new(u16::from_le_bytes([
+            bytes[0], bytes[1],
+        ]))))
+    }
+
+    // copy to consecutive screenblocks starting at screenblock, grit lays out
+    // maps wider than 32 tiles in screenblock order (-mLs)
+    pub fn upload(&self, screenblock: usize) {
+        vram::copy_bytes(self.0, vram::screenblock_address(screenblock));
+    }
+}
+
+// .pal.bin, Bgr555 colors
+#[derive(Debug, Copy, Clone)]
+pub struct GritPalette<'a>(pub &'a [u8]);
+
+impl<'a> GritPalette<'a> {
+    pub fn len(&self) -> usize {
+        self.0.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // copy to the background palette starting at index first
+    pub fn upload_bg(&self, first: u8) {
+        self.upload(PaletteKind::Background, first);
+    }
+
+    // copy to the object palette starting at index first
+    pub fn upload_obj(&self, first: u8) {
+        self.upload(PaletteKind::Object, first);
+    }
+
+    // a color at a time, so a buffered palette takes them in its shadow
+    fn upload(&self, kind: PaletteKind, first: u8) {
+        for (i, bytes) in self.clamped(first).chunks_exact(2).enumerate() {
+            let color = RawU16::new(u16::from_le_bytes([bytes[0], bytes[1]]));
+            kind.write(first + i as u8, Bgr555::from(color));
+        }
+    }
+
+    // colors that fit in the 256 entries from first
+    fn clamped(&self, first: u8) -> &'a [u8] {
+        let colors = self.len().min(256 - first as usize);
+        &self.0[..colors * 2]
+    }
+}
@@ -0,0 +1,165 @@
+use crate::capture::{REPORT_HEADER, REPORT_MAGIC};
+use std::{
+    ffi::OsString,
+    format, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    string::String,
+    thread,
+    time::{Duration, Instant},
+    vec::Vec,
+};
+
+// runs test roms in an emulator and compares their frames against golden
+// checksums, with the harness feature
+// a test rom draws a scene with each display, calls report_scene(n) while the
+// scene is shown, and report_done(count) at the end, see examples/harness_rom.rs
+// the report lands in sram, so the emulator has to write the rom's .sav file,
+// any headless mgba frontend that does works, like mgba-rom-test
+//     let harness = Harness::new("mgba-rom-test");
+//     let checksums = harness.run(Path::new("target/harness.gba"))?;
+//     let mismatches = compare_golden(Path::new("harness.golden"), &checksums)?;
+pub struct Harness {
+    command: PathBuf,
+    args: Vec<OsString>,
+    timeout: Duration,
+}
+
+// a scene that drew something else than its golden frame, a missing checksum
+// is a scene only one side has
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mismatch {
+    pub scene: usize,
+    pub expected: Option<u32>,
+    pub actual: Option<u32>,
+}
+
+impl Harness {
+    // run command with the rom path as its last argument
+    pub fn new<P: Into<PathBuf>>(command: P) -> Self {
+        Harness {
+            command: command.into(),
+            args: Vec::new(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    // arguments before the rom path
+    pub fn with_args<S: Into<OsString>>(self, args: impl IntoIterator<Item = S>) -> Self {
+        Harness {
+            args: args.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
+
+    // how long the rom gets to finish its report
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        Harness { timeout, ..self }
+    }
+
+    // run rom until its report is complete, returns each scene's checksum
+    pub fn run(&self, rom: &Path) -> io::Result<Vec<u32>> {
+        let save = rom.with_extension("sav");
+        if save.exists() {
+            fs::remove_file(&save)?; // a report from the last run
+        }
+        let mut emulator = Command::new(&self.command)
+            .args(&self.args)
+            .arg(rom)
+            .spawn()?;
+        let start = Instant::now();
+        let report = loop {
+            if let Some(report) = fs::read(&save).ok().and_then(|sram| read_report(&sram)) {
+                break Ok(report);
+            }
+            if start.elapsed() > self.timeout {
+                break Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "the rom didn't finish its report",
+                ));
+            }
+            if emulator.try_wait()?.is_some() {
+                // the emulator may write the save as it exits
+                break fs::read(&save)
+                    .ok()
+                    .and_then(|sram| read_report(&sram))
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::Other, "the emulator exited without a report")
+                    });
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
+        emulator.kill().ok();
+        emulator.wait().ok();
+        report
+    }
+}
+
+// the checksums of a complete report in an sram dump
+pub fn read_report(sram: &[u8]) -> Option<Vec<u32>> {
+    if sram.get(..4)? != &REPORT_MAGIC[..] {
+        return None;
+    }
+    let word = |offset: usize| -> Option<u32> {
+        let bytes = sram.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    let scenes = word(4)? as usize;
+    (0..scenes).map(|i| word(REPORT_HEADER + i * 4)).collect()
+}
+
+// golden files hold a checksum per line in scene order, text after the
+// checksum is a comment, like the scene's name
+// a missing file is an error too, an empty golden file expects no scenes
+pub fn read_golden(path: &Path) -> io::Result<Vec<u32>> {
+    let text = fs::read_to_string(path).map_err(|error| {
+        let message = format!("reading golden file {}: {}", path.display(), error);
+        io::Error::new(error.kind(), message)
+    })?;
+    text.lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|checksum| {
+            u32::from_str_radix(checksum.trim_start_matches("0x"), 16)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad golden checksum"))
+        })
+        .collect()
+}
+
+// write checksums as the new golden file, after checking the frames are right
+pub fn write_golden(path: &Path, checksums: &[u32]) -> io::Result<()> {
+    let mut file = fs::File::create(path)?;
+    for (scene, checksum) in checksums.iter().enumerate() {
+        writeln!(file, "0x{:08X} scene {}", checksum, scene)?;
+    }
+    Ok(())
+}
+
+// scenes whose checksum differs from the golden file at path
+pub fn compare_golden(path: &Path, checksums: &[u32]) -> io::Result<Vec<Mismatch>> {
+    let golden = read_golden(path)?;
+    let scenes = golden.len().max(checksums.len());
+    Ok((0..scenes)
+        .map(|scene| Mismatch {
+            scene,
+            expected: golden.get(scene).copied(),
+            actual: checksums.get(scene).copied(),
+        })
+        .filter(|mismatch| mismatch.expected != mismatch.actual)
+        .collect())
+}
+
+impl Mismatch {
+    pub fn describe(&self) -> String {
+        let show = |checksum: Option<u32>| match checksum {
+            Some(checksum) => format!("0x{:08X}", checksum),
+            None => String::from("nothing"),
+        };
+        format!(
+            "scene {}: expected {}, drew {}",
+            self.scene,
+            show(self.expected),
+            show(self.actual)
+        )
+    }
+}
@@ -0,0 +1,131 @@
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Interlace {
+    Rows(u32),  // every nth row, starting one lower each pass
+    Bands(u32), // n bands of rows from the top down, one per pass
+}
+
+// spread the redraw of a scene that doesn't fit in a frame over several, each
+// pass only lets through the pixels of its rows, so draw the whole scene every
+// frame and call advance(), or flush() which does, to move to the next pass
+// skip work outside of the pass with active_area() or is_active()
+pub struct Interlaced<D> {
+    pub display: D,
+    pub interlace: Interlace,
+    pass: u32,
+}
+
+impl<D: DrawTarget> Interlaced<D> {
+    pub fn new(display: D, interlace: Interlace) -> Self {
+        Interlaced {
+            display,
+            interlace,
+            pass: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    // passes to draw every row once
+    pub fn passes(&self) -> u32 {
+        match self.interlace {
+            Interlace::Rows(n) | Interlace::Bands(n) => n.max(1),
+        }
+    }
+
+    // the current pass, from 0
+    pub fn pass(&self) -> u32 {
+        self.pass
+    }
+
+    pub fn advance(&mut self) {
+        self.pass = (self.pass + 1) % self.passes();
+    }
+
+    // true when row y is drawn in this pass
+    pub fn is_active(&self, y: i32) -> bool {
+        match self.interlace {
+            Interlace::Rows(_) => y.rem_euclid(self.passes() as i32) == self.pass as i32,
+            Interlace::Bands(_) => {
+                let band = self.active_area();
+                y >= band.top_left.y && y < band.top_left.y + band.size.height as i32
+            }
+        }
+    }
+
+    // the rows drawn in this pass, every row for Interlace::Rows
+    pub fn active_area(&self) -> Rectangle {
+        let size = self.display.bounding_box().size;
+        match self.interlace {
+            Interlace::Rows(_) => Rectangle::new(Point::zero(), size),
+            Interlace::Bands(_) => {
+                let passes = self.passes();
+                let top = size.height * self.pass / passes;
+                let bottom = size.height * (self.pass + 1) / passes;
+                Rectangle::new(
+                    Point::new(0, top as i32),
+                    Size::new(size.width, bottom - top),
+                )
+            }
+        }
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Interlaced<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (passes, pass) = (self.passes() as i32, self.pass as i32);
+        match self.interlace {
+            Interlace::Rows(_) => self.display.draw_iter(
+                pixels
+                    .into_iter()
+                    .filter(|Pixel(point, _)| point.y.rem_euclid(passes) == pass),
+            ),
+            Interlace::Bands(_) => {
+                let band = self.active_area();
+                let (top, bottom) = (band.top_left.y, band.top_left.y + band.size.height as i32);
+                self.display.draw_iter(
+                    pixels
+                        .into_iter()
+                        .filter(|Pixel(point, _)| point.y >= top && point.y < bottom),
+                )
+            }
+        }
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        match self.interlace {
+            Interlace::Rows(_) => {
+                let passes = self.passes() as i32;
+                let top = area.top_left.y;
+                let first = top + (self.pass as i32 - top).rem_euclid(passes);
+                for y in (first..top + area.size.height as i32).step_by(passes as usize) {
+                    let row = Rectangle::new(
+                        Point::new(area.top_left.x, y),
+                        Size::new(area.size.width, 1),
+                    );
+                    self.display.fill_solid(&row, color)?;
+                }
+                Ok(())
+            }
+            Interlace::Bands(_) => {
+                let area = area.intersection(&self.active_area());
+                self.display.fill_solid(&area, color)
+            }
+        }
+    }
+}
+
+impl<D: DrawTarget> OriginDimensions for Interlaced<D> {
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
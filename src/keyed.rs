@@ -0,0 +1,120 @@
+use crate::{
+    dma, mmio, vram, Mode3Display, Mode4Display, Mode4RowWriter, Mode5Display, PaletteColor,
+};
+use embedded_graphics::{geometry::Size, pixelcolor::Bgr555, prelude::*, primitives::Rectangle};
+use gba::vram::VRAM_BASE_USIZE;
+
+// blits that leave the framebuffer alone where the source has a key color, for
+// drawing sprites and ui with transparency onto the bitmap modes
+// source data has the ImageRaw layout, and each run of opaque pixels in an on
+// screen row is written at once
+
+impl Mode3Display {
+    // draw little endian Bgr555 image data, skipping pixels of key
+    pub fn draw_image_keyed(&mut self, data: &[u8], width: u32, top_left: Point, key: Bgr555) {
+        blit_keyed16(data, width, top_left, key, VRAM_BASE_USIZE, self.size());
+    }
+}
+
+impl Mode5Display {
+    pub fn draw_image_keyed(&mut self, data: &[u8], width: u32, top_left: Point, key: Bgr555) {
+        let page = vram::page_address(self.page);
+        blit_keyed16(data, width, top_left, key, page, self.size());
+    }
+}
+
+impl Mode4Display {
+    // draw 8bpp palette index data, skipping index 0
+    pub fn draw_image_keyed(&mut self, data: &[u8], width: u32, top_left: Point) {
+        let page = self.page;
+        for_each_row(data, 1, width, top_left, self.size(), |x, y, row| {
+            for_each_opaque_run(
+                row.len(),
+                |i| row[i] != 0,
+                |start, len| {
+                    let mut writer = Mode4RowWriter::new(page, x + start, y);
+                    for &index in &row[start..start + len] {
+                        writer.push(PaletteColor::new(index));
+                    }
+                },
+            );
+        });
+    }
+}
+
+fn blit_keyed16(data: &[u8], width: u32, top_left: Point, key: Bgr555, base: usize, screen: Size) {
+    let key = key.into_storage();
+    for_each_row(data, 2, width, top_left, screen, |x, y, row| {
+        let pixel = |i: usize| u16::from_le_bytes([row[i * 2], row[i * 2 + 1]]);
+        let dst = base + (y * screen.width as usize + x) * 2;
+        for_each_opaque_run(
+            row.len() / 2,
+            |i| pixel(i) != key,
+            |start, len| {
+                let dst = dst + start * 2;
+                if row.as_ptr() as usize % 2 == 0 {
+                    let src = row[start * 2..].as_ptr() as *const u16;
+                    unsafe { dma::copy16(src, dst as *mut u16, len as u16) };
+                } else {
+                    for i in 0..len {
+                        mmio::write16(dst + i * 2, pixel(start + i));
+                    }
+                }
+            },
+        );
+    });
+}
+
+// call f(x, y, bytes) with the screen position and data of the on screen part
+// of each image row
+fn for_each_row<F>(
+    data: &[u8],
+    pixel_bytes: usize,
+    width: u32,
+    top_left: Point,
+    screen: Size,
+    mut f: F,
+) where
+    F: FnMut(usize, usize, &[u8]),
+{
+    if width == 0 {
+        return;
+    }
+    let stride = width as usize * pixel_bytes;
+    let height = (data.len() / stride) as u32;
+    let area = Rectangle::new(top_left, Size::new(width, height));
+    let clip = area.intersection(&Rectangle::new(Point::zero(), screen));
+    let skip = (clip.top_left.x - top_left.x) as usize; // columns clipped on the left
+    for row in 0..clip.size.height as usize {
+        let y = clip.top_left.y as usize + row;
+        let start = (y as i32 - top_left.y) as usize * stride + skip * pixel_bytes;
+        let end = start + clip.size.width as usize * pixel_bytes;
+        f(clip.top_left.x as usize, y, &data[start..end]);
+    }
+}
+
+// call f(start, len) for each run of opaque pixels among count pixels
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32),
+    inline(never)
+)]
+pub(crate) fn for_each_opaque_run<O, F>(count: usize, opaque: O, mut f: F)
+where
+    O: Fn(usize) -> bool,
+    F: FnMut(usize, usize),
+{
+    let mut i = 0;
+    while i < count {
+        if opaque(i) {
+            let start = i;
+            while i < count && opaque(i) {
+                i += 1;
+            }
+            f(start, i - start);
+        } else {
+            i += 1;
+        }
+    }
+}
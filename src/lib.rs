@@ -1,18 +1,254 @@
 #![no_std]
-#![feature(exclusive_range_pattern)]
+
+#[cfg(any(
+    test,
+    feature = "simulator",
+    feature = "assets-build",
+    feature = "harness"
+))]
+extern crate std;
 
 use core::convert::{Infallible, TryInto};
 use embedded_graphics::{
     geometry::Size,
-    pixelcolor::{raw::RawU8, Bgr555, PixelColor},
+    image::{Image, ImageRawLE},
+    pixelcolor::{
+        raw::{RawData, RawU8},
+        Bgr555, PixelColor,
+    },
     prelude::*,
+    primitives::Rectangle,
 };
 use gba::{
+    oam::OBJAttr2,
     vram::{
         bitmap::{Mode3, Mode4, Mode5, Page},
-        Tile4bpp, Tile8bpp,
+        Tile4bpp, Tile8bpp, VRAM_BASE_USIZE,
     },
-    Color,
+};
+
+mod aa_line;
+mod affine;
+mod animation;
+mod arena;
+mod aseprite;
+mod asset;
+#[cfg(feature = "assets-build")]
+mod assets_build;
+mod background;
+mod bg_vram;
+mod blend;
+mod blit;
+mod both_pages;
+mod bounds;
+mod buffered;
+mod canvas;
+mod capture;
+mod charblock;
+mod color;
+mod composite;
+mod compositor;
+mod compress;
+mod console;
+mod cursor;
+mod cycle;
+mod dedup;
+mod dialog;
+mod dither;
+mod dma;
+mod dma_queue;
+mod draw_list;
+mod effect;
+mod fade;
+mod fill;
+mod fixed;
+mod flood;
+mod flush;
+mod font;
+mod frame;
+mod gamma;
+mod glyph_cache;
+mod gradient;
+mod grit;
+#[cfg(feature = "harness")]
+mod harness;
+mod interlaced;
+mod keyed;
+mod line;
+mod metasprite;
+mod mmio;
+mod mode;
+mod mode5_double;
+mod mode7;
+mod mosaic;
+mod oam;
+mod obj_affine;
+mod obj_layer;
+mod obj_text;
+mod obj_tiles;
+mod overlay;
+mod page;
+mod palette;
+mod palette_fx;
+mod parallax;
+mod particles;
+#[cfg(feature = "assets-build")]
+mod png;
+#[cfg(feature = "profile")]
+mod profile;
+mod prop_font;
+mod quantize;
+mod queued;
+mod ram_framebuffer;
+mod raster;
+mod raw_image;
+mod read;
+mod redraw;
+mod region;
+mod remapped;
+mod rotated;
+mod saved;
+mod scaled;
+mod scanline;
+mod screenblock;
+mod scrolling;
+mod shared;
+#[cfg(feature = "simulator")]
+mod simulator;
+mod soft_sprites;
+mod split;
+mod sprite;
+mod sprite_sheet;
+mod stamp;
+mod stream;
+mod sub_display;
+mod text_box;
+mod tiled;
+mod tiled_layer;
+mod tilemap;
+mod tileset;
+mod transition;
+mod translated;
+mod video_state;
+mod vram;
+mod widget;
+mod window;
+
+pub use aa_line::AntialiasedLine;
+pub use affine::AffineBgDisplay;
+pub use animation::{Animation, AnimationHandle, AnimationMode, Animations, Frame};
+pub use arena::Vram;
+pub use aseprite::{AsepriteSheet, SheetError};
+pub use asset::{
+    tga_palette, tga_tiles_4bpp, tga_tiles_8bpp, IndexedTga, TileImage, UnsupportedTga,
+};
+#[cfg(feature = "assets-build")]
+pub use assets_build::AssetFile;
+pub use background::Background;
+pub use bg_vram::{BgScreenblocks, BgTiles, BgVramAllocator, BgVramError};
+pub use blend::{blend, Translucent};
+pub use blit::{blit, Framebuffer};
+pub use both_pages::Mode4BothPagesDisplay;
+pub use bounds::{BoundsChecked, BoundsPolicy, OutOfBounds};
+pub use buffered::{BufferedMode3Display, MODE3_BUFFER_WORDS};
+pub use canvas::{upload_tiles, CanvasTile, SpriteCanvas, TileCanvas, TileCanvas4bpp, TileMask};
+pub use capture::{
+    capture, capture_sram, checksum, export, report_done, report_scene, shown_frame, snapshot_sram,
+    CaptureTooLarge, ExportError, ExportFormat, SNAPSHOT_MAGIC,
+};
+pub use charblock::{Charblock4bppDisplay, CharblockDisplay};
+pub use color::{FromGbaColor, IntoGbaColor};
+pub use composite::TilePalette;
+pub use compositor::{Compositor, Layer};
+pub use compress::{compressed_header, Compression, DecompressError};
+pub use console::Console;
+pub use cursor::{Cursor, CursorShape};
+pub use cycle::{PaletteCycle, PaletteCycler};
+pub use dedup::{deduplicate, TilesetFull};
+pub use dialog::{DialogBox, NineSlice, Typewriter};
+pub use dither::{dither, DiffusedDisplay, DitheredDisplay};
+pub use dma_queue::{DmaQueue, TransferId};
+pub use draw_list::{DrawList, DrawListFull};
+pub use effect::{BlendLayers, BlendMode, Brightness, ColorEffect, ScreenFade, ScreenShake};
+pub use fade::PaletteFade;
+pub use fill::{FastFill, Stipple};
+pub use fixed::{Angle, Fixed16, Fixed8, FixedPoint};
+pub use flood::flood_fill;
+pub use flush::Flush;
+pub use font::{BakedFont, FontBaker};
+pub use frame::{
+    draw_budgeted, enable_vblank_irq, frame_count, on_vblank_irq, vblank_lines_left,
+    wait_for_vblank, FrameLimiter, OutOfVBlank, VBlank, VBlankBudget, VSync,
+};
+pub use gamma::{gamma_correct, GammaCorrected};
+pub use glyph_cache::GlyphCache;
+pub use gradient::{fill_gradient, GradientDirection};
+pub use grit::{GritMap, GritPalette, GritTiles};
+#[cfg(feature = "harness")]
+pub use harness::{compare_golden, read_golden, read_report, write_golden, Harness, Mismatch};
+pub use interlaced::{Interlace, Interlaced};
+pub use line::FastLine;
+pub use metasprite::{Metasprite, MetaspritePart};
+#[cfg(feature = "host-vram")]
+pub use mmio::HostMemory;
+pub use mode::{switch_mode, with_forced_blank, Display, VideoMode};
+pub use mode5_double::{Mode5DoubleBuffered, Mode5Placement};
+pub use mode7::{Mode7, Mode7Camera};
+pub use mosaic::{set_bg_mosaic, set_obj_mosaic, Pixelate};
+pub use oam::{
+    OamManager, OamOrder, ScanlineOverflow, OBJ_LINE_CYCLES, OBJ_LINE_CYCLES_HBLANK_FREE,
+};
+pub use obj_affine::AffineMatrix;
+pub use obj_layer::ObjLayer;
+pub use obj_text::ObjText;
+pub use obj_tiles::{Bpp, ObjTileAllocator, ObjVramFull, TileHandle};
+pub use overlay::DebugOverlay;
+pub use page::{shown_page, PageFlipper, PagedDisplay};
+pub use palette::{
+    backdrop, buffer_palettes, commit_palettes, load_palette_bg, load_palette_obj,
+    palettes_buffered, rgb24, set_backdrop, Palette, PaletteKind, PaletteManager,
+};
+pub use palette_fx::PaletteTransform;
+pub use parallax::{Parallax, ParallaxLayer};
+pub use particles::{Particle, ParticleStyle, Particles};
+#[cfg(feature = "profile")]
+pub use profile::{scanlines, ProfileStats, Profiled, Profiler, VBLANK_SCANLINES};
+pub use prop_font::PropFont;
+pub use quantize::{distance, QuantizedDisplay, Quantizer};
+pub use queued::{QueueFull, QueuedDisplay};
+pub use ram_framebuffer::RamFramebuffer;
+pub use raster::{DmaChannel, Gradient, RasterEffect};
+pub use raw_image::{RawGbaImage, RawImageTarget};
+pub use read::ReadPixel;
+pub use redraw::RedrawScheduler;
+pub use remapped::{remap_table, RemappedDisplay, IDENTITY_REMAP};
+pub use rotated::{Rotated, RotatedDisplay, Rotation};
+pub use saved::{BufferTooSmall, SavedRegion};
+pub use scaled::ScaledDisplay;
+pub use scanline::{disable_hblank_irq, enable_hblank_irq, on_hblank_irq, ScanlineRenderer};
+pub use screenblock::{ScreenblockDisplay, TileEntry};
+pub use scrolling::ScrollingMap;
+pub use shared::{interrupt_free, SharedDisplay};
+#[cfg(feature = "simulator")]
+pub use simulator::{Simulator, SCREEN_HEIGHT, SCREEN_WIDTH};
+pub use soft_sprites::{SoftSpriteError, SoftSpriteId, SoftSprites};
+pub use split::{SplitLayout, SplitScreen};
+pub use sprite::{ObjLayout, ObjMapping, Sprite, SpriteError, SpriteMode, SpriteSize};
+pub use sprite_sheet::{SpriteSheet, SpriteSheetError};
+pub use stamp::Stamp;
+pub use stream::RowStreamer;
+pub use sub_display::SubDisplay;
+pub use text_box::TextBox;
+pub use tiled::Mode0Display;
+pub use tiled_layer::{tiled_entry, TiledError, TiledLayer};
+pub use tilemap::{place_image_on_bg, TileMap};
+pub use tileset::TileSet;
+pub use transition::{Blinds, Transition, WindowWipe, WipeShape};
+pub use translated::TranslatedDisplay;
+pub use video_state::VideoState;
+pub use widget::{Counter, Gauge, ProgressBar};
+pub use window::{
+    set_outside_layers, ClipToWindow, ObjWindow, Window, WindowClipped, WindowId, WindowLayers,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -43,37 +279,225 @@ impl From<PaletteColor> for RawU8 {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PaletteColor4(pub RawU8); // 4bpp palette bank in the high nibble, index in the low
+
+impl PaletteColor4 {
+    pub const fn new(bank: u8, index: u8) -> Self {
+        Self(RawU8::new((bank << 4) | (index & 0xF)))
+    }
+
+    pub fn bank(self) -> u8 {
+        self.0.into_inner() >> 4
+    }
+
+    pub fn index(self) -> u8 {
+        self.0.into_inner() & 0xF
+    }
+
+    // index 0 of every bank is transparent
+    pub fn is_transparent(self) -> bool {
+        self.index() == 0
+    }
+}
+
+impl PixelColor for PaletteColor4 {
+    type Raw = RawU8;
+}
+
+impl From<RawU8> for PaletteColor4 {
+    fn from(data: RawU8) -> Self {
+        Self(data)
+    }
+}
+
+impl From<PaletteColor4> for RawU8 {
+    fn from(value: PaletteColor4) -> Self {
+        value.0
+    }
+}
+
+// bank n of a 4bpp palette is entries 16n to 16n+15 of the flat 8bpp palette
+impl From<PaletteColor> for PaletteColor4 {
+    fn from(color: PaletteColor) -> Self {
+        Self(color.0)
+    }
+}
+
+impl From<PaletteColor4> for PaletteColor {
+    fn from(color: PaletteColor4) -> Self {
+        Self(color.0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Flip {
+    None,
+    Horizontal,
+    Vertical,
+    Both,
+}
+
+impl Flip {
+    pub fn new(horizontal: bool, vertical: bool) -> Self {
+        match (horizontal, vertical) {
+            (false, false) => Flip::None,
+            (true, false) => Flip::Horizontal,
+            (false, true) => Flip::Vertical,
+            (true, true) => Flip::Both,
+        }
+    }
+
+    // apply other on top of self, flipping twice undoes a flip
+    pub fn combine(self, other: Flip) -> Self {
+        Flip::new(
+            self.horizontal() != other.horizontal(),
+            self.vertical() != other.vertical(),
+        )
+    }
+
+    pub fn horizontal(self) -> bool {
+        self == Flip::Horizontal || self == Flip::Both
+    }
+
+    pub fn vertical(self) -> bool {
+        self == Flip::Vertical || self == Flip::Both
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default)]
 pub struct Mode3Display;
 
+impl Mode3Display {
+    // draw a little endian Bgr555 ImageRaw, the gba's native pixel layout
+    // the on screen part of each row is copied to vram with dma
+    pub fn draw_image_raw(&mut self, raw: &ImageRawLE<Bgr555>, top_left: Point) {
+        let width = raw.size().width;
+        if !blit_raw(raw.data(), width, top_left, VRAM_BASE_USIZE, self.size()) {
+            Image::new(raw, top_left).draw(self).ok();
+        }
+    }
+
+    // copy the on screen part of src to dst_top_left, mirrored according to flip
+    // each source row is buffered before it is written, so rows can overlap
+    // without a vertical flip, rows are copied in the order that reads a source
+    // row before it gets overwritten, with a vertical flip src and dst should not
+    // overlap vertically (like a reflection drawn below the source)
+    pub fn blit_flipped(&mut self, src: Rectangle, dst_top_left: Point, flip: Flip) {
+        let screen = Rectangle::new(Point::zero(), self.size());
+        let clip = src.intersection(&screen);
+        let (width, height) = (src.size.width as i32, src.size.height as i32);
+        let (clip_width, clip_height) = (clip.size.width as usize, clip.size.height as i32);
+
+        let bottom_up = dst_top_left.y > src.top_left.y;
+        let mut buffer = [0u16; Mode3::WIDTH];
+
+        for i in 0..clip_height {
+            let y = if bottom_up {
+                clip.top_left.y + clip_height - 1 - i
+            } else {
+                clip.top_left.y + i
+            };
+
+            for (col, pixel) in buffer[..clip_width].iter_mut().enumerate() {
+                let x = clip.top_left.x as usize + col;
+                *pixel = mmio::read16(VRAM_BASE_USIZE + (y as usize * Mode3::WIDTH + x) * 2);
+            }
+
+            let row = y - src.top_left.y; // row within src
+            let dst_y = dst_top_left.y
+                + if flip.vertical() {
+                    height - 1 - row
+                } else {
+                    row
+                };
+
+            for (col, pixel) in buffer[..clip_width].iter().enumerate() {
+                let col = clip.top_left.x - src.top_left.x + col as i32; // column within src
+                let dst_x = dst_top_left.x
+                    + if flip.horizontal() {
+                        width - 1 - col
+                    } else {
+                        col
+                    };
+                if let Ok((x @ 0..=239, y @ 0..=159)) = Point::new(dst_x, dst_y).try_into() {
+                    let (x, y): (u32, u32) = (x, y);
+                    let address = VRAM_BASE_USIZE + (y as usize * Mode3::WIDTH + x as usize) * 2;
+                    mmio::write16(address, *pixel);
+                }
+            }
+        }
+    }
+}
+
 impl DrawTarget for Mode3Display {
     type Color = Bgr555;
     type Error = Infallible;
 
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        for Pixel(coord, color) in pixels.into_iter() {
-            Mode3::write(
-                coord.x as usize,
-                coord.y as usize,
-                Color(color.into_storage()),
-            );
-        }
+        // primitives draw rows of one color, so write runs a word at a time
+        for_each_run(pixels, self.size(), |x, y, len, color| {
+            let address = VRAM_BASE_USIZE + (y * Mode3::WIDTH + x) * 2;
+            vram::fill16(address, len, color.into_storage());
+        });
+        Ok(())
+    }
 
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let pixels = Mode3::WIDTH * Mode3::HEIGHT;
+        vram::fill16(VRAM_BASE_USIZE, pixels, color.into_storage());
         Ok(())
     }
 
-    fn size(&self) -> Size {
-        Size::new(Mode3::WIDTH as u32, Mode3::HEIGHT as u32)
+    // each row is filled with 32 bit dma
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&Rectangle::new(Point::zero(), self.size()));
+        let (x, y) = (area.top_left.x as usize, area.top_left.y as usize);
+        for row in y..y + area.size.height as usize {
+            let address = VRAM_BASE_USIZE + (row * Mode3::WIDTH + x) * 2;
+            vram::fill16(address, area.size.width as usize, color.into_storage());
+        }
+        Ok(())
     }
 
-    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        Mode3::dma_clear_to(Color(color.into_storage()));
+    // runs of one color in a row are filled at once
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let pixels = area_pixels(area, colors);
+        for_each_run(pixels, self.size(), |x, y, len, color| {
+            let address = VRAM_BASE_USIZE + (y * Mode3::WIDTH + x) * 2;
+            vram::fill16(address, len, color.into_storage());
+        });
         Ok(())
     }
 }
 
+impl OriginDimensions for Mode3Display {
+    fn size(&self) -> Size {
+        Size::new(Mode3::WIDTH as u32, Mode3::HEIGHT as u32)
+    }
+}
+
+// a handle to a page of vram, copies draw to the same page, the page is
+// also reachable through PagedDisplay for code generic over the bitmap modes
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Mode4Display {
     pub page: Page,
 }
@@ -86,61 +510,255 @@ impl DrawTarget for Mode4Display {
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        for Pixel(coord, color) in pixels.into_iter() {
-            Mode4::write(
-                self.page,
-                coord.x as usize,
-                coord.y as usize,
-                color.into_storage(),
-            );
+        self.write_runs(pixels);
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let page = vram::page_address(self.page);
+        vram::fill8(page, Mode4::WIDTH * Mode4::HEIGHT, color.into_storage());
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&Rectangle::new(Point::zero(), self.size()));
+        let (x, y) = (area.top_left.x as usize, area.top_left.y as usize);
+        for row in y..y + area.size.height as usize {
+            Mode4RowWriter::new(self.page, x, row).push_run(color, area.size.width as usize);
         }
+        Ok(())
+    }
 
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.write_runs(area_pixels(area, colors));
         Ok(())
     }
+}
 
+impl OriginDimensions for Mode4Display {
     fn size(&self) -> Size {
         Size::new(Mode4::WIDTH as u32, Mode4::HEIGHT as u32)
     }
+}
 
-    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        Mode4::dma_clear_to(self.page, color.into_storage());
-        Ok(())
+impl Default for Mode4Display {
+    fn default() -> Self {
+        Self::page0()
+    }
+}
+
+impl Mode4Display {
+    pub const fn page0() -> Self {
+        Mode4Display { page: Page::Zero }
+    }
+
+    pub const fn page1() -> Self {
+        Mode4Display { page: Page::One }
+    }
+
+    // copy the whole of another page into this one with dma, so a back buffer
+    // can start from the shown frame and only redraw what changed
+    pub fn copy_from_page(&mut self, page: Page) {
+        if page == self.page {
+            return;
+        }
+        let src = vram::page_address(page) as *const u32;
+        let dst = vram::page_address(self.page) as *mut u32;
+        unsafe { dma::copy32(src, dst, (Mode4::WIDTH * Mode4::HEIGHT / 4) as u16) };
+    }
+
+    // runs that follow each other along a row share a writer, so neighbouring
+    // pixels of different colors are packed into one halfword too
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
+    fn write_runs<I>(&mut self, pixels: I)
+    where
+        I: IntoIterator<Item = Pixel<PaletteColor>>,
+    {
+        let page = self.page;
+        let mut writer: Option<Mode4RowWriter> = None;
+        for_each_run(pixels, self.size(), |x, y, len, color| {
+            let address = vram::page_address(page) + y * Mode4::WIDTH + x;
+            if writer.as_ref().map_or(true, |w| w.address != address) {
+                writer = Some(Mode4RowWriter::new(page, x, y)); // finishes the previous one
+            }
+            if let Some(writer) = writer.as_mut() {
+                writer.push_run(color, len);
+            }
+        });
     }
 }
 
+// writes consecutive Mode4 pixels along a row two per halfword, instead of a
+// read-modify-write per pixel, the last odd pixel is written by finish() or
+// when the writer is dropped
+// the writer doesn't check bounds, keep it within the row
+#[derive(Debug)]
+pub struct Mode4RowWriter {
+    address: usize,  // next pixel
+    low: Option<u8>, // even pixel waiting for its odd neighbour
+}
+
+impl Mode4RowWriter {
+    pub fn new(page: Page, x: usize, y: usize) -> Self {
+        Self::at(vram::page_address(page) + y * Mode4::WIDTH + x)
+    }
+
+    pub(crate) fn at(address: usize) -> Self {
+        Mode4RowWriter { address, low: None }
+    }
+
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32)
+    )]
+    pub fn push(&mut self, color: PaletteColor) {
+        let value = color.into_storage();
+        if let Some(low) = self.low.take() {
+            mmio::write16(self.address - 1, low as u16 | (value as u16) << 8);
+        } else if self.address % 2 == 0 {
+            self.low = Some(value);
+        } else {
+            vram::write8(self.address, value); // odd first pixel
+        }
+        self.address += 1;
+    }
+
+    // push len pixels of color, with dma for long runs
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
+    pub fn push_run(&mut self, color: PaletteColor, len: usize) {
+        let mut len = len;
+        while len > 0 && (self.address % 2 != 0 || len < 4) {
+            self.push(color);
+            len -= 1;
+        }
+        let pairs = len / 2;
+        vram::fill8(self.address, pairs * 2, color.into_storage());
+        self.address += pairs * 2;
+        if len % 2 != 0 {
+            self.push(color);
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if let Some(low) = self.low.take() {
+            vram::write8(self.address - 1, low);
+        }
+    }
+}
+
+impl Drop for Mode4RowWriter {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+// like Mode4Display, a handle to a page of vram
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Mode5Display {
     pub page: Page,
 }
 
+impl Mode5Display {
+    pub const fn page0() -> Self {
+        Mode5Display { page: Page::Zero }
+    }
+
+    pub const fn page1() -> Self {
+        Mode5Display { page: Page::One }
+    }
+
+    // draw a little endian Bgr555 ImageRaw, same as Mode3Display::draw_image_raw
+    pub fn draw_image_raw(&mut self, raw: &ImageRawLE<Bgr555>, top_left: Point) {
+        let page = vram::page_address(self.page);
+        if !blit_raw(raw.data(), raw.size().width, top_left, page, self.size()) {
+            Image::new(raw, top_left).draw(self).ok();
+        }
+    }
+}
+
 impl DrawTarget for Mode5Display {
     type Color = Bgr555;
     type Error = Infallible;
 
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        for Pixel(coord, color) in pixels.into_iter() {
-            Mode5::write(
-                self.page,
-                coord.x as usize,
-                coord.y as usize,
-                Color(color.into_storage()),
-            );
+        let page = vram::page_address(self.page);
+        for_each_run(pixels, self.size(), |x, y, len, color| {
+            vram::fill16(page + (y * Mode5::WIDTH + x) * 2, len, color.into_storage());
+        });
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let page = vram::page_address(self.page);
+        vram::fill16(page, Mode5::WIDTH * Mode5::HEIGHT, color.into_storage());
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&Rectangle::new(Point::zero(), self.size()));
+        let (x, y) = (area.top_left.x as usize, area.top_left.y as usize);
+        for row in y..y + area.size.height as usize {
+            let address = vram::page_address(self.page) + (row * Mode5::WIDTH + x) * 2;
+            vram::fill16(address, area.size.width as usize, color.into_storage());
         }
         Ok(())
     }
 
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let page = vram::page_address(self.page);
+        let pixels = area_pixels(area, colors);
+        for_each_run(pixels, self.size(), |x, y, len, color| {
+            vram::fill16(page + (y * Mode5::WIDTH + x) * 2, len, color.into_storage());
+        });
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Mode5Display {
     fn size(&self) -> Size {
         Size::new(Mode5::WIDTH as u32, Mode5::HEIGHT as u32)
     }
+}
 
-    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
-        Mode5::dma_clear_to(self.page, Color(color.into_storage()));
-        Ok(())
+impl Default for Mode5Display {
+    fn default() -> Self {
+        Self::page0()
     }
 }
 
+#[derive(Debug, Copy, Clone)]
 pub struct Tile4bppDisplay {
     pub tile: Tile4bpp,
 }
@@ -151,18 +769,64 @@ impl Tile4bppDisplay {
             tile: Tile4bpp([color.into_storage().into(); 8]),
         }
     }
+
+    // pack 64 row major palette indices, only the low nibble of each is kept
+    // a const fn so tiles written as byte arrays are packed at compile time
+    //     const ARROW: Tile4bppDisplay = Tile4bppDisplay::from_indices(&[...]);
+    pub const fn from_indices(indices: &[u8; 64]) -> Self {
+        let mut words = [0; 8];
+        let mut i = 0;
+        while i < 64 {
+            words[i / 8] |= ((indices[i] & 0xF) as u32) << ((i % 8) * 4);
+            i += 1;
+        }
+        Tile4bppDisplay {
+            tile: Tile4bpp(words),
+        }
+    }
+
+    // decode the packed palette index at x, y
+    pub fn pixel(&self, x: u32, y: u32) -> Option<PaletteColor> {
+        if x >= 8 || y >= 8 {
+            return None;
+        }
+        let index: u32 = x + (y * 8);
+        let word: u32 = self.tile.0[index as usize / 8];
+        let shift = (index % 8) * 4;
+        Some(PaletteColor::new(((word >> shift) & 0xF) as u8))
+    }
+
+    // every pixel in row major order
+    pub fn iter_pixels(&self) -> impl Iterator<Item = (Point, PaletteColor)> + '_ {
+        (0..64).filter_map(move |index: u32| {
+            let (x, y) = (index % 8, index / 8);
+            self.pixel(x, y)
+                .map(|color| (Point::new(x as i32, y as i32), color))
+        })
+    }
+
+    // every pixel as a drawable, to redraw the tile onto another display
+    pub fn pixels(&self) -> impl Iterator<Item = Pixel<PaletteColor>> + '_ {
+        self.iter_pixels().map(|(point, color)| Pixel(point, color))
+    }
 }
 
 impl DrawTarget for Tile4bppDisplay {
     type Color = PaletteColor;
     type Error = Infallible;
 
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
         for Pixel(coord, color) in pixels.into_iter() {
-            if let Ok((x @ 0..8, y @ 0..8)) = coord.try_into() {
+            if let Ok((x @ 0..=7, y @ 0..=7)) = coord.try_into() {
                 let index: u32 = x + (y * 8); // index into [u4; 64] array
                 let word: &mut u32 = &mut self.tile.0[index as usize / 8];
                 *word &= !(0xF << ((index % 8) * 4)); // clear nibble
@@ -172,11 +836,99 @@ impl DrawTarget for Tile4bppDisplay {
         Ok(())
     }
 
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        fill_tile(&mut self.tile.0, 4, area, colors);
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Tile4bppDisplay {
     fn size(&self) -> Size {
         Size::new(8, 8)
     }
 }
 
+impl Default for Tile4bppDisplay {
+    fn default() -> Self {
+        Self::new(PaletteColor::TANSPARENT)
+    }
+}
+
+// a 4bpp tile drawn with absolute palette indices from a single 16 color bank
+#[derive(Debug, Copy, Clone)]
+pub struct Bank4 {
+    pub display: Tile4bppDisplay,
+    bank: u8,
+}
+
+// the color is not in the bank's 16 entry window
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OutOfBank(pub PaletteColor);
+
+impl Bank4 {
+    pub fn new(bank: u8) -> Self {
+        assert!(bank < 16, "there are only 16 palette banks");
+        Bank4 {
+            display: Tile4bppDisplay::new(PaletteColor::TANSPARENT),
+            bank,
+        }
+    }
+
+    pub fn bank(&self) -> u8 {
+        self.bank
+    }
+
+    // absolute palette index to the nibble stored in the tile
+    pub fn to_nibble(&self, color: PaletteColor) -> Result<PaletteColor, OutOfBank> {
+        if color == PaletteColor::TANSPARENT {
+            return Ok(PaletteColor::TANSPARENT); // transparent in every bank
+        }
+        let banked = PaletteColor4::from(color);
+        if banked.bank() != self.bank {
+            return Err(OutOfBank(color));
+        }
+        Ok(PaletteColor::new(banked.index()))
+    }
+
+    // color index within this bank
+    pub fn color(&self, index: u8) -> PaletteColor {
+        PaletteColor4::new(self.bank, index).into()
+    }
+
+    // attributes for an object showing this tile with the matching palette bank
+    pub fn attr2(&self, tile_id: u16) -> OBJAttr2 {
+        OBJAttr2::new()
+            .with_tile_id(tile_id)
+            .with_palbank(self.bank as u16)
+    }
+}
+
+impl DrawTarget for Bank4 {
+    type Color = PaletteColor;
+    type Error = OutOfBank;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            let nibble = self.to_nibble(color)?;
+            self.display.draw_iter(Some(Pixel(coord, nibble))).ok();
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Bank4 {
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
 pub struct Tile8bppDisplay {
     pub tile: Tile8bpp,
 }
@@ -187,18 +939,63 @@ impl Tile8bppDisplay {
             tile: Tile8bpp([color.into_storage().into(); 16]),
         }
     }
+
+    // pack 64 row major palette indices at compile time, like from_indices of
+    // Tile4bppDisplay
+    pub const fn from_indices(indices: &[u8; 64]) -> Self {
+        let mut words = [0; 16];
+        let mut i = 0;
+        while i < 64 {
+            words[i / 4] |= (indices[i] as u32) << ((i % 4) * 8);
+            i += 1;
+        }
+        Tile8bppDisplay {
+            tile: Tile8bpp(words),
+        }
+    }
+
+    // decode the packed palette index at x, y
+    pub fn pixel(&self, x: u32, y: u32) -> Option<PaletteColor> {
+        if x >= 8 || y >= 8 {
+            return None;
+        }
+        let index: u32 = x + (y * 8);
+        let word: u32 = self.tile.0[index as usize / 4];
+        let shift = (index % 4) * 8;
+        Some(PaletteColor::new(((word >> shift) & 0xFF) as u8))
+    }
+
+    // every pixel in row major order
+    pub fn iter_pixels(&self) -> impl Iterator<Item = (Point, PaletteColor)> + '_ {
+        (0..64).filter_map(move |index: u32| {
+            let (x, y) = (index % 8, index / 8);
+            self.pixel(x, y)
+                .map(|color| (Point::new(x as i32, y as i32), color))
+        })
+    }
+
+    // every pixel as a drawable, to redraw the tile onto another display
+    pub fn pixels(&self) -> impl Iterator<Item = Pixel<PaletteColor>> + '_ {
+        self.iter_pixels().map(|(point, color)| Pixel(point, color))
+    }
 }
 
 impl DrawTarget for Tile8bppDisplay {
     type Color = PaletteColor;
     type Error = Infallible;
 
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
     fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
         for Pixel(coord, color) in pixels.into_iter() {
-            if let Ok((x @ 0..8, y @ 0..8)) = coord.try_into() {
+            if let Ok((x @ 0..=7, y @ 0..=7)) = coord.try_into() {
                 let index: u32 = x + (y * 8); // index into [u8; 64] array
                 let word: &mut u32 = &mut self.tile.0[index as usize / 4];
                 *word &= !(0xFF << ((index % 4) * 8)); // clear byte
@@ -208,7 +1005,313 @@ impl DrawTarget for Tile8bppDisplay {
         Ok(())
     }
 
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        fill_tile(&mut self.tile.0, 8, area, colors);
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Tile8bppDisplay {
     fn size(&self) -> Size {
         Size::new(8, 8)
     }
 }
+
+impl Default for Tile8bppDisplay {
+    fn default() -> Self {
+        Self::new(PaletteColor::TANSPARENT)
+    }
+}
+
+// copy the on screen part of each row of Bgr555 image data to a 16bpp
+// framebuffer at base with dma, false when the data isn't halfword aligned
+fn blit_raw(data: &[u8], width: u32, top_left: Point, base: usize, screen: Size) -> bool {
+    if data.as_ptr() as usize % 2 != 0 {
+        return false;
+    }
+    if width == 0 {
+        return true;
+    }
+    let height = data.len() as u32 / 2 / width;
+    let area = Rectangle::new(top_left, Size::new(width, height));
+    let clip = area.intersection(&Rectangle::new(Point::zero(), screen));
+    let skip = (clip.top_left - top_left).x as usize; // clipped columns on the left
+    let stride = width as usize * 2; // bytes per source row
+
+    for row in 0..clip.size.height as usize {
+        let y = clip.top_left.y as usize + row;
+        let src_row = (y as i32 - top_left.y) as usize;
+        let src = data[src_row * stride + skip * 2..].as_ptr() as *const u16;
+        let dst = (base + (y * screen.width as usize + clip.top_left.x as usize) * 2) as *mut u16;
+        unsafe { dma::copy16(src, dst, clip.size.width as u16) };
+    }
+    true
+}
+
+// call fill(x, y, len, color) for every run of one color along a row, skipping
+// pixels off a screen of size
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32),
+    inline(never)
+)]
+fn for_each_run<C, I, F>(pixels: I, size: Size, mut fill: F)
+where
+    C: PixelColor,
+    I: IntoIterator<Item = Pixel<C>>,
+    F: FnMut(usize, usize, usize, C),
+{
+    let mut run: Option<(usize, usize, usize, C)> = None; // x, y, len, color
+    for Pixel(point, color) in pixels.into_iter() {
+        let on_screen = point.x >= 0
+            && point.y >= 0
+            && point.x < size.width as i32
+            && point.y < size.height as i32;
+        let (x, y) = (point.x as usize, point.y as usize);
+        match run {
+            Some((rx, ry, len, c)) if on_screen && ry == y && rx + len == x && c == color => {
+                run = Some((rx, ry, len + 1, c));
+            }
+            _ => {
+                if let Some((rx, ry, len, c)) = run.take() {
+                    fill(rx, ry, len, c);
+                }
+                if on_screen {
+                    run = Some((x, y, 1, color));
+                }
+            }
+        }
+    }
+    if let Some((x, y, len, color)) = run {
+        fill(x, y, len, color);
+    }
+}
+
+// pair row major colors with the points of area
+fn area_pixels<C, I>(area: &Rectangle, colors: I) -> impl Iterator<Item = Pixel<C>>
+where
+    I: IntoIterator<Item = C>,
+{
+    let top_left = area.top_left;
+    let width = area.size.width.max(1) as i32;
+    colors
+        .into_iter()
+        .take(area.size.width as usize * area.size.height as usize)
+        .enumerate()
+        .map(move |(i, color)| {
+            Pixel(
+                top_left + Point::new(i as i32 % width, i as i32 / width),
+                color,
+            )
+        })
+}
+
+// write row major colors for area into a packed 8x8 tile of bits per pixel
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32),
+    inline(never)
+)]
+fn fill_tile<I>(words: &mut [u32], bits: u32, area: &Rectangle, colors: I)
+where
+    I: IntoIterator<Item = PaletteColor>,
+{
+    let mut colors = colors.into_iter();
+    let words_per_row = (8 * bits / 32) as usize;
+    let pixels_per_word = (32 / bits) as usize;
+
+    for y in area.top_left.y..area.top_left.y + area.size.height as i32 {
+        // gather the part of this row that lands inside of the tile
+        let mut row = [None; 8];
+        for x in area.top_left.x..area.top_left.x + area.size.width as i32 {
+            match colors.next() {
+                Some(color) if (0..8).contains(&x) => row[x as usize] = Some(color),
+                Some(_) => {} // clipped
+                None => return,
+            }
+        }
+        if !(0..8).contains(&y) {
+            continue; // clipped
+        }
+
+        let start = y as usize * words_per_row;
+        for (word, pixels) in words[start..start + words_per_row]
+            .iter_mut()
+            .zip(row.chunks(pixels_per_word))
+        {
+            pack_word(word, pixels, bits);
+        }
+    }
+}
+
+// a word with every pixel present is written at once, otherwise mask per pixel
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32)
+)]
+fn pack_word(word: &mut u32, pixels: &[Option<PaletteColor>], bits: u32) {
+    let mask: u32 = (1 << bits) - 1;
+    if pixels.iter().all(Option::is_some) {
+        *word = pixels
+            .iter()
+            .flatten()
+            .enumerate()
+            .fold(0, |packed, (i, color)| {
+                packed | ((color.into_storage() as u32 & mask) << (i as u32 * bits))
+            });
+    } else {
+        for (i, pixel) in pixels.iter().enumerate() {
+            if let Some(color) = pixel {
+                let shift = i as u32 * bits;
+                *word &= !(mask << shift); // clear pixel
+                *word |= (color.into_storage() as u32 & mask) << shift; // set pixel
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "host-vram"))]
+mod tests {
+    use super::*;
+    use crate::mmio::{self, lock_host_memory};
+
+    // a palette index for each of the 64 pixels, wrapped to bits
+    fn indices(bits: u32) -> [u8; 64] {
+        let mut indices = [0; 64];
+        for (i, index) in indices.iter_mut().enumerate() {
+            *index = ((i * 37 + 5) % (1 << bits)) as u8;
+        }
+        indices
+    }
+
+    fn byte(address: usize) -> u8 {
+        mmio::read16(address & !1).to_le_bytes()[address & 1]
+    }
+
+    #[test]
+    fn tile4bpp_draw_replaces_one_nibble() {
+        let mut tile = Tile4bppDisplay::default();
+        tile.draw_iter([Pixel(Point::new(3, 5), PaletteColor::new(9))])
+            .ok();
+        tile.draw_iter([Pixel(Point::new(3, 5), PaletteColor::new(2))])
+            .ok();
+        tile.draw_iter([Pixel(Point::new(8, 0), PaletteColor::new(1))])
+            .ok(); // clipped
+        for (point, color) in tile.iter_pixels() {
+            let expected = if point == Point::new(3, 5) { 2 } else { 0 };
+            assert_eq!(color, PaletteColor::new(expected), "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn tile8bpp_draw_replaces_one_byte() {
+        let mut tile = Tile8bppDisplay::default();
+        tile.draw_iter([Pixel(Point::new(6, 1), PaletteColor::new(200))])
+            .ok();
+        tile.draw_iter([Pixel(Point::new(6, 1), PaletteColor::new(17))])
+            .ok();
+        tile.draw_iter([Pixel(Point::new(0, -1), PaletteColor::new(1))])
+            .ok(); // clipped
+        for (point, color) in tile.iter_pixels() {
+            let expected = if point == Point::new(6, 1) { 17 } else { 0 };
+            assert_eq!(color, PaletteColor::new(expected), "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn from_indices_round_trips() {
+        let indices4 = indices(4);
+        let tile4 = Tile4bppDisplay::from_indices(&indices4);
+        let indices8 = indices(8);
+        let tile8 = Tile8bppDisplay::from_indices(&indices8);
+        for i in 0..64 {
+            let (x, y) = (i as u32 % 8, i as u32 / 8);
+            assert_eq!(tile4.pixel(x, y), Some(PaletteColor::new(indices4[i])));
+            assert_eq!(tile8.pixel(x, y), Some(PaletteColor::new(indices8[i])));
+        }
+
+        let mut redrawn4 = Tile4bppDisplay::default();
+        redrawn4.draw_iter(tile4.pixels()).ok();
+        assert_eq!(redrawn4.tile.0, tile4.tile.0);
+        let mut redrawn8 = Tile8bppDisplay::default();
+        redrawn8.draw_iter(tile8.pixels()).ok();
+        assert_eq!(redrawn8.tile.0, tile8.tile.0);
+    }
+
+    #[test]
+    fn fill_tile_matches_draw_iter() {
+        // inside, partial words, and hanging off each side of the tile
+        let areas = [
+            Rectangle::new(Point::zero(), Size::new(8, 8)),
+            Rectangle::new(Point::new(1, 2), Size::new(5, 3)),
+            Rectangle::new(Point::new(-3, -2), Size::new(6, 5)),
+            Rectangle::new(Point::new(5, 6), Size::new(7, 4)),
+        ];
+        for area in areas.iter() {
+            let colors = (0..).map(|i: u32| PaletteColor::new((i * 7 % 16) as u8));
+            let pixels = || area.points().zip(colors.clone()).map(|(p, c)| Pixel(p, c));
+
+            let mut filled4 = Tile4bppDisplay::from_indices(&indices(4));
+            filled4.fill_contiguous(area, colors.clone()).ok();
+            let mut drawn4 = Tile4bppDisplay::from_indices(&indices(4));
+            drawn4.draw_iter(pixels()).ok();
+            assert_eq!(filled4.tile.0, drawn4.tile.0, "{:?}", area);
+
+            let mut filled8 = Tile8bppDisplay::from_indices(&indices(8));
+            filled8.fill_contiguous(area, colors.clone()).ok();
+            let mut drawn8 = Tile8bppDisplay::from_indices(&indices(8));
+            drawn8.draw_iter(pixels()).ok();
+            assert_eq!(filled8.tile.0, drawn8.tile.0, "{:?}", area);
+        }
+    }
+
+    #[test]
+    fn mode4_row_writer_odd_start_and_end() {
+        let _memory = lock_host_memory();
+        let row = vram::page_address(Page::Zero) + 2 * Mode4::WIDTH;
+        // (first pixel, pixels pushed) for odd and even starts and ends
+        for &(x, len) in [(1, 5), (3, 2), (1, 4), (2, 3), (0, 1), (5, 1)].iter() {
+            vram::fill8(row, 16, 0xEE);
+            let mut writer = Mode4RowWriter::new(Page::Zero, x, 2);
+            for i in 0..len {
+                writer.push(PaletteColor::new(i as u8 + 1));
+            }
+            drop(writer);
+            for column in 0..16 {
+                let expected = if (x..x + len).contains(&column) {
+                    (column - x) as u8 + 1
+                } else {
+                    0xEE
+                };
+                assert_eq!(byte(row + column), expected, "x {} len {}", x, len);
+            }
+        }
+    }
+
+    #[test]
+    fn mode4_row_writer_runs() {
+        let _memory = lock_host_memory();
+        let row = vram::page_address(Page::One) + 7 * Mode4::WIDTH;
+        for &(x, len) in [(1, 9), (3, 4), (2, 7), (0, 12), (5, 2)].iter() {
+            vram::fill8(row, 16, 0xEE);
+            let mut writer = Mode4RowWriter::new(Page::One, x, 7);
+            writer.push(PaletteColor::new(1));
+            writer.push_run(PaletteColor::new(2), len);
+            writer.finish();
+            for column in 0..16 {
+                let expected = match column {
+                    c if c == x => 1,
+                    c if c > x && c <= x + len => 2,
+                    _ => 0xEE,
+                };
+                assert_eq!(byte(row + column), expected, "x {} len {}", x, len);
+            }
+        }
+    }
+}
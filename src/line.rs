@@ -0,0 +1,195 @@
+use crate::{mmio, vram, Mode3Display, Mode4Display, Mode4RowWriter, Mode5Display, PaletteColor};
+use embedded_graphics::{
+    pixelcolor::Bgr555,
+    prelude::*,
+    primitives::{Line, PrimitiveStyle},
+};
+use gba::vram::{
+    bitmap::{Mode3, Mode4, Mode5},
+    VRAM_BASE_USIZE,
+};
+
+// one pixel wide lines on the bitmap displays without going through a pixel
+// iterator, horizontal lines are a single dma fill, vertical lines a strided
+// write per row, and other lines are bresenham with the pixels on each row
+// batched into one span fill
+pub trait FastLine: DrawTarget + Sized {
+    // fill len pixels along row y starting at x, doesn't check bounds
+    fn fill_span(&mut self, x: usize, y: usize, len: usize, color: Self::Color);
+
+    // fill len pixels down column x starting at y, doesn't check bounds
+    fn fill_column(&mut self, x: usize, y: usize, len: usize, color: Self::Color);
+
+    // draw a line from start to end, both included, clipped to the display
+    fn draw_line(&mut self, start: Point, end: Point, color: Self::Color) {
+        let size = self.bounding_box().size;
+        let (width, height) = (size.width as i32, size.height as i32);
+        if start.x == end.x {
+            let (top, bottom) = (
+                start.y.min(end.y).max(0),
+                start.y.max(end.y).min(height - 1),
+            );
+            if (0..width).contains(&start.x) && top <= bottom {
+                self.fill_column(
+                    start.x as usize,
+                    top as usize,
+                    (bottom - top + 1) as usize,
+                    color,
+                );
+            }
+            return;
+        }
+        if start.y == end.y {
+            let (left, right) = (start.x.min(end.x).max(0), start.x.max(end.x).min(width - 1));
+            if (0..height).contains(&start.y) && left <= right {
+                self.fill_span(
+                    left as usize,
+                    start.y as usize,
+                    (right - left + 1) as usize,
+                    color,
+                );
+            }
+            return;
+        }
+
+        // only step along the part on the display
+        let (start, end) = match clip(start, end, width, height) {
+            Some(clipped) => clipped,
+            None => return,
+        };
+
+        // emit the span from x1 to x2 on row y
+        let span = |display: &mut Self, x1: i32, x2: i32, y: i32| {
+            let (left, right) = (x1.min(x2).max(0), x1.max(x2).min(width - 1));
+            if (0..height).contains(&y) && left <= right {
+                display.fill_span(
+                    left as usize,
+                    y as usize,
+                    (right - left + 1) as usize,
+                    color,
+                );
+            }
+        };
+        let (dx, dy) = ((end.x - start.x).abs(), -(end.y - start.y).abs());
+        let (sx, sy) = ((end.x - start.x).signum(), (end.y - start.y).signum());
+        let mut error = dx + dy;
+        let (mut x, mut y) = (start.x, start.y);
+        let mut run_start = x;
+        while x != end.x || y != end.y {
+            let double = error * 2;
+            let (mut next_x, mut next_y) = (x, y);
+            if double >= dy {
+                error += dy;
+                next_x += sx;
+            }
+            if double <= dx {
+                error += dx;
+                next_y += sy;
+            }
+            if next_y != y {
+                span(self, run_start, x, y);
+                run_start = next_x;
+            }
+            x = next_x;
+            y = next_y;
+        }
+        span(self, run_start, x, y);
+    }
+
+    // draw a styled line, one pixel wide strokes take the fast path and
+    // anything else is drawn by embedded-graphics
+    fn draw_styled_line(
+        &mut self,
+        line: &Line,
+        style: &PrimitiveStyle<Self::Color>,
+    ) -> Result<(), Self::Error> {
+        match style.stroke_color {
+            Some(color) if style.stroke_width == 1 => {
+                self.draw_line(line.start, line.end, color);
+                Ok(())
+            }
+            _ => line.into_styled(*style).draw(self),
+        }
+    }
+}
+
+impl FastLine for Mode3Display {
+    fn fill_span(&mut self, x: usize, y: usize, len: usize, color: Bgr555) {
+        let address = VRAM_BASE_USIZE + (y * Mode3::WIDTH + x) * 2;
+        vram::fill16(address, len, color.into_storage());
+    }
+
+    fn fill_column(&mut self, x: usize, y: usize, len: usize, color: Bgr555) {
+        let address = VRAM_BASE_USIZE + (y * Mode3::WIDTH + x) * 2;
+        for row in 0..len {
+            mmio::write16(address + row * Mode3::WIDTH * 2, color.into_storage());
+        }
+    }
+}
+
+impl FastLine for Mode4Display {
+    fn fill_span(&mut self, x: usize, y: usize, len: usize, color: PaletteColor) {
+        Mode4RowWriter::new(self.page, x, y).push_run(color, len);
+    }
+
+    fn fill_column(&mut self, x: usize, y: usize, len: usize, color: PaletteColor) {
+        let address = vram::page_address(self.page) + y * Mode4::WIDTH + x;
+        for row in 0..len {
+            vram::write8(address + row * Mode4::WIDTH, color.into_storage());
+        }
+    }
+}
+
+impl FastLine for Mode5Display {
+    fn fill_span(&mut self, x: usize, y: usize, len: usize, color: Bgr555) {
+        let address = vram::page_address(self.page) + (y * Mode5::WIDTH + x) * 2;
+        vram::fill16(address, len, color.into_storage());
+    }
+
+    fn fill_column(&mut self, x: usize, y: usize, len: usize, color: Bgr555) {
+        let address = vram::page_address(self.page) + (y * Mode5::WIDTH + x) * 2;
+        for row in 0..len {
+            mmio::write16(address + row * Mode5::WIDTH * 2, color.into_storage());
+        }
+    }
+}
+
+// the part of the line from start to end inside a width by height display,
+// the ends are where it crosses the edges, rounded to the nearest pixel
+fn clip(start: Point, end: Point, width: i32, height: i32) -> Option<(Point, Point)> {
+    let (x0, y0) = (start.x as i128, start.y as i128);
+    let (dx, dy) = (end.x as i128 - x0, end.y as i128 - y0);
+    // t along the line as a fraction with a positive denominator
+    let (mut enter, mut exit) = ((0, 1), (1, 1));
+    let edges = [
+        (-dx, x0),
+        (dx, width as i128 - 1 - x0),
+        (-dy, y0),
+        (dy, height as i128 - 1 - y0),
+    ];
+    for &(p, q) in edges.iter() {
+        if p == 0 {
+            if q < 0 {
+                return None; // parallel to the edge and outside of it
+            }
+        } else if p < 0 {
+            let t = (-q, -p);
+            if t.0 * enter.1 > enter.0 * t.1 {
+                enter = t;
+            }
+        } else {
+            let t = (q, p);
+            if t.0 * exit.1 < exit.0 * t.1 {
+                exit = t;
+            }
+        }
+    }
+    if enter.0 * exit.1 > exit.0 * enter.1 {
+        return None;
+    }
+    let at = |(num, den): (i128, i128)| {
+        let round = |d: i128| (2 * d * num + den).div_euclid(2 * den);
+        Point::new((x0 + round(dx)) as i32, (y0 + round(dy)) as i32)
+    };
+    Some((at(enter), at(exit)))
+}
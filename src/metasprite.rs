@@ -0,0 +1,75 @@
+use crate::{
+    oam::OamManager,
+    sprite::{ObjLayout, Sprite, SpriteError},
+    Flip,
+};
+use embedded_graphics::prelude::*;
+
+// one hardware object of a metasprite, offset from the metasprite's origin
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MetaspritePart {
+    pub offset: Point,
+    pub sprite: Sprite,
+}
+
+// several objects moved and flipped as one
+// flipping mirrors each part's offset around the origin, so put the origin
+// at the center of the character
+pub struct Metasprite<'a> {
+    pub parts: &'a [MetaspritePart],
+    pub position: Point,
+    pub flip: Flip,
+    pub first_slot: usize, // parts use consecutive oam slots from here
+}
+
+impl<'a> Metasprite<'a> {
+    pub fn new(parts: &'a [MetaspritePart], first_slot: usize) -> Self {
+        Metasprite {
+            parts,
+            position: Point::zero(),
+            flip: Flip::None,
+            first_slot,
+        }
+    }
+
+    pub fn with_position(self, position: Point) -> Self {
+        Metasprite { position, ..self }
+    }
+
+    pub fn with_flip(self, flip: Flip) -> Self {
+        Metasprite { flip, ..self }
+    }
+
+    // a part placed on screen with the group's position and flip
+    pub fn place(&self, part: &MetaspritePart) -> Sprite {
+        let size = part.sprite.size.size();
+        let mut offset = part.offset;
+        if self.flip.horizontal() {
+            offset.x = -offset.x - size.width as i32;
+        }
+        if self.flip.vertical() {
+            offset.y = -offset.y - size.height as i32;
+        }
+        part.sprite
+            .with_position(self.position + offset)
+            .with_flip(part.sprite.flip.combine(self.flip))
+    }
+
+    // write every part to the shadow oam, nothing is written if a part is invalid
+    pub fn write(&self, oam: &mut OamManager, layout: ObjLayout) -> Result<(), SpriteError> {
+        for part in self.parts {
+            self.place(part).validate(layout)?;
+        }
+        for (i, part) in self.parts.iter().enumerate() {
+            let attributes = self.place(part).attributes(layout)?;
+            oam.set(self.first_slot + i, attributes);
+        }
+        Ok(())
+    }
+
+    pub fn hide(&self, oam: &mut OamManager) {
+        for i in 0..self.parts.len() {
+            oam.hide(self.first_slot + i);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+// volatile access for vram and io registers the gba crate doesn't wrap
+// every access goes through a VramBackend, the hardware on the gba, or memory
+// arrays on the host with the host-vram feature so drawing code can be tested
+// off device
+
+#[cfg(feature = "host-vram")]
+use core::ptr::addr_of_mut;
+use gba::io::display::DisplayControlSetting;
+
+pub(crate) trait VramBackend {
+    fn read16(address: usize) -> u16;
+    fn write16(address: usize, value: u16);
+
+    fn read32(address: usize) -> u32 {
+        Self::read16(address) as u32 | (Self::read16(address + 2) as u32) << 16
+    }
+
+    fn write32(address: usize, value: u32) {
+        Self::write16(address, value as u16);
+        Self::write16(address + 2, (value >> 16) as u16);
+    }
+}
+
+#[cfg(not(feature = "host-vram"))]
+type Backend = Hardware;
+#[cfg(feature = "host-vram")]
+type Backend = HostMemory;
+
+#[cfg(not(feature = "host-vram"))]
+pub(crate) struct Hardware;
+
+#[cfg(not(feature = "host-vram"))]
+impl VramBackend for Hardware {
+    fn read16(address: usize) -> u16 {
+        unsafe { (address as *const u16).read_volatile() }
+    }
+
+    fn write16(address: usize, value: u16) {
+        unsafe { (address as *mut u16).write_volatile(value) }
+    }
+
+    fn read32(address: usize) -> u32 {
+        unsafe { (address as *const u32).read_volatile() }
+    }
+
+    fn write32(address: usize, value: u32) {
+        unsafe { (address as *mut u32).write_volatile(value) }
+    }
+}
+
+// io registers, palram, vram and oam as arrays of halfwords, any other address
+// is ordinary memory, like a buffer being copied from
+#[cfg(feature = "host-vram")]
+pub struct HostMemory;
+
+#[cfg(feature = "host-vram")]
+static mut IO: [u16; 0x200] = [0; 0x200];
+#[cfg(feature = "host-vram")]
+static mut PALRAM: [u16; 0x200] = [0; 0x200];
+#[cfg(feature = "host-vram")]
+static mut VRAM: [u16; 0xC000] = [0; 0xC000];
+#[cfg(feature = "host-vram")]
+static mut OAM: [u16; 0x200] = [0; 0x200];
+
+#[cfg(feature = "host-vram")]
+impl HostMemory {
+    // the halfword backing address, None outside of the emulated regions
+    // a raw pointer, so no reference to the static arrays is ever held and two
+    // lookups of one address don't alias, tests take lock_host_memory first
+    fn halfword(address: usize) -> Option<*mut u16> {
+        unsafe {
+            let (memory, offset) = match address {
+                0x400_0000..=0x400_03FF => (addr_of_mut!(IO) as *mut u16, address - 0x400_0000),
+                0x500_0000..=0x500_03FF => (addr_of_mut!(PALRAM) as *mut u16, address - 0x500_0000),
+                0x600_0000..=0x601_7FFF => (addr_of_mut!(VRAM) as *mut u16, address - 0x600_0000),
+                0x700_0000..=0x700_03FF => (addr_of_mut!(OAM) as *mut u16, address - 0x700_0000),
+                _ => return None,
+            };
+            Some(memory.add(offset / 2)) // each range is exactly its array
+        }
+    }
+
+    pub fn read16(address: usize) -> u16 {
+        <Self as VramBackend>::read16(address)
+    }
+
+    // set a register or write memory directly, like DISPCNT for the simulator
+    pub fn write16(address: usize, value: u16) {
+        <Self as VramBackend>::write16(address, value)
+    }
+
+    // zero every region, call before each test
+    pub fn reset() {
+        unsafe {
+            IO = [0; 0x200];
+            PALRAM = [0; 0x200];
+            VRAM = [0; 0xC000];
+            OAM = [0; 0x200];
+        }
+    }
+}
+
+#[cfg(feature = "host-vram")]
+impl VramBackend for HostMemory {
+    fn read16(address: usize) -> u16 {
+        match Self::halfword(address) {
+            Some(halfword) => unsafe { halfword.read() },
+            None => unsafe { (address as *const u16).read() },
+        }
+    }
+
+    fn write16(address: usize, value: u16) {
+        match Self::halfword(address) {
+            Some(halfword) => unsafe { halfword.write(value) },
+            None => unsafe { (address as *mut u16).write(value) },
+        }
+    }
+}
+
+pub(crate) fn read16(address: usize) -> u16 {
+    Backend::read16(address)
+}
+
+pub(crate) fn write16(address: usize, value: u16) {
+    Backend::write16(address, value)
+}
+
+#[cfg(feature = "host-vram")]
+pub(crate) fn read32(address: usize) -> u32 {
+    Backend::read32(address)
+}
+
+pub(crate) fn write32(address: usize, value: u32) {
+    Backend::write32(address, value)
+}
+
+// DISPCNT through the backend, in place of the gba crate's register, so the
+// mode and layers set on the host land in HostMemory
+#[derive(Copy, Clone)]
+pub(crate) struct Dispcnt;
+
+pub(crate) const DISPCNT: Dispcnt = Dispcnt;
+
+impl Dispcnt {
+    pub(crate) fn read(self) -> DisplayControlSetting {
+        // the setting is a transparent wrapper of the register's u16
+        unsafe { core::mem::transmute::<u16, DisplayControlSetting>(read16(0x400_0000)) }
+    }
+
+    pub(crate) fn write(self, setting: DisplayControlSetting) {
+        write16(0x400_0000, unsafe {
+            core::mem::transmute::<DisplayControlSetting, u16>(setting)
+        })
+    }
+}
+
+// the emulated memory is shared by every test, hold this while using it, the
+// regions are zeroed when it's taken
+#[cfg(all(test, feature = "host-vram"))]
+pub(crate) struct HostMemoryLock;
+
+#[cfg(all(test, feature = "host-vram"))]
+static LOCKED: core::sync::atomic::AtomicBool = core::sync::atomic::AtomicBool::new(false);
+
+#[cfg(all(test, feature = "host-vram"))]
+pub(crate) fn lock_host_memory() -> HostMemoryLock {
+    use core::sync::atomic::Ordering;
+    while LOCKED
+        .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        std::thread::yield_now();
+    }
+    HostMemory::reset();
+    HostMemoryLock
+}
+
+#[cfg(all(test, feature = "host-vram"))]
+impl Drop for HostMemoryLock {
+    fn drop(&mut self) {
+        LOCKED.store(false, core::sync::atomic::Ordering::Release);
+    }
+}
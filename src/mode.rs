@@ -0,0 +1,254 @@
+use crate::{
+    mmio::DISPCNT, AffineBgDisplay, Mode0Display, Mode3Display, Mode4Display, Mode5Display,
+    PaletteColor,
+};
+use embedded_graphics::{pixelcolor::Bgr555, prelude::*};
+use gba::{
+    io::display::{DisplayControlSetting, DisplayMode},
+    vram::bitmap::Page,
+};
+
+// a display that owns the screen in one of the gba video modes
+pub trait VideoMode {
+    const MODE: DisplayMode;
+
+    // enable the layers this display draws to
+    fn configure(&self, setting: DisplayControlSetting) -> DisplayControlSetting {
+        setting.with_bg2(true)
+    }
+
+    // clear the vram this display draws to
+    fn clear_vram(&mut self);
+
+    // set up DISPCNT from scratch for this display, the returned display is
+    // the only way to draw to the mode, so DISPCNT and the drawing code can't
+    // disagree, objects use 1d tile mapping when shown
+    //     let mut display = Mode3Display.enable(true);
+    fn enable(self, obj: bool) -> Self
+    where
+        Self: Sized,
+    {
+        let setting = DisplayControlSetting::new()
+            .with_mode(Self::MODE)
+            .with_obj(obj)
+            .with_oam_memory_1d(obj);
+        DISPCNT.write(self.configure(setting));
+        self
+    }
+}
+
+impl VideoMode for Mode3Display {
+    const MODE: DisplayMode = DisplayMode::Mode3;
+
+    fn clear_vram(&mut self) {
+        self.clear(Bgr555::BLACK).ok();
+    }
+}
+
+impl VideoMode for Mode4Display {
+    const MODE: DisplayMode = DisplayMode::Mode4;
+
+    fn configure(&self, setting: DisplayControlSetting) -> DisplayControlSetting {
+        setting.with_bg2(true).with_frame1(self.page == Page::One)
+    }
+
+    fn clear_vram(&mut self) {
+        self.clear(PaletteColor::new(0)).ok();
+    }
+}
+
+impl VideoMode for Mode5Display {
+    const MODE: DisplayMode = DisplayMode::Mode5;
+
+    fn configure(&self, setting: DisplayControlSetting) -> DisplayControlSetting {
+        setting.with_bg2(true).with_frame1(self.page == Page::One)
+    }
+
+    fn clear_vram(&mut self) {
+        self.clear(Bgr555::BLACK).ok();
+    }
+}
+
+// switch the video mode without showing a frame of garbage
+// the screen is blanked before DISPCNT changes and only shown again once the
+// new mode is configured and (optionally) cleared
+// object and window settings in DISPCNT are kept, backgrounds are replaced
+pub fn switch_mode<D: VideoMode>(mut display: D, clear: bool) -> D {
+    with_forced_blank(|| {
+        let setting = DISPCNT
+            .read()
+            .with_mode(D::MODE)
+            .with_bg0(false)
+            .with_bg1(false)
+            .with_bg2(false)
+            .with_bg3(false)
+            .with_frame1(false);
+        DISPCNT.write(display.configure(setting));
+
+        if clear {
+            display.clear_vram();
+        }
+    });
+    display
+}
+
+// run f with the display force blanked, so bulk uploads to vram, oam and palram
+// run at full speed and no half loaded frame is shown, the screen is white
+// until f returns, nested calls stay blanked until the outermost one returns
+//     with_forced_blank(|| {
+//         LEVEL_TILES.upload(0, 0);
+//         LEVEL_MAP.upload(28);
+//     });
+pub fn with_forced_blank<R>(f: impl FnOnce() -> R) -> R {
+    let blanked = DISPCNT.read().force_vblank();
+    DISPCNT.write(DISPCNT.read().with_force_vblank(true));
+    let result = f();
+    if !blanked {
+        DISPCNT.write(DISPCNT.read().with_force_vblank(false));
+    }
+    result
+}
+
+// whichever display owns the screen, for apps moving between modes, like a
+// mode 0 menu and a mode 3 game screen
+//     let screen = Display::from(menu);
+//     let mut screen = screen.switch_to(Mode3Display, true);
+//     if let Display::Mode3(display) = &mut screen { ... }
+pub enum Display {
+    Mode0(Mode0Display),
+    Mode1(AffineBgDisplay),
+    Mode3(Mode3Display),
+    Mode4(Mode4Display),
+    Mode5(Mode5Display),
+}
+
+impl Display {
+    // switch_mode to display's mode, the old display is given up
+    pub fn switch_to<D>(self, display: D, clear: bool) -> Display
+    where
+        D: VideoMode + Into<Display>,
+    {
+        switch_mode(display, clear).into()
+    }
+
+    pub fn mode(&self) -> DisplayMode {
+        match self {
+            Display::Mode0(_) => Mode0Display::MODE,
+            Display::Mode1(_) => AffineBgDisplay::MODE,
+            Display::Mode3(_) => Mode3Display::MODE,
+            Display::Mode4(_) => Mode4Display::MODE,
+            Display::Mode5(_) => Mode5Display::MODE,
+        }
+    }
+}
+
+impl From<Mode0Display> for Display {
+    fn from(display: Mode0Display) -> Self {
+        Display::Mode0(display)
+    }
+}
+
+impl From<AffineBgDisplay> for Display {
+    fn from(display: AffineBgDisplay) -> Self {
+        Display::Mode1(display)
+    }
+}
+
+impl From<Mode3Display> for Display {
+    fn from(display: Mode3Display) -> Self {
+        Display::Mode3(display)
+    }
+}
+
+impl From<Mode4Display> for Display {
+    fn from(display: Mode4Display) -> Self {
+        Display::Mode4(display)
+    }
+}
+
+impl From<Mode5Display> for Display {
+    fn from(display: Mode5Display) -> Self {
+        Display::Mode5(display)
+    }
+}
+
+#[cfg(all(test, feature = "host-vram"))]
+mod tests {
+    use super::*;
+    use crate::mmio::{self, lock_host_memory};
+    use core::cell::Cell;
+
+    const DISPCNT_ADDRESS: usize = 0x400_0000;
+    const FORCED_BLANK: u16 = 1 << 7;
+    const OBJ: u16 = 1 << 12;
+
+    fn dispcnt() -> u16 {
+        mmio::read16(DISPCNT_ADDRESS)
+    }
+
+    // a mode 3 display that records DISPCNT while it is being configured,
+    // before the new mode is written, and while it is cleared, after
+    struct Probe {
+        configuring: Cell<u16>,
+        clearing: u16,
+    }
+
+    impl Probe {
+        fn new() -> Self {
+            Probe {
+                configuring: Cell::new(0),
+                clearing: 0,
+            }
+        }
+    }
+
+    impl VideoMode for Probe {
+        const MODE: DisplayMode = DisplayMode::Mode3;
+
+        fn configure(&self, setting: DisplayControlSetting) -> DisplayControlSetting {
+            self.configuring.set(dispcnt());
+            setting.with_bg2(true)
+        }
+
+        fn clear_vram(&mut self) {
+            self.clearing = dispcnt();
+        }
+    }
+
+    #[test]
+    fn switch_mode_blanks_changes_mode_then_unblanks() {
+        let _memory = lock_host_memory();
+        mmio::write16(DISPCNT_ADDRESS, 1 | OBJ); // mode 1 with objects
+        let probe = switch_mode(Probe::new(), true);
+
+        // blanked before the mode changes
+        assert_ne!(probe.configuring.get() & FORCED_BLANK, 0);
+        assert_eq!(probe.configuring.get() & 7, 1);
+        // still blanked once it has
+        assert_ne!(probe.clearing & FORCED_BLANK, 0);
+        assert_eq!(probe.clearing & 7, 3);
+        // shown again afterwards, keeping the object setting
+        assert_eq!(dispcnt() & FORCED_BLANK, 0);
+        assert_eq!(dispcnt() & 7, 3);
+        assert_ne!(dispcnt() & OBJ, 0);
+    }
+
+    #[test]
+    fn switch_mode_keeps_an_earlier_forced_blank() {
+        let _memory = lock_host_memory();
+        mmio::write16(DISPCNT_ADDRESS, FORCED_BLANK);
+        switch_mode(Probe::new(), false);
+        assert_ne!(dispcnt() & FORCED_BLANK, 0);
+        assert_eq!(dispcnt() & 7, 3);
+    }
+
+    #[test]
+    fn nested_forced_blank_unblanks_when_the_outer_call_returns() {
+        let _memory = lock_host_memory();
+        with_forced_blank(|| {
+            with_forced_blank(|| assert_ne!(dispcnt() & FORCED_BLANK, 0));
+            assert_ne!(dispcnt() & FORCED_BLANK, 0);
+        });
+        assert_eq!(dispcnt() & FORCED_BLANK, 0);
+    }
+}
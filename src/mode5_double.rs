@@ -0,0 +1,112 @@
+use crate::{mmio::DISPCNT, Mode5Display, VideoMode};
+use core::convert::Infallible;
+use embedded_graphics::{
+    geometry::Size, image::ImageRawLE, pixelcolor::Bgr555, prelude::*, primitives::Rectangle,
+};
+use gba::{io::display::VCOUNT, vram::bitmap::Page};
+
+// where the 160x128 page shows on the 240x160 screen
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Mode5Placement {
+    TopLeft,
+    Centered,  // with a backdrop border
+    Stretched, // filling the screen, see Mode5Display::stretch_to_screen
+}
+
+// mode 5 double buffered without pages, drawing always goes to the page that
+// isn't shown and present() shows it during vblank
+//     let mut display = Mode5DoubleBuffered::new(false, Mode5Placement::Centered);
+//     loop {
+//         display.clear(Bgr555::BLACK)?;
+//         draw_frame(&mut display)?;
+//         display.present();
+//     }
+// after present() the hidden page holds the frame before last, not the one
+// just shown, so redraw the whole frame each time
+// not Clone, a copy would keep drawing to a page after it is shown
+#[derive(Debug)]
+pub struct Mode5DoubleBuffered {
+    display: Mode5Display, // points at the hidden page
+}
+
+impl Mode5DoubleBuffered {
+    // switch to mode 5 showing a cleared page zero, drawing goes to page one
+    pub fn new(obj: bool, placement: Mode5Placement) -> Self {
+        let mut shown = Mode5Display::page0().enable(obj);
+        shown.clear_vram();
+        let buffered = Mode5DoubleBuffered {
+            display: Mode5Display::page1(),
+        };
+        buffered.set_placement(placement);
+        buffered
+    }
+
+    pub fn set_placement(&self, placement: Mode5Placement) {
+        match placement {
+            Mode5Placement::TopLeft => self.display.unstretch(),
+            Mode5Placement::Centered => self.display.center_on_screen(),
+            Mode5Placement::Stretched => self.display.stretch_to_screen(),
+        }
+    }
+
+    // the page being drawn to
+    pub fn hidden_page(&self) -> Page {
+        self.display.page
+    }
+
+    // wait for the start of the next vblank and show what was drawn, called
+    // during vblank it waits for the one after, so two presents in one vblank
+    // don't flip twice and the page drawn next isn't the one on screen
+    pub fn present(&mut self) {
+        while VCOUNT.read() >= 160 {
+            // the rest of a vblank that already started
+        }
+        while VCOUNT.read() < 160 {
+            // drawing the screen
+        }
+        let hidden = self.display.page;
+        DISPCNT.write(DISPCNT.read().with_frame1(hidden == Page::One));
+        self.display.page = match hidden {
+            Page::Zero => Page::One,
+            Page::One => Page::Zero,
+        };
+    }
+
+    // draw little endian Bgr555 image data to the hidden page
+    pub fn draw_image_raw(&mut self, raw: &ImageRawLE<Bgr555>, top_left: Point) {
+        self.display.draw_image_raw(raw, top_left);
+    }
+}
+
+impl DrawTarget for Mode5DoubleBuffered {
+    type Color = Bgr555;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.display.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.clear(color)
+    }
+}
+
+impl OriginDimensions for Mode5DoubleBuffered {
+    fn size(&self) -> Size {
+        self.display.size()
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{
+    fixed::{Angle, Fixed16, FixedPoint},
+    raster::{DmaChannel, RasterEffect},
+};
+
+const LINES: usize = 160;
+const WORDS_PER_LINE: usize = 4; // pa and pb, pc and pd, x, y
+const BG2PA: usize = 0x400_0020;
+const CENTER: i64 = 120; // screen column the camera looks along
+
+// where the camera is over the floor
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Mode7Camera {
+    pub position: FixedPoint, // on the floor map, in pixels
+    pub height: Fixed16,      // above the floor, in pixels
+    pub angle: Angle,         // 0 looks up the map, towards -y
+}
+
+// a perspective floor on the affine bg2 of mode 1 or 2, every line below the
+// horizon gets its own scale and reference point through hblank dma
+//     let mut floor = Mode7::new(40, 160);
+//     floor.update(&camera);
+//     floor.effect(DmaChannel::Dma0).start(); // every vblank
+// lines above the horizon point outside of the map, so turn off wrapping to
+// show the backdrop or another background as the sky there
+pub struct Mode7 {
+    pub table: [u32; LINES * WORDS_PER_LINE],
+    pub horizon: i32,      // the floor starts on the line below
+    pub focal_length: i32, // in pixels, longer looks further down the floor
+}
+
+impl Mode7 {
+    pub fn new(horizon: i32, focal_length: i32) -> Self {
+        let mut mode7 = Mode7 {
+            table: [0; LINES * WORDS_PER_LINE],
+            horizon,
+            focal_length,
+        };
+        mode7.update(&Mode7Camera::default());
+        mode7
+    }
+
+    // work out every line for the camera, call once a frame before start()
+    pub fn update(&mut self, camera: &Mode7Camera) {
+        let (cos, sin) = (camera.angle.cos().0 as i64, camera.angle.sin().0 as i64);
+        let (x, y) = (camera.position.x.0 as i64, camera.position.y.0 as i64);
+        for (line, values) in self.table.chunks_exact_mut(WORDS_PER_LINE).enumerate() {
+            let below = line as i32 - self.horizon;
+            if below <= 0 {
+                // every pixel reads the one left of the map's top left
+                values.copy_from_slice(&[0, 0, (-1i32 << 8) as u32, 0]);
+                continue;
+            }
+            // floor pixels per screen pixel, 16.16
+            let scale = camera.height.0 as i64 / below as i64;
+            let distance = scale * self.focal_length as i64;
+            let pa = clamp_i16((scale * cos) >> 24); // 8.8
+            let pc = clamp_i16((scale * sin) >> 24);
+            // the line's left end, forward by distance and left by 120 pixels
+            let left_x = x + ((distance * sin - CENTER * scale * cos) >> 16);
+            let left_y = y - ((distance * cos + CENTER * scale * sin) >> 16);
+            values[0] = pa as u16 as u32; // pb is unused, the reference is set per line
+            values[1] = pc as u16 as u32; // so is pd
+            values[2] = (left_x >> 8) as i32 as u32; // 20.8
+            values[3] = (left_y >> 8) as i32 as u32;
+        }
+    }
+
+    // the effect borrows the table, start() it every vblank
+    pub fn effect(&self, channel: DmaChannel) -> RasterEffect<'_> {
+        RasterEffect::words(channel, BG2PA, &self.table, WORDS_PER_LINE as u16)
+    }
+}
+
+fn clamp_i16(value: i64) -> i16 {
+    value.max(i16::MIN as i64).min(i16::MAX as i64) as i16
+}
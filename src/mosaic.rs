@@ -0,0 +1,127 @@
+use crate::{
+    background::Background,
+    video_state::{shadowed, write_shadowed},
+    AffineBgDisplay, Mode0Display, Mode3Display, Mode4Display, Mode5Display,
+};
+use embedded_graphics::{geometry::Size, prelude::*};
+
+const MOSAIC: usize = 0x400_004C;
+
+// block size for every background with mosaic enabled
+// sizes are clamped to 1 to 16 pixels, 1x1 is no mosaic
+pub fn set_bg_mosaic(size: Size) {
+    write_mosaic(0x00FF, mosaic_bits(size));
+}
+
+// block size for every sprite with mosaic enabled, see Sprite::with_mosaic
+pub fn set_obj_mosaic(size: Size) {
+    write_mosaic(0xFF00, mosaic_bits(size) << 8);
+}
+
+fn mosaic_bits(size: Size) -> u16 {
+    let clamp = |n: u32| (n.max(1).min(16) - 1) as u16;
+    clamp(size.width) | clamp(size.height) << 4
+}
+
+// MOSAIC is write only, the shadow keeps the half that isn't changing
+fn write_mosaic(mask: u16, bits: u16) {
+    write_shadowed(MOSAIC, (shadowed(MOSAIC) & !mask) | bits);
+}
+
+impl Background {
+    pub fn set_mosaic(self, enable: bool) {
+        self.set_control(self.control().with_mosaic(enable));
+    }
+}
+
+impl Mode3Display {
+    // show the display in size blocks
+    pub fn set_mosaic(&mut self, size: Size) {
+        Background::Bg2.set_mosaic(size.width > 1 || size.height > 1);
+        set_bg_mosaic(size);
+    }
+}
+
+impl Mode4Display {
+    pub fn set_mosaic(&mut self, size: Size) {
+        Background::Bg2.set_mosaic(size.width > 1 || size.height > 1);
+        set_bg_mosaic(size);
+    }
+}
+
+impl Mode5Display {
+    pub fn set_mosaic(&mut self, size: Size) {
+        Background::Bg2.set_mosaic(size.width > 1 || size.height > 1);
+        set_bg_mosaic(size);
+    }
+}
+
+impl Mode0Display {
+    pub fn set_mosaic(&mut self, size: Size) {
+        self.bg.set_mosaic(size.width > 1 || size.height > 1);
+        set_bg_mosaic(size);
+    }
+}
+
+impl AffineBgDisplay {
+    pub fn set_mosaic(&mut self, size: Size) {
+        Background::Bg2.set_mosaic(size.width > 1 || size.height > 1);
+        set_bg_mosaic(size);
+    }
+}
+
+// grow or shrink the mosaic of backgrounds and sprites with mosaic enabled,
+// for a pixelate transition, call tick() once per vblank
+pub struct Pixelate {
+    max: u8,   // largest block size
+    out: bool, // from sharp to blocks
+    frames: u16,
+    frame: u16,
+}
+
+impl Pixelate {
+    // sharp to max sized blocks
+    pub fn pixelate_out(max: u8, frames: u16) -> Self {
+        Self::new(max, true, frames)
+    }
+
+    // max sized blocks to sharp
+    pub fn pixelate_in(max: u8, frames: u16) -> Self {
+        Self::new(max, false, frames)
+    }
+
+    fn new(max: u8, out: bool, frames: u16) -> Self {
+        let pixelate = Pixelate {
+            max: max.max(1).min(16),
+            out,
+            frames: frames.max(1),
+            frame: 0,
+        };
+        pixelate.write();
+        pixelate
+    }
+
+    // step the transition, returns true once it is done
+    pub fn tick(&mut self) -> bool {
+        if !self.done() {
+            self.frame += 1;
+            self.write();
+        }
+        self.done()
+    }
+
+    pub fn done(&self) -> bool {
+        self.frame >= self.frames
+    }
+
+    fn write(&self) {
+        let step = if self.out {
+            self.frame
+        } else {
+            self.frames - self.frame
+        };
+        let n = 1 + (self.max as u32 - 1) * step as u32 / self.frames as u32;
+        set_bg_mosaic(Size::new(n, n));
+        set_obj_mosaic(Size::new(n, n));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{dma, mmio};
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use gba::oam::{
+    OBJAttr0, OBJAttr1, OBJAttr2, ObjectAttributes, ObjectRender, ObjectShape, ObjectSize,
+};
+
+const OAM_BASE: usize = 0x700_0000;
+const DISPCNT: usize = 0x400_0000;
+const HBLANK_INTERVAL_FREE: u16 = 1 << 5;
+
+// cycles the hardware has for drawing objects on each scanline, fewer when
+// DISPCNT's hblank interval free bit lets oam be written during hblank
+pub const OBJ_LINE_CYCLES: u32 = 1210;
+pub const OBJ_LINE_CYCLES_HBLANK_FREE: u32 = 954;
+
+// scanlines that need more object cycles than the hardware has, the objects
+// last in oam on those lines are cut off or not drawn at all
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScanlineOverflow {
+    pub line: u32,   // the first scanline over budget
+    pub cycles: u32, // cycles its objects need
+    pub budget: u32,
+    pub lines: u32, // scanlines over budget
+}
+
+// one 8 byte oam slot, the 4th halfword holds an affine parameter
+#[derive(Debug, Copy, Clone)]
+#[repr(C)]
+struct OamEntry {
+    attr0: OBJAttr0,
+    attr1: OBJAttr1,
+    attr2: OBJAttr2,
+    affine: i16,
+}
+
+// the order commit() writes objects to oam in, where an earlier object is
+// drawn over a later one of the same priority
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum OamOrder {
+    Slot, // slot n is object n
+    Y,    // objects reaching lower on the screen in front, for top-down games
+    Z,    // higher set_z() in front
+}
+
+// oam sized, word aligned for the dma
+#[repr(C, align(4))]
+struct Shadow([OamEntry; 128]);
+
+// shadow copy of oam that is copied to the hardware in one dma transfer
+// writing oam while the screen is drawn tears sprites, so mutate the shadow
+// during the frame and call commit() from the vblank handler
+// the stack is in iwram, so a manager owned by main lives in iwram
+// with a sorted order slots keep their numbers, only the copy in oam is
+// reordered, affine parameters stay in their slots' entries
+//     oam.set_order(OamOrder::Y);
+//     oam.set(player_slot, player.attributes(layout)?);
+//     oam.commit(); // the character lower on screen overlaps the other
+// with culling, objects entirely outside of the view are hidden in the copy,
+// the shadow keeps them so they show again once they move back in view
+#[repr(C, align(4))]
+pub struct OamManager {
+    entries: [OamEntry; 128],
+    allocated: u128, // slots handed out by alloc, bit n is slot n
+    order: OamOrder,
+    z: [i16; 128],
+    culling: Option<Rectangle>, // screen area objects must touch to be shown
+}
+
+impl OamManager {
+    pub const OBJECTS: usize = 128;
+
+    // every object starts hidden
+    pub fn new() -> Self {
+        OamManager {
+            entries: [OamEntry {
+                attr0: OBJAttr0::new().with_obj_rendering(ObjectRender::Disabled),
+                attr1: OBJAttr1::new(),
+                attr2: OBJAttr2::new(),
+                affine: 0,
+            }; 128],
+            allocated: 0,
+            order: OamOrder::Slot,
+            z: [0; 128],
+            culling: None,
+        }
+    }
+
+    pub fn get(&self, slot: usize) -> Option<ObjectAttributes> {
+        self.entries.get(slot).map(|entry| ObjectAttributes {
+            attr0: entry.attr0,
+            attr1: entry.attr1,
+            attr2: entry.attr2,
+        })
+    }
+
+    pub fn set(&mut self, slot: usize, attributes: ObjectAttributes) {
+        if let Some(entry) = self.entries.get_mut(slot) {
+            entry.attr0 = attributes.attr0;
+            entry.attr1 = attributes.attr1;
+            entry.attr2 = attributes.attr2;
+        }
+    }
+
+    pub fn hide(&mut self, slot: usize) {
+        if let Some(entry) = self.entries.get_mut(slot) {
+            entry.attr0 = entry.attr0.with_obj_rendering(ObjectRender::Disabled);
+        }
+    }
+
+    pub fn hide_all(&mut self) {
+        for slot in 0..Self::OBJECTS {
+            self.hide(slot);
+        }
+    }
+
+    pub(crate) fn set_affine_param(&mut self, slot: usize, param: i16) {
+        if let Some(entry) = self.entries.get_mut(slot) {
+            entry.affine = param;
+        }
+    }
+
+    pub fn order(&self) -> OamOrder {
+        self.order
+    }
+
+    pub fn set_order(&mut self, order: OamOrder) {
+        self.order = order;
+    }
+
+    // sort key of a slot for OamOrder::Z, equal keys keep slot order
+    pub fn set_z(&mut self, slot: usize, z: i16) {
+        if let Some(key) = self.z.get_mut(slot) {
+            *key = z;
+        }
+    }
+
+    // hide objects entirely off the 240x160 screen on commit
+    pub fn enable_culling(&mut self) {
+        self.set_culling(Some(Rectangle::new(Point::zero(), Size::new(240, 160))));
+    }
+
+    // hide objects entirely outside of view on commit, like the part of the
+    // screen a split screen or a window shows, None shows every object
+    pub fn set_culling(&mut self, view: Option<Rectangle>) {
+        self.culling = view;
+    }
+
+    pub fn culling(&self) -> Option<Rectangle> {
+        self.culling
+    }
+
+    // the screen area an object covers, including the doubled area of double
+    // size affine objects, None for a hidden object
+    pub fn bounds(&self, slot: usize) -> Option<Rectangle> {
+        let entry = self.entries.get(slot)?;
+        bounds(entry)
+    }
+
+    // reserve count consecutive slots, for helpers that place their own
+    // objects, returns the first slot
+    pub fn alloc(&mut self, count: usize) -> Option<usize> {
+        if count == 0 || count > Self::OBJECTS {
+            return None;
+        }
+        let mask = if count == Self::OBJECTS {
+            !0
+        } else {
+            (1u128 << count) - 1
+        };
+        let first =
+            (0..=Self::OBJECTS - count).find(|first| self.allocated & (mask << first) == 0)?;
+        self.allocated |= mask << first;
+        Some(first)
+    }
+
+    // hide and release slots from alloc
+    pub fn free(&mut self, first: usize, count: usize) {
+        for slot in first..(first + count).min(Self::OBJECTS) {
+            self.hide(slot);
+            self.allocated &= !(1 << slot);
+        }
+    }
+
+    // object cycles each scanline needs once committed, an object costs its
+    // width on every line it covers, an affine one 10 plus twice the width of
+    // its area, hidden and culled objects cost nothing
+    pub fn scanline_cycles(&self) -> [u32; 160] {
+        let mut lines = [0; 160];
+        for entry in self.entries.iter() {
+            let area = match bounds(entry) {
+                Some(area) => area,
+                None => continue,
+            };
+            if let Some(view) = self.culling {
+                if area.intersection(&view).is_zero_sized() {
+                    continue;
+                }
+            }
+            let cost = match entry.attr0.obj_rendering() {
+                ObjectRender::Normal => area.size.width,
+                _ => 10 + area.size.width * 2,
+            };
+            let top = area.top_left.y.max(0) as usize;
+            let bottom = (area.top_left.y + area.size.height as i32).clamp(0, 160) as usize;
+            for line in lines.iter_mut().take(bottom).skip(top) {
+                *line += cost;
+            }
+        }
+        lines
+    }
+
+    // a debug check for sprites that will drop out, against the budget for
+    // the current DISPCNT, None when every scanline fits
+    //     if let Some(overflow) = oam.scanline_overflow() {
+    //         warn_about(overflow.line);
+    //     }
+    pub fn scanline_overflow(&self) -> Option<ScanlineOverflow> {
+        let budget = if mmio::read16(DISPCNT) & HBLANK_INTERVAL_FREE != 0 {
+            OBJ_LINE_CYCLES_HBLANK_FREE
+        } else {
+            OBJ_LINE_CYCLES
+        };
+        let cycles = self.scanline_cycles();
+        let mut over = cycles.iter().enumerate().filter(|(_, c)| **c > budget);
+        let (line, first) = over.next()?;
+        Some(ScanlineOverflow {
+            line: line as u32,
+            cycles: *first,
+            budget,
+            lines: 1 + over.count() as u32,
+        })
+    }
+
+    // copy the whole shadow to oam, in the manager's order and culled
+    pub fn commit(&self) {
+        if self.order == OamOrder::Slot && self.culling.is_none() {
+            copy_to_oam(&self.entries);
+            return;
+        }
+        let mut shadow = if self.order == OamOrder::Slot {
+            Shadow(self.entries)
+        } else {
+            self.sorted()
+        };
+        if let Some(view) = self.culling {
+            for entry in shadow.0.iter_mut() {
+                let visible =
+                    bounds(entry).map_or(false, |area| !area.intersection(&view).is_zero_sized());
+                if !visible {
+                    entry.attr0 = entry.attr0.with_obj_rendering(ObjectRender::Disabled);
+                }
+            }
+        }
+        copy_to_oam(&shadow.0);
+    }
+
+    // the entries front to back, hidden objects last
+    fn sorted(&self) -> Shadow {
+        let mut keys = [0i16; 128];
+        for (slot, key) in keys.iter_mut().enumerate() {
+            *key = self.sort_key(slot);
+        }
+        // insertion sort is stable and there are only 128
+        let mut slots = [0u8; 128];
+        for slot in 0..Self::OBJECTS {
+            let mut i = slot;
+            while i > 0 && keys[slots[i - 1] as usize] < keys[slot] {
+                slots[i] = slots[i - 1];
+                i -= 1;
+            }
+            slots[i] = slot as u8;
+        }
+        let mut sorted = Shadow(self.entries);
+        for (entry, slot) in sorted.0.iter_mut().zip(slots.iter()) {
+            let object = &self.entries[*slot as usize];
+            entry.attr0 = object.attr0;
+            entry.attr1 = object.attr1;
+            entry.attr2 = object.attr2;
+        }
+        sorted
+    }
+
+    // higher is further in front
+    fn sort_key(&self, slot: usize) -> i16 {
+        let entry = &self.entries[slot];
+        let rendering = entry.attr0.obj_rendering();
+        if matches!(rendering, ObjectRender::Disabled) {
+            return i16::MIN;
+        }
+        match self.order {
+            OamOrder::Z => self.z[slot],
+            _ => {
+                // the bottom edge
+                bounds(entry).map_or(i16::MIN, |area| {
+                    (area.top_left.y + area.size.height as i32) as i16
+                })
+            }
+        }
+    }
+}
+
+fn copy_to_oam(entries: &[OamEntry; 128]) {
+    let words = core::mem::size_of_val(entries) / 4;
+    unsafe {
+        dma::copy32(
+            entries.as_ptr() as *const u32,
+            OAM_BASE as *mut u32,
+            words as u16,
+        )
+    };
+}
+
+// the area a shown object covers, x wraps at 512 and y at 256 so objects near
+// the left and top edges can start off screen
+fn bounds(entry: &OamEntry) -> Option<Rectangle> {
+    let rendering = entry.attr0.obj_rendering();
+    if matches!(rendering, ObjectRender::Disabled) {
+        return None;
+    }
+    let x = entry.attr1.col_coordinate() as i32;
+    let x = if x >= 240 { x - 512 } else { x };
+    let y = entry.attr0.row_coordinate() as i32;
+    let y = if y >= 160 { y - 256 } else { y };
+    let size = object_size(entry.attr0.obj_shape(), entry.attr1.obj_size());
+    let size = if matches!(rendering, ObjectRender::DoubleAreaAffine) {
+        size * 2
+    } else {
+        size
+    };
+    Some(Rectangle::new(Point::new(x, y), size))
+}
+
+fn object_size(shape: ObjectShape, size: ObjectSize) -> Size {
+    let size = match size {
+        ObjectSize::Zero => 0,
+        ObjectSize::One => 1,
+        ObjectSize::Two => 2,
+        ObjectSize::Three => 3,
+    };
+    let (width, height) = match shape {
+        ObjectShape::Horizontal => ([16, 32, 32, 64][size], [8, 8, 16, 32][size]),
+        ObjectShape::Vertical => ([8, 8, 16, 32][size], [16, 32, 32, 64][size]),
+        _ => ([8, 16, 32, 64][size], [8, 16, 32, 64][size]),
+    };
+    Size::new(width, height)
+}
+
+impl Default for OamManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
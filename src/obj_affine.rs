@@ -0,0 +1,51 @@
+use crate::{
+    fixed::{Angle, Fixed16, Fixed8},
+    oam::OamManager,
+};
+
+// 8.8 fixed point matrix mapping screen pixels to texture pixels
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AffineMatrix {
+    pub pa: i16,
+    pub pb: i16,
+    pub pc: i16,
+    pub pd: i16,
+}
+
+impl AffineMatrix {
+    pub const IDENTITY: Self = AffineMatrix {
+        pa: 1 << 8,
+        pb: 0,
+        pc: 0,
+        pd: 1 << 8,
+    };
+
+    // rotate counter-clockwise by angle and scale, Fixed8::ONE is the original size
+    pub fn from_angle_scale(angle: Angle, scale_x: Fixed8, scale_y: Fixed8) -> Self {
+        // the matrix is the inverse transform, so divide by the scale
+        let (sin, cos) = (angle.sin(), angle.cos());
+        let scale_x = Fixed16::from(Fixed8(scale_x.0.max(1)));
+        let scale_y = Fixed16::from(Fixed8(scale_y.0.max(1)));
+        let param = |value: Fixed16| Fixed8::from(value).0 as i16;
+        AffineMatrix {
+            pa: param(cos / scale_x),
+            pb: param(-sin / scale_x),
+            pc: param(sin / scale_y),
+            pd: param(cos / scale_y),
+        }
+    }
+}
+
+impl OamManager {
+    pub const AFFINE_SLOTS: usize = 32;
+
+    // matrix slot n is stored in the unused 4th halfword of objects 4n to 4n+3
+    pub fn set_affine(&mut self, slot: usize, matrix: AffineMatrix) {
+        if slot < Self::AFFINE_SLOTS {
+            let params = [matrix.pa, matrix.pb, matrix.pc, matrix.pd];
+            for (i, param) in params.iter().enumerate() {
+                self.set_affine_param(slot * 4 + i, *param);
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{
+    oam::OamManager,
+    sprite::{ObjLayout, ObjMapping, Sprite, SpriteError, SpriteMode, SpriteSize},
+    PaletteColor, SpriteCanvas,
+};
+use core::convert::Infallible;
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+const OBJECT_SIZE: u32 = 64;
+const OBJECT_IDS: u16 = 128; // 64 8bpp tiles, two ids each
+
+// part of the screen covered by a grid of 64x64 8bpp objects, each backed by
+// a SpriteCanvas, and drawn to as one surface, for a hud or a translucent
+// layer over a bitmap mode without touching the framebuffer
+//     #[link_section = ".ewram"]
+//     static mut HUD: [SpriteCanvas<8, 8>; 2] = [...];
+//     let mut hud = ObjLayer::new(unsafe { &mut HUD }, 2, Point::new(56, 96), 512, 0);
+//     Text::new("PAUSED", Point::new(8, 16), style).draw(&mut hud)?;
+//     hud.upload(layout.mapping);
+//     hud.place(&mut oam, layout)?;
+// each object takes 128 tile ids, the upper half of obj vram left in the
+// bitmap modes holds 4, with 2d mapping first_tile should start a row of 32
+// ids and objects go two side by side
+pub struct ObjLayer<'a> {
+    canvases: &'a mut [SpriteCanvas<8, 8>],
+    across: usize, // objects per row
+    pub top_left: Point,
+    pub priority: u8,
+    pub mode: SpriteMode,
+    first_tile: u16,
+    first_slot: usize,
+}
+
+impl<'a> ObjLayer<'a> {
+    // canvases are row major, across to a row
+    pub fn new(
+        canvases: &'a mut [SpriteCanvas<8, 8>],
+        across: usize,
+        top_left: Point,
+        first_tile: u16,
+        first_slot: usize,
+    ) -> Self {
+        assert!(
+            across > 0 && canvases.len() % across == 0,
+            "canvases must fill whole rows"
+        );
+        ObjLayer {
+            canvases,
+            across,
+            top_left,
+            priority: 0,
+            mode: SpriteMode::Normal,
+            first_tile,
+            first_slot,
+        }
+    }
+
+    pub fn with_priority(self, priority: u8) -> Self {
+        ObjLayer { priority, ..self }
+    }
+
+    // SpriteMode::SemiTransparent blends the layer over what is below
+    pub fn with_mode(self, mode: SpriteMode) -> Self {
+        ObjLayer { mode, ..self }
+    }
+
+    pub fn objects(&self) -> usize {
+        self.canvases.len()
+    }
+
+    // first tile id of object n
+    pub fn tile_id(&self, object: usize, mapping: ObjMapping) -> u16 {
+        match mapping {
+            ObjMapping::OneDimensional => self.first_tile + object as u16 * OBJECT_IDS,
+            ObjMapping::TwoDimensional => {
+                self.first_tile + (object % 2) as u16 * 16 + (object / 2) as u16 * 8 * 32
+            }
+        }
+    }
+
+    // copy every canvas to obj vram, after drawing
+    pub fn upload(&self, mapping: ObjMapping) {
+        for (object, canvas) in self.canvases.iter().enumerate() {
+            canvas.upload(self.tile_id(object, mapping), mapping);
+        }
+    }
+
+    // set the objects in oam slots first_slot onwards at the layer's position
+    pub fn place(&self, oam: &mut OamManager, layout: ObjLayout) -> Result<(), SpriteError> {
+        for object in 0..self.objects() {
+            let sprite = Sprite::new(self.tile_id(object, layout.mapping), SpriteSize::S64x64)
+                .with_8bpp(true)
+                .with_position(self.top_left + self.offset(object))
+                .with_priority(self.priority)
+                .with_mode(self.mode);
+            oam.set(self.first_slot + object, sprite.attributes(layout)?);
+        }
+        Ok(())
+    }
+
+    pub fn hide(&self, oam: &mut OamManager) {
+        for object in 0..self.objects() {
+            oam.hide(self.first_slot + object);
+        }
+    }
+
+    // top left of object n within the layer
+    fn offset(&self, object: usize) -> Point {
+        let (col, row) = (object % self.across, object / self.across);
+        Point::new(col as i32, row as i32) * OBJECT_SIZE as i32
+    }
+}
+
+impl DrawTarget for ObjLayer<'_> {
+    type Color = PaletteColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(coord, color) in pixels.into_iter() {
+            if !bounds.contains(coord) {
+                continue;
+            }
+            let (col, row) = (coord.x as u32 / OBJECT_SIZE, coord.y as u32 / OBJECT_SIZE);
+            let object = row as usize * self.across + col as usize;
+            let local = coord - self.offset(object);
+            self.canvases[object].draw_iter(core::iter::once(Pixel(local, color)))?;
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        for object in 0..self.objects() {
+            let offset = self.offset(object);
+            let local = Rectangle::new(area.top_left - offset, area.size)
+                .intersection(&Rectangle::new(Point::zero(), Size::new_equal(OBJECT_SIZE)));
+            if !local.is_zero_sized() {
+                self.canvases[object].fill_solid(&local, color)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        for canvas in self.canvases.iter_mut() {
+            *canvas = SpriteCanvas::new(color);
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for ObjLayer<'_> {
+    fn size(&self) -> Size {
+        let rows = self.canvases.len() / self.across;
+        Size::new(self.across as u32, rows as u32) * OBJECT_SIZE
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    font::BakedFont,
+    oam::OamManager,
+    sprite::{ObjLayout, Sprite, SpriteSize},
+};
+use embedded_graphics::prelude::*;
+
+// a short string drawn as a row of objects, one per glyph, for hud text on
+// top of any background without touching the framebuffer
+// glyphs come from a font baked into obj vram, charblock 4, with FontBaker,
+// so its tile ids are obj tile ids, from tile 512 in the bitmap modes
+// FontBaker::bake_obj claims those tiles from the Vram arena
+// glyphs are one or two tiles across and down and obj vram must use 1d mapping
+pub struct ObjText {
+    font: BakedFont,
+    size: SpriteSize,
+    first_slot: usize,
+    capacity: usize,
+    pub palbank: u8,
+    pub priority: u8,
+}
+
+impl ObjText {
+    // reserve oam slots for up to capacity glyphs, None when the glyph size
+    // has no sprite size or the slots are taken
+    pub fn new(oam: &mut OamManager, font: BakedFont, capacity: usize) -> Option<Self> {
+        let size = match (font.tiles_wide, font.tiles_high) {
+            (1, 1) => SpriteSize::S8x8,
+            (2, 1) => SpriteSize::S16x8,
+            (1, 2) => SpriteSize::S8x16,
+            (2, 2) => SpriteSize::S16x16,
+            _ => return None,
+        };
+        let first_slot = oam.alloc(capacity)?;
+        Some(ObjText {
+            font,
+            size,
+            first_slot,
+            capacity,
+            palbank: 0,
+            priority: 0,
+        })
+    }
+
+    // show text with its top left at position, glyphs advance by advance
+    // pixels, characters past capacity or outside the font are skipped
+    pub fn set_text(&self, oam: &mut OamManager, text: &str, position: Point, advance: i32) {
+        let layout = ObjLayout::current();
+        let mut chars = text.chars();
+        for (i, slot) in (self.first_slot..self.first_slot + self.capacity).enumerate() {
+            let tile_id = chars.next().and_then(|c| self.font.tile_id(c));
+            let sprite = tile_id.map(|tile_id| {
+                Sprite::new(tile_id, self.size)
+                    .with_position(position + Point::new(i as i32 * advance, 0))
+                    .with_palbank(self.palbank)
+                    .with_priority(self.priority)
+            });
+            match sprite.map(|sprite| sprite.attributes(layout)) {
+                Some(Ok(attributes)) => oam.set(slot, attributes),
+                _ => oam.hide(slot),
+            }
+        }
+    }
+
+    pub fn hide(&self, oam: &mut OamManager) {
+        for slot in self.first_slot..self.first_slot + self.capacity {
+            oam.hide(slot);
+        }
+    }
+
+    // give the slots back to the manager
+    pub fn free(self, oam: &mut OamManager) {
+        oam.free(self.first_slot, self.capacity);
+    }
+}
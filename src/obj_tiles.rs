@@ -0,0 +1,194 @@
+use crate::{
+    sprite::{ObjLayout, ObjMapping},
+    vram, SpriteSize,
+};
+
+const TILE_BYTES: usize = 32;
+const OBJ_TILES: usize = 1024; // 4bpp tiles in obj vram, charblocks 4 and 5
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Bpp {
+    Bpp4, // one tile id per tile
+    Bpp8, // two tile ids per tile, starting at an even id
+}
+
+// every free run of obj vram is too short
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ObjVramFull;
+
+// tiles handed out by ObjTileAllocator, give it back with free()
+// not Copy, so the same tiles can't be freed twice
+#[derive(Debug, PartialEq)]
+pub struct TileHandle {
+    tile_id: u16,
+    row_ids: u16, // tile ids used in each row, twice the tiles for 8bpp
+    rows: u16,    // more than one for an object's block of tiles with 2d mapping
+    bpp: Bpp,
+}
+
+impl TileHandle {
+    // first tile id, for Sprite::new
+    pub fn tile_id(&self) -> u16 {
+        self.tile_id
+    }
+
+    pub fn tile_count(&self) -> usize {
+        let ids = self.row_ids as usize * self.rows as usize;
+        match self.bpp {
+            Bpp::Bpp4 => ids,
+            Bpp::Bpp8 => ids / 2,
+        }
+    }
+
+    pub fn bpp(&self) -> Bpp {
+        self.bpp
+    }
+
+    // copy tile data to the tiles, anything past the last tile is dropped
+    // data holds the rows of tiles one after another like 1d mapping, a 2d
+    // block from alloc_sprite gets each row on its own line of obj vram
+    pub fn upload(&self, data: &[u8]) {
+        let row_bytes = self.row_ids as usize * TILE_BYTES;
+        for (row, chunk) in data.chunks(row_bytes).take(self.rows as usize).enumerate() {
+            vram::copy_bytes(chunk, self.address(row));
+        }
+    }
+
+    // rows of a 2d block are 32 tile ids apart
+    fn address(&self, row: usize) -> usize {
+        let id = self.tile_id as usize + row * 32;
+        vram::charblock_address(4) + id * TILE_BYTES
+    }
+
+    fn ids(&self) -> impl Iterator<Item = usize> {
+        let (first, row_ids) = (self.tile_id as usize, self.row_ids as usize);
+        (0..self.rows as usize).flat_map(move |row| {
+            let start = first + row * 32;
+            start..start + row_ids
+        })
+    }
+}
+
+// hands out runs of tile ids in obj vram so sprites don't overwrite each
+// other's tiles, in the bitmap modes only ids 512 and up are used
+// alloc_obj_tiles runs are consecutive ids, which fits an object larger than
+// 8x8 only with 1d mapping, alloc_sprite follows the layout's mapping
+pub struct ObjTileAllocator {
+    used: [u32; OBJ_TILES / 32], // bit n is tile id n
+    layout: ObjLayout,
+}
+
+impl ObjTileAllocator {
+    pub fn new(layout: ObjLayout) -> Self {
+        ObjTileAllocator {
+            used: [0; OBJ_TILES / 32],
+            layout,
+        }
+    }
+
+    // first fit run of count tiles
+    pub fn alloc_obj_tiles(&mut self, count: usize, bpp: Bpp) -> Result<TileHandle, ObjVramFull> {
+        let (ids, step) = match bpp {
+            Bpp::Bpp4 => (count, 1),
+            Bpp::Bpp8 => (count * 2, 2),
+        };
+        if count == 0 || ids > OBJ_TILES {
+            return Err(ObjVramFull);
+        }
+        let first = self.layout.first_tile() as usize;
+        let start = (first..=OBJ_TILES - ids)
+            .step_by(step)
+            .find(|start| (*start..start + ids).all(|id| !self.is_used(id)))
+            .ok_or(ObjVramFull)?;
+        let handle = TileHandle {
+            tile_id: start as u16,
+            row_ids: ids as u16,
+            rows: 1,
+            bpp,
+        };
+        self.mark(&handle, true);
+        Ok(handle)
+    }
+
+    // tiles for an object of size, a run with 1d mapping and a block of the
+    // 32x32 tile grid with 2d mapping
+    pub fn alloc_sprite(&mut self, size: SpriteSize, bpp: Bpp) -> Result<TileHandle, ObjVramFull> {
+        let size = size.size();
+        let (across, rows) = ((size.width / 8) as usize, (size.height / 8) as usize);
+        if self.layout.mapping == ObjMapping::OneDimensional {
+            return self.alloc_obj_tiles(across * rows, bpp);
+        }
+        self.alloc_block(across, rows, bpp)
+    }
+
+    // a block of the 32x32 tile grid, across tiles wide and rows tiles high
+    pub(crate) fn alloc_block(
+        &mut self,
+        across: usize,
+        rows: usize,
+        bpp: Bpp,
+    ) -> Result<TileHandle, ObjVramFull> {
+        let (row_ids, step) = match bpp {
+            Bpp::Bpp4 => (across, 1),
+            Bpp::Bpp8 => (across * 2, 2),
+        };
+        let first_row = self.layout.first_tile() as usize / 32;
+        if row_ids == 0 || row_ids > 32 || rows == 0 || first_row + rows > 32 {
+            return Err(ObjVramFull);
+        }
+        let start = (first_row..=32 - rows)
+            .flat_map(|row| {
+                (0..=32 - row_ids)
+                    .step_by(step)
+                    .map(move |col| row * 32 + col)
+            })
+            .find(|start| {
+                (0..rows).all(|row| {
+                    let first = start + row * 32;
+                    (first..first + row_ids).all(|id| !self.is_used(id))
+                })
+            })
+            .ok_or(ObjVramFull)?;
+        let handle = TileHandle {
+            tile_id: start as u16,
+            row_ids: row_ids as u16,
+            rows: rows as u16,
+            bpp,
+        };
+        self.mark(&handle, true);
+        Ok(handle)
+    }
+
+    pub(crate) fn mapping(&self) -> ObjMapping {
+        self.layout.mapping
+    }
+
+    pub fn free(&mut self, handle: TileHandle) {
+        self.mark(&handle, false);
+    }
+
+    // free tile ids, in 4bpp tiles
+    pub fn free_tiles(&self) -> usize {
+        let first = self.layout.first_tile() as usize;
+        (first..OBJ_TILES).filter(|id| !self.is_used(*id)).count()
+    }
+
+    // forget every allocation, after switching between tiled and bitmap modes
+    pub fn reset(&mut self, layout: ObjLayout) {
+        *self = Self::new(layout);
+    }
+
+    fn mark(&mut self, handle: &TileHandle, used: bool) {
+        for id in handle.ids() {
+            if used {
+                self.used[id / 32] |= 1 << (id % 32);
+            } else {
+                self.used[id / 32] &= !(1 << (id % 32));
+            }
+        }
+    }
+
+    fn is_used(&self, id: usize) -> bool {
+        self.used[id / 32] & (1 << (id % 32)) != 0
+    }
+}
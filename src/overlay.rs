@@ -0,0 +1,155 @@
+use crate::{
+    frame::frame_count,
+    mmio,
+    oam::OamManager,
+    palette::PaletteKind,
+    sprite::{ObjLayout, Sprite, SpriteSize},
+    PaletteColor, Tile4bppDisplay,
+};
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X8, MonoTextStyle},
+    pixelcolor::Bgr555,
+    prelude::*,
+    text::{Baseline, Text},
+};
+
+const OBJ_VRAM_BASE: usize = 0x601_0000;
+const TILE_BYTES: usize = 32;
+const GLYPHS: &[u8] = b" 0123456789FPSLN";
+const COLUMNS: usize = 6;
+const ROWS: usize = 2;
+
+// fps and draw time in the top left corner, drawn with objects so it shows on
+// top of any video mode without touching the framebuffer
+//     FPS 60
+//     LN  42   scanlines spent drawing, from the profiler
+// takes 16 4bpp obj tiles from first_tile, a palbank, and 12 oam slots from
+// first_slot
+// with_warning turns the text another color while OamManager::scanline_overflow
+// finds scanlines with more objects than the hardware draws
+pub struct DebugOverlay {
+    first_tile: u16,
+    palbank: u8,
+    color: Bgr555,
+    warning: Option<Bgr555>,
+    first_slot: usize,
+    visible: bool,
+    fps: u32,
+    frames: u32, // updates since second_start
+    second_start: u32,
+}
+
+impl DebugOverlay {
+    pub const SLOTS: usize = COLUMNS * ROWS;
+
+    // draws the glyphs to obj vram and sets color 1 of the palbank
+    pub fn new(first_tile: u16, palbank: u8, first_slot: usize, color: Bgr555) -> Self {
+        let style = MonoTextStyle::new(&FONT_6X8, PaletteColor::new(1));
+        for (i, c) in GLYPHS.iter().enumerate() {
+            let mut tile = Tile4bppDisplay::new(PaletteColor::TANSPARENT);
+            let bytes = [*c];
+            if let Ok(text) = core::str::from_utf8(&bytes) {
+                Text::with_baseline(text, Point::new(1, 0), style, Baseline::Top)
+                    .draw(&mut tile)
+                    .ok();
+            }
+            let address = OBJ_VRAM_BASE + (first_tile as usize + i) * TILE_BYTES;
+            for (j, word) in tile.tile.0.iter().enumerate() {
+                mmio::write32(address + j * 4, *word);
+            }
+        }
+        PaletteKind::Object.write(palbank * 16 + 1, color);
+
+        DebugOverlay {
+            first_tile,
+            palbank,
+            color,
+            warning: None,
+            first_slot,
+            visible: true,
+            fps: 0,
+            frames: 0,
+            second_start: frame_count(),
+        }
+    }
+
+    pub fn with_warning(self, warning: Bgr555) -> Self {
+        DebugOverlay {
+            warning: Some(warning),
+            ..self
+        }
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    // count a drawn frame and update the objects in the oam shadow, call once
+    // per drawn frame, fps comes from the vblank frame counter
+    pub fn update(&mut self, oam: &mut OamManager, draw_scanlines: u32) {
+        self.frames += 1;
+        let now = frame_count();
+        if now.wrapping_sub(self.second_start) >= 60 {
+            self.fps = self.frames;
+            self.frames = 0;
+            self.second_start = now;
+        }
+
+        if !self.visible {
+            for slot in self.first_slot..self.first_slot + Self::SLOTS {
+                oam.hide(slot);
+            }
+            return;
+        }
+
+        if let Some(warning) = self.warning {
+            let color = if oam.scanline_overflow().is_some() {
+                warning
+            } else {
+                self.color
+            };
+            PaletteKind::Object.write(self.palbank * 16 + 1, color);
+        }
+
+        let mut rows = [[b' '; COLUMNS]; ROWS];
+        write_line(&mut rows[0], b"FPS", self.fps);
+        write_line(&mut rows[1], b"LN", draw_scanlines);
+
+        let layout = ObjLayout::current();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                let glyph = GLYPHS.iter().position(|g| g == c).unwrap_or(0);
+                let sprite = Sprite::new(self.first_tile + glyph as u16, SpriteSize::S8x8)
+                    .with_position(Point::new(x as i32 * 6, y as i32 * 8))
+                    .with_palbank(self.palbank)
+                    .with_hidden(*c == b' ');
+                let slot = self.first_slot + y * COLUMNS + x;
+                match sprite.attributes(layout) {
+                    Ok(attributes) => oam.set(slot, attributes),
+                    Err(_) => oam.hide(slot),
+                }
+            }
+        }
+    }
+}
+
+// a label then a right aligned number, like "FPS 60"
+fn write_line(line: &mut [u8; COLUMNS], label: &[u8], value: u32) {
+    line[..label.len()].copy_from_slice(label);
+    let mut value = value.min(999);
+    for c in line[COLUMNS - 3..].iter_mut().rev() {
+        *c = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+}
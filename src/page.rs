@@ -0,0 +1,124 @@
+use crate::{mmio::DISPCNT, Mode4Display, Mode5Display};
+use core::convert::Infallible;
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+use gba::{io::display::VCOUNT, vram::bitmap::Page};
+
+// the bitmap displays with two pages
+pub trait PagedDisplay {
+    fn page(&self) -> Page;
+    fn set_page(&mut self, page: Page);
+}
+
+impl PagedDisplay for Mode4Display {
+    fn page(&self) -> Page {
+        self.page
+    }
+
+    fn set_page(&mut self, page: Page) {
+        self.page = page;
+    }
+}
+
+impl PagedDisplay for Mode5Display {
+    fn page(&self) -> Page {
+        self.page
+    }
+
+    fn set_page(&mut self, page: Page) {
+        self.page = page;
+    }
+}
+
+// page shown by DISPCNT
+pub fn shown_page() -> Page {
+    if DISPCNT.read().frame1() {
+        Page::One
+    } else {
+        Page::Zero
+    }
+}
+
+// double buffering for Mode4Display and Mode5Display
+// draw to back(), then flip() to show it, the display then points at the other
+// page, which holds the frame before last
+pub struct PageFlipper<D> {
+    pub(crate) display: D,
+}
+
+impl<D: PagedDisplay> PageFlipper<D> {
+    // point the display at the page that isn't shown
+    pub fn new(mut display: D) -> Self {
+        display.set_page(other(shown_page()));
+        PageFlipper { display }
+    }
+
+    pub fn back(&mut self) -> &mut D {
+        &mut self.display
+    }
+
+    pub fn front_page(&self) -> Page {
+        other(self.display.page())
+    }
+
+    // wait for vblank and show the back page
+    // if called during vblank, the flip happens right away
+    pub fn flip(&mut self) {
+        while VCOUNT.read() < 160 {
+            // drawing the screen
+        }
+        let back = self.display.page();
+        DISPCNT.write(DISPCNT.read().with_frame1(back == Page::One));
+        self.display.set_page(other(back));
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+}
+
+// draws to the back page
+impl<D> DrawTarget for PageFlipper<D>
+where
+    D: PagedDisplay + DrawTarget<Error = Infallible>,
+{
+    type Color = D::Color;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.display.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.clear(color)
+    }
+}
+
+impl<D> OriginDimensions for PageFlipper<D>
+where
+    D: PagedDisplay + DrawTarget<Error = Infallible>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+fn other(page: Page) -> Page {
+    match page {
+        Page::Zero => Page::One,
+        Page::One => Page::Zero,
+    }
+}
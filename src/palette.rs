@@ -0,0 +1,235 @@
+use crate::{dma, mmio, PaletteColor};
+use core::ptr::{addr_of, addr_of_mut};
+use embedded_graphics::{
+    pixelcolor::{raw::RawU16, Bgr555},
+    prelude::*,
+};
+
+// a palette's colors, like the array gba_palette! makes
+pub type Palette<const N: usize> = [Bgr555; N];
+
+// a 24 bit 0xRRGGBB color, like from an image editor, dropping the low 3 bits
+// of each channel the same way tga_palette does
+pub const fn rgb24(hex: u32) -> Bgr555 {
+    Bgr555::new(
+        (hex >> 19) as u8 & 0x1F,
+        (hex >> 11) as u8 & 0x1F,
+        (hex >> 3) as u8 & 0x1F,
+    )
+}
+
+// 24 bit colors converted at compile time, in place of hand converted 15 bit
+// values
+//     const SKY: Palette<3> = gba_palette![0x000000, 0x6080F0, 0xFFFFFF];
+// or a module holding the palette and a PaletteColor for each entry
+//     gba_palette!(pub mod sky { BLACK = 0x000000, BLUE = 0x6080F0 });
+//     load_palette_bg(&sky::PALETTE, 0);
+//     Pixel(point, sky::BLUE).draw(&mut mode4)?;
+#[macro_export]
+macro_rules! gba_palette {
+    ($vis:vis mod $module:ident { $($name:ident = $color:expr),* $(,)? }) => {
+        $vis mod $module {
+            #[allow(non_camel_case_types, dead_code)]
+            #[repr(u8)]
+            enum Index {
+                $($name),*
+            }
+
+            pub const PALETTE: $crate::Palette<{ [$(stringify!($name)),*].len() }> =
+                [$($crate::rgb24($color)),*];
+
+            $(pub const $name: $crate::PaletteColor = $crate::PaletteColor::new(Index::$name as u8);)*
+        }
+    };
+    ($($color:expr),* $(,)?) => {
+        [$($crate::rgb24($color)),*]
+    };
+}
+
+const PALRAM: usize = 0x500_0000;
+const PALRAM_WORDS: u16 = 256; // both palettes
+
+// palram, background then object entries, word aligned for the dma
+#[repr(C, align(4))]
+struct Shadow([u16; 512]);
+
+// while buffering, palette writes go to the shadow and the vblank handler
+// copies it to palram
+static mut SHADOW: Shadow = Shadow([0; 512]);
+static mut BUFFERING: bool = false;
+static mut DIRTY: bool = false;
+
+// collect palette changes in ram and copy them to palram in one dma during
+// vblank, so fades, cycles and palette effects don't change colors halfway
+// down the screen or fight a raster effect for palram
+//     buffer_palettes(true);
+//     enable_vblank_irq(); // on_vblank_irq commits
+//     cycler.tick(); // any time during the frame
+// every PaletteKind write goes through the buffer, and reads see the buffered
+// colors, writes made directly to palram are overwritten by the next commit
+// turning buffering off commits what is pending
+pub fn buffer_palettes(enable: bool) {
+    if enable && !palettes_buffered() {
+        unsafe {
+            let shadow = addr_of_mut!(SHADOW.0) as *mut u16;
+            for i in 0..512 {
+                shadow.add(i).write(mmio::read16(PALRAM + i * 2));
+            }
+        }
+    }
+    if !enable {
+        commit_palettes();
+    }
+    unsafe { addr_of_mut!(BUFFERING).write_volatile(enable) };
+}
+
+pub fn palettes_buffered() -> bool {
+    unsafe { addr_of!(BUFFERING).read_volatile() }
+}
+
+// copy the buffer to palram if anything changed, on_vblank_irq calls this, call
+// it from your own vblank handler otherwise
+pub fn commit_palettes() {
+    unsafe {
+        if !palettes_buffered() || !addr_of!(DIRTY).read_volatile() {
+            return;
+        }
+        addr_of_mut!(DIRTY).write_volatile(false);
+        dma::copy32(
+            addr_of!(SHADOW) as *const u32,
+            PALRAM as *mut u32,
+            PALRAM_WORDS,
+        );
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PaletteKind {
+    Background,
+    Object,
+}
+
+impl PaletteKind {
+    fn address(self, index: u8) -> usize {
+        PALRAM + self.entry(index) * 2
+    }
+
+    // halfword of palram, and of the buffer
+    fn entry(self, index: u8) -> usize {
+        match self {
+            PaletteKind::Background => index as usize,
+            PaletteKind::Object => 256 + index as usize,
+        }
+    }
+
+    pub(crate) fn write(self, index: u8, color: Bgr555) {
+        if palettes_buffered() {
+            unsafe {
+                SHADOW.0[self.entry(index)] = color.into_storage();
+                addr_of_mut!(DIRTY).write_volatile(true);
+            }
+        } else {
+            mmio::write16(self.address(index), color.into_storage());
+        }
+    }
+
+    pub(crate) fn read(self, index: u8) -> Bgr555 {
+        let value = if palettes_buffered() {
+            unsafe { SHADOW.0[self.entry(index)] }
+        } else {
+            mmio::read16(self.address(index))
+        };
+        Bgr555::from(RawU16::new(value))
+    }
+
+    // write colors to consecutive entries from first, colors past entry 255
+    // are dropped
+    pub fn load(self, colors: &[Bgr555], first: u8) {
+        for (i, color) in colors.iter().take(256 - first as usize).enumerate() {
+            self.write(first + i as u8, *color);
+        }
+    }
+
+    // write up to 16 colors to a 4bpp palette bank, colors[0] goes to the
+    // bank's entry 0, which 4bpp tiles show as transparent
+    pub fn load_palbank(self, bank: u8, colors: &[Bgr555]) {
+        assert!(bank < 16, "there are only 16 palette banks");
+        self.load(&colors[..colors.len().min(16)], bank * 16);
+    }
+}
+
+// load an image's color map, like IndexedTga colors or a tga_palette
+// converted to Bgr555, into background palram starting at entry first
+pub fn load_palette_bg(colors: &[Bgr555], first: u8) {
+    PaletteKind::Background.load(colors, first);
+}
+
+// the same for object palram
+pub fn load_palette_obj(colors: &[Bgr555], first: u8) {
+    PaletteKind::Object.load(colors, first);
+}
+
+// the color shown where no layer draws, background palette entry 0, like the
+// tiled modes cleared to nothing, window outsides, or the border around a
+// centered mode 5 page
+pub fn set_backdrop(color: Bgr555) {
+    PaletteKind::Background.write(0, color);
+}
+
+pub fn backdrop() -> Bgr555 {
+    PaletteKind::Background.read(0)
+}
+
+// hands out palette entries for colors, sharing an entry between every
+// allocation of the same color, entry 0 is left alone since it is transparent
+// (and the backdrop color for the background palette)
+pub struct PaletteManager {
+    pub kind: PaletteKind,
+    colors: [Bgr555; 256],
+    users: [u16; 256], // allocations holding each entry, 0 is free
+}
+
+impl PaletteManager {
+    pub fn new(kind: PaletteKind) -> Self {
+        PaletteManager {
+            kind,
+            colors: [Bgr555::BLACK; 256],
+            users: [0; 256],
+        }
+    }
+
+    // entry showing color, None when the palette is full
+    pub fn alloc(&mut self, color: Bgr555) -> Option<PaletteColor> {
+        let shared = (1..256).find(|&i| self.users[i] > 0 && self.colors[i] == color);
+        let index = match shared {
+            Some(index) => index,
+            None => {
+                let index = (1..256).find(|&i| self.users[i] == 0)?;
+                self.colors[index] = color;
+                self.kind.write(index as u8, color);
+                index
+            }
+        };
+        self.users[index] += 1;
+        Some(PaletteColor::new(index as u8))
+    }
+
+    // give back an entry from alloc, it is free once every allocation is freed
+    pub fn free(&mut self, color: PaletteColor) {
+        let index = color.into_storage() as usize;
+        self.users[index] = self.users[index].saturating_sub(1);
+    }
+
+    pub fn get(&self, color: PaletteColor) -> Option<Bgr555> {
+        let index = color.into_storage() as usize;
+        if self.users[index] > 0 {
+            Some(self.colors[index])
+        } else {
+            None
+        }
+    }
+
+    pub fn free_entries(&self) -> usize {
+        self.users[1..].iter().filter(|&&users| users == 0).count()
+    }
+}
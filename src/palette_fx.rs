@@ -0,0 +1,76 @@
+use crate::{fixed::Angle, palette::PaletteKind};
+use embedded_graphics::{pixelcolor::Bgr555, prelude::*};
+
+// whole palette color changes, for pause screens and damage flashes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PaletteTransform {
+    Grayscale,
+    Invert,
+    Sepia,
+    Tint(Bgr555, u8), // mix towards a color, 0 is unchanged and 255 is the color
+    HueRotate(Angle),
+}
+
+impl PaletteTransform {
+    pub fn color(self, color: Bgr555) -> Bgr555 {
+        let (r, g, b) = (color.r() as i32, color.g() as i32, color.b() as i32);
+        match self {
+            PaletteTransform::Grayscale => {
+                let y = luma(r, g, b);
+                rgb(y, y, y)
+            }
+            PaletteTransform::Invert => rgb(31 - r, 31 - g, 31 - b),
+            PaletteTransform::Sepia => rgb(
+                (r * 393 + g * 769 + b * 189) / 1000,
+                (r * 349 + g * 686 + b * 168) / 1000,
+                (r * 272 + g * 534 + b * 131) / 1000,
+            ),
+            PaletteTransform::Tint(tint, amount) => {
+                let amount = amount as i32;
+                let mix = |a: i32, b: u8| a + (b as i32 - a) * amount / 255;
+                rgb(mix(r, tint.r()), mix(g, tint.g()), mix(b, tint.b()))
+            }
+            PaletteTransform::HueRotate(angle) => {
+                // rotation around the gray axis, weights are in thousandths
+                let (c, s) = (angle.cos().0, angle.sin().0);
+                let m = |a: i32, b: i32, d: i32| ((a << 16) + b * c + d * s) / 1000;
+                let channel = |m0: i32, m1: i32, m2: i32| (r * m0 + g * m1 + b * m2) >> 16;
+                rgb(
+                    channel(m(299, 701, 168), m(587, -587, 330), m(114, -114, -497)),
+                    channel(m(299, -299, -328), m(587, 413, 35), m(114, -114, 292)),
+                    channel(m(299, -300, 1250), m(587, -588, -1050), m(114, 886, -203)),
+                )
+            }
+        }
+    }
+
+    pub fn transform(self, colors: &mut [Bgr555]) {
+        for color in colors.iter_mut() {
+            *color = self.color(*color);
+        }
+    }
+
+    // transform every entry of a palette in palram
+    pub fn apply(self, kind: PaletteKind) {
+        for index in 0..=255 {
+            kind.write(index, self.color(kind.read(index)));
+        }
+    }
+
+    // write transformed colors to palram starting at index first
+    // colors stays unchanged, so it can be used to undo the transform
+    pub fn apply_from(self, colors: &[Bgr555], kind: PaletteKind, first: u8) {
+        for (index, color) in (first..=255).zip(colors.iter()) {
+            kind.write(index, self.color(*color));
+        }
+    }
+}
+
+fn luma(r: i32, g: i32, b: i32) -> i32 {
+    (r * 299 + g * 587 + b * 114) / 1000
+}
+
+fn rgb(r: i32, g: i32, b: i32) -> Bgr555 {
+    let clamp = |c: i32| c.max(0).min(31) as u8;
+    Bgr555::new(clamp(r), clamp(g), clamp(b))
+}
@@ -0,0 +1,109 @@
+use crate::{
+    effect::{BlendLayers, BlendMode, ColorEffect},
+    fixed::Fixed8,
+    Background,
+};
+use embedded_graphics::prelude::*;
+
+// a background and how it follows the camera
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParallaxLayer {
+    pub bg: Background,
+    pub speed: Fixed8, // scroll per camera pixel, ONE moves with the camera
+    pub offset: Point, // scroll when the camera is at 0, 0
+}
+
+impl ParallaxLayer {
+    pub fn new(bg: Background, speed: Fixed8) -> Self {
+        ParallaxLayer {
+            bg,
+            speed,
+            offset: Point::zero(),
+        }
+    }
+
+    pub fn scroll(&self, camera: Point) -> Point {
+        let x = (self.speed * camera.x).to_int();
+        let y = (self.speed * camera.y).to_int();
+        self.offset + Point::new(x, y)
+    }
+}
+
+// two text backgrounds scrolled from one camera at their own speeds, like a
+// level over distant hills, with the front optionally alpha blended over the
+// back, give the front background the higher priority (lower number)
+//     let parallax = Parallax::new(Background::Bg0, Background::Bg1)
+//         .with_alpha(12, 4); // a translucent mist layer in front
+//     parallax.enable();
+//     parallax.set_camera(camera); // every vblank
+pub struct Parallax {
+    pub front: ParallaxLayer,
+    pub back: ParallaxLayer,
+    alpha: Option<(u8, u8)>, // coefficients of the front and back, in 16ths
+}
+
+impl Parallax {
+    // the front moves with the camera and the back at half its speed
+    pub fn new(front: Background, back: Background) -> Self {
+        Parallax {
+            front: ParallaxLayer::new(front, Fixed8::ONE),
+            back: ParallaxLayer::new(back, Fixed8::from_ratio(1, 2)),
+            alpha: None,
+        }
+    }
+
+    pub fn with_speeds(self, front: Fixed8, back: Fixed8) -> Self {
+        Parallax {
+            front: ParallaxLayer {
+                speed: front,
+                ..self.front
+            },
+            back: ParallaxLayer {
+                speed: back,
+                ..self.back
+            },
+            ..self
+        }
+    }
+
+    // blend the front over the back, and the backdrop where the back is
+    // transparent, with coefficients in 16ths
+    pub fn with_alpha(self, front: u8, back: u8) -> Self {
+        Parallax {
+            alpha: Some((front, back)),
+            ..self
+        }
+    }
+
+    // show both backgrounds and set up the blending, if any
+    pub fn enable(&self) {
+        self.front.bg.show();
+        self.back.bg.show();
+        match self.alpha {
+            Some((first, second)) => ColorEffect::new(BlendMode::Alpha { first, second })
+                .with_first(blend_layer(self.front.bg))
+                .with_second(BlendLayers {
+                    backdrop: true,
+                    ..blend_layer(self.back.bg)
+                })
+                .apply(),
+            None => ColorEffect::disable(),
+        }
+    }
+
+    // scroll both backgrounds for the camera's top left, call during vblank
+    pub fn set_camera(&self, camera: Point) {
+        self.front.bg.set_scroll(self.front.scroll(camera));
+        self.back.bg.set_scroll(self.back.scroll(camera));
+    }
+}
+
+fn blend_layer(bg: Background) -> BlendLayers {
+    let none = BlendLayers::NONE;
+    match bg {
+        Background::Bg0 => BlendLayers { bg0: true, ..none },
+        Background::Bg1 => BlendLayers { bg1: true, ..none },
+        Background::Bg2 => BlendLayers { bg2: true, ..none },
+        Background::Bg3 => BlendLayers { bg3: true, ..none },
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{
+    fixed::{Angle, Fixed16, FixedPoint},
+    oam::OamManager,
+    sprite::{ObjLayout, Sprite, SpriteSize},
+};
+use embedded_graphics::prelude::*;
+
+// how every particle of a system looks and moves
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ParticleStyle<'a> {
+    pub tiles: &'a [u16], // tile id of each animation frame
+    pub size: SpriteSize,
+    pub palbank: u8,
+    pub priority: u8,
+    pub gravity: Fixed16,     // added to the vertical velocity every tick
+    pub ticks_per_frame: u16, // vblanks per animation frame
+}
+
+impl<'a> ParticleStyle<'a> {
+    pub const fn new(tiles: &'a [u16], size: SpriteSize) -> Self {
+        ParticleStyle {
+            tiles,
+            size,
+            palbank: 0,
+            priority: 0,
+            gravity: Fixed16::ZERO,
+            ticks_per_frame: 4,
+        }
+    }
+
+    pub fn with_palbank(self, palbank: u8) -> Self {
+        ParticleStyle { palbank, ..self }
+    }
+
+    pub fn with_priority(self, priority: u8) -> Self {
+        ParticleStyle { priority, ..self }
+    }
+
+    pub fn with_gravity(self, gravity: Fixed16) -> Self {
+        ParticleStyle { gravity, ..self }
+    }
+
+    pub fn with_ticks_per_frame(self, ticks_per_frame: u16) -> Self {
+        ParticleStyle {
+            ticks_per_frame: ticks_per_frame.max(1),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Particle {
+    pub position: FixedPoint, // top left of the sprite
+    pub velocity: FixedPoint, // per tick
+    pub life: u16,            // ticks left
+    age: u16,
+}
+
+// up to N particles drawn with hardware sprites, for sparks, explosions, rain
+// and smoke without drawing pixels, each particle owns one of N oam slots
+// reserved from the manager, call update() once per vblank before commit()
+// a particle retires when its life runs out or it leaves the screen
+pub struct Particles<'a, const N: usize> {
+    pub style: ParticleStyle<'a>,
+    particles: [Option<Particle>; N],
+    first_slot: usize,
+}
+
+impl<'a, const N: usize> Particles<'a, N> {
+    // None when there aren't N free oam slots in a row
+    pub fn new(oam: &mut OamManager, style: ParticleStyle<'a>) -> Option<Self> {
+        let first_slot = oam.alloc(N)?;
+        Some(Particles {
+            style,
+            particles: [None; N],
+            first_slot,
+        })
+    }
+
+    // false when every particle is alive
+    pub fn spawn(&mut self, position: Point, velocity: FixedPoint, life: u16) -> bool {
+        match self
+            .particles
+            .iter_mut()
+            .find(|particle| particle.is_none())
+        {
+            Some(free) => {
+                *free = Some(Particle {
+                    position: FixedPoint::from_point(position),
+                    velocity,
+                    life,
+                    age: 0,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    // count particles flying out of center in a ring at speed pixels per tick,
+    // returns how many were spawned
+    pub fn burst(&mut self, center: Point, count: u16, speed: Fixed16, life: u16) -> u16 {
+        let offset = self.style.size.size() / 2;
+        let position = center - Point::new(offset.width as i32, offset.height as i32);
+        let step = 0x10000 / count.max(1) as u32;
+        (0..count)
+            .take_while(|i| {
+                let angle = Angle((*i as u32 * step) as u16);
+                let velocity = FixedPoint::new(angle.cos() * speed, -(angle.sin() * speed));
+                self.spawn(position, velocity, life)
+            })
+            .count() as u16
+    }
+
+    // move, age and retire the particles and update their oam slots
+    pub fn update(&mut self, oam: &mut OamManager) {
+        let layout = ObjLayout::current();
+        let size = self.style.size.size();
+        let style = self.style;
+        for (i, entry) in self.particles.iter_mut().enumerate() {
+            let slot = self.first_slot + i;
+            if let Some(particle) = entry {
+                particle.velocity.y += style.gravity;
+                particle.position += particle.velocity;
+                particle.age = particle.age.saturating_add(1);
+                particle.life = particle.life.saturating_sub(1);
+                let point = particle.position.to_point();
+                let visible = point.x > -(size.width as i32)
+                    && point.y > -(size.height as i32)
+                    && point.x < 240
+                    && point.y < 160;
+                if particle.life == 0 || !visible || style.tiles.is_empty() {
+                    *entry = None;
+                } else {
+                    let frame = (particle.age / style.ticks_per_frame) as usize % style.tiles.len();
+                    let sprite = Sprite::new(style.tiles[frame], style.size)
+                        .with_position(point)
+                        .with_palbank(style.palbank)
+                        .with_priority(style.priority);
+                    if let Ok(attributes) = sprite.attributes(layout) {
+                        oam.set(slot, attributes);
+                        continue;
+                    }
+                }
+            }
+            oam.hide(slot);
+        }
+    }
+
+    // particles alive
+    pub fn live(&self) -> usize {
+        self.particles
+            .iter()
+            .filter(|particle| particle.is_some())
+            .count()
+    }
+
+    pub fn particles(&self) -> impl Iterator<Item = &Particle> {
+        self.particles.iter().flatten()
+    }
+
+    // retire every particle
+    pub fn clear(&mut self, oam: &mut OamManager) {
+        self.particles = [None; N];
+        for slot in self.first_slot..self.first_slot + N {
+            oam.hide(slot);
+        }
+    }
+
+    // give the oam slots back to the manager
+    pub fn free(self, oam: &mut OamManager) {
+        oam.free(self.first_slot, N);
+    }
+}
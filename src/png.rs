@@ -0,0 +1,434 @@
+use std::{io, vec::Vec};
+
+// png input for the assets-build feature, decoded into an uncompressed tga so
+// the converters only read one format, indexed pngs become 8 bit color mapped
+// tgas and every other png a 24 bit true color one, alpha is dropped
+// interlaced pngs aren't supported, re-save them without interlacing
+
+const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+const INDEXED: u8 = 3;
+const GRAY: u8 = 0;
+const GRAY_ALPHA: u8 = 4;
+const RGB: u8 = 2;
+const RGBA: u8 = 6;
+
+pub(crate) fn is_png(data: &[u8]) -> bool {
+    data.starts_with(SIGNATURE)
+}
+
+// the png in data as a top down tga
+pub(crate) fn to_tga(data: &[u8]) -> io::Result<Vec<u8>> {
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut rest = data
+        .get(SIGNATURE.len()..)
+        .ok_or_else(|| invalid("not a png"))?;
+    while rest.len() >= 12 {
+        let len = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = &rest[4..8];
+        let body = rest
+            .get(8..8 + len)
+            .ok_or_else(|| invalid("png chunk is truncated"))?;
+        match kind {
+            b"IHDR" => header = Some(Header::read(body)?),
+            b"PLTE" => palette = body,
+            b"IDAT" => compressed.extend_from_slice(body),
+            b"IEND" => break,
+            _ => {} // ancillary chunks, like tRNS and gAMA
+        }
+        rest = &rest[(12 + len).min(rest.len())..];
+    }
+    let header = header.ok_or_else(|| invalid("png has no IHDR"))?;
+    let pixels = unfilter(&header, &inflate(&compressed)?)?;
+    if header.color_type == INDEXED {
+        header.indexed_tga(palette, &pixels)
+    } else {
+        header.true_color_tga(&pixels)
+    }
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8, // bits per sample
+    color_type: u8,
+}
+
+impl Header {
+    fn read(body: &[u8]) -> io::Result<Self> {
+        if body.len() < 13 {
+            return Err(invalid("png IHDR is truncated"));
+        }
+        let word = |i: usize| u32::from_be_bytes([body[i], body[i + 1], body[i + 2], body[i + 3]]);
+        let header = Header {
+            width: word(0) as usize,
+            height: word(4) as usize,
+            depth: body[8],
+            color_type: body[9],
+        };
+        if body[12] != 0 {
+            return Err(invalid("interlaced pngs aren't supported"));
+        }
+        if header.width > 0xFFFF || header.height > 0xFFFF {
+            return Err(invalid("png is too large for a tga"));
+        }
+        match (header.color_type, header.depth) {
+            (INDEXED, 1) | (INDEXED, 2) | (INDEXED, 4) | (INDEXED, 8) => Ok(header),
+            (GRAY, 1) | (GRAY, 2) | (GRAY, 4) => Ok(header),
+            (GRAY, 8) | (GRAY, 16) | (GRAY_ALPHA, 8) | (GRAY_ALPHA, 16) => Ok(header),
+            (RGB, 8) | (RGB, 16) | (RGBA, 8) | (RGBA, 16) => Ok(header),
+            _ => Err(invalid("unsupported png color type or bit depth")),
+        }
+    }
+
+    fn samples(&self) -> usize {
+        match self.color_type {
+            GRAY_ALPHA => 2,
+            RGB => 3,
+            RGBA => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.samples() * self.depth as usize
+    }
+
+    // bytes of a row of pixels, without the filter byte
+    fn stride(&self) -> usize {
+        (self.width * self.bits_per_pixel() + 7) / 8
+    }
+
+    // sample s of pixel x in row, depths below 8 unpacked, 16 bit cut to 8
+    fn sample(&self, row: &[u8], x: usize, s: usize) -> u8 {
+        let depth = self.depth as usize;
+        match depth {
+            8 => row[x * self.samples() + s],
+            16 => row[(x * self.samples() + s) * 2], // high byte first
+            _ => {
+                let bit = x * depth;
+                let value = row[bit / 8] >> (8 - depth - bit % 8);
+                value & ((1 << depth) - 1) as u8
+            }
+        }
+    }
+
+    fn tga_header(&self, image_type: u8, colors: usize, depth: u8) -> Vec<u8> {
+        let mut tga = Vec::with_capacity(18);
+        tga.push(0); // no image id
+        tga.push((colors > 0) as u8);
+        tga.push(image_type);
+        tga.extend_from_slice(&[0, 0]); // first color map entry
+        tga.extend_from_slice(&(colors as u16).to_le_bytes());
+        tga.push(if colors > 0 { 24 } else { 0 });
+        tga.extend_from_slice(&[0, 0, 0, 0]); // origin
+        tga.extend_from_slice(&(self.width as u16).to_le_bytes());
+        tga.extend_from_slice(&(self.height as u16).to_le_bytes());
+        tga.push(depth);
+        tga.push(1 << 5); // top down
+        tga
+    }
+
+    fn indexed_tga(&self, palette: &[u8], pixels: &[u8]) -> io::Result<Vec<u8>> {
+        let colors = palette.len() / 3;
+        if colors == 0 {
+            return Err(invalid("indexed png has no PLTE"));
+        }
+        let mut tga = self.tga_header(1, colors, 8);
+        for rgb in palette.chunks_exact(3) {
+            tga.extend_from_slice(&[rgb[2], rgb[1], rgb[0]]);
+        }
+        for row in pixels.chunks(self.stride()) {
+            tga.extend((0..self.width).map(|x| self.sample(row, x, 0)));
+        }
+        Ok(tga)
+    }
+
+    fn true_color_tga(&self, pixels: &[u8]) -> io::Result<Vec<u8>> {
+        let mut tga = self.tga_header(2, 0, 24);
+        let scale = match self.depth {
+            1 => 0xFF,
+            2 => 0x55,
+            4 => 0x11,
+            _ => 1,
+        };
+        for row in pixels.chunks(self.stride()) {
+            for x in 0..self.width {
+                let (r, g, b) = match self.color_type {
+                    RGB | RGBA => (
+                        self.sample(row, x, 0),
+                        self.sample(row, x, 1),
+                        self.sample(row, x, 2),
+                    ),
+                    _ => {
+                        let gray = self.sample(row, x, 0) * scale;
+                        (gray, gray, gray)
+                    }
+                };
+                tga.extend_from_slice(&[b, g, r]);
+            }
+        }
+        Ok(tga)
+    }
+}
+
+// undo each row's filter, the rows come back without their filter bytes
+fn unfilter(header: &Header, data: &[u8]) -> io::Result<Vec<u8>> {
+    let stride = header.stride();
+    let bpp = ((header.bits_per_pixel() + 7) / 8).max(1); // left neighbour distance
+    if data.len() < (stride + 1) * header.height {
+        return Err(invalid("png image data is truncated"));
+    }
+    let mut pixels = std::vec![0u8; stride * header.height];
+    for y in 0..header.height {
+        let filter = data[y * (stride + 1)];
+        let line = &data[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, current) = pixels.split_at_mut(y * stride);
+        let prior = if y > 0 {
+            &done[(y - 1) * stride..]
+        } else {
+            &[][..]
+        };
+        let row = &mut current[..stride];
+        for i in 0..stride {
+            let a = if i >= bpp { row[i - bpp] } else { 0 };
+            let b = prior.get(i).copied().unwrap_or(0);
+            let c = if i >= bpp {
+                prior.get(i - bpp).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(invalid("bad png filter type")),
+            };
+            row[i] = line[i].wrapping_add(predicted);
+        }
+    }
+    Ok(pixels)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// order of the code length code lengths in a dynamic block header
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+// decompress a zlib stream, the checksum isn't checked
+fn inflate(data: &[u8]) -> io::Result<Vec<u8>> {
+    if data.len() < 2 || data[0] & 0x0F != 8 || data[1] & 0x20 != 0 {
+        return Err(invalid("png image data isn't a deflate stream"));
+    }
+    let mut bits = Bits { data, position: 16 };
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => stored(&mut bits, &mut out)?,
+            1 => {
+                let mut lengths = [0; 288];
+                lengths[..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..].fill(8);
+                let literals = Huffman::new(&lengths);
+                let distances = Huffman::new(&[5; 30]);
+                codes(&mut bits, &mut out, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic(&mut bits)?;
+                codes(&mut bits, &mut out, &literals, &distances)?;
+            }
+            _ => return Err(invalid("bad deflate block type")),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+// least significant bit first reader over the deflate stream
+struct Bits<'a> {
+    data: &'a [u8],
+    position: usize, // in bits
+}
+
+impl Bits<'_> {
+    fn read(&mut self, count: u32) -> io::Result<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = self
+                .data
+                .get(self.position / 8)
+                .ok_or_else(|| invalid("png image data is truncated"))?;
+            value |= ((byte >> (self.position % 8)) as u32 & 1) << i;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+}
+
+// a canonical huffman code, symbols ordered by code length then value
+struct Huffman {
+    counts: [u16; 16], // symbols of each code length
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for length in 1..16 {
+            for (symbol, _) in lengths.iter().enumerate().filter(|(_, &l)| l == length) {
+                symbols.push(symbol as u16);
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> io::Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for length in 1..16 {
+            code |= bits.read(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("bad huffman code in png image data"))
+    }
+}
+
+fn stored(bits: &mut Bits, out: &mut Vec<u8>) -> io::Result<()> {
+    let start = (bits.position + 7) / 8;
+    let header = bits
+        .data
+        .get(start..start + 4)
+        .ok_or_else(|| invalid("png image data is truncated"))?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) {
+        return Err(invalid("bad stored block length"));
+    }
+    let block = bits
+        .data
+        .get(start + 4..start + 4 + len as usize)
+        .ok_or_else(|| invalid("png image data is truncated"))?;
+    out.extend_from_slice(block);
+    bits.position = (start + 4 + len as usize) * 8;
+    Ok(())
+}
+
+// the literal and distance codes described at the start of a dynamic block
+fn dynamic(bits: &mut Bits) -> io::Result<(Huffman, Huffman)> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_length_count = bits.read(4)? as usize + 4;
+    let mut code_lengths = [0; 19];
+    for &symbol in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[symbol] = bits.read(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = [0u8; 320];
+    let mut i = 0;
+    while i < literal_count + distance_count {
+        let (value, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 if i > 0 => (lengths[i - 1], 3 + bits.read(2)? as usize),
+            17 => (0, 3 + bits.read(3)? as usize),
+            18 => (0, 11 + bits.read(7)? as usize),
+            _ => return Err(invalid("bad code lengths in png image data")),
+        };
+        if i + repeat > literal_count + distance_count {
+            return Err(invalid("bad code lengths in png image data"));
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count]),
+        Huffman::new(&lengths[literal_count..i]),
+    ))
+}
+
+// decode literals and back references up to the end of block symbol
+fn codes(
+    bits: &mut Bits,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> io::Result<()> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let code = symbol - 257;
+                let length =
+                    LENGTH_BASE[code] as usize + bits.read(LENGTH_EXTRA[code] as u32)? as usize;
+                let code = distances.decode(bits)? as usize;
+                if code >= 30 {
+                    return Err(invalid("bad distance in png image data"));
+                }
+                let distance =
+                    DISTANCE_BASE[code] as usize + bits.read(DISTANCE_EXTRA[code] as u32)? as usize;
+                if distance > out.len() {
+                    return Err(invalid("bad distance in png image data"));
+                }
+                let start = out.len() - distance;
+                for i in 0..length {
+                    out.push(out[start + i]); // may overlap what it copies
+                }
+            }
+            _ => return Err(invalid("bad length in png image data")),
+        }
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,167 @@
+use crate::{flush::Flush, mmio};
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+// draw time measured with timer 3, to see whether drawing fits in vblank
+// the timer counts every 64 cycles, a scanline is 1232 cycles and a frame is
+// 228 scanlines, so one frame fits in the 16 bit counter
+
+const TM3CNT_L: usize = 0x400_010C;
+const TM3CNT_H: usize = 0x400_010E;
+const ENABLE_DIV64: u16 = 0x0081;
+const CYCLES_PER_TICK: u32 = 64;
+const CYCLES_PER_SCANLINE: u32 = 1232;
+
+// scanlines of vblank, drawing longer than this in vblank shows up on screen
+pub const VBLANK_SCANLINES: u32 = 68;
+
+// timer ticks of the last frame, the worst frame so far, and frames counted
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct ProfileStats {
+    pub last: u32,
+    pub worst: u32,
+    pub frames: u32,
+}
+
+impl ProfileStats {
+    pub fn last_scanlines(&self) -> u32 {
+        scanlines(self.last)
+    }
+
+    pub fn worst_scanlines(&self) -> u32 {
+        scanlines(self.worst)
+    }
+}
+
+pub fn scanlines(ticks: u32) -> u32 {
+    ticks * CYCLES_PER_TICK / CYCLES_PER_SCANLINE
+}
+
+pub struct Profiler {
+    start: u16,
+    current: u32, // ticks measured this frame
+    stats: ProfileStats,
+}
+
+impl Profiler {
+    // start timer 3, it must not be used for anything else
+    pub fn new() -> Self {
+        mmio::write16(TM3CNT_H, 0);
+        mmio::write16(TM3CNT_L, 0);
+        mmio::write16(TM3CNT_H, ENABLE_DIV64);
+        Profiler {
+            start: 0,
+            current: 0,
+            stats: ProfileStats::default(),
+        }
+    }
+
+    pub fn begin(&mut self) {
+        self.start = mmio::read16(TM3CNT_L);
+    }
+
+    pub fn end(&mut self) {
+        let elapsed = mmio::read16(TM3CNT_L).wrapping_sub(self.start);
+        self.current += elapsed as u32;
+    }
+
+    // time f as part of this frame
+    pub fn measure<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        self.begin();
+        let result = f();
+        self.end();
+        result
+    }
+
+    // finish the frame's measurements, call once per frame
+    pub fn end_frame(&mut self) -> ProfileStats {
+        self.stats.last = self.current;
+        self.stats.worst = self.stats.worst.max(self.current);
+        self.stats.frames += 1;
+        self.current = 0;
+        self.stats
+    }
+
+    pub fn stats(&self) -> ProfileStats {
+        self.stats
+    }
+
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.stats = ProfileStats::default();
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// time every draw and flush of a display, flush ends the frame
+pub struct Profiled<D> {
+    pub display: D,
+    pub profiler: Profiler,
+}
+
+impl<D: DrawTarget> Profiled<D> {
+    pub fn new(display: D) -> Self {
+        Profiled {
+            display,
+            profiler: Profiler::new(),
+        }
+    }
+
+    pub fn stats(&self) -> ProfileStats {
+        self.profiler.stats()
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Profiled<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let display = &mut self.display;
+        self.profiler.measure(|| display.draw_iter(pixels))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let display = &mut self.display;
+        self.profiler
+            .measure(|| display.fill_contiguous(area, colors))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let display = &mut self.display;
+        self.profiler.measure(|| display.fill_solid(area, color))
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let display = &mut self.display;
+        self.profiler.measure(|| display.clear(color))
+    }
+}
+
+impl<D: DrawTarget> OriginDimensions for Profiled<D> {
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+impl<D: Flush> Flush for Profiled<D> {
+    fn flush(&mut self) {
+        let display = &mut self.display;
+        self.profiler.measure(|| display.flush());
+        self.profiler.end_frame();
+    }
+}
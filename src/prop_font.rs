@@ -0,0 +1,194 @@
+use core::ops::Range;
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+const UNKNOWN_CHAR: char = '?';
+
+// proportional 1 bit font for drawing text on the bitmap modes, fits a lot
+// more on the screen than the monospaced fonts
+// glyphs are stored back to back, height bytes each, one byte per row with the
+// leftmost pixel in the high bit, so glyphs are at most 8 pixels wide
+// widths has one entry per glyph, in pixels, without the letter spacing
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PropFont<'a> {
+    glyphs: &'a [u8],
+    widths: &'a [u8],
+    height: u32,
+    first_char: u8,
+    letter_spacing: u32, // pixels after every glyph
+    line_spacing: u32,   // pixels between lines
+}
+
+impl<'a> PropFont<'a> {
+    // panics, at compile time for a const font, on a glyph wider than 8 pixels
+    pub const fn new(glyphs: &'a [u8], widths: &'a [u8], height: u32, first_char: u8) -> Self {
+        let mut i = 0;
+        while i < widths.len() {
+            assert!(widths[i] <= 8, "glyphs are at most 8 pixels wide");
+            i += 1;
+        }
+        PropFont {
+            glyphs,
+            widths,
+            height,
+            first_char,
+            letter_spacing: 1,
+            line_spacing: 1,
+        }
+    }
+
+    pub fn with_spacing(self, letter_spacing: u32, line_spacing: u32) -> Self {
+        PropFont {
+            letter_spacing,
+            line_spacing,
+            ..self
+        }
+    }
+
+    pub fn line_height(&self) -> u32 {
+        self.height + self.line_spacing
+    }
+
+    // pen advance for c, characters missing from the font are drawn as ?
+    pub fn char_width(&self, c: char) -> u32 {
+        match self.glyph(c) {
+            Some(glyph) => self.widths[glyph] as u32 + self.letter_spacing,
+            None => 0,
+        }
+    }
+
+    // width of a single line of text
+    pub fn text_width(&self, text: &str) -> u32 {
+        text.chars().map(|c| self.char_width(c)).sum()
+    }
+
+    // draw text with its top left at position, a newline goes back to
+    // position.x one line down, returns the pen position after the text
+    pub fn draw_text<D: DrawTarget>(
+        &self,
+        text: &str,
+        position: Point,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<Point, D::Error> {
+        let mut pen = position;
+        for c in text.chars() {
+            if c == '\n' {
+                pen = Point::new(position.x, pen.y + self.line_height() as i32);
+            } else {
+                pen = self.draw_char(c, pen, color, target)?;
+            }
+        }
+        Ok(pen)
+    }
+
+    // draw text word wrapped to area, stops at the first line that doesn't fit
+    // and returns how many bytes of text were drawn, so the rest can go on the
+    // next page, words wider than area are broken between characters
+    pub fn draw_wrapped<D: DrawTarget>(
+        &self,
+        text: &str,
+        area: &Rectangle,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<usize, D::Error> {
+        self.draw_wrapped_part(text, 0..text.len(), area, color, target)
+    }
+
+    // like draw_wrapped but only the characters starting in range of text are
+    // drawn, the rest are laid out without drawing, to add text to what was
+    // drawn before without redrawing it or moving words between lines
+    pub fn draw_wrapped_part<D: DrawTarget>(
+        &self,
+        text: &str,
+        range: Range<usize>,
+        area: &Rectangle,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<usize, D::Error> {
+        let left = area.top_left.x;
+        let right = left + area.size.width as i32;
+        let bottom = area.top_left.y + area.size.height as i32;
+        let line_height = self.line_height() as i32;
+        let fits = |pen: Point| pen.y + self.height as i32 <= bottom;
+
+        let mut pen = area.top_left;
+        let mut i = 0;
+        while i < text.len() {
+            if !fits(pen) {
+                return Ok(i);
+            }
+            let rest = &text[i..];
+            if rest.starts_with('\n') {
+                pen = Point::new(left, pen.y + line_height);
+                i += 1;
+                continue;
+            }
+            if rest.starts_with(' ') {
+                if pen.x != left {
+                    pen.x += self.char_width(' ') as i32; // no spaces at the start of a line
+                }
+                i += 1;
+                continue;
+            }
+
+            let word = &rest[..rest
+                .find(|c: char| c == ' ' || c == '\n')
+                .unwrap_or(rest.len())];
+            if pen.x != left && pen.x + self.text_width(word) as i32 > right {
+                pen = Point::new(left, pen.y + line_height);
+                continue;
+            }
+            for (j, c) in word.char_indices() {
+                if pen.x != left && pen.x + self.char_width(c) as i32 > right {
+                    pen = Point::new(left, pen.y + line_height);
+                    if !fits(pen) {
+                        return Ok(i + j);
+                    }
+                }
+                if range.contains(&(i + j)) {
+                    pen = self.draw_char(c, pen, color, target)?;
+                } else {
+                    pen.x += self.char_width(c) as i32;
+                }
+            }
+            i += word.len();
+        }
+        Ok(text.len())
+    }
+
+    // draw a single glyph, returns the advanced pen
+    fn draw_char<D: DrawTarget>(
+        &self,
+        c: char,
+        pen: Point,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<Point, D::Error> {
+        let glyph = match self.glyph(c) {
+            Some(glyph) => glyph,
+            None => return Ok(pen),
+        };
+        let width = self.widths[glyph] as u32;
+        let start = glyph * self.height as usize;
+        let rows = &self.glyphs[start..start + self.height as usize];
+        target.draw_iter(rows.iter().enumerate().flat_map(|(y, row)| {
+            (0..width)
+                .filter(move |x| row & (0x80 >> x) != 0)
+                .map(move |x| Pixel(pen + Point::new(x as i32, y as i32), color))
+        }))?;
+        Ok(pen + Point::new((width + self.letter_spacing) as i32, 0))
+    }
+
+    // index of c's glyph, or of the ? glyph
+    fn glyph(&self, c: char) -> Option<usize> {
+        let index = |c: char| -> Option<usize> {
+            let index = (c as u32).checked_sub(self.first_char as u32)? as usize;
+            if index < self.widths.len() {
+                Some(index)
+            } else {
+                None
+            }
+        };
+        index(c).or_else(|| index(UNKNOWN_CHAR))
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{palette::PaletteKind, PaletteColor};
+use core::convert::Infallible;
+use embedded_graphics::{geometry::Size, pixelcolor::Bgr555, prelude::*};
+
+// maps any Bgr555 color to the closest color of a palette
+pub struct Quantizer {
+    colors: [Bgr555; 256],
+    len: usize,
+}
+
+impl Quantizer {
+    // palette index i is colors[i], at most 256 colors are used
+    pub fn from_slice(colors: &[Bgr555]) -> Self {
+        let mut quantizer = Quantizer {
+            colors: [Bgr555::BLACK; 256],
+            len: colors.len().min(256),
+        };
+        quantizer.colors[..quantizer.len].copy_from_slice(&colors[..quantizer.len]);
+        quantizer
+    }
+
+    // the colors currently in palram
+    pub fn from_palram(kind: PaletteKind) -> Self {
+        let mut quantizer = Quantizer {
+            colors: [Bgr555::BLACK; 256],
+            len: 256,
+        };
+        for (i, color) in quantizer.colors.iter_mut().enumerate() {
+            *color = kind.read(i as u8);
+        }
+        quantizer
+    }
+
+    pub fn nearest(&self, color: Bgr555) -> PaletteColor {
+        let index = self.colors[..self.len]
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, candidate)| distance(color, **candidate))
+            .map_or(0, |(index, _)| index);
+        PaletteColor::new(index as u8)
+    }
+}
+
+// weighted squared distance, the eye is most sensitive to green and least to blue
+pub fn distance(a: Bgr555, b: Bgr555) -> u32 {
+    let dr = a.r() as i32 - b.r() as i32;
+    let dg = a.g() as i32 - b.g() as i32;
+    let db = a.b() as i32 - b.b() as i32;
+    (3 * dr * dr + 4 * dg * dg + 2 * db * db) as u32
+}
+
+// draw Bgr555 content onto a palette display like Mode4Display
+pub struct QuantizedDisplay<'a, D> {
+    pub display: D,
+    quantizer: &'a Quantizer,
+    last: Option<(Bgr555, PaletteColor)>, // runs of one color are common
+}
+
+impl<'a, D> QuantizedDisplay<'a, D>
+where
+    D: DrawTarget<Color = PaletteColor>,
+{
+    pub fn new(display: D, quantizer: &'a Quantizer) -> Self {
+        QuantizedDisplay {
+            display,
+            quantizer,
+            last: None,
+        }
+    }
+
+    fn convert(&mut self, color: Bgr555) -> PaletteColor {
+        match self.last {
+            Some((last, index)) if last == color => index,
+            _ => {
+                let index = self.quantizer.nearest(color);
+                self.last = Some((color, index));
+                index
+            }
+        }
+    }
+}
+
+impl<'a, D> DrawTarget for QuantizedDisplay<'a, D>
+where
+    D: DrawTarget<Color = PaletteColor, Error = Infallible>,
+{
+    type Color = Bgr555;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            let index = self.convert(color);
+            self.display.draw_iter(Some(Pixel(coord, index)))?;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let index = self.convert(color);
+        self.display.clear(index)
+    }
+}
+
+impl<'a, D> OriginDimensions for QuantizedDisplay<'a, D>
+where
+    D: DrawTarget<Color = PaletteColor, Error = Infallible>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
@@ -0,0 +1,111 @@
+use crate::flush::Flush;
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct QueueFull;
+
+// a horizontal run of one color
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Span<C> {
+    start: Point,
+    width: u32,
+    color: C,
+}
+
+// queue up to N spans of drawing during the frame, commit() draws them to the
+// inner display during vblank, so game logic doesn't wait on vram access
+// consecutive pixels of a row with the same color share one span
+pub struct QueuedDisplay<D: DrawTarget, const N: usize> {
+    pub display: D,
+    queue: [Option<Span<D::Color>>; N],
+    len: usize,
+}
+
+impl<D: DrawTarget, const N: usize> QueuedDisplay<D, N> {
+    pub fn new(display: D) -> Self {
+        QueuedDisplay {
+            display,
+            queue: [None; N],
+            len: 0,
+        }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // drop everything queued
+    pub fn discard(&mut self) {
+        self.len = 0;
+    }
+
+    // draw the queue to the display and empty it
+    pub fn commit(&mut self) -> Result<(), D::Error> {
+        for span in self.queue[..self.len].iter().flatten() {
+            self.display.fill_solid(
+                &Rectangle::new(span.start, Size::new(span.width, 1)),
+                span.color,
+            )?;
+        }
+        self.len = 0;
+        Ok(())
+    }
+
+    fn push(&mut self, Pixel(coord, color): Pixel<D::Color>) -> Result<(), QueueFull> {
+        if let Some(Some(last)) = self.len.checked_sub(1).map(|i| self.queue[i]) {
+            if last.color == color && coord == last.start + Point::new(last.width as i32, 0) {
+                self.queue[self.len - 1] = Some(Span {
+                    width: last.width + 1,
+                    ..last
+                });
+                return Ok(());
+            }
+        }
+        let slot = self.queue.get_mut(self.len).ok_or(QueueFull)?;
+        *slot = Some(Span {
+            start: coord,
+            width: 1,
+            color,
+        });
+        self.len += 1;
+        Ok(())
+    }
+}
+
+impl<D: DrawTarget, const N: usize> DrawTarget for QueuedDisplay<D, N> {
+    type Color = D::Color;
+    type Error = QueueFull;
+
+    // pixels up to the one that didn't fit are queued
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels.into_iter() {
+            self.push(pixel)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D: DrawTarget, const N: usize> OriginDimensions for QueuedDisplay<D, N> {
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+impl<D: DrawTarget + Flush, const N: usize> Flush for QueuedDisplay<D, N> {
+    // commit then flush the inner display, errors drawing the queue are dropped
+    fn flush(&mut self) {
+        self.commit().ok();
+        self.display.flush();
+    }
+}
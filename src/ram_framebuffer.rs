@@ -0,0 +1,175 @@
+use crate::read::ReadPixel;
+use core::convert::{Infallible, TryInto};
+use embedded_graphics::{
+    geometry::Size,
+    image::{Image, ImageDrawable},
+    pixelcolor::PixelColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+
+// a W by H canvas of Bgr555 or PaletteColor pixels in ram, for drawing a
+// widget or a badge once and stamping it onto a display wherever it is needed
+// with blit() or Image::new(&framebuffer, point)
+// a 64x64 Bgr555 canvas is 8KiB, put larger ones in ewram:
+//     #[link_section = ".ewram"]
+//     static mut PANEL: RamFramebuffer<Bgr555, 120, 80> = RamFramebuffer {
+//         pixels: [[Bgr555::BLACK; 120]; 80],
+//     };
+pub struct RamFramebuffer<C, const W: usize, const H: usize> {
+    pub pixels: [[C; W]; H],
+}
+
+impl<C: PixelColor, const W: usize, const H: usize> RamFramebuffer<C, W, H> {
+    pub fn new(color: C) -> Self {
+        RamFramebuffer {
+            pixels: [[color; W]; H],
+        }
+    }
+
+    // draw every pixel onto display with its top left at top_left, through the
+    // display's fill_contiguous, clipped to the display
+    pub fn blit<D>(&self, display: &mut D, top_left: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        Image::new(self, top_left).draw(display)
+    }
+
+    // draw only the pixels that aren't key, like PaletteColor::TANSPARENT, so
+    // a round widget doesn't stamp its corners
+    pub fn blit_keyed<D>(&self, display: &mut D, top_left: Point, key: C) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let pixels = self.pixels.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(move |(_, color)| **color != key)
+                .map(move |(x, color)| Pixel(top_left + Point::new(x as i32, y as i32), *color))
+        });
+        display.draw_iter(pixels)
+    }
+}
+
+impl<C: PixelColor, const W: usize, const H: usize> DrawTarget for RamFramebuffer<C, W, H> {
+    type Color = C;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x, y)) = coord.try_into() {
+                let (x, y): (u32, u32) = (x, y);
+                if let Some(pixel) = self
+                    .pixels
+                    .get_mut(y as usize)
+                    .and_then(|row| row.get_mut(x as usize))
+                {
+                    *pixel = color;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        if let Some(bottom_right) = area.bottom_right() {
+            let (x1, x2) = (area.top_left.x as usize, bottom_right.x as usize);
+            for row in &mut self.pixels[area.top_left.y as usize..=bottom_right.y as usize] {
+                row[x1..=x2].fill(color);
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.pixels = [[color; W]; H];
+        Ok(())
+    }
+}
+
+impl<C, const W: usize, const H: usize> OriginDimensions for RamFramebuffer<C, W, H> {
+    fn size(&self) -> Size {
+        Size::new(W as u32, H as u32)
+    }
+}
+
+impl<C: PixelColor, const W: usize, const H: usize> ReadPixel for RamFramebuffer<C, W, H> {
+    fn read_pixel(&self, point: Point) -> Option<C> {
+        let (x, y): (u32, u32) = point.try_into().ok()?;
+        self.pixels.get(y as usize)?.get(x as usize).copied()
+    }
+}
+
+impl<C: PixelColor, const W: usize, const H: usize> ImageDrawable for RamFramebuffer<C, W, H> {
+    type Color = C;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.draw_sub_image(target, &self.bounding_box())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        let colors = clipped.rows().flat_map(|y| {
+            clipped
+                .columns()
+                .map(move |x| self.pixels[y as usize][x as usize])
+        });
+        let top_left = clipped.top_left - area.top_left;
+        target.fill_contiguous(&Rectangle::new(top_left, clipped.size), colors)
+    }
+}
+
+#[cfg(all(test, feature = "host-vram"))]
+mod tests {
+    use super::*;
+    use embedded_graphics::pixelcolor::Bgr555;
+
+    fn source() -> RamFramebuffer<Bgr555, 4, 4> {
+        let mut framebuffer = RamFramebuffer::new(Bgr555::BLACK);
+        for (y, row) in framebuffer.pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = Bgr555::new(x as u8 + 1, y as u8 + 1, 0);
+            }
+        }
+        framebuffer
+    }
+
+    #[test]
+    fn sub_image_inside() {
+        let mut target = RamFramebuffer::<Bgr555, 4, 4>::new(Bgr555::BLACK);
+        let area = Rectangle::new(Point::new(1, 1), Size::new(2, 2));
+        source().draw_sub_image(&mut target, &area).unwrap();
+        assert_eq!(target.pixels[0][0], Bgr555::new(2, 2, 0));
+        assert_eq!(target.pixels[1][1], Bgr555::new(3, 3, 0));
+        assert_eq!(target.pixels[2][2], Bgr555::BLACK);
+    }
+
+    #[test]
+    fn sub_image_partly_outside() {
+        let mut target = RamFramebuffer::<Bgr555, 4, 4>::new(Bgr555::BLACK);
+        // one column left of the source and one row below it are cut off
+        let area = Rectangle::new(Point::new(-1, 2), Size::new(3, 3));
+        source().draw_sub_image(&mut target, &area).unwrap();
+        for (y, row) in target.pixels.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                let expected = if (1..3).contains(&x) && y < 2 {
+                    Bgr555::new(x as u8, y as u8 + 3, 0) // source x - 1, y + 2
+                } else {
+                    Bgr555::BLACK
+                };
+                assert_eq!(*pixel, expected, "at {}, {}", x, y);
+            }
+        }
+    }
+}
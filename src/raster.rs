@@ -0,0 +1,182 @@
+use crate::{background::Background, blend::blend, mmio};
+use embedded_graphics::{pixelcolor::Bgr555, prelude::*};
+use gba::io::dma::{
+    DMAControlSetting, DMADestAddressControl, DMASrcAddressControl, DMAStartTiming, DMA0, DMA1,
+    DMA2,
+};
+
+const LINES: usize = 160;
+const BG2PA: usize = 0x400_0020;
+const BLDY: usize = 0x400_0054;
+const BG_PALRAM: usize = 0x500_0000;
+
+// DMA3 is left for the crate's immediate copies
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DmaChannel {
+    Dma0,
+    Dma1,
+    Dma2,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Table<'a> {
+    Halfwords(&'a [u16]),
+    Words(&'a [u32]),
+}
+
+// a per scanline register write, driven by hblank dma
+// the table holds the value(s) for each of the 160 lines in order, and start()
+// has to be called every vblank to rewind it, each effect takes a dma channel
+// the hblank after the last line also reads one line past the table, that write
+// happens during vblank so it doesn't show
+pub struct RasterEffect<'a> {
+    channel: DmaChannel,
+    register: usize,
+    table: Table<'a>,
+    per_line: u16, // halfwords or words written each line
+}
+
+impl<'a> RasterEffect<'a> {
+    // write per_line halfwords starting at register every line
+    pub fn halfwords(
+        channel: DmaChannel,
+        register: usize,
+        table: &'a [u16],
+        per_line: u16,
+    ) -> Self {
+        assert!(
+            table.len() >= per_line as usize * LINES,
+            "table needs values for 160 lines"
+        );
+        RasterEffect {
+            channel,
+            register,
+            table: Table::Halfwords(table),
+            per_line,
+        }
+    }
+
+    // write per_line words starting at register every line
+    pub fn words(channel: DmaChannel, register: usize, table: &'a [u32], per_line: u16) -> Self {
+        assert!(
+            table.len() >= per_line as usize * LINES,
+            "table needs values for 160 lines"
+        );
+        RasterEffect {
+            channel,
+            register,
+            table: Table::Words(table),
+            per_line,
+        }
+    }
+
+    // horizontal scroll of a text background per line, for wavy backgrounds
+    pub fn scroll_x(channel: DmaChannel, bg: Background, table: &'a [u16]) -> Self {
+        Self::halfwords(channel, 0x400_0010 + bg as usize * 4, table, 1)
+    }
+
+    // both scroll offsets of a text background per line, x in the low halfword
+    pub fn scroll(channel: DmaChannel, bg: Background, table: &'a [u32]) -> Self {
+        Self::words(channel, 0x400_0010 + bg as usize * 4, table, 1)
+    }
+
+    // a background palette entry per line, for gradients
+    pub fn palette_entry(channel: DmaChannel, index: u8, table: &'a [u16]) -> Self {
+        Self::halfwords(channel, BG_PALRAM + index as usize * 2, table, 1)
+    }
+
+    // BLDY per line, for brightness effects
+    pub fn brightness(channel: DmaChannel, table: &'a [u16]) -> Self {
+        Self::halfwords(channel, BLDY, table, 1)
+    }
+
+    // bg2 pa, pb, pc and pd per line as two words, for mode 7 style floors
+    pub fn affine(channel: DmaChannel, table: &'a [u32]) -> Self {
+        Self::words(channel, BG2PA, table, 2)
+    }
+
+    // write the first line's values and start the dma, call every vblank
+    pub fn start(&self) {
+        self.stop();
+        let (source, wide) = match self.table {
+            Table::Halfwords(table) => {
+                for (i, value) in table[..self.per_line as usize].iter().enumerate() {
+                    mmio::write16(self.register + i * 2, *value);
+                }
+                (table[self.per_line as usize..].as_ptr() as usize, false)
+            }
+            Table::Words(table) => {
+                for (i, value) in table[..self.per_line as usize].iter().enumerate() {
+                    mmio::write32(self.register + i * 4, *value);
+                }
+                (table[self.per_line as usize..].as_ptr() as usize, true)
+            }
+        };
+
+        // a single unit goes to a fixed address, several reload the
+        // destination each line
+        let dest = if self.per_line == 1 {
+            DMADestAddressControl::Fixed
+        } else {
+            DMADestAddressControl::IncrementReload
+        };
+        let control = DMAControlSetting::new()
+            .with_source_address_control(DMASrcAddressControl::Increment)
+            .with_dest_address_control(dest)
+            .with_dma_repeat(true)
+            .with_use_32bit(wide)
+            .with_start_time(DMAStartTiming::HBlank)
+            .with_enabled(true);
+
+        match self.channel {
+            DmaChannel::Dma0 => {
+                DMA0::DMA0SAD.write(source as *const u32);
+                DMA0::DMA0DAD.write(self.register as *mut u32);
+                DMA0::DMA0CNT_L.write(self.per_line);
+                DMA0::DMA0CNT_H.write(control);
+            }
+            DmaChannel::Dma1 => {
+                DMA1::DMA1SAD.write(source as *const u32);
+                DMA1::DMA1DAD.write(self.register as *mut u32);
+                DMA1::DMA1CNT_L.write(self.per_line);
+                DMA1::DMA1CNT_H.write(control);
+            }
+            DmaChannel::Dma2 => {
+                DMA2::DMA2SAD.write(source as *const u32);
+                DMA2::DMA2DAD.write(self.register as *mut u32);
+                DMA2::DMA2CNT_L.write(self.per_line);
+                DMA2::DMA2CNT_H.write(control);
+            }
+        }
+    }
+
+    pub fn stop(&self) {
+        match self.channel {
+            DmaChannel::Dma0 => DMA0::DMA0CNT_H.write(DMAControlSetting::new()),
+            DmaChannel::Dma1 => DMA1::DMA1CNT_H.write(DMAControlSetting::new()),
+            DmaChannel::Dma2 => DMA2::DMA2CNT_H.write(DMAControlSetting::new()),
+        }
+    }
+}
+
+// vertical color gradient table for a palette entry, drawn wherever that color
+// shows, entry 0 is the backdrop behind every layer
+pub struct Gradient {
+    pub table: [u16; LINES],
+}
+
+impl Gradient {
+    pub fn new(top: Bgr555, bottom: Bgr555) -> Self {
+        let mut table = [0; LINES];
+        for (line, value) in table.iter_mut().enumerate() {
+            let alpha = (line * 255 / (LINES - 1)) as u8;
+            *value = blend(bottom, top, alpha).into_storage();
+        }
+        Gradient { table }
+    }
+
+    // the effect borrows the table, start() it every vblank
+    pub fn effect(&self, channel: DmaChannel, index: u8) -> RasterEffect<'_> {
+        RasterEffect::palette_entry(channel, index, &self.table)
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{Mode3Display, Mode5Display};
+use embedded_graphics::{
+    geometry::Size,
+    image::{ImageDrawable, ImageRawLE},
+    pixelcolor::{raw::RawU16, Bgr555},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+// an image already in the gba's native little endian Bgr555 format, read in
+// place from rom, lighter than decoding a tga
+// with Image::new(&image, point) it draws onto any Bgr555 display, and
+// blit() copies rows with dma onto the 16 bit bitmap displays
+#[derive(Debug, Copy, Clone)]
+pub struct RawGbaImage<'a> {
+    data: &'a [u8],
+    width: u32,
+}
+
+impl<'a> RawGbaImage<'a> {
+    pub fn new(data: &'a [u8], width: u32) -> Self {
+        RawGbaImage {
+            data,
+            width: width.max(1),
+        }
+    }
+
+    pub fn from_halfwords(data: &'a [u16], width: u32) -> Self {
+        let bytes =
+            unsafe { core::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * 2) };
+        Self::new(bytes, width)
+    }
+
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    fn color(&self, x: u32, y: u32) -> Bgr555 {
+        let i = ((y * self.width + x) * 2) as usize;
+        let raw = u16::from_le_bytes([self.data[i], self.data[i + 1]]);
+        Bgr555::from(RawU16::new(raw))
+    }
+
+    // copy the image to a Mode3Display or Mode5Display, row by row with dma
+    pub fn blit<D: RawImageTarget>(&self, display: &mut D, top_left: Point) {
+        display.draw_image_raw(&ImageRawLE::new(self.data, self.width), top_left);
+    }
+}
+
+impl<'a> ImageDrawable for RawGbaImage<'a> {
+    type Color = Bgr555;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Bgr555>,
+    {
+        self.draw_sub_image(target, &self.bounding_box())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Bgr555>,
+    {
+        let clipped = area.intersection(&self.bounding_box());
+        let colors = clipped
+            .points()
+            .map(|point| self.color(point.x as u32, point.y as u32));
+        let top_left = clipped.top_left - area.top_left;
+        target.fill_contiguous(&Rectangle::new(top_left, clipped.size), colors)
+    }
+}
+
+impl<'a> OriginDimensions for RawGbaImage<'a> {
+    fn size(&self) -> Size {
+        let pixels = self.data.len() as u32 / 2;
+        Size::new(self.width, pixels / self.width)
+    }
+}
+
+// bitmap displays with a dma path for raw Bgr555 rows
+pub trait RawImageTarget {
+    fn draw_image_raw(&mut self, raw: &ImageRawLE<Bgr555>, top_left: Point);
+}
+
+impl RawImageTarget for Mode3Display {
+    fn draw_image_raw(&mut self, raw: &ImageRawLE<Bgr555>, top_left: Point) {
+        Mode3Display::draw_image_raw(self, raw, top_left);
+    }
+}
+
+impl RawImageTarget for Mode5Display {
+    fn draw_image_raw(&mut self, raw: &ImageRawLE<Bgr555>, top_left: Point) {
+        Mode5Display::draw_image_raw(self, raw, top_left);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{
+    mmio,
+    page::{PageFlipper, PagedDisplay},
+    vram, Mode3Display, Mode4Display, Mode5Display, PaletteColor, Tile4bppDisplay, Tile8bppDisplay,
+};
+use core::convert::{Infallible, TryInto};
+use embedded_graphics::{
+    pixelcolor::{raw::RawU16, Bgr555},
+    prelude::*,
+};
+use gba::vram::{
+    bitmap::{Mode3, Mode4, Mode5},
+    VRAM_BASE_USIZE,
+};
+
+// displays that can read back what was drawn, for software blending, flood
+// fills and saving what's under a cursor
+pub trait ReadPixel: DrawTarget {
+    // None when point is off the display
+    fn read_pixel(&self, point: Point) -> Option<Self::Color>;
+}
+
+impl ReadPixel for Mode3Display {
+    fn read_pixel(&self, point: Point) -> Option<Bgr555> {
+        if let Ok((x @ 0..=239, y @ 0..=159)) = point.try_into() {
+            let (x, y): (u32, u32) = (x, y);
+            let address = VRAM_BASE_USIZE + (y as usize * Mode3::WIDTH + x as usize) * 2;
+            Some(Bgr555::from(RawU16::new(mmio::read16(address))))
+        } else {
+            None
+        }
+    }
+}
+
+impl ReadPixel for Mode4Display {
+    fn read_pixel(&self, point: Point) -> Option<PaletteColor> {
+        if let Ok((x @ 0..=239, y @ 0..=159)) = point.try_into() {
+            let (x, y): (u32, u32) = (x, y);
+            let address = vram::page_address(self.page) + y as usize * Mode4::WIDTH + x as usize;
+            let halfword = mmio::read16(address & !1);
+            Some(PaletteColor::new((halfword >> ((address & 1) * 8)) as u8))
+        } else {
+            None
+        }
+    }
+}
+
+impl ReadPixel for Mode5Display {
+    fn read_pixel(&self, point: Point) -> Option<Bgr555> {
+        if let Ok((x @ 0..=159, y @ 0..=127)) = point.try_into() {
+            let (x, y): (u32, u32) = (x, y);
+            let address =
+                vram::page_address(self.page) + (y as usize * Mode5::WIDTH + x as usize) * 2;
+            Some(Bgr555::from(RawU16::new(mmio::read16(address))))
+        } else {
+            None
+        }
+    }
+}
+
+impl ReadPixel for Tile4bppDisplay {
+    fn read_pixel(&self, point: Point) -> Option<PaletteColor> {
+        let (x, y): (u32, u32) = point.try_into().ok()?;
+        self.pixel(x, y)
+    }
+}
+
+impl ReadPixel for Tile8bppDisplay {
+    fn read_pixel(&self, point: Point) -> Option<PaletteColor> {
+        let (x, y): (u32, u32) = point.try_into().ok()?;
+        self.pixel(x, y)
+    }
+}
+
+// reads the back page, the one being drawn to
+impl<D> ReadPixel for PageFlipper<D>
+where
+    D: PagedDisplay + ReadPixel<Error = Infallible>,
+{
+    fn read_pixel(&self, point: Point) -> Option<D::Color> {
+        self.display.read_pixel(point)
+    }
+}
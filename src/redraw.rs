@@ -0,0 +1,148 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+// areas of a slow scene waiting to be redrawn, handed out a few pixels at a
+// time so an expensive redraw is spread over several frames
+//     let mut scheduler = RedrawScheduler::<8>::new();
+//     scheduler.invalidate(window.bounding_box());
+//     scheduler.frame(240 * 32, |area| scene.draw(&mut display.clipped(&area)).unwrap());
+// areas that overlap or touch are merged into their bounding box, when all N
+// slots are taken a new area is merged into the one it grows the least
+// areas come out oldest first, the same invalidations always give the same
+// regions on the same frames
+pub struct RedrawScheduler<const N: usize> {
+    regions: [Rectangle; N],
+    len: usize,
+}
+
+impl<const N: usize> RedrawScheduler<N> {
+    pub fn new() -> Self {
+        RedrawScheduler {
+            regions: [Rectangle::zero(); N],
+            len: 0,
+        }
+    }
+
+    // areas still waiting to be redrawn, oldest first
+    pub fn pending(&self) -> &[Rectangle] {
+        &self.regions[..self.len]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // pixels still waiting to be redrawn
+    pub fn pending_pixels(&self) -> u32 {
+        self.pending().iter().map(pixels).sum()
+    }
+
+    // forget everything, like after redrawing the whole scene
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn invalidate(&mut self, area: Rectangle) {
+        if area.is_zero_sized() {
+            return;
+        }
+        let mut area = area;
+        let mut merged: Option<usize> = None; // slot that will hold area
+        while let Some(i) =
+            (0..self.len).find(|&i| Some(i) != merged && touches(&self.regions[i], &area))
+        {
+            area = envelope(&self.regions[i], &area);
+            match merged {
+                None => merged = Some(i),
+                Some(slot) => {
+                    self.remove(i);
+                    if i < slot {
+                        merged = Some(slot - 1);
+                    }
+                }
+            }
+        }
+        match merged {
+            Some(slot) => self.regions[slot] = area,
+            None if self.len < N => {
+                self.regions[self.len] = area;
+                self.len += 1;
+            }
+            None => {
+                // full, grow the area that grows the least and merge again in
+                // case it now touches others
+                let grown = (0..self.len).min_by_key(|&i| {
+                    pixels(&envelope(&self.regions[i], &area)) - pixels(&self.regions[i])
+                });
+                if let Some(i) = grown {
+                    let area = envelope(&self.regions[i], &area);
+                    self.remove(i);
+                    self.invalidate(area);
+                }
+            }
+        }
+    }
+
+    // hand up to budget pixels of the oldest areas to redraw, an area that
+    // doesn't fit is split and its top rows go now, returns the pixels handed out
+    // at least one row is handed out when anything is pending, so a budget
+    // smaller than an area's width still makes progress
+    pub fn frame(&mut self, budget: u32, mut redraw: impl FnMut(Rectangle)) -> u32 {
+        let mut used = 0;
+        while self.len > 0 {
+            let area = self.regions[0];
+            let size = pixels(&area);
+            if used + size <= budget {
+                redraw(area);
+                self.remove(0);
+                used += size;
+                continue;
+            }
+            let mut rows = (budget - used) / area.size.width;
+            if rows == 0 && used == 0 {
+                rows = 1;
+            }
+            if rows > 0 {
+                let top = Rectangle::new(area.top_left, Size::new(area.size.width, rows));
+                redraw(top);
+                used += pixels(&top);
+                self.regions[0] = Rectangle::new(
+                    area.top_left + Point::new(0, rows as i32),
+                    Size::new(area.size.width, area.size.height - rows),
+                );
+            }
+            break;
+        }
+        used
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.regions.copy_within(index + 1..self.len, index);
+        self.len -= 1;
+    }
+}
+
+impl<const N: usize> Default for RedrawScheduler<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn pixels(area: &Rectangle) -> u32 {
+    area.size.width * area.size.height
+}
+
+// overlapping or sharing an edge or corner
+fn touches(a: &Rectangle, b: &Rectangle) -> bool {
+    let (a_end, b_end) = (a.top_left + a.size, b.top_left + b.size);
+    a.top_left.x <= b_end.x
+        && b.top_left.x <= a_end.x
+        && a.top_left.y <= b_end.y
+        && b.top_left.y <= a_end.y
+}
+
+fn envelope(a: &Rectangle, b: &Rectangle) -> Rectangle {
+    let top_left = a.top_left.component_min(b.top_left);
+    let bottom_right = (a.top_left + a.size).component_max(b.top_left + b.size);
+    let size = bottom_right - top_left;
+    Rectangle::new(top_left, Size::new(size.x as u32, size.y as u32))
+}
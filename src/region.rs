@@ -0,0 +1,171 @@
+use crate::{
+    dma, mmio, vram, Mode3Display, Mode4Display, Mode4RowWriter, Mode5Display, PaletteColor,
+    RamFramebuffer,
+};
+use embedded_graphics::{
+    geometry::Size,
+    pixelcolor::{raw::RawU16, Bgr555},
+    prelude::*,
+    primitives::Rectangle,
+};
+use gba::vram::{
+    bitmap::{Mode3, Mode4},
+    VRAM_BASE_USIZE,
+};
+
+// copy part of the screen out to a RamFramebuffer and back, to blur, tint or
+// otherwise work on it in software and then put it back
+//     let mut panel: RamFramebuffer<Bgr555, 96, 64> = RamFramebuffer::new(Bgr555::BLACK);
+//     display.read_region(&menu, &mut panel);
+//     darken(&mut panel);
+//     display.write_region(&menu, &panel);
+// pixel (x, y) of area is pixel (x, y) of the framebuffer, area is clipped to
+// the screen and the framebuffer's size, the part copied is returned
+// the 16bpp modes copy a row at a time with dma through a buffer on the stack
+
+impl Mode3Display {
+    pub fn read_region<const W: usize, const H: usize>(
+        &self,
+        area: &Rectangle,
+        framebuffer: &mut RamFramebuffer<Bgr555, W, H>,
+    ) -> Rectangle {
+        read16(VRAM_BASE_USIZE, self.size(), area, framebuffer)
+    }
+
+    pub fn write_region<const W: usize, const H: usize>(
+        &mut self,
+        area: &Rectangle,
+        framebuffer: &RamFramebuffer<Bgr555, W, H>,
+    ) -> Rectangle {
+        write16(VRAM_BASE_USIZE, self.size(), area, framebuffer)
+    }
+}
+
+impl Mode5Display {
+    pub fn read_region<const W: usize, const H: usize>(
+        &self,
+        area: &Rectangle,
+        framebuffer: &mut RamFramebuffer<Bgr555, W, H>,
+    ) -> Rectangle {
+        read16(
+            vram::page_address(self.page),
+            self.size(),
+            area,
+            framebuffer,
+        )
+    }
+
+    pub fn write_region<const W: usize, const H: usize>(
+        &mut self,
+        area: &Rectangle,
+        framebuffer: &RamFramebuffer<Bgr555, W, H>,
+    ) -> Rectangle {
+        write16(
+            vram::page_address(self.page),
+            self.size(),
+            area,
+            framebuffer,
+        )
+    }
+}
+
+impl Mode4Display {
+    pub fn read_region<const W: usize, const H: usize>(
+        &self,
+        area: &Rectangle,
+        framebuffer: &mut RamFramebuffer<PaletteColor, W, H>,
+    ) -> Rectangle {
+        let page = vram::page_address(self.page);
+        let clip = clip(area, self.size(), W, H);
+        for_each_row(area, &clip, |x, y, fx, fy, len| {
+            let row = &mut framebuffer.pixels[fy][fx..fx + len];
+            for (i, pixel) in row.iter_mut().enumerate() {
+                let address = page + y * Mode4::WIDTH + x + i;
+                let halfword = mmio::read16(address & !1);
+                *pixel = PaletteColor::new((halfword >> ((address & 1) * 8)) as u8);
+            }
+        });
+        clip
+    }
+
+    // pixels are packed two to a halfword, the ends of odd runs read back
+    pub fn write_region<const W: usize, const H: usize>(
+        &mut self,
+        area: &Rectangle,
+        framebuffer: &RamFramebuffer<PaletteColor, W, H>,
+    ) -> Rectangle {
+        let page = self.page;
+        let clip = clip(area, self.size(), W, H);
+        for_each_row(area, &clip, |x, y, fx, fy, len| {
+            let mut writer = Mode4RowWriter::new(page, x, y);
+            for &pixel in &framebuffer.pixels[fy][fx..fx + len] {
+                writer.push(pixel);
+            }
+        });
+        clip
+    }
+}
+
+fn read16<const W: usize, const H: usize>(
+    base: usize,
+    screen: Size,
+    area: &Rectangle,
+    framebuffer: &mut RamFramebuffer<Bgr555, W, H>,
+) -> Rectangle {
+    let clip = clip(area, screen, W, H);
+    let mut buffer = [0u16; Mode3::WIDTH]; // the widest row
+    for_each_row(area, &clip, |x, y, fx, fy, len| {
+        let src = (base + (y * screen.width as usize + x) * 2) as *const u16;
+        unsafe { dma::copy16(src, buffer.as_mut_ptr(), len as u16) };
+        let row = &mut framebuffer.pixels[fy][fx..fx + len];
+        for (pixel, value) in row.iter_mut().zip(buffer.iter()) {
+            *pixel = Bgr555::from(RawU16::new(*value));
+        }
+    });
+    clip
+}
+
+fn write16<const W: usize, const H: usize>(
+    base: usize,
+    screen: Size,
+    area: &Rectangle,
+    framebuffer: &RamFramebuffer<Bgr555, W, H>,
+) -> Rectangle {
+    let clip = clip(area, screen, W, H);
+    let mut buffer = [0u16; Mode3::WIDTH]; // the widest row
+    for_each_row(area, &clip, |x, y, fx, fy, len| {
+        let row = &framebuffer.pixels[fy][fx..fx + len];
+        for (value, pixel) in buffer.iter_mut().zip(row.iter()) {
+            *value = pixel.into_storage();
+        }
+        let dst = (base + (y * screen.width as usize + x) * 2) as *mut u16;
+        unsafe { dma::copy16(buffer.as_ptr(), dst, len as u16) };
+    });
+    clip
+}
+
+// area no larger than a W by H framebuffer, on a screen of size
+fn clip(area: &Rectangle, screen: Size, width: usize, height: usize) -> Rectangle {
+    let size = Size::new(
+        area.size.width.min(width as u32),
+        area.size.height.min(height as u32),
+    );
+    Rectangle::new(area.top_left, size).intersection(&Rectangle::new(Point::zero(), screen))
+}
+
+// call f(x, y, framebuffer x, framebuffer y, len) for each row of clip
+fn for_each_row<F>(area: &Rectangle, clip: &Rectangle, mut f: F)
+where
+    F: FnMut(usize, usize, usize, usize, usize),
+{
+    let offset = clip.top_left - area.top_left;
+    for row in 0..clip.size.height as usize {
+        f(
+            clip.top_left.x as usize,
+            clip.top_left.y as usize + row,
+            offset.x as usize,
+            offset.y as usize + row,
+            clip.size.width as usize,
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use crate::PaletteColor;
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+// a table that maps every index to itself
+pub const IDENTITY_REMAP: [u8; 256] = identity();
+
+const fn identity() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+// a table that maps each from index to its to index and the rest to themselves
+pub fn remap_table(pairs: &[(u8, u8)]) -> [u8; 256] {
+    let mut table = IDENTITY_REMAP;
+    for (from, to) in pairs {
+        table[*from as usize] = *to;
+    }
+    table
+}
+
+// replace palette indices through a table while drawing, so the same drawing
+// code and assets can make palette swapped variants, like team colors or
+// night time tiles, without a second copy of the art
+pub struct RemappedDisplay<'a, D> {
+    pub display: D,
+    pub table: &'a [u8; 256],
+}
+
+impl<'a, D> RemappedDisplay<'a, D>
+where
+    D: DrawTarget<Color = PaletteColor>,
+{
+    pub fn new(display: D, table: &'a [u8; 256]) -> Self {
+        RemappedDisplay { display, table }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    fn remap(&self, color: PaletteColor) -> PaletteColor {
+        PaletteColor::new(self.table[color.into_storage() as usize])
+    }
+}
+
+impl<'a, D> DrawTarget for RemappedDisplay<'a, D>
+where
+    D: DrawTarget<Color = PaletteColor>,
+{
+    type Color = PaletteColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let table = self.table;
+        self.display
+            .draw_iter(pixels.into_iter().map(|Pixel(coord, color)| {
+                Pixel(
+                    coord,
+                    PaletteColor::new(table[color.into_storage() as usize]),
+                )
+            }))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let table = self.table;
+        self.display.fill_contiguous(
+            area,
+            colors
+                .into_iter()
+                .map(|color| PaletteColor::new(table[color.into_storage() as usize])),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let color = self.remap(color);
+        self.display.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let color = self.remap(color);
+        self.display.clear(color)
+    }
+}
+
+impl<'a, D> OriginDimensions for RemappedDisplay<'a, D>
+where
+    D: DrawTarget<Color = PaletteColor>,
+{
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
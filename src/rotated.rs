@@ -0,0 +1,96 @@
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+// clockwise rotation of the drawn content
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Rotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+// draw to the inner display rotated by a right angle, like a portrait
+// orientation for a vertical shooter, size() is the rotated size
+pub struct Rotated<D> {
+    pub display: D,
+    pub rotation: Rotation,
+}
+
+impl<D: DrawTarget> Rotated<D> {
+    pub fn new(display: D, rotation: Rotation) -> Self {
+        Rotated { display, rotation }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    // map a point in rotated space onto the inner display
+    fn transform(&self, point: Point) -> Point {
+        transform(self.rotation, self.display.bounding_box().size, point)
+    }
+}
+
+pub type RotatedDisplay<D> = Rotated<D>;
+
+impl<D: DrawTarget> DrawTarget for Rotated<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.size();
+        let (rotation, inner) = (self.rotation, self.display.bounding_box().size);
+        self.display.draw_iter(
+            pixels
+                .into_iter()
+                // clip in rotated space so off screen points can't wrap onto the screen
+                .filter(|Pixel(coord, _)| {
+                    coord.x >= 0
+                        && coord.y >= 0
+                        && coord.x < size.width as i32
+                        && coord.y < size.height as i32
+                })
+                .map(|Pixel(coord, color)| Pixel(transform(rotation, inner, coord), color)),
+        )
+    }
+
+    // a rotated rectangle is still a rectangle, so fills keep the inner
+    // display's fast path
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.intersection(&self.bounding_box());
+        if let Some(bottom_right) = area.bottom_right() {
+            let a = self.transform(area.top_left);
+            let b = self.transform(bottom_right);
+            let area = Rectangle::with_corners(a, b);
+            self.display.fill_solid(&area, color)?;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.clear(color)
+    }
+}
+
+impl<D: DrawTarget> OriginDimensions for Rotated<D> {
+    fn size(&self) -> Size {
+        let size = self.display.bounding_box().size;
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => size,
+            Rotation::Deg90 | Rotation::Deg270 => Size::new(size.height, size.width),
+        }
+    }
+}
+
+fn transform(rotation: Rotation, inner: Size, point: Point) -> Point {
+    let (width, height) = (inner.width as i32, inner.height as i32);
+    match rotation {
+        Rotation::Deg0 => point,
+        Rotation::Deg90 => Point::new(width - 1 - point.y, point.x),
+        Rotation::Deg180 => Point::new(width - 1 - point.x, height - 1 - point.y),
+        Rotation::Deg270 => Point::new(point.y, height - 1 - point.x),
+    }
+}
@@ -0,0 +1,78 @@
+use crate::read::ReadPixel;
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+// the buffer has fewer entries than the region has pixels
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BufferTooSmall;
+
+// what was on screen under a pop-up menu or software cursor, copied out before
+// drawing over it so restore() can put it back without redrawing the scene
+// the buffer holds a color per pixel, a large one can go in ewram:
+//     #[link_section = ".ewram"]
+//     static mut UNDER: [Bgr555; 64 * 64] = [Bgr555::BLACK; 64 * 64];
+pub struct SavedRegion<'a, C> {
+    buffer: &'a mut [C],
+    area: Rectangle, // clipped to the display when saved
+}
+
+impl<'a, C: PixelColor> SavedRegion<'a, C> {
+    // copy the part of area on the display into buffer
+    pub fn save<D>(
+        display: &D,
+        area: &Rectangle,
+        buffer: &'a mut [C],
+    ) -> Result<Self, BufferTooSmall>
+    where
+        D: ReadPixel<Color = C>,
+    {
+        let area = read(display, area, buffer)?;
+        Ok(SavedRegion { buffer, area })
+    }
+
+    // the saved area, in display coordinates
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    // put the saved pixels back, the region can be restored again, like a
+    // cursor moving over a background that doesn't change
+    pub fn restore<D>(&self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let pixels = self.area.size.width as usize * self.area.size.height as usize;
+        display.fill_contiguous(&self.area, self.buffer[..pixels].iter().copied())
+    }
+
+    // save a new area into the same buffer, like when the cursor moves
+    pub fn resave<D>(&mut self, display: &D, area: &Rectangle) -> Result<(), BufferTooSmall>
+    where
+        D: ReadPixel<Color = C>,
+    {
+        self.area = read(display, area, self.buffer)?;
+        Ok(())
+    }
+
+    pub fn into_buffer(self) -> &'a mut [C] {
+        self.buffer
+    }
+}
+
+// copy the part of area on the display into buffer, returns that part
+fn read<D: ReadPixel>(
+    display: &D,
+    area: &Rectangle,
+    buffer: &mut [D::Color],
+) -> Result<Rectangle, BufferTooSmall> {
+    let area = area.intersection(&display.bounding_box());
+    let pixels = area.size.width as usize * area.size.height as usize;
+    if buffer.len() < pixels {
+        return Err(BufferTooSmall);
+    }
+    for (point, color) in area.points().zip(buffer.iter_mut()) {
+        if let Some(read) = display.read_pixel(point) {
+            *color = read;
+        }
+    }
+    Ok(area)
+}
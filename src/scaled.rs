@@ -0,0 +1,56 @@
+use crate::for_each_run;
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+// draw every pixel as a 2x2 block, so Mode5Display is a 80x64 canvas and
+// Mode3Display is 120x80, for chunky pixel games without changing the drawing
+// code, runs of one color become one fill of two rows
+pub struct ScaledDisplay<D> {
+    pub display: D,
+}
+
+impl<D: DrawTarget> ScaledDisplay<D> {
+    pub fn new(display: D) -> Self {
+        ScaledDisplay { display }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for ScaledDisplay<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.size();
+        let display = &mut self.display;
+        let mut result = Ok(());
+        for_each_run(pixels, size, |x, y, len, color| {
+            if result.is_ok() {
+                let top_left = Point::new(x as i32 * 2, y as i32 * 2);
+                let area = Rectangle::new(top_left, Size::new(len as u32 * 2, 2));
+                result = display.fill_solid(&area, color);
+            }
+        });
+        result
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = Rectangle::new(area.top_left * 2, area.size * 2);
+        self.display.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.clear(color)
+    }
+}
+
+impl<D: DrawTarget> OriginDimensions for ScaledDisplay<D> {
+    fn size(&self) -> Size {
+        self.display.bounding_box().size / 2
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{frame, shared::interrupt_free, SharedDisplay};
+use gba::io::{
+    display::{DISPSTAT, VCOUNT},
+    irq::{set_irq_handler, IrqEnableSetting, BIOS_IF, IE, IF, IME},
+};
+
+const LINES: u16 = 228; // 160 drawn and 68 of vblank
+
+// per line effects run from the hblank interrupt, like a wavy background from
+// a scroll offset per line, without writing an irq handler
+//     struct Wave { phase: i32 }
+//     impl ScanlineRenderer for Wave {
+//         fn hblank(&mut self, vcount: u16) {
+//             // set the scroll for line vcount + 1
+//         }
+//     }
+//     static WAVE: SharedDisplay<Wave> = SharedDisplay::new();
+//     WAVE.set(Wave { phase: 0 });
+//     enable_hblank_irq(&WAVE);
+//     WAVE.with(|wave| wave.phase += 1); // from the main loop
+// with() keeps interrupts off, so a long one from the main loop makes the next
+// line's hblank late, keep those short
+pub trait ScanlineRenderer {
+    // vcount is the line just drawn, registers written now show from the next
+    // line, called for 227 before line 0 and 0..=158, not during vblank
+    fn hblank(&mut self, vcount: u16);
+
+    // called every vblank, before line 0's setup
+    fn vblank(&mut self) {}
+}
+
+// the renderer behind the static, without its type
+trait Dispatch {
+    fn hblank(&self, vcount: u16);
+    fn vblank(&self);
+}
+
+impl<R: ScanlineRenderer> Dispatch for SharedDisplay<R> {
+    fn hblank(&self, vcount: u16) {
+        self.with(|renderer| renderer.hblank(vcount));
+    }
+
+    fn vblank(&self) {
+        self.with(|renderer| renderer.vblank());
+    }
+}
+
+// set by enable_hblank_irq, only touched with interrupts off
+static mut RENDERER: Option<&'static dyn Dispatch> = None;
+
+// run renderer from the hblank interrupt, replacing the one before, this also
+// installs the crate's irq handler, which handles vblank as well
+// for a custom irq handler, call on_hblank_irq from it instead
+pub fn enable_hblank_irq<R: ScanlineRenderer + Send>(renderer: &'static SharedDisplay<R>) {
+    interrupt_free(|| unsafe { RENDERER = Some(renderer) });
+    set_irq_handler(frame::irq_handler);
+    DISPSTAT.write(DISPSTAT.read().with_hblank_irq_enable(true));
+    IE.write(IE.read().with_hblank(true));
+    IME.write(IrqEnableSetting::IRQ_YES);
+}
+
+// stop the hblank interrupt and drop the renderer
+pub fn disable_hblank_irq() {
+    IE.write(IE.read().with_hblank(false));
+    DISPSTAT.write(DISPSTAT.read().with_hblank_irq_enable(false));
+    interrupt_free(|| unsafe { RENDERER = None });
+}
+
+// run the renderer for this line and acknowledge the interrupt
+pub fn on_hblank_irq() {
+    let vcount = VCOUNT.read();
+    if (vcount + 1) % LINES < 160 {
+        if let Some(renderer) = unsafe { RENDERER } {
+            renderer.hblank(vcount);
+        }
+    }
+    BIOS_IF.write(BIOS_IF.read().with_hblank(true));
+    IF.write(IF.read().with_hblank(true));
+}
+
+// from on_vblank_irq
+pub(crate) fn on_vblank() {
+    if let Some(renderer) = unsafe { RENDERER } {
+        renderer.vblank();
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    arena::Vram,
+    bg_vram::{BgScreenblocks, BgVramError},
+    dma, mmio, vram,
+};
+use core::convert::{Infallible, TryInto};
+use embedded_graphics::{
+    geometry::Size,
+    pixelcolor::{
+        raw::{RawData, RawU16},
+        PixelColor,
+    },
+    prelude::*,
+};
+
+// a text background map entry: tile id, h/v flip, and 4bpp palette bank
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TileEntry(pub RawU16);
+
+impl TileEntry {
+    pub const fn new(tile_id: u16) -> Self {
+        Self(RawU16::new(tile_id & 0x3FF))
+    }
+
+    pub fn tile_id(self) -> u16 {
+        self.0.into_inner() & 0x3FF
+    }
+
+    pub fn hflip(self) -> bool {
+        self.0.into_inner() & (1 << 10) != 0
+    }
+
+    pub fn vflip(self) -> bool {
+        self.0.into_inner() & (1 << 11) != 0
+    }
+
+    pub fn palbank(self) -> u8 {
+        (self.0.into_inner() >> 12) as u8
+    }
+
+    pub fn with_tile_id(self, tile_id: u16) -> Self {
+        self.with_bits(0x3FF, tile_id)
+    }
+
+    pub fn with_hflip(self, hflip: bool) -> Self {
+        self.with_bits(1 << 10, if hflip { 1 << 10 } else { 0 })
+    }
+
+    pub fn with_vflip(self, vflip: bool) -> Self {
+        self.with_bits(1 << 11, if vflip { 1 << 11 } else { 0 })
+    }
+
+    pub fn with_palbank(self, palbank: u8) -> Self {
+        self.with_bits(0xF << 12, (palbank as u16) << 12)
+    }
+
+    fn with_bits(self, mask: u16, bits: u16) -> Self {
+        Self(RawU16::new((self.0.into_inner() & !mask) | (bits & mask)))
+    }
+}
+
+impl PixelColor for TileEntry {
+    type Raw = RawU16;
+}
+
+impl From<RawU16> for TileEntry {
+    fn from(data: RawU16) -> Self {
+        Self(data)
+    }
+}
+
+impl From<TileEntry> for RawU16 {
+    fn from(value: TileEntry) -> Self {
+        value.0
+    }
+}
+
+// a 32x32 text background map where each pixel is a map entry
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ScreenblockDisplay {
+    pub screenblock: usize,
+}
+
+impl ScreenblockDisplay {
+    // a screenblock picked by hand bypasses the Vram arena, see from_vram
+    pub fn new(screenblock: usize) -> Self {
+        assert!(screenblock < 32, "there are only 32 screenblocks");
+        ScreenblockDisplay { screenblock }
+    }
+
+    // a map on a screenblock claimed from vram, hand it back with
+    // vram.bg.free_screenblocks
+    pub fn from_vram(vram: &mut Vram) -> Result<(Self, BgScreenblocks), BgVramError> {
+        let screenblocks = vram.bg.alloc_screenblocks(1)?;
+        Ok((Self::new(screenblocks.screenblock()), screenblocks))
+    }
+
+    pub fn entry(&self, point: Point) -> Option<TileEntry> {
+        match point.try_into() {
+            Ok((x @ 0..=31, y @ 0..=31)) => {
+                Some(TileEntry(RawU16::new(mmio::read16(self.address(x, y)))))
+            }
+            _ => None,
+        }
+    }
+
+    fn address(&self, x: u32, y: u32) -> usize {
+        vram::screenblock_address(self.screenblock) + (x + y * 32) as usize * 2
+    }
+}
+
+impl DrawTarget for ScreenblockDisplay {
+    type Color = TileEntry;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, entry) in pixels.into_iter() {
+            if let Ok((x @ 0..=31, y @ 0..=31)) = coord.try_into() {
+                mmio::write16(self.address(x, y), entry.into_storage());
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, entry: Self::Color) -> Result<(), Self::Error> {
+        let entry = entry.into_storage() as u32;
+        let word = entry | (entry << 16);
+        let dst = vram::screenblock_address(self.screenblock) as *mut u32;
+        unsafe { dma::fill32(&word, dst, (vram::SCREENBLOCK_SIZE / 4) as u16) };
+        Ok(())
+    }
+}
+
+impl OriginDimensions for ScreenblockDisplay {
+    fn size(&self) -> Size {
+        Size::new(32, 32)
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{background::Background, screenblock::ScreenblockDisplay, TileEntry};
+use embedded_graphics::{pixelcolor::raw::RawU16, prelude::*};
+
+const COLUMNS: i32 = 31; // tiles touched by a 240 pixel wide screen at any offset
+const ROWS: i32 = 21; // tiles touched by a 160 pixel high screen at any offset
+
+// a text background map of any size streamed through one 32x32 screenblock
+// world tile (x, y) lives in screenblock cell (x % 32, y % 32), so when the
+// camera moves only the newly exposed columns and rows are written
+pub struct ScrollingMap<'a> {
+    pub bg: Background,
+    map: ScreenblockDisplay,
+    data: &'a [u16],       // row major map entries
+    width: usize,          // in tiles
+    height: usize,         // in tiles
+    window: Option<Point>, // top left tile of the loaded area
+    camera: Point,
+}
+
+impl<'a> ScrollingMap<'a> {
+    // the background should be a 256x256 text background using screenblock
+    pub fn new(bg: Background, screenblock: usize, data: &'a [u16], width: usize) -> Self {
+        ScrollingMap {
+            bg,
+            map: ScreenblockDisplay::new(screenblock),
+            data,
+            width,
+            height: data.len() / width.max(1),
+            window: None,
+            camera: Point::zero(),
+        }
+    }
+
+    // map size in pixels
+    pub fn size(&self) -> Size {
+        Size::new(self.width as u32 * 8, self.height as u32 * 8)
+    }
+
+    pub fn camera(&self) -> Point {
+        self.camera
+    }
+
+    // move the camera's top left to a world pixel, call during vblank
+    pub fn set_camera(&mut self, camera: Point) {
+        let window = Point::new(camera.x.div_euclid(8), camera.y.div_euclid(8));
+
+        match self.window {
+            Some(old) if (window.x - old.x).abs() < COLUMNS && (window.y - old.y).abs() < ROWS => {
+                // columns exposed on the left or right, for the new rows
+                let columns = if window.x > old.x {
+                    old.x + COLUMNS..window.x + COLUMNS
+                } else {
+                    window.x..old.x
+                };
+                for x in columns {
+                    for y in window.y..window.y + ROWS {
+                        self.load(x, y);
+                    }
+                }
+                // rows exposed on the top or bottom
+                let rows = if window.y > old.y {
+                    old.y + ROWS..window.y + ROWS
+                } else {
+                    window.y..old.y
+                };
+                for y in rows {
+                    for x in window.x..window.x + COLUMNS {
+                        self.load(x, y);
+                    }
+                }
+            }
+            _ => {
+                for y in window.y..window.y + ROWS {
+                    for x in window.x..window.x + COLUMNS {
+                        self.load(x, y);
+                    }
+                }
+            }
+        }
+
+        self.window = Some(window);
+        self.camera = camera;
+        self.bg.set_scroll(camera);
+    }
+
+    // force every visible entry to be written again, after changing the data
+    pub fn reload(&mut self) {
+        self.window = None;
+        self.set_camera(self.camera);
+    }
+
+    // copy world tile x, y to its screenblock cell, outside the map is tile 0
+    fn load(&mut self, x: i32, y: i32) {
+        let entry = if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.data[y as usize * self.width + x as usize]
+        } else {
+            0
+        };
+        let cell = Point::new(x.rem_euclid(32), y.rem_euclid(32));
+        Pixel(cell, TileEntry(RawU16::new(entry)))
+            .draw(&mut self.map)
+            .ok();
+    }
+}
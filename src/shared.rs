@@ -0,0 +1,76 @@
+use core::cell::{Cell, UnsafeCell};
+use gba::io::irq::{IrqEnableSetting, IME};
+
+// run f with interrupts off, the previous IME setting is restored after
+pub fn interrupt_free<R>(f: impl FnOnce() -> R) -> R {
+    let ime = IME.read();
+    IME.write(IrqEnableSetting::IRQ_NO);
+    let result = f();
+    IME.write(ime);
+    result
+}
+
+// a display, or anything else like the oam shadow, used by both the main loop
+// and an irq handler, in a static without static mut
+//     static DISPLAY: SharedDisplay<Mode3Display> = SharedDisplay::new();
+//     DISPLAY.set(Mode3Display).ok();
+//     DISPLAY.with(|display| Pixel(point, color).draw(display));
+// with() runs with interrupts off, so the handler can't interrupt the main
+// loop's access, a nested with() on the same value returns None
+pub struct SharedDisplay<D> {
+    display: UnsafeCell<Option<D>>,
+    busy: Cell<bool>,
+}
+
+// the gba has one core, and every access is inside of interrupt_free
+unsafe impl<D: Send> Sync for SharedDisplay<D> {}
+
+impl<D> SharedDisplay<D> {
+    pub const fn new() -> Self {
+        SharedDisplay {
+            display: UnsafeCell::new(None),
+            busy: Cell::new(false),
+        }
+    }
+
+    // store display, Err(display) when one is already stored or in use, take()
+    // the stored one first to replace it
+    pub fn set(&self, display: D) -> Result<(), D> {
+        let mut display = Some(display);
+        self.with_slot(|slot| {
+            if slot.is_none() {
+                *slot = display.take();
+            }
+        });
+        match display {
+            Some(display) => Err(display),
+            None => Ok(()),
+        }
+    }
+
+    pub fn take(&self) -> Option<D> {
+        self.with_slot(|slot| slot.take()).flatten()
+    }
+
+    // run f on the display, None when empty or already in use
+    pub fn with<R>(&self, f: impl FnOnce(&mut D) -> R) -> Option<R> {
+        self.with_slot(|slot| slot.as_mut().map(f)).flatten()
+    }
+
+    fn with_slot<R>(&self, f: impl FnOnce(&mut Option<D>) -> R) -> Option<R> {
+        interrupt_free(|| {
+            if self.busy.replace(true) {
+                return None;
+            }
+            let result = f(unsafe { &mut *self.display.get() });
+            self.busy.set(false);
+            Some(result)
+        })
+    }
+}
+
+impl<D> Default for SharedDisplay<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
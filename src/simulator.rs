@@ -0,0 +1,129 @@
+use crate::mmio::{self, HostMemory};
+use std::io::{self, Write};
+
+// desktop preview of what the displays drew into host memory, build with the
+// simulator feature and draw through the usual display types, then save the
+// screen as a png to look at layouts without flashing or starting an emulator
+// DISPCNT is read from host memory, where VideoMode::enable and the other
+// display settings in this crate put it
+// the bitmap modes are rendered, the tiled modes only show the backdrop color
+
+pub const SCREEN_WIDTH: usize = 240;
+pub const SCREEN_HEIGHT: usize = 160;
+
+const DISPCNT: usize = 0x400_0000;
+const PALRAM: usize = 0x500_0000;
+const VRAM: usize = 0x600_0000;
+const PAGE_SIZE: usize = 0xA000;
+
+pub struct Simulator;
+
+impl Simulator {
+    // the shown screen as 8 bit per channel rgb, row by row
+    pub fn screen() -> [[u8; 3]; SCREEN_WIDTH * SCREEN_HEIGHT] {
+        let dispcnt = HostMemory::read16(DISPCNT);
+        let page = if dispcnt & 0x10 != 0 { PAGE_SIZE } else { 0 };
+        let backdrop = mmio::read16(PALRAM);
+        let mut screen = [rgb(backdrop); SCREEN_WIDTH * SCREEN_HEIGHT];
+        for (i, pixel) in screen.iter_mut().enumerate() {
+            let (x, y) = (i % SCREEN_WIDTH, i / SCREEN_WIDTH);
+            let color = match dispcnt & 0x7 {
+                3 => mmio::read16(VRAM + i * 2),
+                4 => {
+                    let halfword = mmio::read16(VRAM + page + (i & !1));
+                    let index = halfword.to_le_bytes()[i % 2];
+                    mmio::read16(PALRAM + index as usize * 2)
+                }
+                5 if x < 160 && y < 128 => mmio::read16(VRAM + page + (y * 160 + x) * 2),
+                _ => continue,
+            };
+            *pixel = rgb(color);
+        }
+        screen
+    }
+
+    // write the shown screen to writer as a png image
+    pub fn write_png<W: Write>(mut writer: W) -> io::Result<()> {
+        let screen = Self::screen();
+        writer.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = [0; 13];
+        header[0..4].copy_from_slice(&(SCREEN_WIDTH as u32).to_be_bytes());
+        header[4..8].copy_from_slice(&(SCREEN_HEIGHT as u32).to_be_bytes());
+        header[8] = 8; // bits per channel
+        header[9] = 2; // truecolor
+        write_chunk(&mut writer, b"IHDR", &header)?;
+
+        // every row is a filter type byte then the pixels
+        let mut rows = std::vec::Vec::with_capacity(SCREEN_HEIGHT * (1 + SCREEN_WIDTH * 3));
+        for row in screen.chunks(SCREEN_WIDTH) {
+            rows.push(0); // no filter
+            rows.extend(row.iter().flatten());
+        }
+        write_chunk(&mut writer, b"IDAT", &zlib_stored(&rows))?;
+        write_chunk(&mut writer, b"IEND", &[])
+    }
+
+    // write the shown screen to a png file at path
+    pub fn save_png<P: AsRef<std::path::Path>>(path: P) -> io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        Self::write_png(io::BufWriter::new(file))
+    }
+}
+
+// scale a bgr555 color up to 8 bit channels
+fn rgb(color: u16) -> [u8; 3] {
+    let channel = |shift: u16| {
+        let value = ((color >> shift) & 0x1F) as u8;
+        value << 3 | value >> 2
+    };
+    [channel(0), channel(5), channel(10)]
+}
+
+fn write_chunk<W: Write>(writer: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = crc32(crc32(!0, kind), data);
+    writer.write_all(&(!crc).to_be_bytes())
+}
+
+// a zlib stream of uncompressed deflate blocks, no compressor needed and the
+// file is still a valid png
+fn zlib_stored(data: &[u8]) -> std::vec::Vec<u8> {
+    let mut stream = std::vec![0x78, 0x01];
+    let blocks = data.chunks(0xFFFF);
+    let count = blocks.len();
+    for (i, block) in blocks.enumerate() {
+        stream.push((i + 1 == count) as u8); // final block flag, stored type
+        let len = block.len() as u16;
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
@@ -0,0 +1,358 @@
+use crate::read::ReadPixel;
+use embedded_graphics::{
+    geometry::Size,
+    image::{Image, ImageDrawable},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SoftSpriteError {
+    TooManySprites,
+    BufferTooSmall, // not enough room left to save what's under the sprite
+}
+
+// a sprite added to SoftSprites
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SoftSpriteId(usize);
+
+struct Slot<'i, I> {
+    image: &'i I,
+    position: Point,
+    z: i16,
+    visible: bool,
+    dirty: bool,              // moved, changed or shown since the last update
+    drawn: Option<Rectangle>, // where it is on the display, the saved area
+    under: usize,             // start of its saved pixels in the buffer
+    capacity: usize,
+}
+
+// not derived, that would need I: Copy
+impl<I> Clone for Slot<'_, I> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<I> Copy for Slot<'_, I> {}
+
+impl<I: ImageDrawable> Slot<'_, I> {
+    fn area(&self, display: &Rectangle) -> Option<Rectangle> {
+        if !self.visible {
+            return None;
+        }
+        let area = Rectangle::new(self.position, self.image.size()).intersection(display);
+        if area.is_zero_sized() {
+            None
+        } else {
+            Some(area)
+        }
+    }
+}
+
+// sprites drawn in software onto a bitmap framebuffer, for mode 3 and 5 games
+// that need more than the hardware's 128 objects or don't have the obj vram
+// left, each frame only the sprites that moved are erased and drawn again
+//     #[link_section = ".ewram"]
+//     static mut UNDER: [Bgr555; 32 * 32 * 32] = [Bgr555::BLACK; 32 * 32 * 32];
+//     let mut sprites: SoftSprites<_, _, 32> = SoftSprites::new(unsafe { &mut UNDER })
+//         .with_key(Bgr555::MAGENTA);
+//     let coin = sprites.add(&COIN, Point::new(40, 80), 1)?;
+//     loop {
+//         sprites.move_to(coin, position);
+//         wait_for_vblank();
+//         sprites.update(&mut display)?;
+//     }
+// what's under each sprite is copied into the buffer before it's drawn and
+// put back before it moves, so the scene behind isn't redrawn, but the scene
+// mustn't be drawn over under a sprite either, hide it first
+// sprites overlapping one that changed are redrawn with it, in z order, higher
+// z in front, every pixel is written by the cpu so a few dozen small sprites
+// fit in a frame
+pub struct SoftSprites<'a, 'i, C, I, const N: usize> {
+    slots: [Option<Slot<'i, I>>; N],
+    buffer: &'a mut [C],
+    used: usize, // buffer entries given to sprites
+    key: Option<C>,
+}
+
+impl<'a, 'i, C, I, const N: usize> SoftSprites<'a, 'i, C, I, N>
+where
+    C: PixelColor,
+    I: ImageDrawable<Color = C>,
+{
+    // buffer needs width * height entries for each sprite added
+    pub fn new(buffer: &'a mut [C]) -> Self {
+        SoftSprites {
+            slots: [None; N],
+            buffer,
+            used: 0,
+            key: None,
+        }
+    }
+
+    // pixels of this color are transparent
+    pub fn with_key(self, key: C) -> Self {
+        SoftSprites {
+            key: Some(key),
+            ..self
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.iter().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // buffer entries not yet given to a sprite
+    pub fn buffer_left(&self) -> usize {
+        self.buffer.len() - self.used
+    }
+
+    // add a visible sprite, drawn at the next update
+    pub fn add(
+        &mut self,
+        image: &'i I,
+        position: Point,
+        z: i16,
+    ) -> Result<SoftSpriteId, SoftSpriteError> {
+        let index = self
+            .slots
+            .iter()
+            .position(Option::is_none)
+            .ok_or(SoftSpriteError::TooManySprites)?;
+        let size = image.size();
+        let capacity = size.width as usize * size.height as usize;
+        if capacity > self.buffer_left() {
+            return Err(SoftSpriteError::BufferTooSmall);
+        }
+        self.slots[index] = Some(Slot {
+            image,
+            position,
+            z,
+            visible: true,
+            dirty: true,
+            drawn: None,
+            under: self.used,
+            capacity,
+        });
+        self.used += capacity;
+        Ok(SoftSpriteId(index))
+    }
+
+    pub fn position(&self, id: SoftSpriteId) -> Point {
+        self.slot(id).position
+    }
+
+    pub fn move_to(&mut self, id: SoftSpriteId, position: Point) {
+        let slot = self.slot_mut(id);
+        if slot.position != position {
+            slot.position = position;
+            slot.dirty = true;
+        }
+    }
+
+    pub fn set_visible(&mut self, id: SoftSpriteId, visible: bool) {
+        let slot = self.slot_mut(id);
+        if slot.visible != visible {
+            slot.visible = visible;
+            slot.dirty = true;
+        }
+    }
+
+    pub fn set_z(&mut self, id: SoftSpriteId, z: i16) {
+        let slot = self.slot_mut(id);
+        if slot.z != z {
+            slot.z = z;
+            slot.dirty = true;
+        }
+    }
+
+    // change the image, like the next frame of an animation, it can't have
+    // more pixels than the image the sprite was added with
+    pub fn set_image(&mut self, id: SoftSpriteId, image: &'i I) {
+        let slot = self.slot_mut(id);
+        let size = image.size();
+        assert!(
+            size.width as usize * size.height as usize <= slot.capacity,
+            "image is larger than the sprite's saved area"
+        );
+        slot.image = image;
+        slot.dirty = true;
+    }
+
+    // redraw a sprite whose image changed in place
+    pub fn invalidate(&mut self, id: SoftSpriteId) {
+        self.slot_mut(id).dirty = true;
+    }
+
+    // erase every sprite from the display and forget them, the buffer is
+    // given back
+    pub fn clear<D>(&mut self, display: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        self.erase(display, |_| true)?;
+        self.slots = [None; N];
+        self.used = 0;
+        Ok(())
+    }
+
+    // erase the sprites that changed and draw them where they are now, along
+    // with the sprites overlapping them, returns how many were drawn
+    pub fn update<D>(&mut self, display: &mut D) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = C> + ReadPixel<Color = C>,
+    {
+        let bounds = display.bounding_box();
+        self.spread_dirty(&bounds);
+        let order = self.order();
+        self.erase(display, |slot| slot.dirty)?;
+        let mut drawn = 0;
+        for &index in order.iter().take(self.len()) {
+            let key = self.key;
+            let slot = match &mut self.slots[index] {
+                Some(slot) if slot.dirty => slot,
+                _ => continue,
+            };
+            slot.dirty = false;
+            let area = match slot.area(&bounds) {
+                Some(area) => area,
+                None => continue,
+            };
+            let saved = &mut self.buffer[slot.under..slot.under + slot.capacity];
+            for (point, color) in area.points().zip(saved.iter_mut()) {
+                if let Some(below) = display.read_pixel(point) {
+                    *color = below;
+                }
+            }
+            slot.drawn = Some(area);
+            let image = Image::new(slot.image, slot.position);
+            match key {
+                Some(key) => image.draw(&mut Keyed {
+                    display: &mut *display,
+                    key,
+                })?,
+                None => image.draw(display)?,
+            }
+            drawn += 1;
+        }
+        Ok(drawn)
+    }
+
+    // put back what was under the drawn sprites that erase(slot) picks, front
+    // to back so overlapping sprites restore in the reverse of drawing
+    fn erase<D, F>(&mut self, display: &mut D, erase: F) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+        F: Fn(&Slot<'i, I>) -> bool,
+    {
+        let order = self.order();
+        for &index in order.iter().take(self.len()).rev() {
+            if let Some(slot) = &mut self.slots[index] {
+                if !erase(slot) {
+                    continue;
+                }
+                if let Some(area) = slot.drawn.take() {
+                    let pixels = area.size.width as usize * area.size.height as usize;
+                    let saved = &self.buffer[slot.under..slot.under + pixels];
+                    display.fill_contiguous(&area, saved.iter().copied())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // a sprite touching one that changed, where it was or where it will be,
+    // has to be erased and drawn again too, repeated until nothing new is
+    // touched
+    fn spread_dirty(&mut self, bounds: &Rectangle) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in 0..N {
+                let slot = match self.slots[i] {
+                    Some(slot) if !slot.dirty => slot,
+                    _ => continue,
+                };
+                let area = match slot.drawn {
+                    Some(area) => area,
+                    None => continue,
+                };
+                let touches = self.slots.iter().flatten().any(|other| {
+                    other.dirty
+                        && (overlaps(&area, other.drawn) || overlaps(&area, other.area(bounds)))
+                });
+                if touches {
+                    if let Some(slot) = &mut self.slots[i] {
+                        slot.dirty = true;
+                    }
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    // indices of the sprites back to front, ties in the order they were added
+    fn order(&self) -> [usize; N] {
+        let mut order = [0; N];
+        let mut len = 0;
+        for (index, slot) in self.slots.iter().enumerate() {
+            if let Some(slot) = slot {
+                let mut i = len;
+                while i > 0 && self.z(order[i - 1]) > slot.z {
+                    order[i] = order[i - 1];
+                    i -= 1;
+                }
+                order[i] = index;
+                len += 1;
+            }
+        }
+        order
+    }
+
+    fn z(&self, index: usize) -> i16 {
+        self.slots[index].map_or(0, |slot| slot.z)
+    }
+
+    fn slot(&self, id: SoftSpriteId) -> &Slot<'i, I> {
+        self.slots[id.0].as_ref().expect("sprite was cleared")
+    }
+
+    fn slot_mut(&mut self, id: SoftSpriteId) -> &mut Slot<'i, I> {
+        self.slots[id.0].as_mut().expect("sprite was cleared")
+    }
+}
+
+fn overlaps(area: &Rectangle, other: Option<Rectangle>) -> bool {
+    other.map_or(false, |other| !area.intersection(&other).is_zero_sized())
+}
+
+// drops pixels of the key color on the way to the display
+struct Keyed<'d, D: DrawTarget> {
+    display: &'d mut D,
+    key: D::Color,
+}
+
+impl<D: DrawTarget> DrawTarget for Keyed<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<P>(&mut self, pixels: P) -> Result<(), Self::Error>
+    where
+        P: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let key = self.key;
+        self.display
+            .draw_iter(pixels.into_iter().filter(|Pixel(_, color)| *color != key))
+    }
+}
+
+impl<D: DrawTarget> OriginDimensions for Keyed<'_, D> {
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
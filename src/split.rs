@@ -0,0 +1,230 @@
+use crate::{
+    window::set_outside_layers, Background, ScanlineRenderer, SubDisplay, Window, WindowId,
+    WindowLayers,
+};
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+use gba::vram::bitmap::Mode3;
+
+// where the screen is split, in pixels from the top left
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SplitLayout {
+    Horizontal { y: u16 },   // viewport 0 above viewport 1
+    Vertical { x: u16 },     // viewport 0 left of viewport 1
+    Quad { x: u16, y: u16 }, // 0 and 1 on top, 2 and 3 below, left to right
+}
+
+// two or four viewports, like two player views or a map above a status bar
+// Win0 and Win1 each show one background, a column of the screen for vertical
+// and quad splits, and each viewport has its own scroll, which is written to
+// its background every vblank and swapped at the split line from hblank
+//     static SPLIT: SharedDisplay<SplitScreen> = SharedDisplay::new();
+//     let layout = SplitLayout::Vertical { x: 120 };
+//     let split = SplitScreen::new(layout, [Background::Bg0, Background::Bg1]);
+//     split.enable();
+//     SPLIT.set(split);
+//     enable_hblank_irq(&SPLIT);
+//     SPLIT.with(|split| split.set_scroll(1, player2.camera)); // every frame
+// the viewport sub-displays are in screen coordinates, for the bitmap modes and
+// for drawing to the screen area a viewport covers
+pub struct SplitScreen {
+    layout: SplitLayout,
+    backgrounds: [Background; 2], // shown in Win0 and Win1
+    scroll: [Point; 4],
+    windows: [Window; 2],
+}
+
+impl SplitScreen {
+    // backgrounds[0] is shown in the top or left viewports and backgrounds[1]
+    // in the others, a horizontal split can use the same background in both,
+    // the columns of a vertical or quad split need one each
+    pub fn new(layout: SplitLayout, backgrounds: [Background; 2]) -> Self {
+        let (first, second) = match layout {
+            SplitLayout::Horizontal { y } => (
+                area(0, 0, Mode3::WIDTH, y),
+                area(0, y, Mode3::WIDTH, Mode3::HEIGHT),
+            ),
+            SplitLayout::Vertical { x } | SplitLayout::Quad { x, .. } => (
+                area(0, 0, x, Mode3::HEIGHT),
+                area(x, 0, Mode3::WIDTH, Mode3::HEIGHT),
+            ),
+        };
+        let mut windows = [
+            Window::new(WindowId::Win0, &first),
+            Window::new(WindowId::Win1, &second),
+        ];
+        for (window, bg) in windows.iter_mut().zip(backgrounds.iter()) {
+            window.layers = with_background(*bg);
+        }
+        SplitScreen {
+            layout,
+            backgrounds,
+            scroll: [Point::zero(); 4],
+            windows,
+        }
+    }
+
+    pub fn layout(&self) -> SplitLayout {
+        self.layout
+    }
+
+    // 2 for horizontal and vertical splits, 4 for quad
+    pub fn viewports(&self) -> usize {
+        match self.layout {
+            SplitLayout::Quad { .. } => 4,
+            _ => 2,
+        }
+    }
+
+    // the screen area of a viewport
+    pub fn area(&self, viewport: usize) -> Rectangle {
+        assert!(viewport < self.viewports(), "no such viewport");
+        match self.layout {
+            SplitLayout::Horizontal { .. } | SplitLayout::Vertical { .. } => {
+                self.windows[viewport].area()
+            }
+            SplitLayout::Quad { y, .. } => {
+                let column = self.windows[viewport % 2].area();
+                let (top, bottom) = if viewport < 2 {
+                    (0, y)
+                } else {
+                    (y, Mode3::HEIGHT)
+                };
+                column.intersection(&area(0, top, Mode3::WIDTH, bottom))
+            }
+        }
+    }
+
+    // background shown in a viewport
+    pub fn background(&self, viewport: usize) -> Background {
+        match self.layout {
+            SplitLayout::Quad { .. } => self.backgrounds[viewport % 2],
+            _ => self.backgrounds[viewport],
+        }
+    }
+
+    // the background pixel shown at the viewport's top left, takes effect
+    // from the next frame
+    pub fn set_scroll(&mut self, viewport: usize, offset: Point) {
+        assert!(viewport < self.viewports(), "no such viewport");
+        self.scroll[viewport] = offset;
+    }
+
+    pub fn scroll(&self, viewport: usize) -> Point {
+        self.scroll[viewport]
+    }
+
+    // show only the viewports' backgrounds and sprites inside of them, plus
+    // layers, like a status bar background, shown over the whole screen
+    pub fn set_shared_layers(&mut self, layers: WindowLayers) {
+        for (window, bg) in self.windows.iter_mut().zip(self.backgrounds.iter()) {
+            window.set_layers(merge(with_background(*bg), layers));
+        }
+    }
+
+    // turn on both windows, nothing is shown outside of them
+    pub fn enable(&self) {
+        set_outside_layers(WindowLayers::NONE);
+        for window in &self.windows {
+            window.enable();
+        }
+        for bg in &self.backgrounds {
+            bg.show();
+        }
+    }
+
+    pub fn disable(&self) {
+        for window in &self.windows {
+            window.disable();
+        }
+    }
+
+    // drawing clipped to a viewport, at screen coordinates
+    pub fn viewport<'a, D: DrawTarget>(
+        &self,
+        display: &'a mut D,
+        viewport: usize,
+    ) -> SubDisplay<'a, D> {
+        SubDisplay::new(display, self.area(viewport))
+    }
+
+    // the line the bottom viewports start at, none without a horizontal split
+    fn split_line(&self) -> Option<u16> {
+        match self.layout {
+            SplitLayout::Horizontal { y } | SplitLayout::Quad { y, .. } => Some(y),
+            SplitLayout::Vertical { .. } => None,
+        }
+    }
+
+    // scroll each viewport's background so its scroll shows at its top left
+    fn write_scroll(&self, viewports: core::ops::Range<usize>) {
+        for viewport in viewports {
+            let top_left = self.area(viewport).top_left;
+            self.background(viewport)
+                .set_scroll(self.scroll[viewport] - top_left);
+        }
+    }
+}
+
+impl ScanlineRenderer for SplitScreen {
+    fn hblank(&mut self, vcount: u16) {
+        if let Some(y) = self.split_line() {
+            if vcount + 1 == y {
+                self.write_scroll(self.viewports() / 2..self.viewports());
+            }
+        }
+    }
+
+    fn vblank(&mut self) {
+        let half = self.viewports() / 2;
+        match self.layout {
+            SplitLayout::Vertical { .. } => self.write_scroll(0..2),
+            // the top viewports are empty and there's no hblank before line 0,
+            // so the bottom ones are set up here
+            _ if self.split_line() == Some(0) => self.write_scroll(half..self.viewports()),
+            _ => self.write_scroll(0..half),
+        }
+    }
+}
+
+fn area(x1: u16, y1: u16, x2: u16, y2: u16) -> Rectangle {
+    Rectangle::new(
+        Point::new(x1 as i32, y1 as i32),
+        Size::new(x2.saturating_sub(x1) as u32, y2.saturating_sub(y1) as u32),
+    )
+}
+
+fn with_background(bg: Background) -> WindowLayers {
+    let layers = WindowLayers {
+        obj: true,
+        ..WindowLayers::NONE
+    };
+    match bg {
+        Background::Bg0 => WindowLayers {
+            bg0: true,
+            ..layers
+        },
+        Background::Bg1 => WindowLayers {
+            bg1: true,
+            ..layers
+        },
+        Background::Bg2 => WindowLayers {
+            bg2: true,
+            ..layers
+        },
+        Background::Bg3 => WindowLayers {
+            bg3: true,
+            ..layers
+        },
+    }
+}
+
+fn merge(a: WindowLayers, b: WindowLayers) -> WindowLayers {
+    WindowLayers {
+        bg0: a.bg0 || b.bg0,
+        bg1: a.bg1 || b.bg1,
+        bg2: a.bg2 || b.bg2,
+        bg3: a.bg3 || b.bg3,
+        obj: a.obj || b.obj,
+        effects: a.effects || b.effects,
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{mmio::DISPCNT, vram, Flip};
+use embedded_graphics::{geometry::Size, prelude::*};
+use gba::{
+    io::display::DisplayMode,
+    oam::{
+        OBJAttr0, OBJAttr1, OBJAttr2, ObjectAttributes, ObjectMode, ObjectRender, ObjectShape,
+        ObjectSize,
+    },
+};
+
+const OBJ_VRAM_BASE: usize = 0x601_0000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpriteSize {
+    S8x8,
+    S16x16,
+    S32x32,
+    S64x64,
+    S16x8,
+    S32x8,
+    S32x16,
+    S64x32,
+    S8x16,
+    S8x32,
+    S16x32,
+    S32x64,
+}
+
+impl SpriteSize {
+    pub fn shape_size(self) -> (ObjectShape, ObjectSize) {
+        use SpriteSize::*;
+        match self {
+            S8x8 => (ObjectShape::Square, ObjectSize::Zero),
+            S16x16 => (ObjectShape::Square, ObjectSize::One),
+            S32x32 => (ObjectShape::Square, ObjectSize::Two),
+            S64x64 => (ObjectShape::Square, ObjectSize::Three),
+            S16x8 => (ObjectShape::Horizontal, ObjectSize::Zero),
+            S32x8 => (ObjectShape::Horizontal, ObjectSize::One),
+            S32x16 => (ObjectShape::Horizontal, ObjectSize::Two),
+            S64x32 => (ObjectShape::Horizontal, ObjectSize::Three),
+            S8x16 => (ObjectShape::Vertical, ObjectSize::Zero),
+            S8x32 => (ObjectShape::Vertical, ObjectSize::One),
+            S16x32 => (ObjectShape::Vertical, ObjectSize::Two),
+            S32x64 => (ObjectShape::Vertical, ObjectSize::Three),
+        }
+    }
+
+    // size in pixels
+    pub fn size(self) -> Size {
+        use SpriteSize::*;
+        match self {
+            S8x8 => Size::new(8, 8),
+            S16x16 => Size::new(16, 16),
+            S32x32 => Size::new(32, 32),
+            S64x64 => Size::new(64, 64),
+            S16x8 => Size::new(16, 8),
+            S32x8 => Size::new(32, 8),
+            S32x16 => Size::new(32, 16),
+            S64x32 => Size::new(64, 32),
+            S8x16 => Size::new(8, 16),
+            S8x32 => Size::new(8, 32),
+            S16x32 => Size::new(16, 32),
+            S32x64 => Size::new(32, 64),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ObjMapping {
+    OneDimensional, // an object's tiles follow each other
+    TwoDimensional, // obj vram is a 32x32 grid of tiles, rows are 32 tiles apart
+}
+
+impl ObjMapping {
+    // tile id of the tile col ids across and row tiles down in an object
+    // starting at first, row_ids is the object's tile ids per row of tiles,
+    // 8bpp tiles take two ids, so col and row_ids count double
+    pub fn tile_id(self, first: u16, col: u16, row: u16, row_ids: u16) -> u16 {
+        match self {
+            ObjMapping::OneDimensional => first + row * row_ids + col,
+            ObjMapping::TwoDimensional => first + row * 32 + col,
+        }
+    }
+}
+
+// how obj vram is used, which decides the valid tile ids
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ObjLayout {
+    pub mapping: ObjMapping,
+    pub bitmap_mode: bool, // the lower half of obj vram is a framebuffer in modes 3 to 5
+}
+
+impl ObjLayout {
+    // layout configured in DISPCNT
+    pub fn current() -> Self {
+        let dispcnt = DISPCNT.read();
+        let mode = dispcnt.mode();
+        ObjLayout {
+            mapping: if dispcnt.oam_memory_1d() {
+                ObjMapping::OneDimensional
+            } else {
+                ObjMapping::TwoDimensional
+            },
+            bitmap_mode: mode == DisplayMode::Mode3
+                || mode == DisplayMode::Mode4
+                || mode == DisplayMode::Mode5,
+        }
+    }
+
+    pub fn first_tile(self) -> u16 {
+        if self.bitmap_mode {
+            512
+        } else {
+            0
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpriteError {
+    TileOutOfRange, // tiles run past obj vram or into the bitmap framebuffer
+    TileMisaligned, // 8bpp tiles take two tile ids and must start on an even one
+    InvalidPalbank,
+    InvalidPriority,
+    InvalidAffineSlot,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpriteMode {
+    Normal,
+    SemiTransparent, // alpha blended with the second blend target
+    Window,          // not drawn, its opaque pixels are the obj window's shape
+}
+
+// a hardware object, regular or affine
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sprite {
+    pub position: Point,
+    pub size: SpriteSize,
+    pub tile_id: u16,
+    pub is_8bpp: bool,
+    pub palbank: u8, // only used by 4bpp sprites
+    pub flip: Flip,
+    pub priority: u8, // 0 is drawn on top
+    pub mosaic: bool, // block size is set with set_obj_mosaic
+    pub mode: SpriteMode,
+    pub hidden: bool,
+    pub affine: Option<u8>, // affine matrix slot, flip is ignored for affine sprites
+    pub double_size: bool,  // affine sprites are clipped to twice their size
+}
+
+impl Sprite {
+    pub fn new(tile_id: u16, size: SpriteSize) -> Self {
+        Sprite {
+            position: Point::zero(),
+            size,
+            tile_id,
+            is_8bpp: false,
+            palbank: 0,
+            flip: Flip::None,
+            priority: 0,
+            mosaic: false,
+            mode: SpriteMode::Normal,
+            hidden: false,
+            affine: None,
+            double_size: false,
+        }
+    }
+
+    pub fn with_position(self, position: Point) -> Self {
+        Sprite { position, ..self }
+    }
+
+    pub fn with_size(self, size: SpriteSize) -> Self {
+        Sprite { size, ..self }
+    }
+
+    pub fn with_tile_id(self, tile_id: u16) -> Self {
+        Sprite { tile_id, ..self }
+    }
+
+    pub fn with_8bpp(self, is_8bpp: bool) -> Self {
+        Sprite { is_8bpp, ..self }
+    }
+
+    pub fn with_palbank(self, palbank: u8) -> Self {
+        Sprite { palbank, ..self }
+    }
+
+    pub fn with_flip(self, flip: Flip) -> Self {
+        Sprite { flip, ..self }
+    }
+
+    pub fn with_priority(self, priority: u8) -> Self {
+        Sprite { priority, ..self }
+    }
+
+    pub fn with_mosaic(self, mosaic: bool) -> Self {
+        Sprite { mosaic, ..self }
+    }
+
+    pub fn with_mode(self, mode: SpriteMode) -> Self {
+        Sprite { mode, ..self }
+    }
+
+    pub fn with_hidden(self, hidden: bool) -> Self {
+        Sprite { hidden, ..self }
+    }
+
+    pub fn with_affine(self, affine: Option<u8>) -> Self {
+        Sprite { affine, ..self }
+    }
+
+    // a double size affine sprite has room to rotate without being clipped,
+    // its position is the top left of the doubled area
+    pub fn with_double_size(self, double_size: bool) -> Self {
+        Sprite {
+            double_size,
+            ..self
+        }
+    }
+
+    // tile ids in each row of the sprite's tiles
+    pub fn row_ids(&self) -> u16 {
+        let tiles = (self.size.size().width / 8) as u16;
+        if self.is_8bpp {
+            tiles * 2
+        } else {
+            tiles
+        }
+    }
+
+    // copy tile data to the sprite's tiles, data holds the rows of tiles one
+    // after another like 1d mapping, they are spread out for 2d mapping
+    pub fn upload(&self, data: &[u8], mapping: ObjMapping) {
+        let row_ids = self.row_ids();
+        let rows = (self.size.size().height / 8) as usize;
+        for (row, chunk) in data.chunks(row_ids as usize * 32).take(rows).enumerate() {
+            let id = mapping.tile_id(self.tile_id, 0, row as u16, row_ids);
+            vram::copy_bytes(chunk, OBJ_VRAM_BASE + id as usize * 32);
+        }
+    }
+
+    // number of 32 byte tile ids the sprite's graphics take up
+    pub fn tile_count(&self) -> u16 {
+        let size = self.size.size();
+        let tiles = (size.width / 8) * (size.height / 8);
+        (if self.is_8bpp { tiles * 2 } else { tiles }) as u16
+    }
+
+    pub fn validate(&self, layout: ObjLayout) -> Result<(), SpriteError> {
+        if self.palbank > 15 {
+            return Err(SpriteError::InvalidPalbank);
+        }
+        if self.priority > 3 {
+            return Err(SpriteError::InvalidPriority);
+        }
+        if self.affine.map_or(false, |slot| slot >= 32) {
+            return Err(SpriteError::InvalidAffineSlot);
+        }
+        if self.is_8bpp && self.tile_id % 2 != 0 {
+            return Err(SpriteError::TileMisaligned);
+        }
+        if self.tile_id < layout.first_tile() {
+            return Err(SpriteError::TileOutOfRange);
+        }
+
+        let size = self.size.size();
+        let fits = match layout.mapping {
+            ObjMapping::OneDimensional => self.tile_id as u32 + self.tile_count() as u32 <= 1024,
+            ObjMapping::TwoDimensional => {
+                let (col, row) = (self.tile_id as u32 % 32, self.tile_id as u32 / 32);
+                col + self.row_ids() as u32 <= 32 && row + size.height / 8 <= 32
+            }
+        };
+        if fits {
+            Ok(())
+        } else {
+            Err(SpriteError::TileOutOfRange)
+        }
+    }
+
+    pub fn attributes(&self, layout: ObjLayout) -> Result<ObjectAttributes, SpriteError> {
+        self.validate(layout)?;
+        let (shape, size) = self.size.shape_size();
+        let rendering = match (self.hidden, self.affine, self.double_size) {
+            (true, _, _) => ObjectRender::Disabled,
+            (false, None, _) => ObjectRender::Normal,
+            (false, Some(_), false) => ObjectRender::Affine,
+            (false, Some(_), true) => ObjectRender::DoubleAreaAffine,
+        };
+        let attr1 = OBJAttr1::new()
+            .with_col_coordinate(self.position.x as u16 & 0x1FF) // wraps at 512
+            .with_obj_size(size);
+        Ok(ObjectAttributes {
+            attr0: OBJAttr0::new()
+                .with_row_coordinate(self.position.y as u16 & 0xFF) // wraps at 256
+                .with_obj_rendering(rendering)
+                .with_obj_mode(match self.mode {
+                    SpriteMode::Normal => ObjectMode::Normal,
+                    SpriteMode::SemiTransparent => ObjectMode::SemiTransparent,
+                    SpriteMode::Window => ObjectMode::OBJWindow,
+                })
+                .with_stationary_mosaic(self.mosaic)
+                .with_is_8bpp(self.is_8bpp)
+                .with_obj_shape(shape),
+            // the affine slot shares bits with the flip flags
+            attr1: match self.affine {
+                Some(slot) => attr1.with_affine_index(slot as u16),
+                None => attr1
+                    .with_hflip(self.flip.horizontal())
+                    .with_vflip(self.flip.vertical()),
+            },
+            attr2: OBJAttr2::new()
+                .with_tile_id(self.tile_id)
+                .with_priority(self.priority as u16)
+                .with_palbank(self.palbank as u16),
+        })
+    }
+}
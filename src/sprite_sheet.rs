@@ -0,0 +1,204 @@
+use crate::{
+    animation::{Animation, AnimationMode, Frame},
+    arena::Vram,
+    canvas::CanvasTile,
+    mmio,
+    obj_tiles::{Bpp, ObjTileAllocator, ObjVramFull, TileHandle},
+    sprite::ObjMapping,
+    IndexedTga, PaletteColor, SpriteSize,
+};
+use core::ops::Range;
+use embedded_graphics::prelude::*;
+use gba::vram::{Tile4bpp, Tile8bpp};
+
+const OBJ_VRAM_BASE: usize = 0x601_0000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SpriteSheetError {
+    FrameSize, // the image isn't a whole number of frames
+    VramFull,
+}
+
+impl From<ObjVramFull> for SpriteSheetError {
+    fn from(_: ObjVramFull) -> Self {
+        SpriteSheetError::VramFull
+    }
+}
+
+// an indexed image cut into frames of one sprite size at runtime, left to
+// right then top to bottom, with every frame's tiles uploaded to obj vram
+//     let image = IndexedTga::new(include_bytes!("hero.tga"))?;
+//     let sheet = SpriteSheet::load(&image, SpriteSize::S16x16, Bpp::Bpp4, &mut tiles)?;
+//     let walk = sheet.animation(0..4, 8, AnimationMode::Loop, &mut frames)?;
+//     let hero = Sprite::new(sheet.tile_id(0).unwrap(), SpriteSize::S16x16);
+// 4bpp frames keep the low 4 bits of each index, pick the palbank on the
+// sprite, with 2d mapping the frames are laid out side by side in obj vram
+pub struct SpriteSheet {
+    handle: TileHandle,
+    size: SpriteSize,
+    frames: usize,
+    mapping: ObjMapping,
+    per_row: usize, // frames side by side in a 2d block
+}
+
+impl SpriteSheet {
+    // load with the obj tiles of the Vram arena
+    pub fn from_vram(
+        image: &IndexedTga,
+        size: SpriteSize,
+        bpp: Bpp,
+        vram: &mut Vram,
+    ) -> Result<Self, SpriteSheetError> {
+        Self::load(image, size, bpp, &mut vram.obj)
+    }
+
+    pub fn load(
+        image: &IndexedTga,
+        size: SpriteSize,
+        bpp: Bpp,
+        allocator: &mut ObjTileAllocator,
+    ) -> Result<Self, SpriteSheetError> {
+        let frame = size.size();
+        let image_size = image.size();
+        if image_size.width % frame.width != 0 || image_size.height % frame.height != 0 {
+            return Err(SpriteSheetError::FrameSize);
+        }
+        let frames_wide = (image_size.width / frame.width) as usize;
+        let frames = frames_wide * (image_size.height / frame.height) as usize;
+        if frames == 0 {
+            return Err(SpriteSheetError::FrameSize);
+        }
+        let (across, rows) = ((frame.width / 8) as usize, (frame.height / 8) as usize);
+        let row_ids = match bpp {
+            Bpp::Bpp4 => across,
+            Bpp::Bpp8 => across * 2,
+        };
+        let mapping = allocator.mapping();
+        let (handle, per_row) = match mapping {
+            ObjMapping::OneDimensional => {
+                (allocator.alloc_obj_tiles(frames * across * rows, bpp)?, 1)
+            }
+            ObjMapping::TwoDimensional => {
+                let per_row = (32 / row_ids).min(frames);
+                let block_rows = rows * ((frames + per_row - 1) / per_row);
+                (
+                    allocator.alloc_block(across * per_row, block_rows, bpp)?,
+                    per_row,
+                )
+            }
+        };
+        let sheet = SpriteSheet {
+            handle,
+            size,
+            frames,
+            mapping,
+            per_row,
+        };
+        for index in 0..frames {
+            let origin = Point::new(
+                ((index % frames_wide) as u32 * frame.width) as i32,
+                ((index / frames_wide) as u32 * frame.height) as i32,
+            );
+            match bpp {
+                Bpp::Bpp4 => sheet.upload_frame::<Tile4bpp>(image, index, origin),
+                Bpp::Bpp8 => sheet.upload_frame::<Tile8bpp>(image, index, origin),
+            }
+        }
+        Ok(sheet)
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    pub fn size(&self) -> SpriteSize {
+        self.size
+    }
+
+    // first tile id of a frame, for Sprite::new or Frame
+    pub fn tile_id(&self, frame: usize) -> Option<u16> {
+        if frame >= self.frames {
+            return None;
+        }
+        let first = self.handle.tile_id();
+        let frame_ids = (self.row_ids() * self.rows()) as u16;
+        Some(match self.mapping {
+            ObjMapping::OneDimensional => first + frame as u16 * frame_ids,
+            ObjMapping::TwoDimensional => {
+                let (col, row) = (frame % self.per_row, frame / self.per_row);
+                first + (row * self.rows() * 32 + col * self.row_ids()) as u16
+            }
+        })
+    }
+
+    // fill frames with the sheet's frames in range, each shown for ticks
+    // vblanks, and return an animation playing them
+    // returns None if the range is past the sheet or frames is too short
+    pub fn animation<'f>(
+        &self,
+        range: Range<usize>,
+        ticks: u16,
+        mode: AnimationMode,
+        frames: &'f mut [Frame],
+    ) -> Option<Animation<'f>> {
+        if range.is_empty() || range.end > self.frames {
+            return None;
+        }
+        let frames = frames.get_mut(..range.len())?;
+        for (frame, index) in frames.iter_mut().zip(range) {
+            *frame = Frame {
+                tile_id: self.tile_id(index)?,
+                ticks,
+            };
+        }
+        Some(Animation::new(frames, mode))
+    }
+
+    // give the tiles back
+    pub fn free(self, allocator: &mut ObjTileAllocator) {
+        allocator.free(self.handle);
+    }
+
+    fn row_ids(&self) -> usize {
+        let across = (self.size.size().width / 8) as usize;
+        match self.handle.bpp() {
+            Bpp::Bpp4 => across,
+            Bpp::Bpp8 => across * 2,
+        }
+    }
+
+    fn rows(&self) -> usize {
+        (self.size.size().height / 8) as usize
+    }
+
+    // copy the frame at origin in the image to the frame's tiles
+    fn upload_frame<T: CanvasTile>(&self, image: &IndexedTga, frame: usize, origin: Point) {
+        let first = match self.tile_id(frame) {
+            Some(first) => first,
+            None => return,
+        };
+        let across = (self.size.size().width / 8) as usize;
+        let step = self.row_ids() / across; // tile ids per tile
+        let mask = ((1 << T::BITS) - 1) as u8;
+        for row in 0..self.rows() {
+            for col in 0..across {
+                let mut tile = T::filled(PaletteColor::TANSPARENT);
+                for i in 0..64 {
+                    let x = origin.x as usize + col * 8 + i % 8;
+                    let y = origin.y as usize + row * 8 + i / 8;
+                    let color = PaletteColor::new(image.index(x, y) & mask);
+                    tile.set_pixel(i % 8, i / 8, color);
+                }
+                let id = self.mapping.tile_id(
+                    first,
+                    (col * step) as u16,
+                    row as u16,
+                    self.row_ids() as u16,
+                );
+                for (i, word) in tile.words().iter().enumerate() {
+                    mmio::write32(OBJ_VRAM_BASE + id as usize * 32 + i * 4, *word);
+                }
+            }
+        }
+    }
+}
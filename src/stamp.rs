@@ -0,0 +1,84 @@
+use crate::{keyed::for_each_opaque_run, RamFramebuffer};
+use embedded_graphics::{
+    geometry::Size, image::Image, pixelcolor::PixelColor, prelude::*, primitives::Rectangle,
+};
+
+// content rendered once into a RamFramebuffer and drawn many times, like hud
+// icons or repeated decorations
+//     let mut heart = Stamp::new(RamFramebuffer::new(PaletteColor::TANSPARENT))
+//         .with_key(PaletteColor::TANSPARENT);
+//     Circle::new(Point::zero(), 8).into_styled(style).draw(heart.canvas())?;
+//     for i in 0..lives {
+//         heart.draw_at(&mut display, Point::new(i * 10, 2))?;
+//     }
+// every row's opaque runs go to the display's fill_contiguous, which the
+// bitmap displays turn into row writes, without a key the whole stamp is one
+pub struct Stamp<C, const W: usize, const H: usize> {
+    pub framebuffer: RamFramebuffer<C, W, H>,
+    pub key: Option<C>, // pixels of this color aren't drawn
+}
+
+impl<C: PixelColor, const W: usize, const H: usize> Stamp<C, W, H> {
+    pub fn new(framebuffer: RamFramebuffer<C, W, H>) -> Self {
+        Stamp {
+            framebuffer,
+            key: None,
+        }
+    }
+
+    pub fn with_key(self, key: C) -> Self {
+        Stamp {
+            key: Some(key),
+            ..self
+        }
+    }
+
+    // draw the content here
+    pub fn canvas(&mut self) -> &mut RamFramebuffer<C, W, H> {
+        &mut self.framebuffer
+    }
+
+    // draw with the top left at point, clipped to the display
+    pub fn draw_at<D>(&self, display: &mut D, point: Point) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let key = match self.key {
+            Some(key) => key,
+            None => return Image::new(&self.framebuffer, point).draw(display),
+        };
+        // only the rows and columns that land on the display
+        let area = Rectangle::new(point, Size::new(W as u32, H as u32))
+            .intersection(&display.bounding_box());
+        let bottom_right = match area.bottom_right() {
+            Some(bottom_right) => bottom_right,
+            None => return Ok(()),
+        };
+        let (x1, x2) = (
+            (area.top_left.x - point.x) as usize,
+            (bottom_right.x - point.x) as usize,
+        );
+        let mut result = Ok(());
+        for y in area.rows() {
+            let row = &self.framebuffer.pixels[(y - point.y) as usize][x1..=x2];
+            for_each_opaque_run(
+                row.len(),
+                |i| row[i] != key,
+                |start, len| {
+                    if result.is_ok() {
+                        let run = Rectangle::new(
+                            Point::new(area.top_left.x + start as i32, y),
+                            Size::new(len as u32, 1),
+                        );
+                        let colors = row[start..start + len].iter().copied();
+                        result = display.fill_contiguous(&run, colors);
+                    }
+                },
+            );
+            if result.is_err() {
+                break;
+            }
+        }
+        result
+    }
+}
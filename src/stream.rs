@@ -0,0 +1,68 @@
+use embedded_graphics::{
+    draw_target::DrawTargetExt, geometry::Size, image::ImageDrawable, prelude::*,
+    primitives::Rectangle,
+};
+
+// draw a large image a band of rows at a time, like a title screen, so no
+// frame stalls on the whole decode and nothing is buffered in ram
+// each tick draws the next rows_per_tick rows through draw_sub_image, call it
+// once per frame, or finish() to draw the rest at once
+//     let mut loader = RowStreamer::new(&tga, Point::zero(), 40);
+//     while !loader.tick(&mut display)? { wait_for_vblank(); }
+pub struct RowStreamer<'a, I> {
+    image: &'a I,
+    top_left: Point,
+    rows_per_tick: u32,
+    next_row: u32,
+}
+
+impl<'a, I: ImageDrawable> RowStreamer<'a, I> {
+    pub fn new(image: &'a I, top_left: Point, rows_per_tick: u32) -> Self {
+        RowStreamer {
+            image,
+            top_left,
+            rows_per_tick: rows_per_tick.max(1),
+            next_row: 0,
+        }
+    }
+
+    // draw the next band of rows, returns true once every row is drawn
+    pub fn tick<D>(&mut self, target: &mut D) -> Result<bool, D::Error>
+    where
+        D: DrawTarget<Color = I::Color>,
+    {
+        let size = self.image.size();
+        if self.done() {
+            return Ok(true);
+        }
+        let rows = self.rows_per_tick.min(size.height - self.next_row);
+        let band = Rectangle::new(
+            Point::new(0, self.next_row as i32),
+            Size::new(size.width, rows),
+        );
+        let offset = self.top_left + band.top_left;
+        self.image
+            .draw_sub_image(&mut target.translated(offset), &band)?;
+        self.next_row += rows;
+        Ok(self.done())
+    }
+
+    // draw every remaining row
+    pub fn finish<D>(&mut self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = I::Color>,
+    {
+        while !self.tick(target)? {}
+        Ok(())
+    }
+
+    pub fn done(&self) -> bool {
+        self.next_row >= self.image.size().height
+    }
+
+    // fraction of the image drawn, out of 256
+    pub fn progress(&self) -> u32 {
+        let height = self.image.size().height.max(1);
+        self.next_row.min(height) * 256 / height
+    }
+}
@@ -0,0 +1,70 @@
+use embedded_graphics::{
+    draw_target::DrawTargetExt, geometry::Size, prelude::*, primitives::Rectangle,
+};
+
+// a viewport into a display, like a status bar or dialog pane, drawing at
+// 0, 0 draws at the viewport's top left corner and nothing leaves the viewport
+pub struct SubDisplay<'a, D> {
+    pub(crate) display: &'a mut D,
+    area: Rectangle,
+}
+
+impl<'a, D: DrawTarget> SubDisplay<'a, D> {
+    // the viewport is clipped to the display
+    pub fn new(display: &'a mut D, area: Rectangle) -> Self {
+        let area = area.intersection(&display.bounding_box());
+        SubDisplay { display, area }
+    }
+
+    // the viewport in display coordinates
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    // a viewport inside of this one, in this one's coordinates
+    pub fn sub_display(&mut self, area: Rectangle) -> SubDisplay<'_, D> {
+        let area = Rectangle::new(area.top_left + self.area.top_left, area.size);
+        let area = area.intersection(&self.area);
+        SubDisplay {
+            display: &mut *self.display,
+            area,
+        }
+    }
+}
+
+// drawing goes through embedded-graphics' cropped view of the area, which
+// keeps the display's fill_contiguous fast path for areas fully inside
+impl<'a, D: DrawTarget> DrawTarget for SubDisplay<'a, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        self.display.cropped(&self.area).draw_iter(pixels)
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.display
+            .cropped(&self.area)
+            .fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.cropped(&self.area).fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.cropped(&self.area).clear(color)
+    }
+}
+
+impl<'a, D: DrawTarget> OriginDimensions for SubDisplay<'a, D> {
+    fn size(&self) -> Size {
+        self.area.size
+    }
+}
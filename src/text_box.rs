@@ -0,0 +1,166 @@
+use core::ops::Range;
+use embedded_graphics::{
+    prelude::*,
+    primitives::Rectangle,
+    text::{renderer::TextRenderer, Alignment, Baseline},
+};
+
+const ELLIPSIS: &str = "...";
+
+// text word wrapped to a rectangle, each line aligned left, centered or right,
+// and cut off with ... when it runs out of room, for menus and item
+// descriptions on the small screen
+//     let style = MonoTextStyle::new(&FONT_6X10, Bgr555::WHITE);
+//     TextBox::new("Restores 50 HP to one party member.", panel, style)
+//         .with_alignment(Alignment::Center)
+//         .draw(&mut display)?;
+// newlines start a new line, words wider than the box are broken between
+// characters, draw() returns how many bytes of text were shown
+pub struct TextBox<'a, S> {
+    pub text: &'a str,
+    pub area: Rectangle,
+    pub style: S,
+    pub alignment: Alignment,
+    pub ellipsis: bool, // end the last line with ... when text is left over
+}
+
+impl<'a, S: TextRenderer> TextBox<'a, S> {
+    pub fn new(text: &'a str, area: Rectangle, style: S) -> Self {
+        TextBox {
+            text,
+            area,
+            style,
+            alignment: Alignment::Left,
+            ellipsis: true,
+        }
+    }
+
+    pub fn with_alignment(self, alignment: Alignment) -> Self {
+        TextBox { alignment, ..self }
+    }
+
+    pub fn with_ellipsis(self, ellipsis: bool) -> Self {
+        TextBox { ellipsis, ..self }
+    }
+
+    // lines of text that fit in the box
+    pub fn max_lines(&self) -> u32 {
+        self.area.size.height / self.style.line_height().max(1)
+    }
+
+    fn width(&self, text: &str) -> u32 {
+        if text.is_empty() {
+            return 0;
+        }
+        self.style
+            .measure_string(text, Point::zero(), Baseline::Top)
+            .bounding_box
+            .size
+            .width
+    }
+
+    // the line starting at start, and where the next one starts
+    fn next_line(&self, start: usize) -> (Range<usize>, usize) {
+        let text = self.text;
+        let max = self.area.size.width;
+        let start = start + text[start..].len() - text[start..].trim_start_matches(' ').len();
+        let mut end = start;
+        loop {
+            let rest = &text[end..];
+            if rest.is_empty() {
+                return (start..end, end);
+            }
+            if rest.starts_with('\n') {
+                return (start..end, end + 1);
+            }
+            let spaces = rest.len() - rest.trim_start_matches(' ').len();
+            let word = rest[spaces..]
+                .find(|c: char| c == ' ' || c == '\n')
+                .unwrap_or(rest.len() - spaces);
+            let candidate = end + spaces + word;
+            if self.width(&text[start..candidate]) <= max {
+                end = candidate;
+                continue;
+            }
+            if end > start {
+                return (start..end, end);
+            }
+            // a word wider than the box, as many characters as fit, at least one
+            let mut split = start;
+            for (i, c) in text[start..candidate].char_indices() {
+                let next = start + i + c.len_utf8();
+                if split > start && self.width(&text[start..next]) > max {
+                    break;
+                }
+                split = next;
+            }
+            return (start..split, split);
+        }
+    }
+
+    fn draw_line<D>(
+        &self,
+        line: &str,
+        ellipsis: &str,
+        y: i32,
+        target: &mut D,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = S::Color>,
+    {
+        let width = self.width(line) + self.width(ellipsis);
+        let space = self.area.size.width.saturating_sub(width) as i32;
+        let x = self.area.top_left.x
+            + match self.alignment {
+                Alignment::Left => 0,
+                Alignment::Center => space / 2,
+                Alignment::Right => space,
+            };
+        let mut pen = Point::new(x, y);
+        if !line.is_empty() {
+            pen = self.style.draw_string(line, pen, Baseline::Top, target)?;
+        }
+        if !ellipsis.is_empty() {
+            self.style
+                .draw_string(ellipsis, pen, Baseline::Top, target)?;
+        }
+        Ok(())
+    }
+}
+
+impl<S: TextRenderer> Drawable for TextBox<'_, S> {
+    type Color = S::Color;
+    type Output = usize;
+
+    fn draw<D>(&self, target: &mut D) -> Result<usize, D::Error>
+    where
+        D: DrawTarget<Color = S::Color>,
+    {
+        let line_height = self.style.line_height() as i32;
+        let bottom = self.area.top_left.y + self.area.size.height as i32;
+        let mut y = self.area.top_left.y;
+        let mut start = 0;
+        while start < self.text.len() && y + line_height <= bottom {
+            let (line, next) = self.next_line(start);
+            let last = y + line_height * 2 > bottom;
+            let more = !self.text[next..].trim().is_empty();
+            if last && more && self.ellipsis {
+                // drop characters until the line and ... fit
+                let mut end = line.end;
+                let max = self.area.size.width;
+                while end > line.start
+                    && self.width(&self.text[line.start..end]) + self.width(ELLIPSIS) > max
+                {
+                    end = self.text[..end].char_indices().last().map_or(0, |(i, _)| i);
+                }
+                let shown = self.text[line.start..end].trim_end();
+                self.draw_line(shown, ELLIPSIS, y, target)?;
+                return Ok(line.start + shown.len());
+            }
+            self.draw_line(&self.text[line], "", y, target)?;
+            y += line_height;
+            start = next;
+        }
+        Ok(start)
+    }
+}
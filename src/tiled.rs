@@ -0,0 +1,142 @@
+use crate::{
+    arena::Vram,
+    background::Background,
+    bg_vram::{BgScreenblocks, BgTiles, BgVramError},
+    dma, mmio,
+    mode::VideoMode,
+    obj_tiles::Bpp,
+    vram::{self, SCREENBLOCK_SIZE},
+    PaletteColor,
+};
+use core::{
+    convert::{Infallible, TryInto},
+    ops::Range,
+};
+use embedded_graphics::{geometry::Size, prelude::*};
+use gba::io::{
+    background::{BGSize, BackgroundControlSetting},
+    display::{DisplayControlSetting, DisplayMode},
+};
+
+const TILES_WIDE: usize = 30; // screen is 240 / 8 tiles wide
+const TILES_HIGH: usize = 20; // screen is 160 / 8 tiles high
+const TILE_BYTES: usize = 64; // 8bpp
+const SCREEN_TILE_BYTES: usize = TILES_WIDE * TILES_HIGH * TILE_BYTES;
+
+// a text background used as a 240x160 8bpp bitmap
+// every on screen map entry points at its own tile, so the 600 tiles take up
+// 38400 bytes starting at charblock, and the map uses one screenblock
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mode0Display {
+    pub bg: Background,
+    charblock: usize,
+}
+
+impl Mode0Display {
+    pub const WIDTH: usize = 240;
+    pub const HEIGHT: usize = 160;
+
+    // the tiles start at charblock and the map is screenblock, picked by hand
+    // this bypasses the Vram arena, see from_vram
+    pub fn new(bg: Background, charblock: usize, screenblock: usize) -> Self {
+        assert!(charblock < 2, "tiles would run past background vram");
+        assert!(screenblock < 32, "there are only 32 screenblocks");
+        let tiles = range(vram::charblock_address(charblock), SCREEN_TILE_BYTES);
+        let map = range(vram::screenblock_address(screenblock), SCREENBLOCK_SIZE);
+        assert!(
+            map.end <= tiles.start || map.start >= tiles.end,
+            "screenblock overlaps the tiles"
+        );
+
+        // give each map entry on screen a unique tile
+        for row in 0..TILES_HIGH {
+            for col in 0..TILES_WIDE {
+                let entry = map.start + (row * 32 + col) * 2;
+                mmio::write16(entry, (row * TILES_WIDE + col) as u16);
+            }
+        }
+
+        bg.set_control(
+            BackgroundControlSetting::new()
+                .with_char_base_block(charblock as u16)
+                .with_screen_base_block(screenblock as u16)
+                .with_is_8bpp(true)
+                .with_size(BGSize::Zero), // 256x256
+        );
+
+        Mode0Display { bg, charblock }
+    }
+
+    // a display on tiles and a screenblock claimed from vram, hand the blocks
+    // back with vram.bg.free_tiles and free_screenblocks when done with it
+    pub fn from_vram(
+        bg: Background,
+        vram: &mut Vram,
+    ) -> Result<(Self, BgTiles, BgScreenblocks), BgVramError> {
+        let count = TILES_WIDE * TILES_HIGH;
+        let (tiles, screenblocks) = vram.tiles_and_screenblock(0..2, count, Bpp::Bpp8)?;
+        let display = Self::new(bg, tiles.charblock(), screenblocks.screenblock());
+        Ok((display, tiles, screenblocks))
+    }
+
+    pub fn set_scroll(&self, offset: Point) {
+        self.bg.set_scroll(offset);
+    }
+
+    fn address(&self, x: usize, y: usize) -> usize {
+        let tile = (y / 8) * TILES_WIDE + (x / 8);
+        vram::charblock_address(self.charblock) + tile * TILE_BYTES + (y % 8) * 8 + (x % 8)
+    }
+}
+
+impl DrawTarget for Mode0Display {
+    type Color = PaletteColor;
+    type Error = Infallible;
+
+    #[cfg_attr(
+        all(feature = "iwram", target_arch = "arm"),
+        link_section = ".iwram",
+        instruction_set(arm::a32),
+        inline(never)
+    )]
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if let Ok((x @ 0..=239, y @ 0..=159)) = coord.try_into() {
+                vram::write8(self.address(x as usize, y as usize), color.into_storage());
+            }
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        let word = u32::from_ne_bytes([color.into_storage(); 4]);
+        let dst = vram::charblock_address(self.charblock) as *mut u32;
+        unsafe { dma::fill32(&word, dst, (SCREEN_TILE_BYTES / 4) as u16) };
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Mode0Display {
+    fn size(&self) -> Size {
+        Size::new(Self::WIDTH as u32, Self::HEIGHT as u32)
+    }
+}
+
+impl VideoMode for Mode0Display {
+    const MODE: DisplayMode = DisplayMode::Mode0;
+
+    fn configure(&self, setting: DisplayControlSetting) -> DisplayControlSetting {
+        self.bg.enable(setting, true)
+    }
+
+    fn clear_vram(&mut self) {
+        self.clear(PaletteColor::TANSPARENT).ok();
+    }
+}
+
+fn range(start: usize, len: usize) -> Range<usize> {
+    start..start + len
+}
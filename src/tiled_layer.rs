@@ -0,0 +1,126 @@
+use crate::{mmio, vram, TileEntry};
+use embedded_graphics::prelude::*;
+
+// a tile layer from the Tiled map editor as little endian u32 gids, row major,
+// which is the layer data of its base64 (uncompressed) format once decoded, or
+// convert the layer at build time with AssetFile::tiled_tmx or tiled_csv
+// instead, which write map entries ScrollingMap and TileMap take directly
+//     let layer = TiledLayer::new(include_bytes!("level.bin"), 64, 1)?;
+//     layer.upload(28, Point::zero());
+// a gid is the tile's index in the tileset plus the tileset's first gid, so
+// the tileset's tiles have to be in the charblock in the same order, and gid 0,
+// an empty cell, becomes tile 0
+
+const FLIP_H: u32 = 1 << 31;
+const FLIP_V: u32 = 1 << 30;
+const FLIP_D: u32 = 1 << 29; // diagonal, for rotated tiles
+const GID_MASK: u32 = 0x0FFF_FFFF; // the top bits also flag hex rotation
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TiledError {
+    Truncated, // less than a whole number of rows
+    Rotated,   // backgrounds can flip tiles but not rotate them
+    TileId,    // past the 1024 tiles a background can use
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TiledLayer<'a> {
+    data: &'a [u8],
+    width: usize, // in tiles
+    first_gid: u32,
+}
+
+// the map entry for a gid, with Tiled's flip flags as the entry's flips
+pub fn tiled_entry(gid: u32, first_gid: u32) -> Result<TileEntry, TiledError> {
+    if gid & FLIP_D != 0 {
+        return Err(TiledError::Rotated);
+    }
+    let tile = gid & GID_MASK;
+    let tile_id = if tile == 0 {
+        0
+    } else {
+        tile.checked_sub(first_gid).ok_or(TiledError::TileId)?
+    };
+    if tile_id > 0x3FF {
+        return Err(TiledError::TileId);
+    }
+    Ok(TileEntry::new(tile_id as u16)
+        .with_hflip(gid & FLIP_H != 0)
+        .with_vflip(gid & FLIP_V != 0))
+}
+
+impl<'a> TiledLayer<'a> {
+    // first_gid is the tileset's firstgid in the map, every gid is checked
+    pub fn new(data: &'a [u8], width: usize, first_gid: u32) -> Result<Self, TiledError> {
+        if width == 0 || data.len() % (width * 4) != 0 {
+            return Err(TiledError::Truncated);
+        }
+        let layer = TiledLayer {
+            data,
+            width,
+            first_gid,
+        };
+        for i in 0..layer.len() {
+            tiled_entry(layer.gid(i), first_gid)?;
+        }
+        Ok(layer)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.len() / self.width
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len() / 4
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn entry(&self, x: usize, y: usize) -> Option<TileEntry> {
+        if x < self.width && y < self.height() {
+            tiled_entry(self.gid(y * self.width + x), self.first_gid).ok()
+        } else {
+            None
+        }
+    }
+
+    // write the layer's map entries to out, row major, for ScrollingMap or
+    // TileMap, returns how many were written
+    pub fn convert(&self, out: &mut [u16]) -> usize {
+        let count = self.len().min(out.len());
+        for (i, value) in out[..count].iter_mut().enumerate() {
+            *value = self
+                .entry(i % self.width, i / self.width)
+                .map_or(0, |entry| entry.into_storage());
+        }
+        count
+    }
+
+    // copy the layer into a 32x32 screenblock with its top left at cell
+    // top_left, the parts outside of the screenblock are clipped
+    pub fn upload(&self, screenblock: usize, top_left: Point) {
+        let base = vram::screenblock_address(screenblock);
+        for y in 0..self.height() {
+            for x in 0..self.width {
+                let cell = top_left + Point::new(x as i32, y as i32);
+                if (0..32).contains(&cell.x) && (0..32).contains(&cell.y) {
+                    if let Some(entry) = self.entry(x, y) {
+                        let offset = (cell.y as usize * 32 + cell.x as usize) * 2;
+                        mmio::write16(base + offset, entry.into_storage());
+                    }
+                }
+            }
+        }
+    }
+
+    fn gid(&self, index: usize) -> u32 {
+        let bytes = &self.data[index * 4..index * 4 + 4];
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    arena::Vram,
+    bg_vram::{BgScreenblocks, BgVramError},
+    dma, mmio, vram, TileEntry,
+};
+use embedded_graphics::{pixelcolor::raw::RawU16, prelude::*};
+use gba::io::background::BGSize;
+
+// row major text background map entries, like the .map output of tile tools
+#[derive(Debug, Copy, Clone)]
+pub struct TileMap<'a> {
+    pub data: &'a [u16],
+    pub width: usize,  // in tiles
+    pub height: usize, // in tiles
+}
+
+impl<'a> TileMap<'a> {
+    pub fn new(data: &'a [u16], width: usize, height: usize) -> Self {
+        assert!(data.len() >= width * height, "not enough map entries");
+        TileMap {
+            data,
+            width,
+            height,
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<TileEntry> {
+        if x < self.width && y < self.height {
+            Some(TileEntry(RawU16::new(self.data[y * self.width + x])))
+        } else {
+            None
+        }
+    }
+
+    // copy the map into a 32x32 screenblock with its top left at cell top_left,
+    // the parts outside of the screenblock are clipped
+    // a screenblock picked by hand bypasses the Vram arena, see upload_to_vram
+    pub fn upload(&self, screenblock: usize, top_left: Point) {
+        self.for_each_row(screenblock, top_left, |row, col, cells, address| {
+            let src = self.data[row * self.width + col..].as_ptr();
+            unsafe { dma::copy16(src, address as *mut u16, cells as u16) };
+        });
+    }
+
+    // upload into a screenblock claimed from vram, hand it back with
+    // vram.bg.free_screenblocks
+    pub fn upload_to_vram(
+        &self,
+        vram: &mut Vram,
+        top_left: Point,
+    ) -> Result<BgScreenblocks, BgVramError> {
+        let screenblocks = vram.bg.alloc_screenblocks(1)?;
+        self.upload(screenblocks.screenblock(), top_left);
+        Ok(screenblocks)
+    }
+
+    // upload with every tile id moved by tile_offset, for tiles loaded later in
+    // the charblock, and optionally with every entry using palbank
+    pub fn upload_remapped(
+        &self,
+        screenblock: usize,
+        top_left: Point,
+        tile_offset: u16,
+        palbank: Option<u8>,
+    ) {
+        self.for_each_row(screenblock, top_left, |row, col, cells, address| {
+            for i in 0..cells {
+                let entry = TileEntry(RawU16::new(self.data[row * self.width + col + i]));
+                let mut entry = entry.with_tile_id(entry.tile_id().wrapping_add(tile_offset));
+                if let Some(palbank) = palbank {
+                    entry = entry.with_palbank(palbank);
+                }
+                mmio::write16(address + i * 2, entry.into_storage());
+            }
+        });
+    }
+
+    // call f(map row, first map column, cell count, vram address) for every
+    // row that lands in the screenblock
+    fn for_each_row<F>(&self, screenblock: usize, top_left: Point, mut f: F)
+    where
+        F: FnMut(usize, usize, usize, usize),
+    {
+        let left = top_left.x.max(0);
+        let right = (top_left.x + self.width as i32).min(32);
+        if right <= left {
+            return;
+        }
+        let col = (left - top_left.x) as usize;
+        let cells = (right - left) as usize;
+        for row in 0..self.height {
+            let y = top_left.y + row as i32;
+            if (0..32).contains(&y) {
+                let address =
+                    vram::screenblock_address(screenblock) + (y as usize * 32 + left as usize) * 2;
+                f(row, col, cells, address);
+            }
+        }
+    }
+}
+
+// stamp an image's map, tiles loaded at first_tile in the charblock, onto a
+// text background of any size with the image's top left at tile top_left, the
+// entries keep their flips and palbanks
+// backgrounds wider or taller than 32 tiles use consecutive screenblocks from
+// screenblock, left to right then top to bottom, and parts of the image off
+// the background are clipped
+//     let tree = TileMap::new(TREE_MAP, 4, 6);
+//     place_image_on_bg(64, &tree, 28, BGSize::One, Point::new(40, 10));
+pub fn place_image_on_bg(
+    first_tile: u16,
+    map: &TileMap,
+    screenblock: usize,
+    size: BGSize,
+    top_left: Point,
+) {
+    let (blocks_wide, blocks_high) = match size {
+        BGSize::Zero => (1, 1),
+        BGSize::One => (2, 1),
+        BGSize::Two => (1, 2),
+        BGSize::Three => (2, 2),
+    };
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let cell = top_left + Point::new(x as i32, y as i32);
+            if cell.x < 0 || cell.y < 0 {
+                continue;
+            }
+            let (col, row) = (cell.x as usize, cell.y as usize);
+            if col >= blocks_wide * 32 || row >= blocks_high * 32 {
+                continue;
+            }
+            let block = screenblock + (row / 32) * blocks_wide + col / 32;
+            let address = vram::screenblock_address(block) + ((row % 32) * 32 + col % 32) * 2;
+            let entry = TileEntry(RawU16::new(map.data[y * map.width + x]));
+            let entry = entry.with_tile_id(entry.tile_id().wrapping_add(first_tile));
+            mmio::write16(address, entry.into_storage());
+        }
+    }
+}
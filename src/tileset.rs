@@ -0,0 +1,75 @@
+use crate::{asset::TileImage, canvas::CanvasTile};
+use embedded_graphics::{
+    geometry::Size,
+    image::ImageDrawable,
+    pixelcolor::{raw::RawU16, Bgr555},
+    prelude::*,
+    primitives::Rectangle,
+};
+use gba::Color;
+
+// tiles in row major order with the palette to color them, drawable with
+// Image::new(&tileset, point) onto any Bgr555 display, index 0 is transparent
+pub struct TileSet<'a, T> {
+    pub tiles: &'a [T],
+    pub palette: &'a [Color],
+    tiles_wide: usize,
+}
+
+impl<'a, T: CanvasTile> TileSet<'a, T> {
+    pub fn new(tiles: &'a [T], palette: &'a [Color], tiles_wide: usize) -> Self {
+        TileSet {
+            tiles,
+            palette,
+            tiles_wide: tiles_wide.max(1),
+        }
+    }
+
+    fn tiles_high(&self) -> usize {
+        self.tiles.len() / self.tiles_wide
+    }
+
+    // opaque pixels of area, relative to the image
+    fn pixels(&self, area: Rectangle) -> impl Iterator<Item = Pixel<Bgr555>> + '_ {
+        let offset = area.top_left;
+        let area = area.intersection(&self.bounding_box());
+        area.points().filter_map(move |point| {
+            let (x, y) = (point.x as usize, point.y as usize);
+            let tile = self.tiles.get((y / 8) * self.tiles_wide + x / 8)?;
+            let index = tile.pixel(x % 8, y % 8).into_storage();
+            let color = self.palette.get(index as usize).filter(|_| index != 0)?;
+            Some(Pixel(point - offset, Bgr555::from(RawU16::new(color.0))))
+        })
+    }
+}
+
+impl<'a, T: CanvasTile> ImageDrawable for TileSet<'a, T> {
+    type Color = Bgr555;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Bgr555>,
+    {
+        target.draw_iter(self.pixels(self.bounding_box()))
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Bgr555>,
+    {
+        target.draw_iter(self.pixels(*area))
+    }
+}
+
+impl<'a, T: CanvasTile> OriginDimensions for TileSet<'a, T> {
+    fn size(&self) -> Size {
+        Size::new(self.tiles_wide as u32 * 8, self.tiles_high() as u32 * 8)
+    }
+}
+
+impl<T: CanvasTile, const TILES: usize, const COLORS: usize> TileImage<T, TILES, COLORS> {
+    // the image as a drawable tileset, tiles_wide is the image width / 8
+    pub fn tileset(&self, tiles_wide: usize) -> TileSet<'_, T> {
+        TileSet::new(&self.tiles, &self.palette, tiles_wide)
+    }
+}
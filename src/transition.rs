@@ -0,0 +1,281 @@
+use crate::{
+    background::Background,
+    effect::{Brightness, ScreenFade},
+    fade::PaletteFade,
+    fixed::isqrt,
+    mosaic::Pixelate,
+    raster::{DmaChannel, RasterEffect},
+    window::{set_outside_layers, Window, WindowId, WindowLayers},
+};
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+
+const WIDTH: i32 = 240;
+const HEIGHT: i32 = 160;
+const LINES: usize = HEIGHT as usize;
+const WIN0H: usize = 0x400_0040;
+
+// a full screen scene change, call tick() once per vblank until it returns
+// true, then switch scenes and run the opposite transition
+//     let mut out = ScreenFade::fade_out(30);
+//     while !out.tick() { wait_for_vblank(); }
+pub trait Transition {
+    // step the transition, returns true once it is done
+    fn tick(&mut self) -> bool;
+
+    fn done(&self) -> bool;
+}
+
+impl Transition for ScreenFade {
+    fn tick(&mut self) -> bool {
+        ScreenFade::tick(self)
+    }
+
+    fn done(&self) -> bool {
+        ScreenFade::done(self)
+    }
+}
+
+impl Transition for Brightness {
+    fn tick(&mut self) -> bool {
+        Brightness::tick(self)
+    }
+
+    fn done(&self) -> bool {
+        Brightness::done(self)
+    }
+}
+
+impl Transition for Pixelate {
+    fn tick(&mut self) -> bool {
+        Pixelate::tick(self)
+    }
+
+    fn done(&self) -> bool {
+        Pixelate::done(self)
+    }
+}
+
+impl Transition for PaletteFade {
+    fn tick(&mut self) -> bool {
+        PaletteFade::tick(self)
+    }
+
+    fn done(&self) -> bool {
+        PaletteFade::done(self)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WipeShape {
+    Box,    // a rectangle with the screen's aspect ratio
+    Circle, // an iris, like the end of a cartoon
+}
+
+// show the screen through window 0 as it shrinks to the center, or grows out
+// of it, outside of the window only the backdrop color is shown
+// the window's span on every line is set by hblank dma, so this takes a dma
+// channel until it is done, the dma reads the wipe's own table and is started
+// by tick(), so don't move the wipe between ticks
+pub struct WindowWipe {
+    shape: WipeShape,
+    channel: DmaChannel,
+    window: Window,
+    open: bool, // from the center out
+    frames: u16,
+    frame: u16,
+    table: [u16; LINES], // WIN0H per line
+}
+
+impl WindowWipe {
+    pub fn close(shape: WipeShape, channel: DmaChannel, frames: u16) -> Self {
+        Self::new(shape, channel, false, frames)
+    }
+
+    pub fn open(shape: WipeShape, channel: DmaChannel, frames: u16) -> Self {
+        Self::new(shape, channel, true, frames)
+    }
+
+    fn new(shape: WipeShape, channel: DmaChannel, open: bool, frames: u16) -> Self {
+        let screen = Rectangle::new(Point::zero(), Size::new(WIDTH as u32, HEIGHT as u32));
+        let mut window = Window::new(WindowId::Win0, &screen);
+        window.set_layers(WindowLayers::ALL);
+        let mut wipe = WindowWipe {
+            shape,
+            channel,
+            window,
+            open,
+            frames: frames.max(1),
+            frame: 0,
+            table: [0; LINES],
+        };
+        set_outside_layers(WindowLayers::NONE);
+        wipe.window.enable();
+        wipe.fill();
+        wipe
+    }
+
+    // the window's span on every line for the current frame
+    fn fill(&mut self) {
+        let step = self.step() as i32;
+        let frames = self.frames as i32;
+        for (y, span) in self.table.iter_mut().enumerate() {
+            let dy = (y as i32 - HEIGHT / 2).abs();
+            let half = match self.shape {
+                WipeShape::Box if dy < HEIGHT / 2 * step / frames => WIDTH / 2 * step / frames,
+                WipeShape::Box => 0,
+                WipeShape::Circle => {
+                    // reaches the corners when fully open
+                    let radius = 145 * step / frames;
+                    if dy < radius {
+                        isqrt((radius * radius - dy * dy) as u64) as i32
+                    } else {
+                        0
+                    }
+                }
+            };
+            let x1 = (WIDTH / 2 - half).max(0) as u16;
+            let x2 = (WIDTH / 2 + half).min(WIDTH) as u16;
+            *span = x1 << 8 | x2;
+        }
+    }
+
+    // frames of the way open
+    fn step(&self) -> u16 {
+        if self.open {
+            self.frame
+        } else {
+            self.frames - self.frame
+        }
+    }
+}
+
+impl Transition for WindowWipe {
+    // a finished close keeps the window shut, a finished open turns it off
+    fn tick(&mut self) -> bool {
+        if self.done() {
+            return true;
+        }
+        self.frame += 1;
+        self.fill();
+        let effect = RasterEffect::halfwords(self.channel, WIN0H, &self.table, 1);
+        effect.start();
+        if self.done() {
+            effect.stop();
+            if self.open {
+                self.window.disable();
+            }
+        }
+        self.done()
+    }
+
+    fn done(&self) -> bool {
+        self.frame >= self.frames
+    }
+}
+
+// venetian blinds on a text background, the screen is cut into bands and
+// every band rolls up into its top line, hidden lines are scrolled to a blank
+// row of the map, a row below the visible 160 by default
+// scroll offsets are set per line by hblank dma, so this takes a dma channel,
+// the dma reads the blinds' own table and is started by tick(), so don't move
+// the blinds between ticks
+pub struct Blinds {
+    bg: Background,
+    channel: DmaChannel,
+    band: u16,      // lines per band
+    scroll: Point,  // the background's normal scroll offset
+    blank_row: u16, // map line that is empty
+    open: bool,
+    frames: u16,
+    frame: u16,
+    table: [u32; LINES], // BGnHOFS and BGnVOFS per line
+}
+
+impl Blinds {
+    pub fn close(bg: Background, channel: DmaChannel, band: u16, frames: u16) -> Self {
+        Self::new(bg, channel, band, false, frames)
+    }
+
+    pub fn open(bg: Background, channel: DmaChannel, band: u16, frames: u16) -> Self {
+        Self::new(bg, channel, band, true, frames)
+    }
+
+    fn new(bg: Background, channel: DmaChannel, band: u16, open: bool, frames: u16) -> Self {
+        let mut blinds = Blinds {
+            bg,
+            channel,
+            band: band.max(1),
+            scroll: Point::zero(),
+            blank_row: 200,
+            open,
+            frames: frames.max(1),
+            frame: 0,
+            table: [0; LINES],
+        };
+        blinds.fill();
+        blinds
+    }
+
+    pub fn with_scroll(self, scroll: Point) -> Self {
+        let mut blinds = Blinds { scroll, ..self };
+        blinds.fill();
+        blinds
+    }
+
+    pub fn with_blank_row(self, blank_row: u16) -> Self {
+        let mut blinds = Blinds { blank_row, ..self };
+        blinds.fill();
+        blinds
+    }
+
+    // stop the dma and go back to the normal scroll
+    pub fn stop(&self) {
+        RasterEffect::scroll(self.channel, self.bg, &self.table).stop();
+        self.bg.set_scroll(self.scroll);
+    }
+
+    // the scroll offsets of every line for the current frame
+    fn fill(&mut self) {
+        let shown = (self.band as u32 * self.step() as u32 / self.frames as u32) as u16;
+        let x = self.scroll.x as u16 & 0x1FF;
+        for (y, offsets) in self.table.iter_mut().enumerate() {
+            let y = y as u16;
+            let scroll_y = if y % self.band < shown {
+                self.scroll.y as u16
+            } else {
+                self.blank_row.wrapping_sub(y)
+            };
+            *offsets = (scroll_y as u32 & 0x1FF) << 16 | x as u32;
+        }
+    }
+
+    // frames of the way open
+    fn step(&self) -> u16 {
+        if self.open {
+            self.frame
+        } else {
+            self.frames - self.frame
+        }
+    }
+}
+
+impl Transition for Blinds {
+    // closed blinds still need the dma, keep calling tick() every vblank until
+    // the next scene is shown, a finished open calls stop()
+    fn tick(&mut self) -> bool {
+        if !self.done() {
+            self.frame += 1;
+        }
+        if self.done() && self.open {
+            self.stop();
+        } else {
+            self.fill();
+            RasterEffect::scroll(self.channel, self.bg, &self.table).start();
+        }
+        self.done()
+    }
+
+    fn done(&self) -> bool {
+        self.frame >= self.frames
+    }
+}
@@ -0,0 +1,85 @@
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+// draw at world coordinates, the camera is the world point at the display's
+// top left, so moving it scrolls everything drawn afterwards
+//     let mut world = TranslatedDisplay::new(display);
+//     world.camera = player.position - Point::new(120, 80);
+//     world.clear(Bgr555::BLACK)?;
+//     for enemy in &enemies {
+//         enemy.sprite.draw(&mut world)?;
+//     }
+// the bounding box is the part of the world on screen, anything outside of it
+// is clipped
+pub struct TranslatedDisplay<D> {
+    pub display: D,
+    pub camera: Point,
+}
+
+impl<D: DrawTarget> TranslatedDisplay<D> {
+    pub fn new(display: D) -> Self {
+        TranslatedDisplay {
+            display,
+            camera: Point::zero(),
+        }
+    }
+
+    pub fn with_camera(self, camera: Point) -> Self {
+        TranslatedDisplay { camera, ..self }
+    }
+
+    pub fn into_inner(self) -> D {
+        self.display
+    }
+
+    pub fn to_screen(&self, world: Point) -> Point {
+        world - self.camera
+    }
+
+    pub fn to_world(&self, screen: Point) -> Point {
+        screen + self.camera
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for TranslatedDisplay<D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let camera = self.camera;
+        let screen = self.display.bounding_box();
+        self.display.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(coord, color)| Pixel(coord - camera, color))
+                .filter(|Pixel(coord, _)| screen.contains(*coord)),
+        )
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let area = Rectangle::new(area.top_left - self.camera, area.size);
+        self.display.fill_contiguous(&area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = Rectangle::new(area.top_left - self.camera, area.size)
+            .intersection(&self.display.bounding_box());
+        self.display.fill_solid(&area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.display.clear(color)
+    }
+}
+
+impl<D: DrawTarget> Dimensions for TranslatedDisplay<D> {
+    fn bounding_box(&self) -> Rectangle {
+        let screen = self.display.bounding_box();
+        Rectangle::new(screen.top_left + self.camera, screen.size)
+    }
+}
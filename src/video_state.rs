@@ -0,0 +1,104 @@
+use crate::{mmio, palette::PaletteKind};
+use embedded_graphics::{
+    pixelcolor::{raw::RawU16, Bgr555},
+    prelude::*,
+};
+
+const IO_BASE: usize = 0x400_0000;
+const PALETTE_HALFWORDS: usize = 512; // bg then obj
+
+// DISPCNT, BG0CNT to BG3CNT, WININ, WINOUT, BLDCNT and BLDALPHA
+const READABLE: [usize; 9] = [0x00, 0x08, 0x0A, 0x0C, 0x0E, 0x48, 0x4A, 0x50, 0x52];
+// BGnHOFS and BGnVOFS, WIN0H, WIN1H, WIN0V, WIN1V, MOSAIC and BLDY
+const WRITE_ONLY: [usize; 14] = [
+    0x10, 0x12, 0x14, 0x16, 0x18, 0x1A, 0x1C, 0x1E, 0x40, 0x42, 0x44, 0x46, 0x4C, 0x54,
+];
+
+// the last value written to each write only display register, by offset / 2
+static mut SHADOW: [u16; 0x2C] = [0; 0x2C];
+
+// write a write only display register and remember the value for capture()
+pub(crate) fn write_shadowed(address: usize, value: u16) {
+    unsafe { SHADOW[(address - IO_BASE) / 2] = value };
+    mmio::write16(address, value);
+}
+
+// the last value written with write_shadowed
+pub(crate) fn shadowed(address: usize) -> u16 {
+    unsafe { SHADOW[(address - IO_BASE) / 2] }
+}
+
+// the display registers, and optionally both palettes, at one moment, so a
+// pause screen or a menu can take over the display and hand it back
+//     let game = VideoState::capture();
+//     run_pause_menu();
+//     game.restore(); // during vblank
+// scroll, window areas, mosaic and BLDY are write only, their values are the
+// last ones set through this crate, like Background::set_scroll or
+// Window::set_area, raster effects writing them every line aren't seen
+// affine background parameters aren't saved, vram and oam are left alone
+#[derive(Clone)]
+pub struct VideoState {
+    readable: [u16; READABLE.len()],
+    write_only: [u16; WRITE_ONLY.len()],
+    palettes: Option<[u16; PALETTE_HALFWORDS]>,
+}
+
+impl VideoState {
+    pub fn capture() -> Self {
+        let mut state = VideoState {
+            readable: [0; READABLE.len()],
+            write_only: [0; WRITE_ONLY.len()],
+            palettes: None,
+        };
+        for (value, offset) in state.readable.iter_mut().zip(READABLE.iter()) {
+            *value = mmio::read16(IO_BASE + offset);
+        }
+        for (value, offset) in state.write_only.iter_mut().zip(WRITE_ONLY.iter()) {
+            *value = shadowed(IO_BASE + offset);
+        }
+        state
+    }
+
+    // also save both palettes, 1KiB more
+    pub fn capture_with_palettes() -> Self {
+        let mut palettes = [0; PALETTE_HALFWORDS];
+        for (i, color) in palettes.iter_mut().enumerate() {
+            *color = palette_kind(i).read(i as u8).into_storage();
+        }
+        VideoState {
+            palettes: Some(palettes),
+            ..Self::capture()
+        }
+    }
+
+    pub fn has_palettes(&self) -> bool {
+        self.palettes.is_some()
+    }
+
+    // put every register back, DISPCNT last so the mode and layers change
+    // together, call during vblank to avoid a torn frame
+    pub fn restore(&self) {
+        if let Some(palettes) = &self.palettes {
+            for (i, color) in palettes.iter().enumerate() {
+                palette_kind(i).write(i as u8, Bgr555::from(RawU16::new(*color)));
+            }
+        }
+        for (value, offset) in self.write_only.iter().zip(WRITE_ONLY.iter()) {
+            write_shadowed(IO_BASE + offset, *value);
+        }
+        for (value, offset) in self.readable.iter().zip(READABLE.iter()).skip(1) {
+            mmio::write16(IO_BASE + offset, *value);
+        }
+        mmio::write16(IO_BASE + READABLE[0], self.readable[0]);
+    }
+}
+
+// background entries first, through PaletteKind so buffered palettes are seen
+fn palette_kind(entry: usize) -> PaletteKind {
+    if entry < 256 {
+        PaletteKind::Background
+    } else {
+        PaletteKind::Object
+    }
+}
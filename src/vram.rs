@@ -0,0 +1,117 @@
+use crate::{
+    dma,
+    mmio::{read16, write16},
+};
+use gba::vram::{bitmap::Page, VRAM_BASE_USIZE};
+
+// vram only accepts 16 and 32 bit writes, byte writes must read-modify-write
+// the per pixel writers are arm code in iwram with the iwram feature, and are
+// inlined into the drawing loops placed there with them
+
+pub(crate) const CHARBLOCK_SIZE: usize = 0x4000;
+pub(crate) const SCREENBLOCK_SIZE: usize = 0x800;
+
+pub(crate) fn charblock_address(charblock: usize) -> usize {
+    VRAM_BASE_USIZE + charblock * CHARBLOCK_SIZE
+}
+
+pub(crate) fn screenblock_address(screenblock: usize) -> usize {
+    VRAM_BASE_USIZE + screenblock * SCREENBLOCK_SIZE
+}
+
+// start of a mode 4 or mode 5 page
+pub(crate) fn page_address(page: Page) -> usize {
+    match page {
+        Page::Zero => VRAM_BASE_USIZE,
+        Page::One => VRAM_BASE_USIZE + 0xA000,
+    }
+}
+
+// write one byte of the halfword that contains address
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32)
+)]
+pub(crate) fn write8(address: usize, value: u8) {
+    let aligned = address & !1;
+    let shift = (address & 1) * 8;
+    let halfword = read16(aligned) & !(0xFF << shift);
+    write16(aligned, halfword | ((value as u16) << shift));
+}
+
+// write the nibble of pixel 0 to 3 in the halfword at address, for 4bpp tiles
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32)
+)]
+pub(crate) fn write4(address: usize, pixel: usize, value: u8) {
+    let shift = (pixel % 4) * 4;
+    let halfword = read16(address) & !(0xF << shift);
+    write16(address, halfword | (((value & 0xF) as u16) << shift));
+}
+
+// read the nibble of pixel 0 to 3 in the halfword at address, for 4bpp tiles
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32)
+)]
+pub(crate) fn read4(address: usize, pixel: usize) -> u8 {
+    ((read16(address) >> ((pixel % 4) * 4)) & 0xF) as u8
+}
+
+// copy bytes to vram or palram, with dma when the source is halfword aligned
+pub(crate) fn copy_bytes(src: &[u8], dst: usize) {
+    if src.as_ptr() as usize % 2 == 0 {
+        for (i, chunk) in src.chunks(0x8000).enumerate() {
+            let dst = (dst + i * 0x8000) as *mut u16;
+            unsafe { dma::copy16(chunk.as_ptr() as *const u16, dst, (chunk.len() / 2) as u16) };
+        }
+    } else {
+        for (i, pair) in src.chunks_exact(2).enumerate() {
+            write16(dst + i * 2, u16::from_le_bytes([pair[0], pair[1]]));
+        }
+    }
+}
+
+// write value to count halfwords starting at address, with 32 bit dma for the
+// word aligned middle
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32)
+)]
+pub(crate) fn fill16(address: usize, count: usize, value: u16) {
+    let (mut address, mut count) = (address, count);
+    if address % 4 != 0 && count > 0 {
+        write16(address, value);
+        address += 2;
+        count -= 1;
+    }
+    let word = value as u32 | (value as u32) << 16;
+    unsafe { dma::fill32(&word, address as *mut u32, (count / 2) as u16) };
+    if count % 2 != 0 {
+        write16(address + (count - 1) * 2, value);
+    }
+}
+
+// write value to count bytes starting at address
+#[cfg_attr(
+    all(feature = "iwram", target_arch = "arm"),
+    link_section = ".iwram",
+    instruction_set(arm::a32)
+)]
+pub(crate) fn fill8(address: usize, count: usize, value: u8) {
+    let (mut address, mut count) = (address, count);
+    if address % 2 != 0 && count > 0 {
+        write8(address, value);
+        address += 1;
+        count -= 1;
+    }
+    fill16(address, count / 2, u16::from_ne_bytes([value; 2]));
+    if count % 2 != 0 {
+        write8(address + count - 1, value);
+    }
+}
@@ -0,0 +1,308 @@
+use crate::GlyphCache;
+use embedded_graphics::{
+    geometry::Size,
+    mono_font::{MonoFont, MonoTextStyle},
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+
+const MAX_DIGITS: usize = 10; // u32::MAX
+
+// a horizontal bar filled from the left in proportion to a value, like an hp
+// bar, draw it once and then update() every frame, only the pixels between
+// the old and new fill are redrawn so an unchanged bar costs nothing
+pub struct ProgressBar<C> {
+    pub area: Rectangle,
+    pub fill: C,
+    pub empty: C,
+    value: u32,
+    max: u32,
+    drawn: Option<u32>, // filled width on screen
+}
+
+impl<C: PixelColor> ProgressBar<C> {
+    pub fn new(area: Rectangle, fill: C, empty: C, max: u32) -> Self {
+        ProgressBar {
+            area,
+            fill,
+            empty,
+            value: max,
+            max: max.max(1),
+            drawn: None,
+        }
+    }
+
+    // values above max are clamped
+    pub fn set_value(&mut self, value: u32) {
+        self.value = value.min(self.max);
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    // redraw the whole bar on the next update, after the screen was cleared
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    // draw the change since the last update
+    pub fn update<D: DrawTarget<Color = C>>(&mut self, target: &mut D) -> Result<(), D::Error> {
+        let width = self.filled_width();
+        match self.drawn {
+            None => self.draw(target)?,
+            Some(drawn) if drawn < width => self.fill_span(target, drawn, width, self.fill)?,
+            Some(drawn) if drawn > width => self.fill_span(target, width, drawn, self.empty)?,
+            Some(_) => {}
+        }
+        self.drawn = Some(width);
+        Ok(())
+    }
+
+    fn filled_width(&self) -> u32 {
+        (self.area.size.width as u64 * self.value as u64 / self.max as u64) as u32
+    }
+
+    fn fill_span<D: DrawTarget<Color = C>>(
+        &self,
+        target: &mut D,
+        start: u32,
+        end: u32,
+        color: C,
+    ) -> Result<(), D::Error> {
+        let area = Rectangle::new(
+            self.area.top_left + Point::new(start as i32, 0),
+            Size::new(end - start, self.area.size.height),
+        );
+        target.fill_solid(&area, color)
+    }
+}
+
+impl<C: PixelColor> Drawable for ProgressBar<C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = C>>(&self, target: &mut D) -> Result<(), D::Error> {
+        let width = self.filled_width();
+        self.fill_span(target, 0, width, self.fill)?;
+        self.fill_span(target, width, self.area.size.width, self.empty)
+    }
+}
+
+// a bar split into segments with gaps between them, a segment is lit while
+// any of its share of the value is left, so 1 hp still shows one segment
+// like ProgressBar, update() only redraws segments that changed
+pub struct Gauge<C> {
+    pub area: Rectangle,
+    pub fill: C,
+    pub empty: C,
+    segments: u32,
+    gap: u32, // pixels between segments, left as they are
+    value: u32,
+    max: u32,
+    drawn: Option<u32>, // lit segments on screen
+}
+
+impl<C: PixelColor> Gauge<C> {
+    pub fn new(area: Rectangle, fill: C, empty: C, segments: u32, max: u32) -> Self {
+        Gauge {
+            area,
+            fill,
+            empty,
+            segments: segments.max(1),
+            gap: 1,
+            value: max,
+            max: max.max(1),
+            drawn: None,
+        }
+    }
+
+    pub fn with_gap(self, gap: u32) -> Self {
+        Gauge { gap, ..self }
+    }
+
+    // values above max are clamped
+    pub fn set_value(&mut self, value: u32) {
+        self.value = value.min(self.max);
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    // redraw the whole gauge on the next update, after the screen was cleared
+    pub fn invalidate(&mut self) {
+        self.drawn = None;
+    }
+
+    // draw the segments that changed since the last update
+    pub fn update<D: DrawTarget<Color = C>>(&mut self, target: &mut D) -> Result<(), D::Error> {
+        let lit = self.lit();
+        match self.drawn {
+            None => self.draw(target)?,
+            Some(drawn) if drawn < lit => self.fill_segments(target, drawn, lit, self.fill)?,
+            Some(drawn) if drawn > lit => self.fill_segments(target, lit, drawn, self.empty)?,
+            Some(_) => {}
+        }
+        self.drawn = Some(lit);
+        Ok(())
+    }
+
+    fn lit(&self) -> u32 {
+        ((self.value as u64 * self.segments as u64 + self.max as u64 - 1) / self.max as u64) as u32
+    }
+
+    fn fill_segments<D: DrawTarget<Color = C>>(
+        &self,
+        target: &mut D,
+        start: u32,
+        end: u32,
+        color: C,
+    ) -> Result<(), D::Error> {
+        let gaps = self.gap * (self.segments - 1);
+        let width = self.area.size.width.saturating_sub(gaps) / self.segments;
+        for segment in start..end {
+            let x = (segment * (width + self.gap)) as i32;
+            let area = Rectangle::new(
+                self.area.top_left + Point::new(x, 0),
+                Size::new(width, self.area.size.height),
+            );
+            target.fill_solid(&area, color)?;
+        }
+        Ok(())
+    }
+}
+
+impl<C: PixelColor> Drawable for Gauge<C> {
+    type Color = C;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = C>>(&self, target: &mut D) -> Result<(), D::Error> {
+        let lit = self.lit();
+        self.fill_segments(target, 0, lit, self.fill)?;
+        self.fill_segments(target, lit, self.segments, self.empty)
+    }
+}
+
+// a right aligned number in a mono font, like a score or a timer, update()
+// draws through a GlyphCache and only the digits that changed since the last
+// update, so counting up by one usually redraws a single glyph
+//     let mut score = Counter::new(&FONT_6X10, Point::new(180, 4), 6, Bgr555::WHITE)
+//         .with_zero_padding();
+//     score.set_value(score.value() + 50);
+//     score.update(&mut display, &mut glyphs)?;
+// give the cache the same background as the counter, values too long for the
+// digits show as all nines
+pub struct Counter<'a, C> {
+    pub top_left: Point,
+    pub color: C,
+    font: &'a MonoFont<'a>,
+    digits: usize,
+    zero_padding: bool,
+    value: u32,
+    drawn: [Option<char>; MAX_DIGITS], // each digit on screen
+}
+
+impl<'a, C: PixelColor> Counter<'a, C> {
+    // digits is clamped to 1..=10, leading zeros show as spaces until
+    // with_zero_padding()
+    pub fn new(font: &'a MonoFont<'a>, top_left: Point, digits: usize, color: C) -> Self {
+        Counter {
+            top_left,
+            color,
+            font,
+            digits: digits.max(1).min(MAX_DIGITS),
+            zero_padding: false,
+            value: 0,
+            drawn: [None; MAX_DIGITS],
+        }
+    }
+
+    pub fn with_zero_padding(self) -> Self {
+        Counter {
+            zero_padding: true,
+            ..self
+        }
+    }
+
+    pub fn set_value(&mut self, value: u32) {
+        self.value = value;
+    }
+
+    pub fn value(&self) -> u32 {
+        self.value
+    }
+
+    pub fn bounding_box(&self) -> Rectangle {
+        let advance = self.font.character_size.width + self.font.character_spacing;
+        Rectangle::new(
+            self.top_left,
+            Size::new(
+                advance * self.digits as u32 - self.font.character_spacing,
+                self.font.character_size.height,
+            ),
+        )
+    }
+
+    // redraw every digit on the next update, after the screen was cleared
+    pub fn invalidate(&mut self) {
+        self.drawn = [None; MAX_DIGITS];
+    }
+
+    // draw the digits that changed since the last update
+    pub fn update<D, const W: usize, const H: usize, const N: usize>(
+        &mut self,
+        target: &mut D,
+        glyphs: &mut GlyphCache<C, W, H, N>,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = C>,
+    {
+        let chars = self.chars();
+        for (i, c) in chars[..self.digits].iter().enumerate() {
+            if self.drawn[i] != Some(*c) {
+                glyphs.draw_char(target, self.font, self.color, *c, self.digit_position(i))?;
+                self.drawn[i] = Some(*c);
+            }
+        }
+        Ok(())
+    }
+
+    fn digit_position(&self, digit: usize) -> Point {
+        let advance = self.font.character_size.width + self.font.character_spacing;
+        self.top_left + Point::new((advance * digit as u32) as i32, 0)
+    }
+
+    // the digits left to right, only the first self.digits are used
+    fn chars(&self) -> [char; MAX_DIGITS] {
+        let mut chars = [if self.zero_padding { '0' } else { ' ' }; MAX_DIGITS];
+        let max = 10u64.pow(self.digits as u32) - 1;
+        let mut value = (self.value as u64).min(max);
+        for (i, c) in chars[..self.digits].iter_mut().enumerate().rev() {
+            if value == 0 && i + 1 < self.digits {
+                break;
+            }
+            *c = char::from(b'0' + (value % 10) as u8);
+            value /= 10;
+        }
+        chars
+    }
+}
+
+impl<C: PixelColor> Drawable for Counter<'_, C> {
+    type Color = C;
+    type Output = ();
+
+    // draws with the font and no background, use update() for opaque digits
+    fn draw<D: DrawTarget<Color = C>>(&self, target: &mut D) -> Result<(), D::Error> {
+        let style = MonoTextStyle::new(self.font, self.color);
+        let chars = self.chars();
+        for (i, c) in chars[..self.digits].iter().enumerate() {
+            let mut buffer = [0u8; 4];
+            let text = c.encode_utf8(&mut buffer);
+            Text::with_baseline(text, self.digit_position(i), style, Baseline::Top).draw(target)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,263 @@
+use crate::{mmio::DISPCNT, video_state::write_shadowed};
+use embedded_graphics::{geometry::Size, prelude::*, primitives::Rectangle};
+use gba::{
+    io::window::{InsideWindowSetting, OutsideWindowSetting, WININ, WINOUT},
+    vram::bitmap::Mode3,
+};
+
+const WIN0H: usize = 0x400_0040;
+const WIN1H: usize = 0x400_0042;
+const WIN0V: usize = 0x400_0044;
+const WIN1V: usize = 0x400_0046;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WindowId {
+    Win0, // higher priority than Win1 where they overlap
+    Win1,
+}
+
+// layers visible inside (or outside) of a window
+// the bitmap modes draw to bg2, so bg2 and obj are the ones that matter there
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct WindowLayers {
+    pub bg0: bool,
+    pub bg1: bool,
+    pub bg2: bool,
+    pub bg3: bool,
+    pub obj: bool,
+    pub effects: bool, // color special effects (blending, fading)
+}
+
+impl WindowLayers {
+    pub const NONE: Self = Self {
+        bg0: false,
+        bg1: false,
+        bg2: false,
+        bg3: false,
+        obj: false,
+        effects: false,
+    };
+
+    pub const ALL: Self = Self {
+        bg0: true,
+        bg1: true,
+        bg2: true,
+        bg3: true,
+        obj: true,
+        effects: true,
+    };
+
+    pub const BITMAP: Self = Self {
+        bg2: true,
+        obj: true,
+        ..Self::NONE
+    };
+}
+
+// hardware window, only the layers enabled in it are shown inside of area
+pub struct Window {
+    pub id: WindowId,
+    pub layers: WindowLayers,
+    area: Rectangle, // clamped to the screen
+}
+
+impl Window {
+    pub fn new(id: WindowId, area: &Rectangle) -> Self {
+        let mut window = Window {
+            id,
+            layers: WindowLayers::BITMAP,
+            area: Rectangle::new(Point::zero(), Size::zero()),
+        };
+        window.set_area(area);
+        window
+    }
+
+    // area is clamped to the screen, an area off screen is an empty window
+    pub fn set_area(&mut self, area: &Rectangle) {
+        let (x1, x2) = clamp_span(area.top_left.x, area.size.width, Mode3::WIDTH);
+        let (y1, y2) = clamp_span(area.top_left.y, area.size.height, Mode3::HEIGHT);
+        self.area = Rectangle::new(
+            Point::new(x1 as i32, y1 as i32),
+            Size::new((x2 - x1) as u32, (y2 - y1) as u32),
+        );
+
+        // start in the high byte, exclusive end in the low byte, the registers
+        // are write only so they go through the shadow for VideoState
+        let (h, v) = match self.id {
+            WindowId::Win0 => (WIN0H, WIN0V),
+            WindowId::Win1 => (WIN1H, WIN1V),
+        };
+        write_shadowed(h, x1 << 8 | x2);
+        write_shadowed(v, y1 << 8 | y2);
+    }
+
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    pub fn set_layers(&mut self, layers: WindowLayers) {
+        self.layers = layers;
+        if self.enabled() {
+            self.write_layers();
+        }
+    }
+
+    pub fn enable(&self) {
+        self.write_layers();
+        let dispcnt = DISPCNT.read();
+        DISPCNT.write(match self.id {
+            WindowId::Win0 => dispcnt.with_win0(true),
+            WindowId::Win1 => dispcnt.with_win1(true),
+        });
+    }
+
+    pub fn disable(&self) {
+        let dispcnt = DISPCNT.read();
+        DISPCNT.write(match self.id {
+            WindowId::Win0 => dispcnt.with_win0(false),
+            WindowId::Win1 => dispcnt.with_win1(false),
+        });
+    }
+
+    pub fn enabled(&self) -> bool {
+        match self.id {
+            WindowId::Win0 => DISPCNT.read().win0(),
+            WindowId::Win1 => DISPCNT.read().win1(),
+        }
+    }
+
+    fn write_layers(&self) {
+        let l = self.layers;
+        let winin = WININ.read();
+        WININ.write(match self.id {
+            WindowId::Win0 => winin
+                .with_win0_bg0(l.bg0)
+                .with_win0_bg1(l.bg1)
+                .with_win0_bg2(l.bg2)
+                .with_win0_bg3(l.bg3)
+                .with_win0_obj(l.obj)
+                .with_win0_color_special(l.effects),
+            WindowId::Win1 => winin
+                .with_win1_bg0(l.bg0)
+                .with_win1_bg1(l.bg1)
+                .with_win1_bg2(l.bg2)
+                .with_win1_bg3(l.bg3)
+                .with_win1_obj(l.obj)
+                .with_win1_color_special(l.effects),
+        });
+    }
+}
+
+// window shaped by the opaque pixels of sprites in window mode, see
+// SpriteMode::Window, it is lower priority than Win0 and Win1
+pub struct ObjWindow {
+    pub layers: WindowLayers,
+}
+
+impl ObjWindow {
+    pub fn new(layers: WindowLayers) -> Self {
+        ObjWindow { layers }
+    }
+
+    pub fn set_layers(&mut self, layers: WindowLayers) {
+        self.layers = layers;
+        if self.enabled() {
+            self.write_layers();
+        }
+    }
+
+    pub fn enable(&self) {
+        self.write_layers();
+        DISPCNT.write(DISPCNT.read().with_obj_window(true));
+    }
+
+    pub fn disable(&self) {
+        DISPCNT.write(DISPCNT.read().with_obj_window(false));
+    }
+
+    pub fn enabled(&self) -> bool {
+        DISPCNT.read().obj_window()
+    }
+
+    // the obj window layers are the high byte of WINOUT
+    fn write_layers(&self) {
+        let l = self.layers;
+        WINOUT.write(
+            WINOUT
+                .read()
+                .with_obj_win_bg0(l.bg0)
+                .with_obj_win_bg1(l.bg1)
+                .with_obj_win_bg2(l.bg2)
+                .with_obj_win_bg3(l.bg3)
+                .with_obj_win_obj(l.obj)
+                .with_obj_win_color_special(l.effects),
+        );
+    }
+}
+
+// draw only inside of a window's area, so software drawing lines up with
+// what the window shows, like a spotlight or one half of a split screen
+pub struct WindowClipped<'a, D> {
+    pub(crate) display: &'a mut D,
+    area: Rectangle,
+}
+
+pub trait ClipToWindow: DrawTarget + Sized {
+    fn clipped_to_window(&mut self, window: &Window) -> WindowClipped<'_, Self> {
+        WindowClipped {
+            display: self,
+            area: window.area(),
+        }
+    }
+}
+
+impl<D: DrawTarget> ClipToWindow for D {}
+
+impl<'a, D: DrawTarget> DrawTarget for WindowClipped<'a, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let top_left = self.area.top_left;
+        let bottom_right = top_left + self.area.size; // exclusive
+        self.display
+            .draw_iter(pixels.into_iter().filter(|Pixel(coord, _)| {
+                coord.x >= top_left.x
+                    && coord.y >= top_left.y
+                    && coord.x < bottom_right.x
+                    && coord.y < bottom_right.y
+            }))
+    }
+}
+
+impl<'a, D: DrawTarget> OriginDimensions for WindowClipped<'a, D> {
+    fn size(&self) -> Size {
+        self.display.bounding_box().size
+    }
+}
+
+// layers shown outside of every enabled window, shared by all windows
+pub fn set_outside_layers(layers: WindowLayers) {
+    WINOUT.write(
+        WINOUT
+            .read()
+            .with_outside_bg0(layers.bg0)
+            .with_outside_bg1(layers.bg1)
+            .with_outside_bg2(layers.bg2)
+            .with_outside_bg3(layers.bg3)
+            .with_outside_obj(layers.obj)
+            .with_outside_color_special(layers.effects),
+    );
+}
+
+// convert a start and length to a clamped start and exclusive end
+fn clamp_span(start: i32, len: u32, max: usize) -> (u16, u16) {
+    let max = max as i32;
+    let end = start.saturating_add(len as i32);
+    let start = start.max(0).min(max);
+    let end = end.max(start).min(max);
+    (start as u16, end as u16)
+}